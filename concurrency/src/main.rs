@@ -11,6 +11,10 @@ fn main() {
         let c = Arc::clone(&counter);
         handles.push(thread::spawn(move || {
             for _ in 0..1000 {
+                // One indivisible step. A plain load-then-store loses updates; see
+                // `cargo run -p interleave_lab -- explore racy`.
+                // A counter nobody reads until after join() would be fine with
+                // Relaxed; `cargo run -p litmus` shows what each ordering allows.
                 c.fetch_add(1, Ordering::SeqCst);
             }
         }));
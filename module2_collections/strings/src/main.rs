@@ -9,7 +9,7 @@ fn main() {
 
 
     println!("{s}");
-    println!("world = {}", &s[2..4]); 
+    println!("world = {}", &s[2..4]); // byte range: panics if 2 or 4 is inside a multi-byte char (see text_lab slice)
     println!("bytes = {:?}", bytes);
     //println!("chars = {:?}", chars);
     println!("s2 = {:?}", s2);
@@ -1,9 +1,16 @@
+// Heap allocations go through alloc_lab. Pick the allocator with
+// ALLOC_LAB=bump|free-list|slab|buddy (default: the system allocator).
+#[global_allocator]
+static GLOBAL: alloc_lab::LabAllocator = alloc_lab::LabAllocator;
+
 fn main() {
+    alloc_lab::select_from_env();
     let x = 5;                     // stack
     let s = String::from("hello"); // heap
 
     print(&s);                     // borrow, no ownership transfer . to transfer ownership, use &mut for mutable references like: let s_mut = &mut s;
     println!("{x}");
+    alloc_lab::print_report();
 }
 
 fn print(data: &String) {
@@ -0,0 +1,21 @@
+// src/lib.rs
+//
+// A simulated kernel process table. module2_structs::Process has a pid, a
+// memory size and one bool for "running"; a kernel needs more around it: who
+// the parent is, which of six lifecycle states the process is in, its exit
+// code once it has one. The table enforces the lifecycle: fork, exec, exit
+// and wait are only allowed when they would be on a real kernel, and
+// everything else comes back as a typed `Error`.
+//
+//   state.rs   the lifecycle states and which transitions are legal
+//   table.rs   the table: fork/exec/exit/wait, scheduling transitions, memory
+//   view.rs    `ps` and `pstree` style listings
+//   script.rs  script files of operations with expected results
+
+pub mod script;
+pub mod state;
+pub mod table;
+pub mod view;
+
+pub use state::State;
+pub use table::{Entry, Error, ProcessTable, WaitFor, Waited, INIT};
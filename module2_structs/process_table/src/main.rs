@@ -0,0 +1,67 @@
+// src/main.rs
+//
+// `process_table run <script>...`   run script files (see script.rs), PASS/FAIL
+//                                   per line; fails if any expectation fails
+// No arguments: the scripts in scripts/ one after the other.
+
+use std::process::ExitCode;
+
+use process_table::script::Script;
+
+const SCRIPTS: [(&str, &str); 3] = [
+    ("scripts/lifecycle.txt", include_str!("../scripts/lifecycle.txt")),
+    ("scripts/orphans.txt", include_str!("../scripts/orphans.txt")),
+    ("scripts/errors.txt", include_str!("../scripts/errors.txt")),
+];
+
+// Returns whether every expectation held.
+fn run_script(name: &str, src: &str) -> bool {
+    println!("=== Script: {name} ===");
+    let script = match Script::parse(src) {
+        Ok(script) => script,
+        Err(e) => {
+            println!("  ERROR: {name}: {e}\n");
+            return false;
+        }
+    };
+    let (report, table) = script.run();
+    println!(
+        "  {} passed, {} failed; {} processes left, {} reaped\n",
+        report.passed,
+        report.failed,
+        table.iter().count(),
+        table.terminated().len()
+    );
+    report.failed == 0
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    let mut all_ok = true;
+    match args {
+        [] => {
+            for (name, src) in SCRIPTS {
+                all_ok &= run_script(name, src);
+            }
+        }
+        ["run", paths @ ..] if !paths.is_empty() => {
+            for path in paths {
+                let src = std::fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+                all_ok &= run_script(path, &src);
+            }
+        }
+        _ => return Err("usage: process_table [run <script>...]".into()),
+    }
+    if all_ok { Ok(()) } else { Err("some expectations failed".into()) }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
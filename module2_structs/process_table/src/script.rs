@@ -0,0 +1,230 @@
+// src/script.rs
+//
+// Script files: drive the process table one operation per line, with an
+// optional expectation, and check that the kernel agrees ('#' starts a comment).
+//
+//   memory 8192              total memory in KB (default 65536), before any operation
+//   pid_max 32768            highest pid before wrapping around (default 32768)
+//
+//   fork 1                   expect pid 2
+//   admit 2
+//   switch 2                 preempt whoever runs, dispatch 2
+//   exec 2 sh 2048           new program name and memory size in KB
+//   wait 2                   expect blocked       (or: wait 2 3 for child 3 only)
+//   exit 3 0                 exit code 0
+//   wait 2                   expect reaped 3
+//   dispatch 4               expect IllegalTransition
+//   admit / dispatch / preempt / block / wake PID
+//   ps | tree                print the table
+//
+// Expectations: ok, pid N (fork), blocked or reaped N (wait), or an error kind
+// (NoSuchProcess, IllegalTransition, NotRunning, CpuBusy, NoChild,
+// InitCannotExit, OutOfMemory, OutOfPids). An operation without one must
+// succeed.
+
+use std::fmt;
+
+use crate::table::{Error, ProcessTable, WaitFor, Waited};
+use crate::view;
+
+#[derive(Debug)]
+pub struct ParseError {
+    line: usize,
+    msg: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+fn err(line: usize, msg: impl Into<String>) -> ParseError {
+    ParseError { line, msg: msg.into() }
+}
+
+fn parse_num<T: std::str::FromStr>(line: usize, s: &str) -> Result<T, ParseError> {
+    s.parse().map_err(|_| err(line, format!("expected a number, got '{s}'")))
+}
+
+#[derive(Debug)]
+enum Op {
+    Fork(u32),
+    Exec(u32, String, u64),
+    Exit(u32, i32),
+    Wait(u32, WaitFor),
+    Admit(u32),
+    Dispatch(u32),
+    Preempt(u32),
+    Block(u32),
+    Wake(u32),
+    Switch(u32),
+    Ps,
+    Tree,
+}
+
+#[derive(Debug)]
+enum Expect {
+    Ok,
+    Pid(u32),
+    Blocked,
+    Reaped(u32),
+    Error(String),
+}
+
+#[derive(Debug)]
+struct Step {
+    line: usize,
+    text: String,
+    op: Op,
+    expect: Option<Expect>,
+}
+
+pub struct Script {
+    memory_kb: u64,
+    pid_max: u32,
+    steps: Vec<Step>,
+}
+
+pub struct Report {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+// What an operation returned, for checking against `expect`.
+enum Outcome {
+    Done,
+    Forked(u32),
+    Waited(Waited),
+}
+
+fn parse_expect(line: usize, words: &[&str]) -> Result<Option<Expect>, ParseError> {
+    Ok(Some(match words {
+        [] => return Ok(None),
+        ["expect", "ok"] => Expect::Ok,
+        ["expect", "pid", n] => Expect::Pid(parse_num(line, n)?),
+        ["expect", "blocked"] => Expect::Blocked,
+        ["expect", "reaped", n] => Expect::Reaped(parse_num(line, n)?),
+        ["expect", kind] if Error::KINDS.contains(kind) => Expect::Error(kind.to_string()),
+        ["expect", kind] => {
+            let kinds = Error::KINDS.join(", ");
+            return Err(err(line, format!("unknown expectation '{kind}' (ok, pid N, blocked, reaped N or {kinds})")));
+        }
+        _ => return Err(err(line, format!("unexpected words {words:?}"))),
+    }))
+}
+
+impl Script {
+    pub fn parse(src: &str) -> Result<Script, ParseError> {
+        let mut script = Script { memory_kb: 65536, pid_max: 32768, steps: Vec::new() };
+        for (i, raw) in src.lines().enumerate() {
+            let line = i + 1;
+            let text = raw.split('#').next().unwrap_or("").trim();
+            let words: Vec<&str> = text.split_whitespace().collect();
+            let Some((&command, rest)) = words.split_first() else { continue };
+            // Operands first, then an optional "expect ...".
+            let at = rest.iter().position(|&w| w == "expect").unwrap_or(rest.len());
+            let (args, expect) = rest.split_at(at);
+            let pid = |n: usize| -> Result<u32, ParseError> {
+                match args {
+                    [pid, ..] if args.len() == n => parse_num(line, pid),
+                    _ => Err(err(line, format!("'{command}' takes {n} operand(s)"))),
+                }
+            };
+            let op = match command {
+                "memory" | "pid_max" if !script.steps.is_empty() => {
+                    return Err(err(line, format!("'{command}' must come before any operation")));
+                }
+                "memory" => {
+                    script.memory_kb = parse_num(line, args.first().ok_or(err(line, "usage: memory <KB>"))?)?;
+                    continue;
+                }
+                "pid_max" => {
+                    script.pid_max = parse_num(line, args.first().ok_or(err(line, "usage: pid_max <N>"))?)?;
+                    continue;
+                }
+                "fork" => Op::Fork(pid(1)?),
+                "exec" => Op::Exec(pid(3)?, args[1].to_string(), parse_num(line, args[2])?),
+                "exit" => Op::Exit(pid(2)?, parse_num(line, args[1])?),
+                "wait" => match args {
+                    [p] => Op::Wait(parse_num(line, p)?, WaitFor::Any),
+                    [p, child] => Op::Wait(parse_num(line, p)?, WaitFor::Child(parse_num(line, child)?)),
+                    _ => return Err(err(line, "usage: wait <PID> [CHILD]")),
+                },
+                "admit" => Op::Admit(pid(1)?),
+                "dispatch" => Op::Dispatch(pid(1)?),
+                "preempt" => Op::Preempt(pid(1)?),
+                "block" => Op::Block(pid(1)?),
+                "wake" => Op::Wake(pid(1)?),
+                "switch" => Op::Switch(pid(1)?),
+                "ps" => Op::Ps,
+                "tree" => Op::Tree,
+                other => return Err(err(line, format!("unknown operation '{other}'"))),
+            };
+            let expect = parse_expect(line, expect)?;
+            if matches!(op, Op::Ps | Op::Tree) && (expect.is_some() || !args.is_empty()) {
+                return Err(err(line, format!("'{command}' takes no operands")));
+            }
+            script.steps.push(Step { line, text: text.to_string(), op, expect });
+        }
+        Ok(script)
+    }
+
+    fn apply(table: &mut ProcessTable, op: &Op) -> Result<Outcome, Error> {
+        let done = |result: Result<(), Error>| result.map(|()| Outcome::Done);
+        match *op {
+            Op::Fork(pid) => table.fork(pid).map(Outcome::Forked),
+            Op::Exec(pid, ref name, kb) => done(table.exec(pid, name, kb)),
+            Op::Exit(pid, code) => done(table.exit(pid, code)),
+            Op::Wait(pid, target) => table.wait(pid, target).map(Outcome::Waited),
+            Op::Admit(pid) => done(table.admit(pid)),
+            Op::Dispatch(pid) => done(table.dispatch(pid)),
+            Op::Preempt(pid) => done(table.preempt(pid)),
+            Op::Block(pid) => done(table.block(pid)),
+            Op::Wake(pid) => done(table.wake(pid)),
+            Op::Switch(pid) => done(table.switch_to(pid)),
+            Op::Ps | Op::Tree => Ok(Outcome::Done),
+        }
+    }
+
+    /// Runs every step against a freshly booted table, printing PASS/FAIL per
+    /// line, and returns the table as the script left it.
+    pub fn run(&self) -> (Report, ProcessTable) {
+        let mut table = ProcessTable::new(self.memory_kb, self.pid_max);
+        let mut report = Report { passed: 0, failed: 0 };
+        for step in &self.steps {
+            match step.op {
+                Op::Ps => println!("{}", view::ps(&table)),
+                Op::Tree => println!("{}", view::tree(&table)),
+                _ => {}
+            }
+            if matches!(step.op, Op::Ps | Op::Tree) {
+                continue; // nothing to check
+            }
+            let result = Self::apply(&mut table, &step.op);
+            let got = match &result {
+                Ok(Outcome::Done) => "ok".to_string(),
+                Ok(Outcome::Forked(pid)) => format!("pid {pid}"),
+                Ok(Outcome::Waited(Waited::Blocked)) => "blocked".to_string(),
+                Ok(Outcome::Waited(Waited::Reaped { pid, code })) => format!("reaped {pid} (exit {code})"),
+                Err(e) => format!("{}: {e}", e.kind()),
+            };
+            let pass = match (&step.expect, &result) {
+                (None | Some(Expect::Ok), Ok(_)) => true,
+                (Some(Expect::Pid(want)), Ok(Outcome::Forked(pid))) => want == pid,
+                (Some(Expect::Blocked), Ok(Outcome::Waited(Waited::Blocked))) => true,
+                (Some(Expect::Reaped(want)), Ok(Outcome::Waited(Waited::Reaped { pid, .. }))) => want == pid,
+                (Some(Expect::Error(kind)), Err(e)) => e.kind() == kind,
+                _ => false,
+            };
+            if pass {
+                report.passed += 1;
+                println!("  PASS  line {:3}: {:<44} => {got}", step.line, step.text);
+            } else {
+                report.failed += 1;
+                println!("  FAIL  line {:3}: {:<44} => got {got}", step.line, step.text);
+            }
+        }
+        (report, table)
+    }
+}
@@ -0,0 +1,71 @@
+// src/state.rs
+//
+// The process lifecycle, as in every OS textbook:
+//
+//          admit        dispatch          exit
+//   New ---------> Ready ------> Running ------> Zombie ------> Terminated
+//                    ^  <------    |               parent's wait
+//                    |  preempt    | block (I/O, wait)
+//                    +-- Blocked <-+
+//                        wake
+//
+// Zombie: the process has exited and its memory is gone, but the table entry
+// (pid, exit code) stays until the parent collects it with wait. Terminated:
+// collected; the entry is gone and the pid can be handed out again.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    New,
+    Ready,
+    Running,
+    Blocked,
+    Zombie,
+    Terminated,
+}
+
+impl State {
+    pub fn can_become(self, to: State) -> bool {
+        use State::*;
+        matches!(
+            (self, to),
+            (New, Ready)
+                | (Ready, Running)
+                | (Running, Ready)
+                | (Running, Blocked)
+                | (Blocked, Ready)
+                | (Running, Zombie)
+                | (Zombie, Terminated)
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            State::New => "new",
+            State::Ready => "ready",
+            State::Running => "running",
+            State::Blocked => "blocked",
+            State::Zombie => "zombie",
+            State::Terminated => "terminated",
+        }
+    }
+
+    /// The letter `ps` shows in its STAT column.
+    pub fn letter(self) -> char {
+        match self {
+            State::New => 'N',
+            State::Ready => 'R',
+            State::Running => 'R',
+            State::Blocked => 'S',
+            State::Zombie => 'Z',
+            State::Terminated => 'X',
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,360 @@
+// src/table.rs
+//
+// The kernel's process table: every process that exists, keyed by pid, each
+// entry a `module2_structs::Process` plus what the kernel needs around it.
+//
+// System calls (fork, exec, exit, wait) are made BY a process, so the caller
+// must be the one on the CPU. The scheduler's transitions (admit, dispatch,
+// preempt, block, wake) are made by the kernel and only have to be legal
+// according to state.rs. There is one CPU: at most one process is running,
+// and only that one has `running` set.
+//
+//   fork   the child is a copy: same name, same memory size (no copy-on-write
+//          here, so its memory is charged in full), parent = caller, state New
+//   exec   replaces the caller's program: new name, new memory size, same pid
+//   exit   memory is freed, the entry becomes a zombie holding the exit code,
+//          children are handed to init (pid 1), a parent blocked in wait for
+//          this child is woken
+//   wait   reaps a zombie child if there is one; otherwise blocks until one
+//          exits, after which the parent calls wait again to collect it
+//
+// init never exits and reaps its zombie children immediately, which is what
+// the real one spends most of its life doing.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use module2_structs::Process;
+
+use crate::state::State;
+
+pub const INIT: u32 = 1;
+const INIT_KB: u64 = 1024;
+
+/// Which children a blocked `wait` is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitFor {
+    Any,
+    Child(u32),
+}
+
+impl WaitFor {
+    fn matches(self, pid: u32) -> bool {
+        self == WaitFor::Any || self == WaitFor::Child(pid)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub process: Process,
+    pub ppid: u32,
+    pub name: String,
+    pub state: State,
+    pub exit_code: Option<i32>,
+    pub waiting_for: Option<WaitFor>,
+}
+
+impl Entry {
+    pub fn pid(&self) -> u32 {
+        self.process.pid
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waited {
+    Reaped { pid: u32, code: i32 },
+    Blocked,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    NoSuchProcess(u32),
+    IllegalTransition { pid: u32, from: State, to: State },
+    NotRunning { pid: u32, state: State },
+    CpuBusy { running: u32 },
+    NoChild(u32),
+    InitCannotExit,
+    OutOfMemory { requested_kb: u64, free_kb: u64 },
+    OutOfPids,
+}
+
+impl Error {
+    /// The variant name, which scripts use in `expect`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::NoSuchProcess(_) => "NoSuchProcess",
+            Error::IllegalTransition { .. } => "IllegalTransition",
+            Error::NotRunning { .. } => "NotRunning",
+            Error::CpuBusy { .. } => "CpuBusy",
+            Error::NoChild(_) => "NoChild",
+            Error::InitCannotExit => "InitCannotExit",
+            Error::OutOfMemory { .. } => "OutOfMemory",
+            Error::OutOfPids => "OutOfPids",
+        }
+    }
+
+    pub const KINDS: [&str; 8] = [
+        "NoSuchProcess",
+        "IllegalTransition",
+        "NotRunning",
+        "CpuBusy",
+        "NoChild",
+        "InitCannotExit",
+        "OutOfMemory",
+        "OutOfPids",
+    ];
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSuchProcess(pid) => write!(f, "no process {pid}"),
+            Error::IllegalTransition { pid, from, to } => write!(f, "process {pid} cannot go from {from} to {to}"),
+            Error::NotRunning { pid, state } => {
+                write!(f, "process {pid} is {state}; only the running process can make system calls")
+            }
+            Error::CpuBusy { running } => write!(f, "the CPU is busy running process {running}"),
+            Error::NoChild(pid) => write!(f, "process {pid} has no such child (ECHILD)"),
+            Error::InitCannotExit => write!(f, "init cannot exit"),
+            Error::OutOfMemory { requested_kb, free_kb } => {
+                write!(f, "out of memory: {requested_kb} KB requested, {free_kb} KB free (ENOMEM)")
+            }
+            Error::OutOfPids => write!(f, "no free pids (EAGAIN)"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub struct ProcessTable {
+    entries: BTreeMap<u32, Entry>,
+    next_pid: u32,
+    pid_max: u32,
+    memory_kb: u64,
+    terminated: Vec<Entry>, // reaped, oldest first
+}
+
+impl ProcessTable {
+    /// Boots with init (pid 1) running. Pids go up to `pid_max` and then
+    /// wrap around, skipping those in use, like Linux's /proc/sys/kernel/pid_max.
+    pub fn new(memory_kb: u64, pid_max: u32) -> Self {
+        let init = Entry {
+            process: Process { pid: INIT, memory_kb: INIT_KB, running: true },
+            ppid: 0,
+            name: "init".into(),
+            state: State::Running,
+            exit_code: None,
+            waiting_for: None,
+        };
+        ProcessTable {
+            entries: BTreeMap::from([(INIT, init)]),
+            next_pid: INIT + 1,
+            pid_max: pid_max.max(INIT + 1),
+            memory_kb: memory_kb.max(INIT_KB),
+            terminated: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&Entry> {
+        self.entries.get(&pid)
+    }
+
+    /// In pid order.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+
+    pub fn children(&self, pid: u32) -> Vec<u32> {
+        self.iter().filter(|e| e.ppid == pid).map(Entry::pid).collect()
+    }
+
+    pub fn running(&self) -> Option<u32> {
+        self.iter().find(|e| e.state == State::Running).map(Entry::pid)
+    }
+
+    /// Processes reaped so far, in the order they were reaped.
+    pub fn terminated(&self) -> &[Entry] {
+        &self.terminated
+    }
+
+    pub fn memory_kb(&self) -> u64 {
+        self.memory_kb
+    }
+
+    pub fn used_kb(&self) -> u64 {
+        self.iter().map(|e| e.process.memory_kb).sum()
+    }
+
+    pub fn free_kb(&self) -> u64 {
+        self.memory_kb - self.used_kb()
+    }
+
+    fn entry(&mut self, pid: u32) -> Result<&mut Entry, Error> {
+        self.entries.get_mut(&pid).ok_or(Error::NoSuchProcess(pid))
+    }
+
+    // Every state change goes through here, so the transition table and the
+    // `running` flag can never disagree. Each operation names the state it
+    // starts from: wake and preempt both lead to Ready, but from different places.
+    fn transition(&mut self, pid: u32, from: State, to: State) -> Result<(), Error> {
+        let entry = self.entry(pid)?;
+        if entry.state != from || !from.can_become(to) {
+            return Err(Error::IllegalTransition { pid, from: entry.state, to });
+        }
+        entry.state = to;
+        entry.process.running = to == State::Running;
+        Ok(())
+    }
+
+    fn caller(&mut self, pid: u32) -> Result<&mut Entry, Error> {
+        let entry = self.entry(pid)?;
+        if entry.state != State::Running {
+            return Err(Error::NotRunning { pid, state: entry.state });
+        }
+        Ok(entry)
+    }
+
+    fn charge(&self, requested_kb: u64, released_kb: u64) -> Result<(), Error> {
+        let free_kb = self.free_kb() + released_kb;
+        if requested_kb > free_kb {
+            return Err(Error::OutOfMemory { requested_kb, free_kb });
+        }
+        Ok(())
+    }
+
+    fn allocate_pid(&mut self) -> Result<u32, Error> {
+        for _ in INIT + 1..=self.pid_max {
+            let pid = self.next_pid;
+            self.next_pid = if pid >= self.pid_max { INIT + 1 } else { pid + 1 };
+            if !self.entries.contains_key(&pid) {
+                return Ok(pid);
+            }
+        }
+        Err(Error::OutOfPids)
+    }
+
+    pub fn admit(&mut self, pid: u32) -> Result<(), Error> {
+        self.transition(pid, State::New, State::Ready)
+    }
+
+    pub fn dispatch(&mut self, pid: u32) -> Result<(), Error> {
+        let state = self.get(pid).ok_or(Error::NoSuchProcess(pid))?.state;
+        if let Some(running) = self.running()
+            && state == State::Ready
+        {
+            return Err(Error::CpuBusy { running });
+        }
+        self.transition(pid, State::Ready, State::Running)
+    }
+
+    pub fn preempt(&mut self, pid: u32) -> Result<(), Error> {
+        self.transition(pid, State::Running, State::Ready)
+    }
+
+    pub fn block(&mut self, pid: u32) -> Result<(), Error> {
+        self.transition(pid, State::Running, State::Blocked)
+    }
+
+    /// A blocked process's I/O finished (or the child it waits for exited).
+    pub fn wake(&mut self, pid: u32) -> Result<(), Error> {
+        self.transition(pid, State::Blocked, State::Ready)?;
+        self.entry(pid)?.waiting_for = None;
+        Ok(())
+    }
+
+    /// Preempt whoever is running and dispatch `pid`: a context switch.
+    pub fn switch_to(&mut self, pid: u32) -> Result<(), Error> {
+        match self.running() {
+            Some(running) if running == pid => return Ok(()),
+            Some(running) => {
+                // Check first, so a failed switch leaves the old process running.
+                let state = self.get(pid).ok_or(Error::NoSuchProcess(pid))?.state;
+                if state != State::Ready {
+                    return Err(Error::IllegalTransition { pid, from: state, to: State::Running });
+                }
+                self.preempt(running)?;
+            }
+            None => {}
+        }
+        self.dispatch(pid)
+    }
+
+    pub fn fork(&mut self, parent: u32) -> Result<u32, Error> {
+        let entry = self.caller(parent)?;
+        let (name, memory_kb) = (entry.name.clone(), entry.process.memory_kb);
+        self.charge(memory_kb, 0)?;
+        let pid = self.allocate_pid()?;
+        let child = Entry {
+            process: Process { pid, memory_kb, running: false },
+            ppid: parent,
+            name,
+            state: State::New,
+            exit_code: None,
+            waiting_for: None,
+        };
+        self.entries.insert(pid, child);
+        Ok(pid)
+    }
+
+    pub fn exec(&mut self, pid: u32, name: &str, memory_kb: u64) -> Result<(), Error> {
+        let old_kb = self.caller(pid)?.process.memory_kb;
+        self.charge(memory_kb, old_kb)?;
+        let entry = self.entry(pid)?;
+        entry.name = name.into();
+        entry.process.memory_kb = memory_kb;
+        Ok(())
+    }
+
+    pub fn exit(&mut self, pid: u32, code: i32) -> Result<(), Error> {
+        if pid == INIT {
+            return Err(Error::InitCannotExit);
+        }
+        self.caller(pid)?;
+        self.transition(pid, State::Running, State::Zombie)?;
+        let entry = self.entry(pid)?;
+        entry.process.memory_kb = 0;
+        entry.exit_code = Some(code);
+        let parent = entry.ppid;
+
+        for child in self.children(pid) {
+            self.entry(child)?.ppid = INIT;
+        }
+        let waiting = self.get(parent).and_then(|p| p.waiting_for);
+        if waiting.is_some_and(|w| w.matches(pid)) {
+            self.wake(parent)?;
+        }
+        self.init_reaps();
+        Ok(())
+    }
+
+    pub fn wait(&mut self, pid: u32, target: WaitFor) -> Result<Waited, Error> {
+        self.caller(pid)?;
+        let children: Vec<&Entry> = self.iter().filter(|e| e.ppid == pid && target.matches(e.pid())).collect();
+        if children.is_empty() {
+            return Err(Error::NoChild(pid));
+        }
+        if let Some(zombie) = children.iter().find(|e| e.state == State::Zombie).map(|e| e.pid()) {
+            let code = self.reap(zombie)?;
+            return Ok(Waited::Reaped { pid: zombie, code });
+        }
+        self.transition(pid, State::Running, State::Blocked)?;
+        self.entry(pid)?.waiting_for = Some(target);
+        Ok(Waited::Blocked)
+    }
+
+    fn reap(&mut self, pid: u32) -> Result<i32, Error> {
+        self.transition(pid, State::Zombie, State::Terminated)?;
+        let entry = self.entries.remove(&pid).ok_or(Error::NoSuchProcess(pid))?;
+        let code = entry.exit_code.unwrap_or(0);
+        self.terminated.push(entry);
+        Ok(code)
+    }
+
+    fn init_reaps(&mut self) {
+        let zombies: Vec<u32> =
+            self.iter().filter(|e| e.ppid == INIT && e.state == State::Zombie).map(Entry::pid).collect();
+        for pid in zombies {
+            let _ = self.reap(pid);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// src/view.rs
+//
+// The table the way `ps -ef` and `pstree` show it.
+
+use std::fmt::Write;
+
+use crate::table::{Entry, ProcessTable, INIT};
+
+fn state(e: &Entry) -> String {
+    match e.exit_code {
+        Some(code) => format!("{} (exit {code})", e.state),
+        None => e.state.to_string(),
+    }
+}
+
+pub fn ps(table: &ProcessTable) -> String {
+    let mut out = format!("  {:>5} {:>5}  {:<17} {:>8}  CMD\n", "PID", "PPID", "STAT", "MEM KB");
+    for e in table.iter() {
+        let _ = writeln!(
+            out,
+            "  {:>5} {:>5}  {} {:<15} {:>8}  {}",
+            e.pid(),
+            e.ppid,
+            e.state.letter(),
+            state(e),
+            e.process.memory_kb,
+            e.name
+        );
+    }
+    let _ = write!(out, "  memory: {} of {} KB used, {} KB free", table.used_kb(), table.memory_kb(), table.free_kb());
+    out
+}
+
+pub fn tree(table: &ProcessTable) -> String {
+    let mut out = String::new();
+    node(table, INIT, "", "", &mut out);
+    out.pop();
+    out
+}
+
+fn node(table: &ProcessTable, pid: u32, first: &str, rest: &str, out: &mut String) {
+    let Some(e) = table.get(pid) else { return };
+    let _ = writeln!(out, "{first}{}({pid}) {} {} KB", e.name, state(e), e.process.memory_kb);
+    let children = table.children(pid);
+    for (i, &child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        node(table, child, &format!("{rest}{branch}"), &format!("{rest}{indent}"), out);
+    }
+}
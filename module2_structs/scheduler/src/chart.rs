@@ -0,0 +1,90 @@
+// src/chart.rs
+//
+// Text output in the shape exam answers use.
+//
+//   gantt     |   P1   | P2 |     P3     |  -  | P1 |
+//             0        4    6            12    15   17
+//
+//   timeline  P1 ####....oooo##        one row per process, one column per tick:
+//             P2     ##..##            # running  . ready  o I/O
+//
+//   table     per-process arrival, CPU, I/O, finish, turnaround, waiting and
+//             response, with averages
+
+use std::fmt::Write;
+
+use crate::sim::Report;
+
+const WIDTH: usize = 100;
+
+/// Each tick is `scale` characters wide, stretched where a label or time
+/// would not fit. Wraps at about 100 columns.
+pub fn gantt(report: &Report, scale: usize) -> String {
+    let mut out = String::new();
+    let (mut bar, mut axis) = (String::new(), String::new());
+    for slice in &report.gantt {
+        let label = slice.task.map_or("-", |t| report.labels[t].as_str());
+        let start = slice.start.to_string();
+        let width = ((slice.end - slice.start) as usize * scale).max(label.len().max(start.len()) + 1);
+        if !bar.is_empty() && bar.len() + width + 1 > WIDTH {
+            let _ = writeln!(out, "{bar}|\n{axis}{}\n", slice.start);
+            bar.clear();
+            axis.clear();
+        }
+        let _ = write!(bar, "|{label:^w$}", w = width - 1);
+        let _ = write!(axis, "{start:<width$}");
+    }
+    let _ = write!(out, "{bar}|\n{axis}{}", report.makespan());
+    out
+}
+
+pub fn timeline(report: &Report) -> String {
+    let pad = report.labels.iter().map(String::len).max().unwrap_or(0);
+    let mut out = String::new();
+    let mut ruler = String::new();
+    for t in (0..report.makespan() as usize).step_by(5) {
+        let _ = write!(ruler, "{t:<5}");
+    }
+    let _ = writeln!(out, "{:pad$}  {}", "", ruler.trim_end());
+    for (label, row) in report.labels.iter().zip(&report.timeline) {
+        let _ = writeln!(out, "{label:pad$}  {}", row.trim_end());
+    }
+    out.push_str("(# running, . ready, o I/O)");
+    out
+}
+
+pub fn table(report: &Report) -> String {
+    let mut out = format!(
+        "  {:<6} {:>7} {:>5} {:>5} {:>7} {:>11} {:>8} {:>9}\n",
+        "proc", "arrival", "cpu", "io", "finish", "turnaround", "waiting", "response"
+    );
+    for (label, s) in report.labels.iter().zip(&report.stats) {
+        let _ = writeln!(
+            out,
+            "  {label:<6} {:>7} {:>5} {:>5} {:>7} {:>11} {:>8} {:>9}",
+            s.arrival,
+            s.cpu,
+            s.io,
+            s.finish,
+            s.turnaround(),
+            s.waiting,
+            s.response()
+        );
+    }
+    let _ = write!(
+        out,
+        "  {:<6} {:>7} {:>5} {:>5} {:>7} {:>11.2} {:>8.2} {:>9.2}\n  makespan {}, CPU busy {:.0}%, {} context switches",
+        "avg",
+        "",
+        "",
+        "",
+        "",
+        report.average(|s| s.turnaround()),
+        report.average(|s| s.waiting),
+        report.average(|s| s.response()),
+        report.makespan(),
+        report.utilization() * 100.0,
+        report.switches
+    );
+    out
+}
@@ -0,0 +1,26 @@
+// src/lib.rs
+//
+// CPU scheduling: with more runnable processes than CPUs, the kernel decides
+// who runs and for how long. No policy wins on every metric:
+//
+//   throughput / turnaround   favour short jobs (SJF, SRTF)
+//   response time             favour whoever has not run yet (RR, MLFQ, CFS)
+//   fairness                  share the CPU by weight (CFS)
+//   overhead                  switch as rarely as possible (FCFS)
+//
+// The workload is a list of `module2_structs::Process`es, each with an
+// arrival time, a priority and alternating CPU and I/O bursts.
+//
+//   workload.rs  the workload file format and parser
+//   policy.rs    FCFS, SJF, SRTF, priority, RR, MLFQ and CFS-like schedulers
+//   sim.rs       the simulated single-CPU machine and the metrics
+//   chart.rs     Gantt charts, per-process timelines and metric tables
+
+pub mod chart;
+pub mod policy;
+pub mod sim;
+pub mod workload;
+
+pub use policy::{Params, Policy, Scheduler};
+pub use sim::{simulate, Report};
+pub use workload::{parse, Task};
@@ -0,0 +1,178 @@
+// src/main.rs
+//
+// `scheduler run <policy|all> [--workload NAME|FILE] [--quantum N] [--quanta A,B,C]
+//                [--boost N|never] [--latency N] [--min-gran N] [--timeline]`
+//     Gantt chart and per-process metrics for one policy, or for each in turn
+// `scheduler compare [--workload NAME|FILE] [same options]`
+//     average turnaround, waiting and response time of every policy side by side
+// `scheduler workloads`
+//     the built-in workloads and their files
+// No arguments: a tour through the built-in workloads.
+//
+// Policies: fcfs sjf srtf priority priority-p rr mlfq cfs
+// Built-in workloads (see workloads/): convoy srtf priority mixed (default);
+// anything else is read as a workload file.
+
+use std::process::ExitCode;
+
+use scheduler::{chart, parse, simulate, Params, Policy, Report, Task};
+
+const WORKLOADS: [(&str, &str); 4] = [
+    ("convoy", include_str!("../workloads/convoy.txt")),
+    ("srtf", include_str!("../workloads/srtf.txt")),
+    ("priority", include_str!("../workloads/priority.txt")),
+    ("mixed", include_str!("../workloads/mixed.txt")),
+];
+
+fn load(name: &str) -> Result<Vec<Task>, String> {
+    let text = match WORKLOADS.iter().find(|(n, _)| *n == name) {
+        Some((_, text)) => text.to_string(),
+        None => std::fs::read_to_string(name).map_err(|e| format!("{name}: {e}"))?,
+    };
+    parse(&text).map_err(|e| format!("{name}: {e}"))
+}
+
+fn number(flag: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{flag} takes a positive number, got '{value}'")),
+    }
+}
+
+struct Options {
+    workload: String,
+    params: Params,
+    timeline: bool,
+}
+
+fn parse_options(args: &[&str]) -> Result<Options, String> {
+    let mut options = Options { workload: "mixed".into(), params: Params::default(), timeline: false };
+    let mut it = args.iter();
+    while let Some(&flag) = it.next() {
+        if flag == "--timeline" {
+            options.timeline = true;
+            continue;
+        }
+        let value = *it.next().ok_or(format!("{flag} needs a value"))?;
+        let p = &mut options.params;
+        match flag {
+            "--workload" => options.workload = value.into(),
+            "--quantum" => p.quantum = number(flag, value)?,
+            "--quanta" => p.quanta = value.split(',').map(|q| number(flag, q)).collect::<Result<_, _>>()?,
+            "--boost" if value == "never" => p.boost = None,
+            "--boost" => p.boost = Some(number(flag, value)?),
+            "--latency" => p.latency = number(flag, value)?,
+            "--min-gran" => p.min_granularity = number(flag, value)?,
+            _ => return Err(format!("unknown argument '{flag}'")),
+        }
+    }
+    Ok(options)
+}
+
+fn show(report: &Report, timeline: bool) {
+    let scale = if report.makespan() <= 45 { 2 } else { 1 };
+    println!("== {} ==\n{}\n", report.policy, chart::gantt(report, scale));
+    if timeline {
+        println!("{}\n", chart::timeline(report));
+    }
+    println!("{}\n", chart::table(report));
+}
+
+fn run_cmd(policy: &str, args: &[&str]) -> Result<(), String> {
+    let options = parse_options(args)?;
+    let tasks = load(&options.workload)?;
+    let policies = match policy {
+        "all" => Policy::all(&options.params),
+        name => vec![Policy::parse(name, &options.params)
+            .ok_or(format!("unknown policy '{name}', expected one of: {} or all", Policy::NAMES.join(" ")))?],
+    };
+    for policy in &policies {
+        show(&simulate(&tasks, policy), options.timeline);
+    }
+    Ok(())
+}
+
+fn compare(tasks: &[Task], params: &Params) {
+    println!(
+        "  {:<24} {:>11} {:>8} {:>9} {:>13} {:>9} {:>5}",
+        "policy", "turnaround", "waiting", "response", "max response", "switches", "busy"
+    );
+    for policy in Policy::all(params) {
+        let r = simulate(tasks, &policy);
+        println!(
+            "  {:<24} {:>11.2} {:>8.2} {:>9.2} {:>13} {:>9} {:>4.0}%",
+            r.policy,
+            r.average(|s| s.turnaround()),
+            r.average(|s| s.waiting),
+            r.average(|s| s.response()),
+            r.stats.iter().map(|s| s.response()).max().unwrap_or(0),
+            r.switches,
+            r.utilization() * 100.0
+        );
+    }
+    println!("  (averages over all processes, in ticks)");
+}
+
+fn compare_cmd(args: &[&str]) -> Result<(), String> {
+    let options = parse_options(args)?;
+    compare(&load(&options.workload)?, &options.params);
+    Ok(())
+}
+
+fn workloads() {
+    for (name, text) in WORKLOADS {
+        let first = text.lines().next().unwrap_or("").trim_start_matches("# ");
+        let summary = first.split(['.', ':']).next().unwrap_or(first);
+        let processes = parse(text).map_or(0, |tasks| tasks.len());
+        println!("  {name:<9} {:<24} {processes} processes  {summary}", format!("workloads/{name}.txt"));
+    }
+}
+
+fn tour() -> Result<(), String> {
+    let params = Params::default();
+    println!("CPU scheduling on one simulated CPU; times are in ticks.\n");
+
+    println!("--- convoy: one long job in front of two short ones ---\n");
+    let convoy = load("convoy")?;
+    for name in ["fcfs", "sjf", "rr"] {
+        show(&simulate(&convoy, &Policy::parse(name, &params).expect("built-in policy")), false);
+    }
+
+    println!("--- srtf: preempting for a shorter job ---\n");
+    let srtf = load("srtf")?;
+    show(&simulate(&srtf, &Policy::Srtf), false);
+
+    println!("--- mixed: batch jobs and interactive jobs that do I/O ---\n");
+    let mixed = load("mixed")?;
+    show(&simulate(&mixed, &Policy::Fcfs), true);
+    show(&simulate(&mixed, &Policy::parse("mlfq", &params).expect("built-in policy")), true);
+    compare(&mixed, &params);
+
+    println!("\nTry: scheduler run cfs --timeline    scheduler compare --workload convoy --quantum 2");
+    Ok(())
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        [] => tour(),
+        ["run", policy, rest @ ..] => run_cmd(policy, rest),
+        ["compare", rest @ ..] => compare_cmd(rest),
+        ["workloads"] => {
+            workloads();
+            Ok(())
+        }
+        _ => Err("usage: scheduler [run <policy|all> [options] | compare [options] | workloads]".into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,351 @@
+// src/policy.rs
+//
+// The schedulers. Each one owns its ready set and answers three questions the
+// simulator asks every tick: who runs next (pick), should the running process
+// be taken off the CPU (preempt), and what changes now that it ran (ran).
+//
+//   fcfs        first come first served, never preempts
+//   sjf         shortest next CPU burst, never preempts
+//   srtf        shortest remaining time first: SJF that preempts
+//   priority    lowest priority number first, never preempts
+//   priority-p  the same, preempting when a more important process arrives
+//   rr          round-robin: FCFS, but nobody runs longer than one quantum
+//   mlfq        multi-level feedback queue: using a whole quantum demotes
+//   cfs         Linux-like: run whoever has had the least weighted CPU time
+//
+// SJF and SRTF know every burst length in advance, which no real kernel does;
+// they are the yardstick the others are measured against. Ties go to whoever
+// has been ready longest.
+
+use std::collections::VecDeque;
+
+/// What a scheduler may know about a process.
+#[derive(Debug, Clone)]
+pub struct Proc {
+    pub priority: i32,
+    pub burst: u32,     // length of the current CPU burst
+    pub remaining: u32, // what is left of it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Arrived,
+    Woke, // finished I/O
+    Preempted,
+}
+
+pub trait Scheduler {
+    /// Called at the start of every tick, before arrivals.
+    fn clock(&mut self, _now: u32) {}
+    /// `task` became ready.
+    fn enqueue(&mut self, task: usize, event: Event, procs: &[Proc]);
+    /// Take the next process off the ready set; None leaves the CPU idle.
+    fn pick(&mut self, procs: &[Proc]) -> Option<usize>;
+    /// `task` just ran for one tick.
+    fn ran(&mut self, _task: usize, _procs: &[Proc]) {}
+    /// Take `running` off the CPU before the next tick?
+    fn preempt(&self, running: usize, procs: &[Proc]) -> bool;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Policy {
+    Fcfs,
+    Sjf,
+    Srtf,
+    Priority { preemptive: bool },
+    RoundRobin { quantum: u32 },
+    Mlfq { quanta: Vec<u32>, boost: Option<u32> },
+    Cfs { latency: u32, min_granularity: u32 },
+}
+
+/// Knobs for the policies that have them.
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub quantum: u32,
+    pub quanta: Vec<u32>, // MLFQ allotment per level, top first
+    pub boost: Option<u32>,
+    pub latency: u32,
+    pub min_granularity: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { quantum: 4, quanta: vec![2, 4, 8], boost: Some(50), latency: 8, min_granularity: 2 }
+    }
+}
+
+impl Policy {
+    pub const NAMES: [&str; 8] = ["fcfs", "sjf", "srtf", "priority", "priority-p", "rr", "mlfq", "cfs"];
+
+    pub fn parse(name: &str, params: &Params) -> Option<Policy> {
+        Some(match name {
+            "fcfs" => Policy::Fcfs,
+            "sjf" => Policy::Sjf,
+            "srtf" => Policy::Srtf,
+            "priority" => Policy::Priority { preemptive: false },
+            "priority-p" => Policy::Priority { preemptive: true },
+            "rr" => Policy::RoundRobin { quantum: params.quantum },
+            "mlfq" => Policy::Mlfq { quanta: params.quanta.clone(), boost: params.boost },
+            "cfs" => Policy::Cfs { latency: params.latency, min_granularity: params.min_granularity },
+            _ => return None,
+        })
+    }
+
+    pub fn all(params: &Params) -> Vec<Policy> {
+        Policy::NAMES.iter().filter_map(|name| Policy::parse(name, params)).collect()
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Policy::Fcfs => "fcfs".into(),
+            Policy::Sjf => "sjf".into(),
+            Policy::Srtf => "srtf".into(),
+            Policy::Priority { preemptive: false } => "priority".into(),
+            Policy::Priority { preemptive: true } => "priority-p".into(),
+            Policy::RoundRobin { quantum } => format!("rr q={quantum}"),
+            Policy::Mlfq { quanta, boost } => {
+                let quanta: Vec<String> = quanta.iter().map(|q| q.to_string()).collect();
+                let boost = boost.map_or("never".into(), |b| b.to_string());
+                format!("mlfq q={} boost={boost}", quanta.join(","))
+            }
+            Policy::Cfs { latency, min_granularity } => format!("cfs lat={latency} gran={min_granularity}"),
+        }
+    }
+
+    pub fn scheduler(&self) -> Box<dyn Scheduler> {
+        match self {
+            Policy::Fcfs => Box::new(Keyed::new(Key::Fifo, false)),
+            Policy::Sjf => Box::new(Keyed::new(Key::Burst, false)),
+            Policy::Srtf => Box::new(Keyed::new(Key::Remaining, true)),
+            Policy::Priority { preemptive } => Box::new(Keyed::new(Key::Priority, *preemptive)),
+            Policy::RoundRobin { quantum } => Box::new(RoundRobin { ready: VecDeque::new(), quantum: *quantum, used: 0 }),
+            Policy::Mlfq { quanta, boost } => Box::new(Mlfq::new(quanta, *boost)),
+            Policy::Cfs { latency, min_granularity } => Box::new(Cfs::new(*latency, *min_granularity)),
+        }
+    }
+}
+
+// FCFS, SJF, SRTF and priority are one scheduler with a different key: run
+// the ready process with the smallest key, oldest first on ties.
+#[derive(Clone, Copy)]
+enum Key {
+    Fifo,
+    Burst,
+    Remaining,
+    Priority,
+}
+
+struct Keyed {
+    ready: Vec<usize>, // in the order they became ready
+    key: Key,
+    preemptive: bool,
+}
+
+impl Keyed {
+    fn new(key: Key, preemptive: bool) -> Self {
+        Keyed { ready: Vec::new(), key, preemptive }
+    }
+
+    fn key(&self, p: &Proc) -> i64 {
+        match self.key {
+            Key::Fifo => 0,
+            Key::Burst => p.burst as i64,
+            Key::Remaining => p.remaining as i64,
+            Key::Priority => p.priority as i64,
+        }
+    }
+}
+
+impl Scheduler for Keyed {
+    fn enqueue(&mut self, task: usize, _event: Event, _procs: &[Proc]) {
+        self.ready.push(task);
+    }
+
+    fn pick(&mut self, procs: &[Proc]) -> Option<usize> {
+        // min_by_key returns the first of equal minimums: the oldest.
+        let pos = (0..self.ready.len()).min_by_key(|&pos| self.key(&procs[self.ready[pos]]))?;
+        Some(self.ready.remove(pos))
+    }
+
+    fn preempt(&self, running: usize, procs: &[Proc]) -> bool {
+        let current = self.key(&procs[running]);
+        self.preemptive && self.ready.iter().any(|&i| self.key(&procs[i]) < current)
+    }
+}
+
+// A process whose quantum runs out goes to the back of the queue, behind
+// anyone who arrived in that same tick.
+struct RoundRobin {
+    ready: VecDeque<usize>,
+    quantum: u32,
+    used: u32, // by the running process
+}
+
+impl Scheduler for RoundRobin {
+    fn enqueue(&mut self, task: usize, _event: Event, _procs: &[Proc]) {
+        self.ready.push_back(task);
+    }
+
+    fn pick(&mut self, _procs: &[Proc]) -> Option<usize> {
+        self.used = 0;
+        self.ready.pop_front()
+    }
+
+    fn ran(&mut self, _task: usize, _procs: &[Proc]) {
+        self.used += 1;
+    }
+
+    fn preempt(&self, _running: usize, _procs: &[Proc]) -> bool {
+        self.used >= self.quantum
+    }
+}
+
+// MLFQ with the rules from OSTEP:
+//   1. a higher level always runs before a lower one (and preempts it)
+//   2. round-robin within a level
+//   3. new processes start at the top
+//   4. a process that has used up its level's allotment moves down one level,
+//      however many times it gave up the CPU on the way (so doing I/O just
+//      before the quantum ends does not keep a process at the top)
+//   5. every `boost` ticks everybody goes back to the top, so long-running
+//      processes starved by a stream of interactive ones get CPU again
+struct Mlfq {
+    quanta: Vec<u32>,
+    boost: Option<u32>,
+    queues: Vec<VecDeque<usize>>,
+    level: Vec<usize>,
+    used: Vec<u32>, // of the allotment at the current level
+}
+
+impl Mlfq {
+    fn new(quanta: &[u32], boost: Option<u32>) -> Self {
+        let quanta = if quanta.is_empty() { vec![1] } else { quanta.to_vec() };
+        Mlfq { queues: vec![VecDeque::new(); quanta.len()], quanta, boost, level: Vec::new(), used: Vec::new() }
+    }
+
+    fn used_up(&self, task: usize) -> bool {
+        self.used[task] >= self.quanta[self.level[task]]
+    }
+}
+
+impl Scheduler for Mlfq {
+    fn clock(&mut self, now: u32) {
+        if self.boost.is_some_and(|b| now > 0 && now.is_multiple_of(b)) {
+            let waiting: Vec<usize> = self.queues.iter_mut().flat_map(|q| q.drain(..)).collect();
+            self.queues[0].extend(waiting);
+            self.level.fill(0);
+            self.used.fill(0);
+        }
+    }
+
+    fn enqueue(&mut self, task: usize, _event: Event, procs: &[Proc]) {
+        if self.level.len() < procs.len() {
+            self.level.resize(procs.len(), 0);
+            self.used.resize(procs.len(), 0);
+        }
+        if self.used_up(task) {
+            self.level[task] = (self.level[task] + 1).min(self.quanta.len() - 1);
+            self.used[task] = 0;
+        }
+        self.queues[self.level[task]].push_back(task);
+    }
+
+    fn pick(&mut self, _procs: &[Proc]) -> Option<usize> {
+        self.queues.iter_mut().find_map(|q| q.pop_front())
+    }
+
+    fn ran(&mut self, task: usize, _procs: &[Proc]) {
+        self.used[task] += 1;
+    }
+
+    fn preempt(&self, running: usize, _procs: &[Proc]) -> bool {
+        self.used_up(running) || self.queues[..self.level[running]].iter().any(|q| !q.is_empty())
+    }
+}
+
+// Linux's weight for each nice value, -20 to 19: every step is about 1.25x,
+// so one nice level is roughly 10% more or less CPU than a neighbour's.
+const NICE_TO_WEIGHT: [u64; 40] = [
+    88761, 71755, 56483, 46273, 36291, 29154, 23254, 18705, 14949, 11916, //
+    9548, 7620, 6100, 4904, 3906, 3121, 2501, 1991, 1586, 1277, //
+    1024, 820, 655, 526, 423, 335, 272, 215, 172, 137, //
+    110, 87, 70, 56, 45, 36, 29, 23, 18, 15,
+];
+
+// Virtual runtime is kept in thousandths of a tick of nice-0 CPU time.
+const MILLI: u64 = 1000;
+
+fn weight(p: &Proc) -> u64 {
+    NICE_TO_WEIGHT[(p.priority.clamp(-20, 19) + 20) as usize]
+}
+
+// A CFS-like scheduler. Every process has a virtual runtime that grows as it
+// runs, slower the heavier (lower nice) it is; the one with the smallest
+// vruntime runs next. Its slice is its weight's share of `latency`, but never
+// less than `min_granularity`. A process is preempted when its slice is used
+// up, or when a ready process is behind it by more than `min_granularity`.
+//
+// A new process starts at min_vruntime, not 0, or it would run alone until it
+// caught up. A process waking from I/O gets at most half a latency of credit:
+// enough to run soon, which is what makes interactive processes feel fast,
+// but not the whole time it slept.
+struct Cfs {
+    latency: u64,
+    min_granularity: u64,
+    ready: Vec<usize>,
+    vruntime: Vec<u64>,
+    min_vruntime: u64,
+    slice: u64,
+    used: u64,
+}
+
+impl Cfs {
+    fn new(latency: u32, min_granularity: u32) -> Self {
+        Cfs {
+            latency: latency as u64,
+            min_granularity: min_granularity as u64,
+            ready: Vec::new(),
+            vruntime: Vec::new(),
+            min_vruntime: 0,
+            slice: 0,
+            used: 0,
+        }
+    }
+}
+
+impl Scheduler for Cfs {
+    fn enqueue(&mut self, task: usize, event: Event, procs: &[Proc]) {
+        if self.vruntime.len() < procs.len() {
+            self.vruntime.resize(procs.len(), 0);
+        }
+        let v = &mut self.vruntime[task];
+        match event {
+            Event::Arrived => *v = self.min_vruntime,
+            Event::Woke => *v = (*v).max(self.min_vruntime.saturating_sub(self.latency * MILLI / 2)),
+            Event::Preempted => {}
+        }
+        self.ready.push(task);
+    }
+
+    fn pick(&mut self, procs: &[Proc]) -> Option<usize> {
+        let pos = (0..self.ready.len()).min_by_key(|&pos| self.vruntime[self.ready[pos]])?;
+        let task = self.ready.remove(pos);
+        let total: u64 = self.ready.iter().map(|&i| weight(&procs[i])).sum::<u64>() + weight(&procs[task]);
+        self.slice = (self.latency * weight(&procs[task]) / total).max(self.min_granularity).max(1);
+        self.used = 0;
+        self.min_vruntime = self.min_vruntime.max(self.vruntime[task]);
+        Some(task)
+    }
+
+    fn ran(&mut self, task: usize, procs: &[Proc]) {
+        self.vruntime[task] += MILLI * 1024 / weight(&procs[task]);
+        self.used += 1;
+        let leftmost = self.ready.iter().map(|&i| self.vruntime[i]).min().unwrap_or(u64::MAX);
+        self.min_vruntime = self.min_vruntime.max(self.vruntime[task].min(leftmost));
+    }
+
+    fn preempt(&self, running: usize, _procs: &[Proc]) -> bool {
+        let behind = |&i: &usize| self.vruntime[i] + self.min_granularity * MILLI < self.vruntime[running];
+        !self.ready.is_empty() && (self.used >= self.slice || self.ready.iter().any(behind))
+    }
+}
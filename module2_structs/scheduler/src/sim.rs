@@ -0,0 +1,213 @@
+// src/sim.rs
+//
+// The simulated machine: one CPU and a clock that ticks in whole time units.
+// Every process is in exactly one state at every tick:
+//
+//   not arrived -> ready <-> running -> I/O -> ready ... -> done
+//
+// I/O needs no CPU and every process has its own device, so any number of
+// processes can do I/O at once. Within a tick the order is fixed: arrivals
+// and I/O completions join the ready set first, then the scheduler may
+// preempt the running process, then it picks who runs. So a process arriving
+// at t=4 gets in line ahead of one whose quantum ran out at t=4, the usual
+// convention in exam answers.
+//
+// Per process:
+//   turnaround = finish - arrival        (how long the whole job took)
+//   waiting    = ticks spent ready       (turnaround - cpu - io)
+//   response   = first run - arrival     (how long until anything happened)
+
+use module2_structs::Process;
+
+use crate::policy::{Event, Policy, Proc, Scheduler};
+use crate::workload::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    NotArrived,
+    Ready,
+    Running,
+    Io,
+    Done,
+}
+
+impl State {
+    // For the per-process timeline.
+    fn symbol(self) -> char {
+        match self {
+            State::Running => '#',
+            State::Ready => '.',
+            State::Io => 'o',
+            State::NotArrived | State::Done => ' ',
+        }
+    }
+}
+
+/// A stretch of consecutive ticks on the CPU; `task` None means idle.
+#[derive(Debug, Clone, Copy)]
+pub struct Slice {
+    pub task: Option<usize>,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub arrival: u32,
+    pub cpu: u32,
+    pub io: u32,
+    pub first_run: u32,
+    pub finish: u32,
+    pub waiting: u32,
+}
+
+impl Stats {
+    pub fn turnaround(&self) -> u32 {
+        self.finish - self.arrival
+    }
+
+    pub fn response(&self) -> u32 {
+        self.first_run - self.arrival
+    }
+}
+
+pub struct Report {
+    pub policy: String,
+    pub labels: Vec<String>,
+    pub stats: Vec<Stats>,
+    pub gantt: Vec<Slice>,
+    pub timeline: Vec<String>, // one char per tick per process
+    pub switches: u32,         // the CPU went from one process to another
+    pub busy: u32,
+}
+
+impl Report {
+    pub fn makespan(&self) -> u32 {
+        self.gantt.last().map_or(0, |s| s.end)
+    }
+
+    pub fn utilization(&self) -> f64 {
+        self.busy as f64 / self.makespan().max(1) as f64
+    }
+
+    pub fn average(&self, metric: impl Fn(&Stats) -> u32) -> f64 {
+        self.stats.iter().map(|s| metric(s) as f64).sum::<f64>() / self.stats.len() as f64
+    }
+}
+
+pub fn simulate(tasks: &[Task], policy: &Policy) -> Report {
+    run(tasks, policy.name(), policy.scheduler().as_mut())
+}
+
+/// `simulate` with any scheduler, for trying out your own.
+pub fn run(tasks: &[Task], name: String, scheduler: &mut dyn Scheduler) -> Report {
+    let n = tasks.len();
+    let mut procs: Vec<Proc> =
+        tasks.iter().map(|t| Proc { priority: t.priority, burst: t.bursts[0], remaining: t.bursts[0] }).collect();
+    // The processes themselves: exactly one has `running` set while the CPU is busy.
+    let mut table: Vec<Process> = tasks.iter().map(|t| Process { running: false, ..t.process.clone() }).collect();
+    let mut state = vec![State::NotArrived; n];
+    let mut burst = vec![0usize; n]; // index of the current burst
+    let mut io_until = vec![0u32; n];
+    let mut first_run = vec![None; n];
+    let mut stats: Vec<Stats> = tasks
+        .iter()
+        .map(|t| Stats { arrival: t.arrival, cpu: t.cpu_total(), io: t.io_total(), first_run: 0, finish: 0, waiting: 0 })
+        .collect();
+    let mut timeline = vec![String::new(); n];
+    let mut ticks: Vec<Option<usize>> = Vec::new();
+    let (mut running, mut last) = (None, None);
+    let (mut switches, mut busy, mut done) = (0, 0, 0);
+    // Nothing can take longer than everything back to back after the last arrival.
+    // Summed in u64: each number fits in a u32, their total need not.
+    let limit = tasks.iter().map(|t| u64::from(t.arrival)).max().unwrap_or(0)
+        + tasks.iter().flat_map(|t| &t.bursts).map(|&b| u64::from(b)).sum::<u64>();
+
+    let mut now = 0;
+    while done < n {
+        assert!(u64::from(now) <= limit, "{name} left the CPU idle with processes ready");
+        scheduler.clock(now);
+        for i in 0..n {
+            let event = match state[i] {
+                State::NotArrived if tasks[i].arrival == now => Event::Arrived,
+                State::Io if io_until[i] == now => Event::Woke,
+                _ => continue,
+            };
+            state[i] = State::Ready;
+            scheduler.enqueue(i, event, &procs);
+        }
+        if let Some(r) = running
+            && scheduler.preempt(r, &procs)
+        {
+            state[r] = State::Ready;
+            table[r].running = false;
+            scheduler.enqueue(r, Event::Preempted, &procs);
+            running = None;
+        }
+        if running.is_none() {
+            running = scheduler.pick(&procs);
+            if let Some(r) = running {
+                assert_eq!(state[r], State::Ready, "{name} picked {}, which is not ready", tasks[r].label());
+                state[r] = State::Running;
+                table[r].running = true;
+                first_run[r].get_or_insert(now);
+                switches += (last.is_some() && last != running) as u32;
+                last = running;
+            }
+        }
+        debug_assert!(table.iter().filter(|p| p.running).count() == running.is_some() as usize);
+
+        for i in 0..n {
+            timeline[i].push(state[i].symbol());
+            stats[i].waiting += (state[i] == State::Ready) as u32;
+        }
+        ticks.push(running);
+        now += 1;
+
+        let Some(r) = running else { continue };
+        busy += 1;
+        procs[r].remaining -= 1;
+        scheduler.ran(r, &procs);
+        if procs[r].remaining > 0 {
+            continue;
+        }
+        table[r].running = false;
+        running = None;
+        let bursts = &tasks[r].bursts;
+        if burst[r] + 1 < bursts.len() {
+            io_until[r] = now + bursts[burst[r] + 1];
+            burst[r] += 2;
+            procs[r].burst = bursts[burst[r]];
+            procs[r].remaining = bursts[burst[r]];
+            state[r] = State::Io;
+        } else {
+            state[r] = State::Done;
+            stats[r].finish = now;
+            done += 1;
+        }
+    }
+
+    for (s, first) in stats.iter_mut().zip(first_run) {
+        s.first_run = first.expect("every process ran");
+    }
+    Report {
+        policy: name,
+        labels: tasks.iter().map(Task::label).collect(),
+        stats,
+        gantt: slices(&ticks),
+        timeline,
+        switches,
+        busy,
+    }
+}
+
+fn slices(ticks: &[Option<usize>]) -> Vec<Slice> {
+    let mut out: Vec<Slice> = Vec::new();
+    for (t, &task) in ticks.iter().enumerate() {
+        match out.last_mut() {
+            Some(s) if s.task == task => s.end += 1,
+            _ => out.push(Slice { task, start: t as u32, end: t as u32 + 1 }),
+        }
+    }
+    out
+}
@@ -0,0 +1,108 @@
+// src/workload.rs
+//
+// Workload files: one process per line, '#' starts a comment.
+//
+//   # name  arrival  priority  cpu io cpu io cpu ...
+//   P1      0        3         8  4  2
+//   P2      1        1         5
+//
+// The bursts alternate CPU and I/O and must start and end with CPU, so P1
+// computes for 8 ticks, waits 4 for I/O, then computes 2 more and exits.
+// Priority: lower is more important; CFS reads it as a nice value (-20..19).
+// Processes that arrive in the same tick queue up in file order.
+//
+// The simulator steps one tick at a time and keeps a chart column per tick, so
+// a workload may span at most MAX_TICKS: the last arrival plus every burst.
+
+use std::fmt;
+
+use module2_structs::Process;
+
+pub const MAX_TICKS: u64 = 100_000;
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub process: Process,
+    pub arrival: u32,
+    pub priority: i32,
+    pub bursts: Vec<u32>, // cpu, io, cpu, ..., cpu
+}
+
+impl Task {
+    pub fn label(&self) -> String {
+        format!("P{}", self.process.pid)
+    }
+
+    pub fn cpu_total(&self) -> u32 {
+        self.bursts.iter().step_by(2).fold(0, |sum, &b| sum.saturating_add(b))
+    }
+
+    pub fn io_total(&self) -> u32 {
+        self.bursts.iter().skip(1).step_by(2).fold(0, |sum, &b| sum.saturating_add(b))
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    line: usize,
+    msg: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+fn err(line: usize, msg: impl Into<String>) -> ParseError {
+    ParseError { line, msg: msg.into() }
+}
+
+fn number<T: std::str::FromStr>(line: usize, what: &str, s: &str) -> Result<T, ParseError> {
+    s.parse().map_err(|_| err(line, format!("{what} must be a number, got '{s}'")))
+}
+
+pub fn parse(text: &str) -> Result<Vec<Task>, ParseError> {
+    let mut tasks: Vec<Task> = Vec::new();
+    let (mut last_arrival, mut all_bursts) = (0u64, 0u64);
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = raw.split('#').next().unwrap_or("");
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        let [name, arrival, priority, bursts @ ..] = fields.as_slice() else {
+            if fields.is_empty() {
+                continue;
+            }
+            return Err(err(line, "expected: name arrival priority cpu [io cpu]..."));
+        };
+        let digits = name.strip_prefix(['P', 'p']).unwrap_or(name);
+        let pid: u32 = digits.parse().map_err(|_| err(line, format!("name must look like P3, got '{name}'")))?;
+        if tasks.iter().any(|t| t.process.pid == pid) {
+            return Err(err(line, format!("P{pid} is listed twice")));
+        }
+        let bursts = bursts.iter().map(|b| number(line, "a burst", b)).collect::<Result<Vec<u32>, _>>()?;
+        if bursts.len() % 2 == 0 {
+            return Err(err(line, "bursts must start and end with CPU (an odd count: cpu io cpu ...)"));
+        }
+        if bursts.contains(&0) {
+            return Err(err(line, "bursts must be at least 1 tick"));
+        }
+        let arrival = number(line, "arrival", arrival)?;
+        // u64: each number fits in a u32, and the cap keeps any sum of them far below.
+        last_arrival = last_arrival.max(u64::from(arrival));
+        all_bursts += bursts.iter().map(|&b| u64::from(b)).sum::<u64>();
+        if last_arrival + all_bursts > MAX_TICKS {
+            return Err(err(line, format!("workload runs past {MAX_TICKS} ticks (last arrival plus every burst)")));
+        }
+        tasks.push(Task {
+            process: Process { pid, memory_kb: 0, running: false },
+            arrival,
+            priority: number(line, "priority", priority)?,
+            bursts,
+        });
+    }
+    if tasks.is_empty() {
+        return Err(err(1, "no processes in workload"));
+    }
+    Ok(tasks)
+}
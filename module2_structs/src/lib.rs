@@ -0,0 +1,12 @@
+// src/lib.rs
+//
+// The Process struct from main.rs, shared so other crates can build on it:
+// module2_structs/scheduler runs workloads of them through CPU schedulers.
+
+/// A struct is a memory layout: these three fields, side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,       // process ID
+    pub memory_kb: u64, // memory usage
+    pub running: bool,  // state flag
+}
@@ -1,9 +1,5 @@
-// Define a struct (a memory layout)
-struct Process {
-    pid: u32,        // process ID
-    memory_kb: u64,  // memory usage
-    running: bool,   // state flag
-}
+// The struct (a memory layout) is defined in lib.rs so other crates can use it
+use module2_structs::Process;
 
 fn main() {
     // Instantiate the struct
@@ -20,4 +16,6 @@ fn main() {
 }
 
 // cargo run --bin module2_ownership  --bin means: run the binary named module2_ownership
-// cargo run --release --bin module2_ownership  --release means: run the optimized version of the binary named module2_ownership
\ No newline at end of file
+// cargo run --release --bin module2_ownership  --release means: run the optimized version of the binary named module2_ownership
+// cargo run -p scheduler  CPU scheduling simulator that runs a workload of these processes
+// cargo run -p process_table  a kernel process table built on Process: states, fork/exec/exit/wait, orphans, zombies
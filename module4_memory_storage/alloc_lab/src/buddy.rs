@@ -0,0 +1,191 @@
+// src/buddy.rs
+//
+// Buddy allocator (what the Linux kernel uses for physical pages).
+//
+// The arena is one power-of-two block. Blocks are only ever split in halves:
+//
+//   order 3: [               256               ]
+//   order 2: [       128       ][      128      ]
+//   order 1: [  64   ][  64   ][  64  ][  64    ]
+//   order 0: [32][32][32][32][32][32][32][32]      (MIN_BLOCK = 32 bytes)
+//
+// Two halves of the same parent are "buddies". A block's buddy is found with one
+// XOR:   buddy_offset = offset ^ block_size
+//
+//   alloc : round up to a power of two, take a free block of that order, or split
+//           a bigger one until it is the right size
+//   free  : while the buddy is also free, merge both into the parent block
+//
+// No headers: the size comes back in `dealloc`'s Layout. Rounding to powers of two
+// makes internal fragmentation easy to see (a 65-byte request takes 128 bytes).
+//
+// "Is my buddy free?" is answered by scanning that order's free list. Real buddy
+// allocators keep a bitmap per order to make this O(1).
+
+use std::alloc::Layout;
+
+use crate::{Backend, Stats};
+
+const MIN_BLOCK: usize = 32;
+const MAX_ORDERS: usize = 32;
+
+pub struct Buddy {
+    base: usize,
+    len: usize,                 // power of two
+    max_order: usize,           // order of the whole arena
+    heads: [usize; MAX_ORDERS], // free list per order (0 = empty); next pointer lives in the block
+    stats: Stats,
+}
+
+unsafe impl Send for Buddy {}
+
+fn block_size(order: usize) -> usize {
+    MIN_BLOCK << order
+}
+
+// Smallest order whose block fits `layout`.
+fn order_for(layout: Layout) -> usize {
+    let need = layout.size().max(layout.align()).max(MIN_BLOCK).next_power_of_two();
+    (need / MIN_BLOCK).trailing_zeros() as usize
+}
+
+impl Buddy {
+    pub const fn new() -> Self {
+        Buddy { base: 0, len: 0, max_order: 0, heads: [0; MAX_ORDERS], stats: Stats::EMPTY }
+    }
+
+    fn push(&mut self, order: usize, block: usize) {
+        unsafe { *(block as *mut usize) = self.heads[order] };
+        self.heads[order] = block;
+    }
+
+    fn pop(&mut self, order: usize) -> Option<usize> {
+        let block = self.heads[order];
+        if block == 0 {
+            return None;
+        }
+        self.heads[order] = unsafe { *(block as *const usize) };
+        Some(block)
+    }
+
+    // Unlink `block` from the free list of `order`, if it is there.
+    fn remove(&mut self, order: usize, block: usize) -> bool {
+        let mut prev = 0;
+        let mut cur = self.heads[order];
+        while cur != 0 {
+            let next = unsafe { *(cur as *const usize) };
+            if cur == block {
+                if prev == 0 {
+                    self.heads[order] = next;
+                } else {
+                    unsafe { *(prev as *mut usize) = next };
+                }
+                return true;
+            }
+            prev = cur;
+            cur = next;
+        }
+        false
+    }
+
+    /// Number of free blocks per order (index = order), for printing.
+    pub fn free_counts(&self) -> [usize; MAX_ORDERS] {
+        let mut counts = [0; MAX_ORDERS];
+        for (order, count) in counts.iter_mut().enumerate().take(self.max_order + 1) {
+            let mut cur = self.heads[order];
+            while cur != 0 {
+                *count += 1;
+                cur = unsafe { *(cur as *const usize) };
+            }
+        }
+        counts
+    }
+}
+
+impl Default for Buddy {
+    fn default() -> Self {
+        Buddy::new()
+    }
+}
+
+impl Backend for Buddy {
+    const NAME: &'static str = "buddy";
+
+    unsafe fn init(&mut self, base: *mut u8, len: usize) {
+        // Largest power of two that fits; the tail (if any) is simply unused.
+        let len = if len.is_power_of_two() { len } else { len.next_power_of_two() / 2 };
+        self.base = base as usize;
+        self.len = len;
+        self.max_order = (len / MIN_BLOCK).trailing_zeros() as usize;
+        self.stats.arena_bytes = len;
+        self.push(self.max_order, self.base);
+    }
+
+    fn is_init(&self) -> bool {
+        self.len != 0
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let order = order_for(layout);
+        // Blocks are aligned to their size relative to `base`, and `base` is only
+        // guaranteed page-aligned.
+        if order > self.max_order || layout.align() > 4096 {
+            self.stats.failed += 1;
+            return std::ptr::null_mut();
+        }
+
+        // Smallest non-empty order that is big enough.
+        let Some(mut k) = (order..=self.max_order).find(|&k| self.heads[k] != 0) else {
+            self.stats.failed += 1;
+            return std::ptr::null_mut();
+        };
+        let block = self.pop(k).unwrap();
+
+        // Split down to the requested order; the upper halves go back as free.
+        while k > order {
+            k -= 1;
+            self.push(k, block + block_size(k));
+        }
+        self.stats.on_alloc(layout.size(), block_size(order));
+        block as *mut u8
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let mut order = order_for(layout);
+        let mut block = ptr as usize;
+        self.stats.on_free(layout.size(), block_size(order));
+
+        while order < self.max_order {
+            let buddy = self.base + ((block - self.base) ^ block_size(order));
+            if !self.remove(order, buddy) {
+                break;
+            }
+            // Merged block starts at the lower of the two halves.
+            block = block.min(buddy);
+            order += 1;
+        }
+        self.push(order, block);
+    }
+
+    unsafe fn resize_in_place(&mut self, _ptr: *mut u8, layout: Layout, new_size: usize) -> bool {
+        // Still the same power of two? Then nothing has to move.
+        let new_layout = Layout::from_size_align(new_size, layout.align()).unwrap();
+        if order_for(new_layout) != order_for(layout) {
+            return false;
+        }
+        self.stats.live_bytes = self.stats.live_bytes - layout.size() + new_size;
+        self.stats.peak_live_bytes = self.stats.peak_live_bytes.max(self.stats.live_bytes);
+        true
+    }
+
+    fn stats(&self) -> Stats {
+        let mut s = self.stats;
+        for (order, count) in self.free_counts().iter().enumerate() {
+            if *count > 0 {
+                s.free_bytes += count * block_size(order);
+                s.largest_free = block_size(order);
+            }
+        }
+        s
+    }
+}
@@ -0,0 +1,120 @@
+// src/bump.rs
+//
+// Bump (arena) allocator.
+//
+//   base                      next                               end
+//   |  A  |pad|   B   |  C  |  -> free space ...                  |
+//
+// alloc  = round `next` up to the alignment, hand it out, move `next` forward.
+// dealloc = nothing... except two cheap special cases:
+//   - freeing the most recent block moves `next` back (stack-like usage)
+//   - when the last live block is freed, the whole arena is reset
+//
+// Memory in the middle of the arena is never reused, which is why this shows up
+// as "used but not live" (internal fragmentation) in the stats.
+
+use std::alloc::Layout;
+
+use crate::{Backend, Stats};
+
+pub struct Bump {
+    base: usize,
+    len: usize,
+    next: usize, // offset of the first free byte
+    last: usize, // address of the most recent allocation (0 = none)
+    live: usize, // number of blocks not yet freed
+    stats: Stats,
+}
+
+unsafe impl Send for Bump {}
+
+impl Bump {
+    pub const fn new() -> Self {
+        Bump {
+            base: 0,
+            len: 0,
+            next: 0,
+            last: 0,
+            live: 0,
+            stats: Stats::EMPTY,
+        }
+    }
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Bump::new()
+    }
+}
+
+impl Backend for Bump {
+    const NAME: &'static str = "bump";
+
+    unsafe fn init(&mut self, base: *mut u8, len: usize) {
+        self.base = base as usize;
+        self.len = len;
+        self.stats.arena_bytes = len;
+    }
+
+    fn is_init(&self) -> bool {
+        self.len != 0
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let start = crate::align_up(self.base + self.next, layout.align());
+        let end = start + layout.size();
+        if end > self.base + self.len {
+            self.stats.failed += 1;
+            return std::ptr::null_mut();
+        }
+        // Padding skipped for alignment counts as used: it is gone until reset.
+        self.stats.on_alloc(layout.size(), end - (self.base + self.next));
+        self.next = end - self.base;
+        self.last = start;
+        self.live += 1;
+        start as *mut u8
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let ptr = ptr as usize;
+        self.live -= 1;
+        if self.live == 0 {
+            // Everything is free: start over from the beginning.
+            self.stats.on_free(layout.size(), 0);
+            self.stats.used_bytes = 0;
+            self.next = 0;
+            self.last = 0;
+        } else if ptr == self.last && ptr + layout.size() == self.base + self.next {
+            // Most recent block: give its bytes back (its alignment padding stays used).
+            self.stats.on_free(layout.size(), layout.size());
+            self.next = ptr - self.base;
+            self.last = 0;
+        } else {
+            // Bytes stay "used" — a bump allocator cannot reuse holes.
+            self.stats.on_free(layout.size(), 0);
+        }
+    }
+
+    unsafe fn resize_in_place(&mut self, ptr: *mut u8, layout: Layout, new_size: usize) -> bool {
+        // Only the block at the top can grow or shrink: just move `next`.
+        let ptr = ptr as usize;
+        if ptr != self.last || ptr + layout.size() != self.base + self.next {
+            return false;
+        }
+        if ptr + new_size > self.base + self.len {
+            return false;
+        }
+        self.stats.live_bytes = self.stats.live_bytes - layout.size() + new_size;
+        self.stats.used_bytes = self.stats.used_bytes - layout.size() + new_size;
+        self.stats.peak_live_bytes = self.stats.peak_live_bytes.max(self.stats.live_bytes);
+        self.next = ptr + new_size - self.base;
+        true
+    }
+
+    fn stats(&self) -> Stats {
+        let mut s = self.stats;
+        s.free_bytes = self.len - self.next;
+        s.largest_free = s.free_bytes;
+        s
+    }
+}
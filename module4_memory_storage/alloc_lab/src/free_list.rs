@@ -0,0 +1,194 @@
+// src/free_list.rs
+//
+// First-fit free-list allocator with coalescing (the classic K&R malloc idea).
+//
+// The arena is cut into blocks. Every block starts with a 16-byte header:
+//
+//   used block:  [size | (unused)] [ ...pad... ][start][ payload ............ ]
+//   free block:  [size | next free block      ] [ garbage ...................... ]
+//
+// `size` is the whole block, header included, always a multiple of 16. The word
+// right before the payload stores where the block starts, so `dealloc` can find
+// the header even when extra alignment padding was needed.
+//
+// Free blocks form a singly linked list sorted by address. That ordering makes
+// coalescing easy: on free, the new block is merged with the previous and/or
+// next list entry if they touch it in memory.
+//
+//   alloc : walk the list, take the FIRST block that fits, split off the rest
+//   free  : insert in address order, merge with neighbours
+
+use std::alloc::Layout;
+
+use crate::{align_up, Backend, Stats};
+
+const HEADER: usize = 16;
+const MIN_BLOCK: usize = 32; // header + room for at least 16 bytes of payload
+
+pub struct FreeList {
+    len: usize,
+    head: usize, // first free block (0 = none)
+    stats: Stats,
+}
+
+unsafe impl Send for FreeList {}
+
+// Raw header access. `addr` always points at a block start inside the arena.
+unsafe fn size_of_block(addr: usize) -> usize {
+    unsafe { *(addr as *const usize) }
+}
+
+unsafe fn set_size(addr: usize, size: usize) {
+    unsafe { *(addr as *mut usize) = size }
+}
+
+unsafe fn next_of(addr: usize) -> usize {
+    unsafe { *((addr + 8) as *const usize) }
+}
+
+unsafe fn set_next(addr: usize, next: usize) {
+    unsafe { *((addr + 8) as *mut usize) = next }
+}
+
+impl FreeList {
+    pub const fn new() -> Self {
+        FreeList { len: 0, head: 0, stats: Stats::EMPTY }
+    }
+
+    // Block size needed to serve `layout` from a block starting at `block`.
+    fn block_size_at(block: usize, layout: Layout) -> (usize, usize) {
+        let payload = align_up(block + HEADER, layout.align().max(8));
+        let size = align_up(payload + layout.size() - block, HEADER).max(MIN_BLOCK);
+        (payload, size)
+    }
+
+    /// Arena bytes currently handed out (headers and rounding included).
+    pub fn used_bytes(&self) -> usize {
+        self.stats.used_bytes
+    }
+
+    /// Sizes of all free blocks in address order (for printing the heap shape).
+    pub fn free_blocks(&self, out: &mut Vec<usize>) {
+        let mut cur = self.head;
+        while cur != 0 {
+            unsafe {
+                out.push(size_of_block(cur));
+                cur = next_of(cur);
+            }
+        }
+    }
+}
+
+impl Default for FreeList {
+    fn default() -> Self {
+        FreeList::new()
+    }
+}
+
+impl Backend for FreeList {
+    const NAME: &'static str = "free-list";
+
+    unsafe fn init(&mut self, base: *mut u8, len: usize) {
+        let start = align_up(base as usize, HEADER);
+        let end = (base as usize + len) & !(HEADER - 1);
+        self.len = end - start;
+        self.stats.arena_bytes = self.len;
+        // One big free block to begin with.
+        unsafe {
+            set_size(start, self.len);
+            set_next(start, 0);
+        }
+        self.head = start;
+    }
+
+    fn is_init(&self) -> bool {
+        self.len != 0
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        unsafe {
+            let mut prev = 0;
+            let mut cur = self.head;
+            while cur != 0 {
+                let have = size_of_block(cur);
+                let (payload, mut need) = FreeList::block_size_at(cur, layout);
+                if have >= need {
+                    let next = next_of(cur);
+                    // Split if the leftover is big enough to be a block of its own.
+                    let replacement = if have - need >= MIN_BLOCK {
+                        let rest = cur + need;
+                        set_size(rest, have - need);
+                        set_next(rest, next);
+                        rest
+                    } else {
+                        need = have; // hand out the crumbs too
+                        next
+                    };
+                    if prev == 0 {
+                        self.head = replacement;
+                    } else {
+                        set_next(prev, replacement);
+                    }
+
+                    set_size(cur, need);
+                    *((payload - 8) as *mut usize) = cur;
+                    self.stats.on_alloc(layout.size(), need);
+                    return payload as *mut u8;
+                }
+                prev = cur;
+                cur = next_of(cur);
+            }
+            self.stats.failed += 1;
+            std::ptr::null_mut()
+        }
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        unsafe {
+            let block = *((ptr as usize - 8) as *const usize);
+            let mut size = size_of_block(block);
+            self.stats.on_free(layout.size(), size);
+
+            // Find the neighbours in the address-ordered list: prev < block < next.
+            let mut prev = 0;
+            let mut next = self.head;
+            while next != 0 && next < block {
+                prev = next;
+                next = next_of(next);
+            }
+
+            // Merge with the following free block?
+            if next != 0 && block + size == next {
+                size += size_of_block(next);
+                next = next_of(next);
+            }
+            set_size(block, size);
+            set_next(block, next);
+
+            // Merge into the preceding free block?
+            if prev != 0 && prev + size_of_block(prev) == block {
+                set_size(prev, size_of_block(prev) + size);
+                set_next(prev, next);
+            } else if prev != 0 {
+                set_next(prev, block);
+            } else {
+                self.head = block;
+            }
+        }
+    }
+
+    fn stats(&self) -> Stats {
+        let mut s = self.stats;
+        let mut cur = self.head;
+        while cur != 0 {
+            unsafe {
+                let size = size_of_block(cur);
+                // Usable bytes of a free block exclude its header.
+                s.free_bytes += size - HEADER;
+                s.largest_free = s.largest_free.max(size - HEADER);
+                cur = next_of(cur);
+            }
+        }
+        s
+    }
+}
@@ -0,0 +1,398 @@
+// src/lib.rs
+//
+// Allocator lab — how a heap actually works.
+//
+// `Box::new`, `Vec::push` and `String::from` all end up calling ONE function:
+// the global allocator's `alloc(Layout)`. A Layout is just {size, align}. The
+// allocator's job is to hand out non-overlapping, correctly aligned byte ranges
+// from a big region of memory (the "arena") and to take them back on `dealloc`.
+//
+// Four classic designs, each managing its own arena:
+//
+//   Bump      : pointer moves forward only; free is (almost) a no-op. Fastest, never reuses.
+//   FreeList  : first-fit over a list of free blocks; neighbours are coalesced on free.
+//   Slab      : fixed size classes (16, 32, ... 2048); each class carves objects from pages.
+//   Buddy     : power-of-two blocks; a block and its "buddy" merge back when both are free.
+//
+// Every backend keeps `Stats` so fragmentation can be inspected:
+//   internal fragmentation = bytes taken from the arena but not requested (headers, rounding)
+//   external fragmentation = free memory exists, but not in one piece big enough
+//
+// `LabAllocator` is a `#[global_allocator]` that forwards to whichever backend is
+// selected at runtime. Frees are routed by address (each backend owns a distinct
+// arena, anything else came from the system allocator), so switching backends in
+// the middle of a program is safe.
+
+pub mod buddy;
+pub mod bump;
+pub mod free_list;
+pub mod slab;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+pub use buddy::Buddy;
+pub use bump::Bump;
+pub use free_list::FreeList;
+pub use slab::Slab;
+
+/// Rounds `addr` up to the next multiple of `align` (a power of two).
+pub fn align_up(addr: usize, align: usize) -> usize {
+    (addr + align - 1) & !(align - 1)
+}
+
+/// Counters every backend keeps.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    pub allocs: u64,
+    pub frees: u64,
+    pub failed: u64,
+    pub live_bytes: usize,      // requested by callers and not yet freed
+    pub peak_live_bytes: usize,
+    pub used_bytes: usize,      // taken from the arena for those (headers, padding, rounding included)
+    pub free_bytes: usize,      // still available for new allocations
+    pub largest_free: usize,    // biggest single block that could still be handed out
+    pub arena_bytes: usize,
+}
+
+impl Stats {
+    // `Default::default()` is not const, and backends must be creatable in a `static`.
+    pub(crate) const EMPTY: Stats = Stats {
+        allocs: 0,
+        frees: 0,
+        failed: 0,
+        live_bytes: 0,
+        peak_live_bytes: 0,
+        used_bytes: 0,
+        free_bytes: 0,
+        largest_free: 0,
+        arena_bytes: 0,
+    };
+
+    pub(crate) fn on_alloc(&mut self, requested: usize, used: usize) {
+        self.allocs += 1;
+        self.live_bytes += requested;
+        self.used_bytes += used;
+        self.peak_live_bytes = self.peak_live_bytes.max(self.live_bytes);
+    }
+
+    pub(crate) fn on_free(&mut self, requested: usize, used: usize) {
+        self.frees += 1;
+        self.live_bytes -= requested;
+        self.used_bytes -= used;
+    }
+
+    /// Share of the arena bytes in use that nobody asked for.
+    pub fn internal_fragmentation(&self) -> f64 {
+        if self.used_bytes == 0 {
+            0.0
+        } else {
+            (self.used_bytes - self.live_bytes) as f64 / self.used_bytes as f64
+        }
+    }
+
+    /// 0.0 = all free memory is one block; close to 1.0 = free memory is scattered crumbs.
+    pub fn external_fragmentation(&self) -> f64 {
+        if self.free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free as f64 / self.free_bytes as f64
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "allocs {:>7}  frees {:>7}  failed {:>3}  live {:>9} B  used {:>9} B  peak live {:>9} B  \
+             free {:>9} B  largest free {:>9} B  int.frag {:>5.1}%  ext.frag {:>5.1}%",
+            self.allocs,
+            self.frees,
+            self.failed,
+            self.live_bytes,
+            self.used_bytes,
+            self.peak_live_bytes,
+            self.free_bytes,
+            self.largest_free,
+            self.internal_fragmentation() * 100.0,
+            self.external_fragmentation() * 100.0,
+        )
+    }
+}
+
+/// What every allocator in this lab implements. All methods work on raw
+/// addresses inside the arena given to `init`; none of them allocate.
+pub trait Backend {
+    const NAME: &'static str;
+
+    /// Takes ownership of `len` bytes at `base` (aligned to at least 4096).
+    ///
+    /// # Safety
+    /// The range must be valid, writable and used by nothing else.
+    unsafe fn init(&mut self, base: *mut u8, len: usize);
+
+    fn is_init(&self) -> bool;
+
+    /// Returns null when the arena cannot satisfy the request.
+    ///
+    /// # Safety
+    /// `init` must have been called.
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8;
+
+    /// # Safety
+    /// `ptr` must come from `alloc` on this backend with the same `layout`.
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout);
+
+    /// Grow or shrink without moving, if the design allows it.
+    ///
+    /// # Safety
+    /// Same as `dealloc`.
+    unsafe fn resize_in_place(&mut self, _ptr: *mut u8, _layout: Layout, _new_size: usize) -> bool {
+        false
+    }
+
+    fn stats(&self) -> Stats;
+}
+
+// A tiny spin lock. std's Mutex would also work, but an allocator is the one
+// place where we want to be 100% sure the lock itself never allocates.
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+pub struct SpinGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> SpinLock<T> {
+    pub const fn new(value: T) -> Self {
+        SpinLock { locked: AtomicBool::new(false), value: UnsafeCell::new(value) }
+    }
+
+    pub fn lock(&self) -> SpinGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            std::hint::spin_loop();
+        }
+        SpinGuard { lock: self }
+    }
+}
+
+impl<T> Deref for SpinGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SpinGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for SpinGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Global allocator
+// ---------------------------------------------------------------------------
+
+/// Size of each backend's arena when used as the global allocator.
+/// Lives in .bss, so pages only become real memory when touched.
+pub const ARENA_BYTES: usize = 32 * 1024 * 1024;
+
+#[repr(C, align(4096))]
+struct Arena(UnsafeCell<[u8; ARENA_BYTES]>);
+
+unsafe impl Sync for Arena {}
+
+impl Arena {
+    fn base(&self) -> *mut u8 {
+        self.0.get() as *mut u8
+    }
+
+    fn contains(&self, ptr: *mut u8) -> bool {
+        let base = self.base() as usize;
+        (base..base + ARENA_BYTES).contains(&(ptr as usize))
+    }
+}
+
+static BUMP_ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_BYTES]));
+static FREE_LIST_ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_BYTES]));
+static SLAB_ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_BYTES]));
+static BUDDY_ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_BYTES]));
+
+static BUMP: SpinLock<Bump> = SpinLock::new(Bump::new());
+static FREE_LIST: SpinLock<FreeList> = SpinLock::new(FreeList::new());
+static SLAB: SpinLock<Slab> = SpinLock::new(Slab::new());
+static BUDDY: SpinLock<Buddy> = SpinLock::new(Buddy::new());
+
+static SELECTED: AtomicU8 = AtomicU8::new(Kind::System as u8);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum Kind {
+    System,
+    Bump,
+    FreeList,
+    Slab,
+    Buddy,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [Kind::System, Kind::Bump, Kind::FreeList, Kind::Slab, Kind::Buddy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::System => "system",
+            Kind::Bump => Bump::NAME,
+            Kind::FreeList => FreeList::NAME,
+            Kind::Slab => Slab::NAME,
+            Kind::Buddy => Buddy::NAME,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Kind> {
+        Kind::ALL.into_iter().find(|k| k.name() == name)
+    }
+
+    fn from_u8(v: u8) -> Kind {
+        Kind::ALL[v as usize]
+    }
+}
+
+/// Use as `#[global_allocator] static GLOBAL: LabAllocator = LabAllocator;`
+pub struct LabAllocator;
+
+impl LabAllocator {
+    /// New allocations go to `kind` from now on. Existing ones are still freed
+    /// by whichever backend handed them out.
+    pub fn select(kind: Kind) {
+        SELECTED.store(kind as u8, Ordering::SeqCst);
+    }
+
+    pub fn selected() -> Kind {
+        Kind::from_u8(SELECTED.load(Ordering::SeqCst))
+    }
+
+    /// Statistics of one backend (`None` for the system allocator).
+    pub fn stats(kind: Kind) -> Option<Stats> {
+        match kind {
+            Kind::System => None,
+            Kind::Bump => Some(BUMP.lock().stats()),
+            Kind::FreeList => Some(FREE_LIST.lock().stats()),
+            Kind::Slab => Some(SLAB.lock().stats()),
+            Kind::Buddy => Some(BUDDY.lock().stats()),
+        }
+    }
+}
+
+/// Reads the `ALLOC_LAB` environment variable (system, bump, free-list, slab,
+/// buddy) and selects that backend. Call it first thing in `main`.
+pub fn select_from_env() -> Kind {
+    let kind = std::env::var("ALLOC_LAB")
+        .ok()
+        .and_then(|name| Kind::from_name(&name))
+        .unwrap_or(Kind::System);
+    LabAllocator::select(kind);
+    kind
+}
+
+/// Prints the selected backend's statistics (nothing for the system allocator).
+pub fn print_report() {
+    let kind = LabAllocator::selected();
+    if let Some(stats) = LabAllocator::stats(kind) {
+        println!("\n[alloc_lab] backend '{}': {stats}", kind.name());
+    }
+}
+
+fn with<B: Backend, R>(lock: &SpinLock<B>, arena: &Arena, f: impl FnOnce(&mut B) -> R) -> R {
+    let mut backend = lock.lock();
+    if !backend.is_init() {
+        unsafe { backend.init(arena.base(), ARENA_BYTES) };
+    }
+    f(&mut backend)
+}
+
+fn owner(ptr: *mut u8) -> Kind {
+    if BUMP_ARENA.contains(ptr) {
+        Kind::Bump
+    } else if FREE_LIST_ARENA.contains(ptr) {
+        Kind::FreeList
+    } else if SLAB_ARENA.contains(ptr) {
+        Kind::Slab
+    } else if BUDDY_ARENA.contains(ptr) {
+        Kind::Buddy
+    } else {
+        Kind::System
+    }
+}
+
+unsafe impl GlobalAlloc for LabAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe {
+            match LabAllocator::selected() {
+                Kind::System => System.alloc(layout),
+                Kind::Bump => with(&BUMP, &BUMP_ARENA, |b| b.alloc(layout)),
+                Kind::FreeList => with(&FREE_LIST, &FREE_LIST_ARENA, |b| b.alloc(layout)),
+                Kind::Slab => with(&SLAB, &SLAB_ARENA, |b| b.alloc(layout)),
+                Kind::Buddy => with(&BUDDY, &BUDDY_ARENA, |b| b.alloc(layout)),
+            }
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe {
+            match owner(ptr) {
+                Kind::System => System.dealloc(ptr, layout),
+                Kind::Bump => with(&BUMP, &BUMP_ARENA, |b| b.dealloc(ptr, layout)),
+                Kind::FreeList => with(&FREE_LIST, &FREE_LIST_ARENA, |b| b.dealloc(ptr, layout)),
+                Kind::Slab => with(&SLAB, &SLAB_ARENA, |b| b.dealloc(ptr, layout)),
+                Kind::Buddy => with(&BUDDY, &BUDDY_ARENA, |b| b.dealloc(ptr, layout)),
+            }
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        unsafe {
+            let owner = owner(ptr);
+            // Only a block owned by the currently selected backend may stay where it is.
+            if owner == LabAllocator::selected() {
+                let in_place = match owner {
+                    Kind::System => false,
+                    Kind::Bump => with(&BUMP, &BUMP_ARENA, |b| b.resize_in_place(ptr, layout, new_size)),
+                    Kind::FreeList => {
+                        with(&FREE_LIST, &FREE_LIST_ARENA, |b| b.resize_in_place(ptr, layout, new_size))
+                    }
+                    Kind::Slab => with(&SLAB, &SLAB_ARENA, |b| b.resize_in_place(ptr, layout, new_size)),
+                    Kind::Buddy => with(&BUDDY, &BUDDY_ARENA, |b| b.resize_in_place(ptr, layout, new_size)),
+                };
+                if in_place {
+                    return ptr;
+                }
+            }
+
+            let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+            let new_ptr = self.alloc(new_layout);
+            if !new_ptr.is_null() {
+                std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+            new_ptr
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// src/main.rs
+//
+// Allocator lab driver.
+//
+// Part 1: the same allocation workloads against each allocator, each one given
+//         its own private arena. Reports throughput and fragmentation.
+// Part 2: the lab allocator is THIS program's #[global_allocator]. We switch the
+//         backend at runtime and run small programs in the style of the repo's
+//         demos (String, Vec, Box, HashMap) to see what ordinary Rust code asks for.
+//
+// Run with --release for meaningful throughput numbers.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use alloc_lab::{Backend, Buddy, Bump, FreeList, Kind, LabAllocator, Slab, Stats};
+
+#[global_allocator]
+static GLOBAL: LabAllocator = LabAllocator;
+
+const BENCH_ARENA: usize = 32 * 1024 * 1024;
+
+// The system allocator (glibc malloc) as a baseline. It has no stats to report.
+#[derive(Default)]
+struct SystemHeap;
+
+impl Backend for SystemHeap {
+    const NAME: &'static str = "system";
+    unsafe fn init(&mut self, _base: *mut u8, _len: usize) {}
+    fn is_init(&self) -> bool {
+        true
+    }
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+    fn stats(&self) -> Stats {
+        Stats::default()
+    }
+}
+
+// xorshift64: tiny deterministic RNG, so every allocator sees the same sequence.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Workload {
+    // Many small objects (8..256 bytes), random frees, ~2000 live at a time.
+    SmallChurn,
+    // Sizes spread from 16 bytes to 16 KiB (log-uniform), random frees.
+    MixedSizes,
+    // Allocate everything, then free every other block, then allocate bigger blocks:
+    // the classic recipe for external fragmentation.
+    Holes,
+}
+
+impl Workload {
+    fn name(self) -> &'static str {
+        match self {
+            Workload::SmallChurn => "small churn",
+            Workload::MixedSizes => "mixed sizes",
+            Workload::Holes => "holes",
+        }
+    }
+}
+
+struct Outcome {
+    ops: usize,
+    elapsed: Duration,
+    stats: Stats, // taken at the end, while blocks are still live
+}
+
+// Runs `workload` on a fresh `B` over its own arena.
+fn bench<B: Backend + Default>(workload: Workload) -> Outcome {
+    let arena_layout = Layout::from_size_align(BENCH_ARENA, 4096).unwrap();
+    let arena = unsafe { System.alloc(arena_layout) };
+    let mut heap = B::default();
+    unsafe { heap.init(arena, BENCH_ARENA) };
+
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut live: Vec<(*mut u8, Layout)> = Vec::with_capacity(20_000);
+    let mut ops = 0;
+
+    let start = Instant::now();
+    match workload {
+        Workload::SmallChurn | Workload::MixedSizes => {
+            for _ in 0..200_000 {
+                let free_one = !live.is_empty() && (live.len() >= 2000 || rng.below(2) == 0);
+                if free_one {
+                    let (ptr, layout) = live.swap_remove(rng.below(live.len()));
+                    unsafe { heap.dealloc(ptr, layout) };
+                } else {
+                    let size = match workload {
+                        Workload::SmallChurn => 8 + rng.below(249),
+                        _ => 16 << rng.below(11) | rng.below(16),
+                    };
+                    let layout = Layout::from_size_align(size, 8).unwrap();
+                    let ptr = unsafe { heap.alloc(layout) };
+                    if !ptr.is_null() {
+                        unsafe { ptr.write(1) };
+                        live.push((ptr, layout));
+                    }
+                }
+                ops += 1;
+            }
+        }
+        Workload::Holes => {
+            let small = Layout::from_size_align(48, 8).unwrap();
+            for _ in 0..20_000 {
+                let ptr = unsafe { heap.alloc(small) };
+                if !ptr.is_null() {
+                    live.push((ptr, small));
+                }
+                ops += 1;
+            }
+            // Free every other block: half the memory is free, but in 48-byte holes.
+            let mut kept = Vec::with_capacity(live.len() / 2);
+            for (i, (ptr, layout)) in live.drain(..).enumerate() {
+                if i % 2 == 0 {
+                    unsafe { heap.dealloc(ptr, layout) };
+                    ops += 1;
+                } else {
+                    kept.push((ptr, layout));
+                }
+            }
+            live = kept;
+            let big = Layout::from_size_align(96, 8).unwrap();
+            for _ in 0..10_000 {
+                let ptr = unsafe { heap.alloc(big) };
+                if !ptr.is_null() {
+                    live.push((ptr, big));
+                }
+                ops += 1;
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    let stats = heap.stats();
+
+    for (ptr, layout) in live {
+        unsafe { heap.dealloc(ptr, layout) };
+    }
+    unsafe { System.dealloc(arena, arena_layout) };
+    Outcome { ops, elapsed, stats }
+}
+
+fn print_outcome(name: &str, o: &Outcome, show_stats: bool) {
+    let mops = o.ops as f64 / o.elapsed.as_secs_f64() / 1e6;
+    print!("  {name:<10} {:>7.2} Mops/s", mops);
+    if show_stats {
+        let s = &o.stats;
+        print!(
+            "  failed {:>6}  live {:>9} B  used {:>9} B  int.frag {:>5.1}%  ext.frag {:>5.1}%",
+            s.failed,
+            s.live_bytes,
+            s.used_bytes,
+            s.internal_fragmentation() * 100.0,
+            s.external_fragmentation() * 100.0
+        );
+    }
+    println!();
+}
+
+fn part1_benchmarks() {
+    println!("=== Part 1: workloads on private arenas ({} MiB each) ===", BENCH_ARENA >> 20);
+    for w in [Workload::SmallChurn, Workload::MixedSizes, Workload::Holes] {
+        println!("\n[{}]", w.name());
+        print_outcome(SystemHeap::NAME, &bench::<SystemHeap>(w), false);
+        print_outcome(Bump::NAME, &bench::<Bump>(w), true);
+        print_outcome(FreeList::NAME, &bench::<FreeList>(w), true);
+        print_outcome(Slab::NAME, &bench::<Slab>(w), true);
+        print_outcome(Buddy::NAME, &bench::<Buddy>(w), true);
+    }
+    println!("\nReading the numbers:");
+    println!("  bump      : fastest, but never reuses memory => 'failed' once the arena is used up");
+    println!("  free-list : exact fit + 16-byte header; first-fit search gets slower as holes pile up");
+    println!("  slab      : O(1) push/pop, rounding to the size class is its internal fragmentation");
+    println!("  buddy     : rounding to powers of two; merging buddies limits external fragmentation");
+    println!("  (slab and buddy never show less than ~50% ext.frag here: slab splits its arena into a");
+    println!("   small-object half and a large-object half, and once anything is allocated the biggest");
+    println!("   free buddy block is at most half the arena)");
+}
+
+// ---------------------------------------------------------------------------
+// Part 2: ordinary Rust code on top of each backend
+// ---------------------------------------------------------------------------
+
+// The same kind of work the repo's demos do: Strings, a byte record, Boxes, a HashMap.
+fn demo_program() -> usize {
+    // module2_memory_basics: a String on the heap
+    let s = String::from("hello");
+
+    // memory_storage_lab: building a binary record byte by byte (Vec growth)
+    let mut record = Vec::new();
+    record.extend_from_slice(&0xA1B2C3D4u32.to_le_bytes());
+    record.extend_from_slice(&(-105i16).to_le_bytes());
+    record.extend_from_slice(&3.98f32.to_bits().to_le_bytes());
+    let name = "sensor-α";
+    record.push(name.len() as u8);
+    record.extend_from_slice(name.as_bytes());
+
+    // module2_collections: a word count
+    let text = "the quick brown fox jumps over the lazy dog the end";
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+
+    // A linked structure of many small Boxes
+    let boxes: Vec<Box<[u64; 4]>> = (0..1000).map(|i| Box::new([i; 4])).collect();
+
+    s.len() + record.len() + counts["the"] + boxes.len()
+}
+
+fn part2_global() {
+    println!("\n=== Part 2: this program's global allocator, switched at runtime ===");
+    for kind in Kind::ALL {
+        LabAllocator::select(kind);
+        let before = LabAllocator::stats(kind);
+        let result = demo_program();
+        let after = LabAllocator::stats(kind);
+        LabAllocator::select(Kind::System);
+
+        match (before, after) {
+            (Some(b), Some(a)) => println!(
+                "  {:<10} result {result}: {:>5} allocs, {:>5} frees, peak live {:>7} B, used now {:>7} B",
+                kind.name(),
+                a.allocs - b.allocs,
+                a.frees - b.frees,
+                a.peak_live_bytes,
+                a.used_bytes
+            ),
+            _ => println!("  {:<10} result {result}: (glibc malloc, no stats)", kind.name()),
+        }
+    }
+    println!("\nOther demos can opt in the same way: add the #[global_allocator] line and");
+    println!("call alloc_lab::select_from_env(), then run with ALLOC_LAB=bump|free-list|slab|buddy.");
+}
+
+fn main() {
+    part1_benchmarks();
+    part2_global();
+}
@@ -0,0 +1,177 @@
+// src/slab.rs
+//
+// Size-class slab allocator (the idea behind the Linux SLUB allocator, jemalloc
+// and mimalloc "bins").
+//
+// Small requests are rounded up to one of a few size classes:
+//
+//   16  32  64  128  256  512  1024  2048
+//
+// Each class owns "slabs": 4 KiB pages cut into equal objects. Free objects of a
+// class are kept on an intrusive list (the first word of a free object points to
+// the next one), so alloc and free are both a single pointer swap.
+//
+//   page for class 64:  [obj][obj][obj] ... [obj]     (64 objects of 64 bytes)
+//
+// No headers and no searching — the price is rounding (a 65-byte request uses a
+// 128-byte object) and pages that stay with one class forever.
+//
+// Requests above 2048 bytes go to a first-fit free list in the second half of
+// the arena.
+
+use std::alloc::Layout;
+
+use crate::{Backend, FreeList, Stats};
+
+pub const CLASSES: [usize; 8] = [16, 32, 64, 128, 256, 512, 1024, 2048];
+const SLAB_PAGE: usize = 4096;
+
+/// Per-class numbers, for printing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassInfo {
+    pub size: usize,
+    pub pages: usize,
+    pub live: usize,
+    pub free: usize,
+}
+
+pub struct Slab {
+    len: usize,
+    next_page: usize,           // first never-used page in the slab half
+    slab_end: usize,
+    heads: [usize; 8],          // free list per class (0 = empty)
+    pages: [usize; 8],
+    live: [usize; 8],
+    free_objs: [usize; 8],
+    large: FreeList,            // everything bigger than the largest class
+    stats: Stats,
+}
+
+unsafe impl Send for Slab {}
+
+fn class_of(layout: Layout) -> Option<usize> {
+    let need = layout.size().max(layout.align());
+    CLASSES.iter().position(|&c| c >= need)
+}
+
+impl Slab {
+    pub const fn new() -> Self {
+        Slab {
+            len: 0,
+            next_page: 0,
+            slab_end: 0,
+            heads: [0; 8],
+            pages: [0; 8],
+            live: [0; 8],
+            free_objs: [0; 8],
+            large: FreeList::new(),
+            stats: Stats::EMPTY,
+        }
+    }
+
+    pub fn classes(&self) -> [ClassInfo; 8] {
+        let mut out = [ClassInfo::default(); 8];
+        for (i, info) in out.iter_mut().enumerate() {
+            *info = ClassInfo {
+                size: CLASSES[i],
+                pages: self.pages[i],
+                live: self.live[i],
+                free: self.free_objs[i],
+            };
+        }
+        out
+    }
+
+    // Carve a fresh page into objects of class `c` and push them all on its list.
+    fn refill(&mut self, c: usize) -> bool {
+        if self.next_page + SLAB_PAGE > self.slab_end {
+            return false;
+        }
+        let page = self.next_page;
+        self.next_page += SLAB_PAGE;
+        let size = CLASSES[c];
+        // Push in reverse so objects come out in address order.
+        for obj in (page..page + SLAB_PAGE).step_by(size).rev() {
+            unsafe { *(obj as *mut usize) = self.heads[c] };
+            self.heads[c] = obj;
+        }
+        self.pages[c] += 1;
+        self.free_objs[c] += SLAB_PAGE / size;
+        true
+    }
+}
+
+impl Default for Slab {
+    fn default() -> Self {
+        Slab::new()
+    }
+}
+
+impl Backend for Slab {
+    const NAME: &'static str = "slab";
+
+    unsafe fn init(&mut self, base: *mut u8, len: usize) {
+        let end = base as usize + len;
+        let base = crate::align_up(base as usize, SLAB_PAGE);
+        self.len = end - base;
+        self.next_page = base;
+        self.slab_end = crate::align_up(base + self.len / 2, SLAB_PAGE);
+        self.stats.arena_bytes = self.len;
+        unsafe { self.large.init(self.slab_end as *mut u8, end - self.slab_end) };
+    }
+
+    fn is_init(&self) -> bool {
+        self.len != 0
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let Some(c) = class_of(layout) else {
+            // The inner free list knows the real footprint (header + rounding).
+            let before = self.large.used_bytes();
+            let ptr = unsafe { self.large.alloc(layout) };
+            if ptr.is_null() {
+                self.stats.failed += 1;
+            } else {
+                self.stats.on_alloc(layout.size(), self.large.used_bytes() - before);
+            }
+            return ptr;
+        };
+
+        if self.heads[c] == 0 && !self.refill(c) {
+            self.stats.failed += 1;
+            return std::ptr::null_mut();
+        }
+        let obj = self.heads[c];
+        self.heads[c] = unsafe { *(obj as *const usize) };
+        self.live[c] += 1;
+        self.free_objs[c] -= 1;
+        self.stats.on_alloc(layout.size(), CLASSES[c]);
+        obj as *mut u8
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let addr = ptr as usize;
+        if addr >= self.slab_end {
+            let before = self.large.used_bytes();
+            unsafe { self.large.dealloc(ptr, layout) };
+            self.stats.on_free(layout.size(), before - self.large.used_bytes());
+            return;
+        }
+        let c = class_of(layout).expect("small block must have a class");
+        unsafe { *(addr as *mut usize) = self.heads[c] };
+        self.heads[c] = addr;
+        self.live[c] -= 1;
+        self.free_objs[c] += 1;
+        self.stats.on_free(layout.size(), CLASSES[c]);
+    }
+
+    fn stats(&self) -> Stats {
+        let mut s = self.stats;
+        let large = self.large.stats();
+        let untouched = self.slab_end - self.next_page;
+        let cached: usize = (0..CLASSES.len()).map(|c| self.free_objs[c] * CLASSES[c]).sum();
+        s.free_bytes = untouched + cached + large.free_bytes;
+        s.largest_free = large.largest_free.max(if untouched > 0 { CLASSES[7] } else { 0 });
+        s
+    }
+}
@@ -0,0 +1,323 @@
+// src/big.rs
+//
+// Just enough arbitrary-precision arithmetic to be EXACT about floats.
+//
+// Every finite float is m * 2^e, and every decimal literal is M * 10^E, so both
+// are ratios of (possibly huge) integers. f64's smallest subnormal, written out
+// in decimal, has 751 significant digits — u128 is not nearly enough.
+//
+// `Big` is an unsigned integer stored as 32-bit limbs, least significant first.
+// `Ratio` is a signed fraction of two `Big`s. Nothing here is fast; it does not
+// need to be.
+
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Big {
+    limbs: Vec<u32>, // no trailing zero limbs; zero is the empty vector
+}
+
+impl Big {
+    pub fn zero() -> Big {
+        Big { limbs: Vec::new() }
+    }
+
+    pub fn from_u64(v: u64) -> Big {
+        let mut b = Big { limbs: vec![v as u32, (v >> 32) as u32] };
+        b.trim();
+        b
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn bit_len(&self) -> usize {
+        match self.limbs.last() {
+            None => 0,
+            Some(top) => self.limbs.len() * 32 - top.leading_zeros() as usize,
+        }
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|l| l & 1 == 1)
+    }
+
+    /// Low 64 bits.
+    pub fn low_u64(&self) -> u64 {
+        let lo = self.limbs.first().copied().unwrap_or(0) as u64;
+        let hi = self.limbs.get(1).copied().unwrap_or(0) as u64;
+        hi << 32 | lo
+    }
+
+    pub fn mul_small(&self, m: u32) -> Big {
+        let mut out = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = 0u64;
+        for &l in &self.limbs {
+            let v = l as u64 * m as u64 + carry;
+            out.push(v as u32);
+            carry = v >> 32;
+        }
+        out.push(carry as u32);
+        let mut b = Big { limbs: out };
+        b.trim();
+        b
+    }
+
+    pub fn add_small(&self, a: u32) -> Big {
+        self.add(&Big::from_u64(a as u64))
+    }
+
+    pub fn add(&self, other: &Big) -> Big {
+        let n = self.limbs.len().max(other.limbs.len());
+        let mut out = Vec::with_capacity(n + 1);
+        let mut carry = 0u64;
+        for i in 0..n {
+            let v = *self.limbs.get(i).unwrap_or(&0) as u64 + *other.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            out.push(v as u32);
+            carry = v >> 32;
+        }
+        out.push(carry as u32);
+        let mut b = Big { limbs: out };
+        b.trim();
+        b
+    }
+
+    /// `self - other`; `other` must not be larger.
+    pub fn sub(&self, other: &Big) -> Big {
+        debug_assert!(*self >= *other);
+        let mut out = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for i in 0..self.limbs.len() {
+            let mut v = self.limbs[i] as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if v < 0 {
+                v += 1 << 32;
+                borrow = 1;
+            }
+            out.push(v as u32);
+        }
+        let mut b = Big { limbs: out };
+        b.trim();
+        b
+    }
+
+    pub fn mul(&self, other: &Big) -> Big {
+        let mut out = vec![0u32; self.limbs.len() + other.limbs.len() + 1];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let v = out[i + j] as u64 + a as u64 * b as u64 + carry;
+                out[i + j] = v as u32;
+                carry = v >> 32;
+            }
+            out[i + other.limbs.len()] = carry as u32;
+        }
+        let mut b = Big { limbs: out };
+        b.trim();
+        b
+    }
+
+    pub fn shl(&self, bits: usize) -> Big {
+        if self.is_zero() {
+            return Big::zero();
+        }
+        let (words, bits) = (bits / 32, bits % 32);
+        let mut out = vec![0u32; words];
+        let mut carry = 0u32;
+        for &l in &self.limbs {
+            out.push(if bits == 0 { l } else { l << bits | carry });
+            carry = if bits == 0 { 0 } else { l >> (32 - bits) };
+        }
+        out.push(carry);
+        let mut b = Big { limbs: out };
+        b.trim();
+        b
+    }
+
+    pub fn pow(base: u32, exp: u32) -> Big {
+        let mut b = Big::from_u64(1);
+        for _ in 0..exp {
+            b = b.mul_small(base);
+        }
+        b
+    }
+
+    /// `(self / d, self % d)` for a small divisor.
+    pub fn divmod_small(&self, d: u32) -> (Big, u32) {
+        let mut out = vec![0u32; self.limbs.len()];
+        let mut rem = 0u64;
+        for i in (0..self.limbs.len()).rev() {
+            let v = rem << 32 | self.limbs[i] as u64;
+            out[i] = (v / d as u64) as u32;
+            rem = v % d as u64;
+        }
+        let mut b = Big { limbs: out };
+        b.trim();
+        (b, rem as u32)
+    }
+
+    /// `(self / d, self % d)` by shift-and-subtract. Fine when the quotient is small.
+    pub fn divmod(&self, d: &Big) -> (Big, Big) {
+        assert!(!d.is_zero(), "division by zero");
+        if *self < *d {
+            return (Big::zero(), self.clone());
+        }
+        let mut rem = self.clone();
+        let mut q = Big::zero();
+        let top = self.bit_len() - d.bit_len();
+        for bit in (0..=top).rev() {
+            let shifted = d.shl(bit);
+            if rem >= shifted {
+                rem = rem.sub(&shifted);
+                q = q.add(&Big::from_u64(1).shl(bit));
+            }
+        }
+        (q, rem)
+    }
+
+    pub fn to_decimal(&self) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.divmod_small(1_000_000_000);
+            chunks.push(r);
+            n = q;
+        }
+        let mut s = chunks.pop().unwrap().to_string();
+        for c in chunks.iter().rev() {
+            s.push_str(&format!("{c:09}"));
+        }
+        s
+    }
+}
+
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+/// Decimal exponents beyond this are out of range for every format by far:
+/// f64, the widest here, spans about 1e-324..1e308 (an 80-bit or 128-bit float
+/// about 1e-4951..1e4932).
+const DECIMAL_EXP_LIMIT: i32 = 5000;
+
+/// A signed fraction `num / den` (`den` > 0). Not reduced.
+#[derive(Clone, Debug)]
+pub struct Ratio {
+    pub neg: bool,
+    pub num: Big,
+    pub den: Big,
+}
+
+impl Ratio {
+    pub fn zero() -> Ratio {
+        Ratio { neg: false, num: Big::zero(), den: Big::from_u64(1) }
+    }
+
+    /// `m * 2^e`.
+    pub fn from_binary(neg: bool, m: u64, e: i32) -> Ratio {
+        let m = Big::from_u64(m);
+        if e >= 0 {
+            Ratio { neg, num: m.shl(e as usize), den: Big::from_u64(1) }
+        } else {
+            Ratio { neg, num: m, den: Big::from_u64(1).shl(e.unsigned_abs() as usize) }
+        }
+    }
+
+    /// Parses a decimal literal like "-3.1415926", "1e-5" or "6.02E23" EXACTLY,
+    /// except that a magnitude past 10^±DECIMAL_EXP_LIMIT is pulled back to
+    /// about that limit: far outside every format either way, it still rounds
+    /// to infinity or zero, without building a power of ten with a billion digits.
+    pub fn parse_decimal(s: &str) -> Option<Ratio> {
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (mantissa, exp) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i32>().ok()?),
+            None => (s, 0),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        let mut num = Big::zero();
+        for c in int_part.chars().chain(frac_part.chars()) {
+            num = num.mul_small(10).add_small(c.to_digit(10)?);
+        }
+        // The value is num * 10^exp. num is >= 1 unless it is zero, and below
+        // 10^digits, so these bounds keep it above 10^LIMIT or below 10^-LIMIT.
+        // In i64, where subtracting the fraction's length cannot overflow.
+        let limit = i64::from(DECIMAL_EXP_LIMIT);
+        let digits = (int_part.len() + frac_part.len()) as i64;
+        let exp = (i64::from(exp) - frac_part.len() as i64).clamp(-(limit + digits), limit);
+        let exp = i32::try_from(exp).ok()?;
+        if exp >= 0 {
+            Some(Ratio { neg, num: num.mul(&Big::pow(10, exp as u32)), den: Big::from_u64(1) })
+        } else {
+            Some(Ratio { neg, num, den: Big::pow(10, exp.unsigned_abs()) })
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn abs(&self) -> Ratio {
+        Ratio { neg: false, ..self.clone() }
+    }
+
+    pub fn sub(&self, other: &Ratio) -> Ratio {
+        let a = self.num.mul(&other.den);
+        let b = other.num.mul(&self.den);
+        let den = self.den.mul(&other.den);
+        // Signed a - b, with each side carrying its own sign.
+        match (self.neg, other.neg) {
+            (false, true) | (true, false) => Ratio { neg: self.neg, num: a.add(&b), den },
+            _ if a >= b => Ratio { neg: self.neg && a != b, num: a.sub(&b), den },
+            _ => Ratio { neg: !self.neg, num: b.sub(&a), den },
+        }
+    }
+
+    pub fn div(&self, other: &Ratio) -> Ratio {
+        Ratio { neg: self.neg != other.neg, num: self.num.mul(&other.den), den: self.den.mul(&other.num) }
+    }
+
+    /// Approximate value, for printing errors and ratios.
+    pub fn to_f64(&self) -> f64 {
+        if self.is_zero() {
+            return 0.0;
+        }
+        // Scale so the quotient has ~62 significant bits, then undo the scale.
+        let shift = 62 + self.den.bit_len() as i64 - self.num.bit_len() as i64;
+        let (q, _) = if shift >= 0 {
+            self.num.shl(shift as usize).divmod(&self.den)
+        } else {
+            self.num.divmod(&self.den.shl(shift.unsigned_abs() as usize))
+        };
+        // Two steps, so values near the subnormal range do not underflow halfway.
+        let half = -shift as i32 / 2;
+        let v = q.low_u64() as f64 * 2f64.powi(half) * 2f64.powi(-shift as i32 - half);
+        if self.neg { -v } else { v }
+    }
+}
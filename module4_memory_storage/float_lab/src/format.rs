@@ -0,0 +1,419 @@
+// src/format.rs
+//
+// IEEE 754 binary formats, handled as raw bit patterns in a u64.
+//
+//   format  sign exponent mantissa  bias   precision (incl. hidden bit)
+//   f16      1     5        10       15     11 bits  (~3.3 decimal digits)
+//   bf16     1     8         7      127      8 bits  (~2.4)  same range as f32
+//   f32      1     8        23      127     24 bits  (~7.2)
+//   f64      1    11        52     1023     53 bits  (~15.9)
+//
+// Exponent field:
+//   0            zero (mantissa 0) or SUBNORMAL: 0.mantissa * 2^(1-bias), no hidden 1
+//   1..max-1     normal: 1.mantissa * 2^(field-bias)
+//   all ones     infinity (mantissa 0) or NaN (mantissa != 0; the top mantissa bit
+//                is the "quiet" bit, the remaining bits are the NaN's payload)
+//
+// Rounding works on exact ratios (big.rs), so the result is the correctly rounded
+// value for any decimal input, in any of the four rounding modes.
+
+use std::fmt;
+
+use crate::big::{Big, Ratio};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Format {
+    pub name: &'static str,
+    pub exp_bits: u32,
+    pub man_bits: u32,
+}
+
+pub const F16: Format = Format { name: "f16", exp_bits: 5, man_bits: 10 };
+pub const BF16: Format = Format { name: "bf16", exp_bits: 8, man_bits: 7 };
+pub const F32: Format = Format { name: "f32", exp_bits: 8, man_bits: 23 };
+pub const F64: Format = Format { name: "f64", exp_bits: 11, man_bits: 52 };
+pub const ALL: [Format; 4] = [F16, BF16, F32, F64];
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        ALL.into_iter().find(|f| f.name == name)
+    }
+
+    pub fn total_bits(self) -> u32 {
+        1 + self.exp_bits + self.man_bits
+    }
+
+    pub fn bias(self) -> i32 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    /// Significant bits, hidden bit included.
+    pub fn precision(self) -> u32 {
+        self.man_bits + 1
+    }
+
+    fn exp_max_field(self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    fn man_mask(self) -> u64 {
+        (1 << self.man_bits) - 1
+    }
+
+    pub fn emin(self) -> i32 {
+        1 - self.bias()
+    }
+
+    pub fn emax(self) -> i32 {
+        self.bias()
+    }
+
+    pub fn pack(self, sign: bool, exp_field: u64, mantissa: u64) -> u64 {
+        (sign as u64) << (self.exp_bits + self.man_bits) | exp_field << self.man_bits | mantissa
+    }
+
+    pub fn infinity(self, sign: bool) -> u64 {
+        self.pack(sign, self.exp_max_field(), 0)
+    }
+
+    pub fn max_finite(self, sign: bool) -> u64 {
+        self.pack(sign, self.exp_max_field() - 1, self.man_mask())
+    }
+
+    /// Quiet NaN with the given payload (truncated to fit).
+    pub fn nan(self, payload: u64) -> u64 {
+        let quiet = 1 << (self.man_bits - 1);
+        self.pack(false, self.exp_max_field(), quiet | (payload & (quiet - 1)))
+    }
+
+    /// Signalling NaN (quiet bit clear). The payload must be non-zero or it would be infinity.
+    pub fn snan(self, payload: u64) -> u64 {
+        let quiet = 1 << (self.man_bits - 1);
+        self.pack(false, self.exp_max_field(), (payload & (quiet - 1)).max(1))
+    }
+
+    pub fn decode(self, bits: u64) -> Parts {
+        Parts {
+            format: self,
+            bits,
+            sign: bits >> (self.exp_bits + self.man_bits) & 1 == 1,
+            exp_field: bits >> self.man_bits & self.exp_max_field(),
+            mantissa: bits & self.man_mask(),
+        }
+    }
+
+    /// Next representable value towards +infinity (NaN and +inf stay put).
+    pub fn next_up(self, bits: u64) -> u64 {
+        let p = self.decode(bits);
+        match p.class() {
+            Class::Nan | Class::Infinite if !p.sign => bits,
+            Class::Zero => 1, // smallest positive subnormal
+            _ if p.sign => bits - 1,
+            _ => bits + 1,
+        }
+    }
+
+    pub fn next_down(self, bits: u64) -> u64 {
+        let flip = 1 << (self.total_bits() - 1);
+        self.next_up(bits ^ flip) ^ flip
+    }
+
+    /// How many representable values apart `a` and `b` are (finite inputs).
+    pub fn ulps_between(self, a: u64, b: u64) -> u64 {
+        // Map the sign-magnitude encoding onto a monotonic integer line.
+        let key = |bits: u64| -> i128 {
+            let p = self.decode(bits);
+            let mag = (bits & !(1 << (self.total_bits() - 1))) as i128;
+            if p.sign { -mag } else { mag }
+        };
+        key(a).abs_diff(key(b)) as u64
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Zero,
+    Subnormal,
+    Normal,
+    Infinite,
+    Nan,
+}
+
+/// A bit pattern split into its fields.
+#[derive(Debug, Clone, Copy)]
+pub struct Parts {
+    pub format: Format,
+    pub bits: u64,
+    pub sign: bool,
+    pub exp_field: u64,
+    pub mantissa: u64,
+}
+
+impl Parts {
+    pub fn class(&self) -> Class {
+        let f = self.format;
+        match (self.exp_field, self.mantissa) {
+            (0, 0) => Class::Zero,
+            (0, _) => Class::Subnormal,
+            (e, 0) if e == f.exp_max_field() => Class::Infinite,
+            (e, _) if e == f.exp_max_field() => Class::Nan,
+            _ => Class::Normal,
+        }
+    }
+
+    /// For NaNs: (quiet?, payload).
+    pub fn nan_info(&self) -> (bool, u64) {
+        let quiet = 1 << (self.format.man_bits - 1);
+        (self.mantissa & quiet != 0, self.mantissa & (quiet - 1))
+    }
+
+    /// Finite value as significand * 2^exponent (significand is an integer).
+    pub fn significand_exponent(&self) -> (u64, i32) {
+        let f = self.format;
+        match self.class() {
+            Class::Normal => (
+                self.mantissa | 1 << f.man_bits,
+                self.exp_field as i32 - f.bias() - f.man_bits as i32,
+            ),
+            _ => (self.mantissa, f.emin() - f.man_bits as i32),
+        }
+    }
+
+    /// The unbiased exponent as used in the value (emin for subnormals).
+    pub fn exponent(&self) -> i32 {
+        match self.class() {
+            Class::Normal => self.exp_field as i32 - self.format.bias(),
+            _ => self.format.emin(),
+        }
+    }
+
+    pub fn to_ratio(&self) -> Ratio {
+        let (m, e) = self.significand_exponent();
+        Ratio::from_binary(self.sign, m, e)
+    }
+
+    /// Size of one step between this value and its neighbour further from zero.
+    pub fn ulp(&self) -> Ratio {
+        Ratio::from_binary(false, 1, self.exponent() - self.format.man_bits as i32)
+    }
+
+    /// Converts to f64 (exact for every format here, since all fit in f64).
+    pub fn to_f64(&self) -> f64 {
+        match self.class() {
+            Class::Nan => f64::NAN,
+            Class::Infinite if self.sign => f64::NEG_INFINITY,
+            Class::Infinite => f64::INFINITY,
+            _ => {
+                let (m, e) = self.significand_exponent();
+                let half = e / 2;
+                let v = m as f64 * 2f64.powi(half) * 2f64.powi(e - half);
+                if self.sign { -v } else { v }
+            }
+        }
+    }
+
+    /// The exact decimal value. Every binary fraction has a finite decimal expansion:
+    /// m * 2^-k = m * 5^k / 10^k.
+    pub fn exact_decimal(&self) -> String {
+        match self.class() {
+            Class::Nan => return "NaN".to_string(),
+            Class::Infinite => return if self.sign { "-inf" } else { "inf" }.to_string(),
+            _ => {}
+        }
+        let (m, e) = self.significand_exponent();
+        let sign = if self.sign { "-" } else { "" };
+        if e >= 0 {
+            return format!("{sign}{}", Big::from_u64(m).shl(e as usize).to_decimal());
+        }
+        let k = e.unsigned_abs() as usize;
+        let digits = Big::from_u64(m).mul(&Big::pow(5, k as u32)).to_decimal();
+        let digits = format!("{digits:0>width$}", width = k + 1);
+        let (int, frac) = digits.split_at(digits.len() - k);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() { format!("{sign}{int}") } else { format!("{sign}{int}.{frac}") }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rounding
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    NearestEven,
+    TowardZero,
+    Up,   // toward +infinity
+    Down, // toward -infinity
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::NearestEven, Mode::TowardZero, Mode::Up, Mode::Down];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::NearestEven => "nearest-even",
+            Mode::TowardZero => "toward zero",
+            Mode::Up => "toward +inf",
+            Mode::Down => "toward -inf",
+        }
+    }
+}
+
+/// Result of rounding an exact value into a format.
+#[derive(Debug, Clone)]
+pub struct Rounded {
+    pub bits: u64,
+    /// `rounded - exact`, exactly (zero when the input was representable).
+    pub error: Ratio,
+    pub overflow: bool,
+}
+
+// Compares num/den with 2^e.
+fn cmp_pow2(num: &Big, den: &Big, e: i32) -> std::cmp::Ordering {
+    if e >= 0 { num.cmp(&den.shl(e as usize)) } else { num.shl(e.unsigned_abs() as usize).cmp(den) }
+}
+
+/// Rounds the exact value `x` to `format` using `mode`.
+pub fn round(x: &Ratio, format: Format, mode: Mode) -> Rounded {
+    let f = format;
+    if x.is_zero() {
+        return Rounded { bits: f.pack(x.neg, 0, 0), error: Ratio::zero(), overflow: false };
+    }
+
+    // e2 = floor(log2(|x|)): start from the bit lengths and correct by one.
+    let mut e2 = x.num.bit_len() as i32 - x.den.bit_len() as i32;
+    if cmp_pow2(&x.num, &x.den, e2) == std::cmp::Ordering::Less {
+        e2 -= 1;
+    }
+    let e = e2.max(f.emin()).min(f.emax() + 1);
+
+    // q = floor(|x| * 2^s) holds the significand; s puts the leading bit at position p-1.
+    let s = f.precision() as i32 - 1 - e;
+    let (n, d) = if s >= 0 {
+        (x.num.shl(s as usize), x.den.clone())
+    } else {
+        (x.num.clone(), x.den.shl(s.unsigned_abs() as usize))
+    };
+    let (mut q, r) = n.divmod(&d);
+
+    let round_away = match mode {
+        _ if r.is_zero() => false,
+        Mode::NearestEven => match r.shl(1).cmp(&d) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Equal => q.is_odd(),
+            std::cmp::Ordering::Less => false,
+        },
+        Mode::TowardZero => false,
+        Mode::Up => !x.neg,
+        Mode::Down => x.neg,
+    };
+    if round_away {
+        q = q.add_small(1);
+    }
+
+    let mut q = q.low_u64();
+    let mut e = e;
+    if q == 1 << f.precision() {
+        q >>= 1;
+        e += 1;
+    }
+
+    if e > f.emax() {
+        // Too big: infinity, unless the mode rounds toward zero from this side.
+        let to_inf = match mode {
+            Mode::NearestEven => true,
+            Mode::TowardZero => false,
+            Mode::Up => !x.neg,
+            Mode::Down => x.neg,
+        };
+        let bits = if to_inf { f.infinity(x.neg) } else { f.max_finite(x.neg) };
+        let error = if to_inf { Ratio::zero() } else { f.decode(bits).to_ratio().sub(x) };
+        return Rounded { bits, error, overflow: true };
+    }
+
+    let bits = if q < 1 << f.man_bits {
+        f.pack(x.neg, 0, q) // subnormal (or zero after rounding down)
+    } else {
+        f.pack(x.neg, (e + f.bias()) as u64, q & f.man_mask())
+    };
+    let error = f.decode(bits).to_ratio().sub(x);
+    Rounded { bits, error, overflow: false }
+}
+
+/// Parses a value for `format`: a decimal literal, "inf", "-inf", "nan", "nan:PAYLOAD",
+/// "snan:PAYLOAD", or "0x..." raw bits.
+pub fn parse_value(s: &str, format: Format, mode: Mode) -> Option<Rounded> {
+    let exact = |bits| Some(Rounded { bits, error: Ratio::zero(), overflow: false });
+    let lower = s.to_ascii_lowercase();
+    let num = |p: &str| match p.strip_prefix("0x") {
+        Some(h) => u64::from_str_radix(h, 16).ok(),
+        None => p.parse().ok(),
+    };
+    match lower.as_str() {
+        "inf" | "+inf" | "infinity" => return exact(format.infinity(false)),
+        "-inf" | "-infinity" => return exact(format.infinity(true)),
+        "nan" => return exact(format.nan(0)),
+        _ => {}
+    }
+    if let Some(p) = lower.strip_prefix("nan:") {
+        return exact(format.nan(num(p)?));
+    }
+    if let Some(p) = lower.strip_prefix("snan:") {
+        return exact(format.snan(num(p)?));
+    }
+    if let Some(h) = lower.strip_prefix("0x") {
+        let bits = u64::from_str_radix(h, 16).ok()?;
+        let width = format.total_bits();
+        return if width < 64 && bits >> width != 0 { None } else { exact(bits) };
+    }
+    Some(round(&Ratio::parse_decimal(s)?, format, mode))
+}
+
+/// Shortest decimal string that rounds back to exactly `bits` (what `{}` prints
+/// for f32/f64). Works for every format by trying 1, 2, 3, ... significant digits.
+pub fn shortest(format: Format, bits: u64) -> String {
+    let parts = format.decode(bits);
+    match parts.class() {
+        Class::Nan | Class::Infinite => return parts.exact_decimal(),
+        Class::Zero => return if parts.sign { "-0.0" } else { "0.0" }.to_string(),
+        _ => {}
+    }
+    let v = parts.to_f64();
+    for digits in 1..=17 {
+        let candidate = format!("{:.*e}", digits - 1, v);
+        let back = Ratio::parse_decimal(&candidate).map(|r| round(&r, format, Mode::NearestEven).bits);
+        if back == Some(bits) {
+            return plain(&candidate);
+        }
+    }
+    format!("{v:e}")
+}
+
+// "3.1415925e0" -> "3.1415925", "1.5e-3" -> "0.0015"; very large or small stay in e-notation.
+fn plain(sci: &str) -> String {
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    if !(-6..21).contains(&exp) {
+        return sci.to_string();
+    }
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let point = 1 + exp; // digits before the decimal point
+    if point <= 0 {
+        format!("{sign}0.{}{digits}", "0".repeat(point.unsigned_abs() as usize))
+    } else if point as usize >= digits.len() {
+        format!("{sign}{digits}{}", "0".repeat(point as usize - digits.len()))
+    } else {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{sign}{int}.{frac}")
+    }
+}
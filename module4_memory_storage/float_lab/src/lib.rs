@@ -0,0 +1,75 @@
+// src/lib.rs
+//
+// IEEE 754 explorer: f16, bf16, f32 and f64 taken apart bit by bit.
+//
+// A float is NOT a decimal number. It is sign * significand * 2^exponent with a
+// fixed number of significand bits, so most decimal literals fall BETWEEN two
+// representable values and get rounded to one of them:
+//
+//   3.1415926 as f32:
+//     representable neighbours ...  3.14159250259  3.14159274101 ...
+//     3.1415926 is closer to the first one, so that is what gets stored.
+//     Printing picks the SHORTEST decimal that maps back to the same bits:
+//     "3.1415925". The value did not change when printing; it changed on input.
+//
+// format.rs knows the bit layouts and does correctly rounded conversion from
+// exact decimals (big.rs) in all four IEEE rounding modes. This file adds the
+// catastrophic-cancellation analysis.
+
+pub mod big;
+pub mod format;
+
+pub use big::Ratio;
+pub use format::{round, shortest, Class, Format, Mode, Parts, Rounded, ALL, BF16, F16, F32, F64};
+
+/// What happens to `a - b` when both are first rounded to `format`.
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    pub a: Rounded,
+    pub b: Rounded,
+    pub result: Rounded,
+    /// `a - b` computed exactly from the decimal inputs.
+    pub exact: Ratio,
+    /// Relative error of each input after rounding, and of the result.
+    pub rel_error_a: f64,
+    pub rel_error_b: f64,
+    pub rel_error_result: f64,
+    /// Roughly how many leading bits cancelled: log2(|a| / |a - b|).
+    pub bits_cancelled: f64,
+}
+
+fn rel_error(error: &Ratio, exact: &Ratio) -> f64 {
+    if exact.is_zero() {
+        if error.is_zero() { 0.0 } else { f64::INFINITY }
+    } else {
+        error.div(exact).abs().to_f64()
+    }
+}
+
+/// Rounds `a` and `b` to `format`, subtracts (IEEE subtraction: the exact
+/// difference of the two stored values, rounded once), and compares with the
+/// exact `a - b`.
+pub fn cancellation(a: &Ratio, b: &Ratio, format: Format) -> Cancellation {
+    let ra = round(a, format, Mode::NearestEven);
+    let rb = round(b, format, Mode::NearestEven);
+    let stored_diff = format.decode(ra.bits).to_ratio().sub(&format.decode(rb.bits).to_ratio());
+    let result = round(&stored_diff, format, Mode::NearestEven);
+    let exact = a.sub(b);
+
+    let result_error = format.decode(result.bits).to_ratio().sub(&exact);
+    let bits_cancelled = if exact.is_zero() {
+        f64::INFINITY
+    } else {
+        a.div(&exact).abs().to_f64().log2().max(0.0)
+    };
+    Cancellation {
+        rel_error_a: rel_error(&ra.error, a),
+        rel_error_b: rel_error(&rb.error, b),
+        rel_error_result: rel_error(&result_error, &exact),
+        a: ra,
+        b: rb,
+        result,
+        exact,
+        bits_cancelled,
+    }
+}
@@ -0,0 +1,263 @@
+// src/main.rs
+//
+// `float_lab`                                 => guided tour
+// `float_lab show <value> [fmt...]`           => bit fields, exact value, neighbours
+// `float_lab round <value> [fmt...]`          => the four rounding modes side by side
+// `float_lab cancel <a> <b> [fmt...]`         => catastrophic cancellation in a - b
+// `float_lab ulps <a> <b> [fmt...]`           => representable values between a and b
+// `float_lab -i`                              => read those commands from stdin, one per line
+//
+// fmt: f16, bf16, f32, f64 (default: all four).
+// value: a decimal literal (3.1415926, 1e-8, -0), inf, -inf, nan, nan:PAYLOAD,
+//        snan:PAYLOAD, or raw bits as 0x... (then give exactly one fmt).
+
+use std::io::BufRead;
+use std::process::ExitCode;
+
+use float_lab::format::parse_value;
+use float_lab::{cancellation, shortest, Class, Format, Mode, Ratio, ALL, BF16, F16, F32};
+
+fn binary(format: Format, bits: u64) -> String {
+    let p = format.decode(bits);
+    format!(
+        "{} | {:0e$b} | {:0m$b}",
+        p.sign as u8,
+        p.exp_field,
+        p.mantissa,
+        e = format.exp_bits as usize,
+        m = format.man_bits as usize
+    )
+}
+
+// Plain notation for everyday magnitudes, e-notation for the rest.
+fn num(v: f64) -> String {
+    if v == 0.0 || (1e-4..1e16).contains(&v.abs()) { format!("{v}") } else { format!("{v:e}") }
+}
+
+fn hex_bits(format: Format, bits: u64) -> String {
+    format!("0x{bits:0w$X}", w = format.total_bits() as usize / 4)
+}
+
+fn show(input: &str, format: Format) -> Result<(), String> {
+    let rounded = parse_value(input, format, Mode::NearestEven).ok_or(format!("cannot parse '{input}' as {format}"))?;
+    let bits = rounded.bits;
+    let p = format.decode(bits);
+
+    println!("{input} as {format}");
+    println!("  bits       {}", hex_bits(format, bits));
+    println!("  fields     {}   (sign | exponent | mantissa)", binary(format, bits));
+
+    let class = p.class();
+    match class {
+        Class::Nan => {
+            let (quiet, payload) = p.nan_info();
+            let kind = if quiet { "quiet" } else { "signalling" };
+            println!("  class      NaN ({kind}), payload 0x{payload:X}");
+            println!("             exponent all ones, mantissa != 0; the payload rides along through arithmetic");
+            return Ok(());
+        }
+        Class::Infinite => {
+            println!("  class      {}infinity (exponent all ones, mantissa 0)", if p.sign { "-" } else { "+" });
+            if rounded.overflow {
+                println!("             the input is beyond the largest finite {format} and overflowed");
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let sign = if p.sign { "-" } else { "+" };
+    match class {
+        Class::Normal => println!(
+            "  class      normal: {sign}1.{:0m$b}b * 2^({} - {}) = 2^{}",
+            p.mantissa,
+            p.exp_field,
+            format.bias(),
+            p.exponent(),
+            m = format.man_bits as usize
+        ),
+        Class::Subnormal => println!(
+            "  class      SUBNORMAL: {sign}0.{:0m$b}b * 2^{}  (no hidden 1, precision shrinks)",
+            p.mantissa,
+            format.emin(),
+            m = format.man_bits as usize
+        ),
+        _ => println!("  class      {}zero", if p.sign { "negative " } else { "positive " }),
+    }
+    println!("  exact      {}", p.exact_decimal());
+    println!("  shortest   {}   (fewest digits that read back as these bits)", shortest(format, bits));
+
+    let ulp = p.ulp();
+    if class == Class::Zero && !rounded.error.is_zero() {
+        println!("  rounding   UNDERFLOW: the input is below half the smallest subnormal, so it became 0");
+    } else if !rounded.error.is_zero() {
+        let in_ulps = rounded.error.div(&ulp).to_f64();
+        let dir = if rounded.error.neg { "below" } else { "above" };
+        // The absolute error can be too small even for f64 (near f64's own subnormals).
+        let abs = match rounded.error.abs().to_f64() {
+            0.0 => "less than 1e-308".to_string(),
+            e => format!("{e:.3e}"),
+        };
+        println!("  rounding   stored value is {abs} {dir} the input = {:.3} ULP (nearest-even)", in_ulps.abs());
+    } else if class != Class::Zero && Ratio::parse_decimal(input).is_some() {
+        println!("  rounding   none: the input is exactly representable");
+    }
+    println!("  ULP        {}   (gap to the next value away from zero)", num(ulp.to_f64()));
+    for (name, n) in [("next down", format.next_down(bits)), ("next up", format.next_up(bits))] {
+        let np = format.decode(n);
+        println!("  {name:<10} {:<26} {}", shortest(format, n), hex_bits(format, n));
+        if np.class() == Class::Infinite {
+            println!("             (the next step overflows to infinity)");
+        }
+    }
+    Ok(())
+}
+
+fn round_modes(input: &str, format: Format) -> Result<(), String> {
+    println!("{input} as {format}:");
+    for mode in Mode::ALL {
+        let r = parse_value(input, format, mode).ok_or(format!("cannot parse '{input}'"))?;
+        let err = match (r.overflow, r.error.is_zero()) {
+            (true, true) => "overflow to infinity".to_string(),
+            (true, false) => format!("overflow, clamped: error {:+.3e}", r.error.to_f64()),
+            (false, true) => "exact".to_string(),
+            (false, false) => format!("error {:+.3e}", r.error.to_f64()),
+        };
+        println!(
+            "  {:<13} {}  {:<26} {err}",
+            mode.name(),
+            hex_bits(format, r.bits),
+            shortest(format, r.bits)
+        );
+    }
+    Ok(())
+}
+
+fn cancel(a: &str, b: &str, format: Format) -> Result<(), String> {
+    let ra = Ratio::parse_decimal(a).ok_or(format!("'{a}' is not a decimal number"))?;
+    let rb = Ratio::parse_decimal(b).ok_or(format!("'{b}' is not a decimal number"))?;
+    let c = cancellation(&ra, &rb, format);
+    println!("{a} - {b} in {format}:");
+    println!("  a stored as {:<24} relative error {:.2e}", shortest(format, c.a.bits), c.rel_error_a);
+    println!("  b stored as {:<24} relative error {:.2e}", shortest(format, c.b.bits), c.rel_error_b);
+    println!("  exact a - b   {}", num(c.exact.to_f64()));
+    println!("  {format} a - b     {:<24} relative error {:.2e}", shortest(format, c.result.bits), c.rel_error_result);
+    if c.bits_cancelled.is_finite() {
+        println!(
+            "  ~{:.1} of {} significant bits cancelled; the subtraction itself was (nearly) exact,",
+            c.bits_cancelled,
+            format.precision()
+        );
+        println!("  but the tiny input errors are now large RELATIVE to the small result.");
+    } else {
+        println!("  a - b is exactly zero; the float result is {}", shortest(format, c.result.bits));
+    }
+    Ok(())
+}
+
+fn ulps(a: &str, b: &str, format: Format) -> Result<(), String> {
+    let ra = parse_value(a, format, Mode::NearestEven).ok_or(format!("cannot parse '{a}'"))?;
+    let rb = parse_value(b, format, Mode::NearestEven).ok_or(format!("cannot parse '{b}'"))?;
+    let finite = |bits| matches!(format.decode(bits).class(), Class::Zero | Class::Subnormal | Class::Normal);
+    if !finite(ra.bits) || !finite(rb.bits) {
+        return Err("ulps needs two finite values".into());
+    }
+    println!(
+        "{format}: {} and {} are {} ULPs apart",
+        shortest(format, ra.bits),
+        shortest(format, rb.bits),
+        format.ulps_between(ra.bits, rb.bits)
+    );
+    Ok(())
+}
+
+fn formats(names: &[&str]) -> Result<Vec<Format>, String> {
+    if names.is_empty() {
+        return Ok(ALL.to_vec());
+    }
+    names.iter().map(|n| Format::from_name(n).ok_or(format!("unknown format '{n}' (f16, bf16, f32, f64)"))).collect()
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        ["show", v, fmts @ ..] => formats(fmts)?.into_iter().try_for_each(|f| {
+            show(v, f)?;
+            println!();
+            Ok(())
+        }),
+        ["round", v, fmts @ ..] => formats(fmts)?.into_iter().try_for_each(|f| round_modes(v, f)),
+        ["cancel", a, b, fmts @ ..] => formats(fmts)?.into_iter().try_for_each(|f| cancel(a, b, f)),
+        ["ulps", a, b, fmts @ ..] => formats(fmts)?.into_iter().try_for_each(|f| ulps(a, b, f)),
+        _ => Err("usage: float_lab [show <v> | round <v> | cancel <a> <b> | ulps <a> <b>] [f16|bf16|f32|f64...] | -i".into()),
+    }
+}
+
+fn interactive() {
+    println!("commands: show <v> | round <v> | cancel <a> <b> | ulps <a> <b>  [formats], empty line quits");
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            break;
+        }
+        if let Err(e) = run(&words) {
+            println!("error: {e}");
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] => {
+            tour();
+            ExitCode::SUCCESS
+        }
+        ["-i"] => {
+            interactive();
+            ExitCode::SUCCESS
+        }
+        _ => match run(&args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+fn tour() {
+    println!("=== 1) Why does 3.1415926f32 print as 3.1415925? ===\n");
+    show("3.1415926", F32).unwrap();
+    println!("\n3.1415926 lies between two f32 values ~2.4e-7 apart and is rounded to the");
+    println!("nearer one. That stored value is 3.14159250259..., and the shortest decimal");
+    println!("that reads back as the same bits is 3.1415925, so that is what gets printed.\n");
+
+    println!("=== 2) 0.1 in every format: none of them can hold it exactly ===\n");
+    for f in ALL {
+        show("0.1", f).unwrap();
+        println!();
+    }
+
+    println!("=== 3) Edges of f16 ===\n");
+    for v in ["65504", "65520", "6e-8", "-0", "nan:0x15", "snan:1"] {
+        show(v, F16).unwrap();
+        println!();
+    }
+
+    println!("=== 4) Rounding modes: a tie in bf16 and a non-tie in f32 ===\n");
+    round_modes("1.00390625", BF16).unwrap(); // exactly halfway between 1 and 1 + 2^-7
+    println!("  (nearest-even breaks the tie toward the value whose last mantissa bit is 0)\n");
+    round_modes("0.1", F32).unwrap();
+    println!();
+
+    println!("=== 5) Catastrophic cancellation ===\n");
+    cancel("1.0000001", "1", F32).unwrap();
+    println!();
+    cancel("1.0000001", "1", float_lab::F64).unwrap();
+    println!();
+    cancel("0.3", "0.1", F16).unwrap();
+    println!("\nTry: float_lab show 16777217 f32    float_lab round 1e40 f32    float_lab -i");
+}
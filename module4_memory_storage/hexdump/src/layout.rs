@@ -0,0 +1,290 @@
+// src/layout.rs
+//
+// Layout descriptions: "which bytes mean what".
+//
+// A layout is a list of `name:type` entries, separated by spaces, commas or
+// newlines (`#` starts a comment). Fields follow each other with no padding:
+//
+//   magic:u32le version:u8 temp:i16le voltage:f32le name:str/u8
+//
+// Types:
+//   u8 i8                          one byte
+//   u16le u32be i64le ...          integers, endianness is REQUIRED for >1 byte
+//   f32le f64be ...                IEEE 754 floats
+//   str:N    bytes:N               exactly N bytes (UTF-8 text / raw)
+//   str/P    bytes/P               length prefix P (u8, u16le, u32be, ...), then that many bytes
+//   skip:N                         N bytes to ignore (padding, reserved)
+
+use std::fmt;
+
+use record_codec::FieldInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Len {
+    Fixed(usize),
+    /// A length prefix of this many bytes (1, 2, 4 or 8) comes first.
+    Prefixed { bytes: usize, endian: Endian },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    Int { bytes: usize, signed: bool, endian: Endian },
+    Float { bytes: usize, endian: Endian },
+    Str(Len),
+    Bytes(Len),
+    Skip(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub spec: String, // the type as written, for display
+    pub ty: FieldType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutError {
+    pub token: String,
+    pub msg: String,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}': {}", self.token, self.msg)
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+fn err(token: &str, msg: impl Into<String>) -> LayoutError {
+    LayoutError { token: token.to_string(), msg: msg.into() }
+}
+
+// "u16le" -> (bytes 2, signed false, Little). Single bytes need no suffix.
+fn parse_int(s: &str) -> Option<(usize, bool, Endian)> {
+    let (body, endian) = if let Some(b) = s.strip_suffix("le") {
+        (b, Some(Endian::Little))
+    } else if let Some(b) = s.strip_suffix("be") {
+        (b, Some(Endian::Big))
+    } else {
+        (s, None)
+    };
+    let signed = match body.as_bytes().first()? {
+        b'u' => false,
+        b'i' => true,
+        _ => return None,
+    };
+    let bytes = match &body[1..] {
+        "8" => 1,
+        "16" => 2,
+        "32" => 4,
+        "64" => 8,
+        _ => return None,
+    };
+    match (bytes, endian) {
+        (1, e) => Some((1, signed, e.unwrap_or(Endian::Little))),
+        (_, Some(e)) => Some((bytes, signed, e)),
+        _ => None,
+    }
+}
+
+fn parse_len(token: &str, rest: &str) -> Result<Len, LayoutError> {
+    if let Some(n) = rest.strip_prefix(':') {
+        return n.parse().map(Len::Fixed).map_err(|_| err(token, "expected a byte count after ':'"));
+    }
+    if let Some(prefix) = rest.strip_prefix('/') {
+        return match parse_int(prefix) {
+            Some((bytes, false, endian)) => Ok(Len::Prefixed { bytes, endian }),
+            _ => Err(err(token, "length prefix must be u8, u16le/be, u32le/be or u64le/be")),
+        };
+    }
+    Err(err(token, "expected ':N' (fixed size) or '/u8', '/u16le', ... (length prefix)"))
+}
+
+pub fn parse_type(token: &str, ty: &str) -> Result<FieldType, LayoutError> {
+    if let Some(rest) = ty.strip_prefix("str") {
+        return parse_len(token, rest).map(FieldType::Str);
+    }
+    if let Some(rest) = ty.strip_prefix("bytes") {
+        return parse_len(token, rest).map(FieldType::Bytes);
+    }
+    if let Some(n) = ty.strip_prefix("skip:") {
+        return n.parse().map(FieldType::Skip).map_err(|_| err(token, "expected skip:N"));
+    }
+    if let Some(bits) = ty.strip_prefix('f') {
+        let (bytes, endian) = match bits {
+            "32le" => (4, Endian::Little),
+            "32be" => (4, Endian::Big),
+            "64le" => (8, Endian::Little),
+            "64be" => (8, Endian::Big),
+            _ => return Err(err(token, "floats are f32le, f32be, f64le or f64be")),
+        };
+        return Ok(FieldType::Float { bytes, endian });
+    }
+    match parse_int(ty) {
+        Some((bytes, signed, endian)) => Ok(FieldType::Int { bytes, signed, endian }),
+        None => Err(err(token, "unknown type (multi-byte integers need an le/be suffix)")),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout {
+    pub fields: Vec<Field>,
+}
+
+impl Layout {
+    pub fn parse(src: &str) -> Result<Layout, LayoutError> {
+        let mut fields = Vec::new();
+        for line in src.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            for token in line.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+                let Some((name, ty)) = token.split_once(':') else {
+                    return Err(err(token, "expected name:type"));
+                };
+                let ty_lower = ty.to_ascii_lowercase();
+                fields.push(Field {
+                    name: name.to_string(),
+                    ty: parse_type(token, &ty_lower)?,
+                    spec: ty_lower,
+                });
+            }
+        }
+        Ok(Layout { fields })
+    }
+
+    /// Builds a layout from a `record_codec` record's `LAYOUT` table.
+    pub fn from_record(info: &[FieldInfo]) -> Result<Layout, LayoutError> {
+        let mut spec = String::new();
+        for f in info {
+            // Codec descriptions look like "i16 LE", "UTF-8 string", "bytes";
+            // prefixes like "u8 length" or "u16 BE length".
+            let ty = match (f.codec.split_once(' '), f.prefix) {
+                (_, Some(prefix)) => {
+                    let kind = if f.codec == "bytes" { "bytes" } else { "str" };
+                    let prefix = prefix.trim_end_matches(" length").replace(' ', "");
+                    format!("{kind}/{prefix}")
+                }
+                (Some((num, endian)), None) => format!("{num}{endian}"),
+                (None, None) => f.codec.to_string(),
+            };
+            spec.push_str(&format!("{}:{} ", f.name, ty.to_ascii_lowercase()));
+        }
+        Layout::parse(&spec)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Applying a layout to bytes
+// ---------------------------------------------------------------------------
+
+/// One field located in the data.
+#[derive(Debug, Clone)]
+pub struct Located {
+    pub name: String,
+    pub spec: String,
+    pub offset: usize, // absolute, in the data
+    pub len: usize,    // bytes covered, length prefix included
+    pub value: String,
+    pub ok: bool,
+}
+
+fn read_uint(bytes: &[u8], endian: Endian) -> u64 {
+    let mut v = 0u64;
+    match endian {
+        Endian::Little => bytes.iter().rev().for_each(|&b| v = v << 8 | b as u64),
+        Endian::Big => bytes.iter().for_each(|&b| v = v << 8 | b as u64),
+    }
+    v
+}
+
+fn show_int(raw: u64, bytes: usize, signed: bool) -> String {
+    let bits = bytes * 8;
+    let digits = bytes * 2;
+    if signed {
+        // Sign-extend from `bits` to 64.
+        let value = ((raw << (64 - bits)) as i64) >> (64 - bits);
+        format!("{value} (0x{raw:0digits$X})")
+    } else {
+        format!("{raw} (0x{raw:0digits$X})")
+    }
+}
+
+fn show_text(bytes: &[u8]) -> (String, bool) {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => (format!("{s:?}"), true),
+        Err(e) => (format!("invalid UTF-8: {e}"), false),
+    }
+}
+
+/// Walks `data` from `start`, one field after another. Stops at the first field
+/// that does not fit (that entry is marked `ok: false`).
+pub fn locate(layout: &Layout, data: &[u8], start: usize) -> Vec<Located> {
+    let mut out = Vec::new();
+    let mut pos = start;
+    for field in &layout.fields {
+        let left = data.len().saturating_sub(pos);
+        let mut entry = Located {
+            name: field.name.clone(),
+            spec: field.spec.clone(),
+            offset: pos,
+            len: 0,
+            value: String::new(),
+            ok: true,
+        };
+
+        // How many bytes the prefix (if any) and the body take.
+        let (prefix, body) = match field.ty {
+            FieldType::Int { bytes, .. } | FieldType::Float { bytes, .. } => (0, bytes),
+            FieldType::Skip(n) | FieldType::Str(Len::Fixed(n)) | FieldType::Bytes(Len::Fixed(n)) => (0, n),
+            FieldType::Str(Len::Prefixed { bytes, endian }) | FieldType::Bytes(Len::Prefixed { bytes, endian }) => {
+                if left < bytes {
+                    (bytes, 0)
+                } else {
+                    // The prefix comes from the data: it can be anything up to 2^64-1.
+                    (bytes, usize::try_from(read_uint(&data[pos..pos + bytes], endian)).unwrap_or(usize::MAX))
+                }
+            }
+        };
+        // Compared without adding, since `body` may be near usize::MAX.
+        if prefix > left || body > left - prefix {
+            entry.len = left;
+            entry.value = match prefix {
+                0 => format!("truncated: needs {body} bytes, {left} left"),
+                _ => format!("truncated: needs {prefix} + {body} bytes, {left} left"),
+            };
+            entry.ok = false;
+            out.push(entry);
+            break;
+        }
+
+        let raw = &data[pos + prefix..pos + prefix + body];
+        entry.len = prefix + body;
+        entry.value = match field.ty {
+            FieldType::Int { bytes, signed, endian } => show_int(read_uint(raw, endian), bytes, signed),
+            FieldType::Float { bytes: 4, endian } => {
+                let bits = read_uint(raw, endian) as u32;
+                format!("{:?} (bits 0x{bits:08X})", f32::from_bits(bits))
+            }
+            FieldType::Float { endian, .. } => {
+                let bits = read_uint(raw, endian);
+                format!("{:?} (bits 0x{bits:016X})", f64::from_bits(bits))
+            }
+            FieldType::Str(_) => {
+                let (text, ok) = show_text(raw);
+                entry.ok = ok;
+                if prefix > 0 { format!("len {body}, {text}") } else { text }
+            }
+            FieldType::Bytes(_) => format!("{body} bytes"),
+            FieldType::Skip(_) => "(skipped)".to_string(),
+        };
+        out.push(entry);
+        pos += prefix + body;
+    }
+    out
+}
@@ -0,0 +1,137 @@
+// src/lib.rs
+//
+// xxd-style hex dumps, optionally annotated with a layout.
+//
+//   00000000: d4c3 b2a1 0197 ff52 b87e 4009 7365 6e73  .......R.~@.sens
+//             a-------- b-c----d---------e-----------
+//
+// Each line: offset, the bytes in groups, then an ASCII gutter ('.' for anything
+// that is not printable ASCII). With a layout (see layout.rs), a marker row under
+// each line shows which field every byte belongs to — the letter marks where a
+// field starts — and a table after the dump lists each field's decoded value.
+//
+// Reading the same bytes with a different layout (u32be instead of u32le, or
+// f32 instead of i32) is the quickest way to see what endianness and IEEE 754
+// encoding actually do.
+
+pub mod layout;
+
+pub use layout::{Layout, LayoutError, Located, locate};
+
+/// Space-separated uppercase hex, for printing one short buffer on one line.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+}
+
+/// Most bytes per line; wider lines only wrap in any terminal.
+pub const MAX_WIDTH: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Bytes per line, 1..=MAX_WIDTH (clamped).
+    pub width: usize,
+    /// Bytes per group (0 = no spaces between bytes).
+    pub group: usize,
+    /// First byte to show.
+    pub start: usize,
+    /// How many bytes to show (`None` = to the end).
+    pub len: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { width: 16, group: 2, start: 0, len: None }
+    }
+}
+
+// Character column where byte `i` (0-based within a line) starts in the hex area.
+fn column(i: usize, group: usize) -> usize {
+    i * 2 + i.checked_div(group).unwrap_or(0)
+}
+
+fn hex_area_width(opts: &Options) -> usize {
+    column(opts.width, opts.group) - usize::from(opts.group != 0 && opts.width.is_multiple_of(opts.group))
+}
+
+/// Labels used in the marker rows: a..z, A..Z, then '*'.
+pub fn label(i: usize) -> char {
+    match i {
+        0..26 => (b'a' + i as u8) as char,
+        26..52 => (b'A' + (i - 26) as u8) as char,
+        _ => '*',
+    }
+}
+
+/// Dumps `data[opts.start..]`. Offsets printed are positions in `data`.
+pub fn dump(data: &[u8], opts: &Options) -> String {
+    dump_annotated(data, opts, &[])
+}
+
+/// Like `dump`, plus a marker row under each line for the `fields` it touches.
+pub fn dump_annotated(data: &[u8], opts: &Options, fields: &[Located]) -> String {
+    let width = opts.width.clamp(1, MAX_WIDTH);
+    let start = opts.start.min(data.len());
+    let end = opts.len.map_or(data.len(), |n| start.saturating_add(n).min(data.len()));
+    let hex_width = hex_area_width(&Options { width, ..*opts });
+    let mut out = String::new();
+
+    let mut line_start = start;
+    while line_start < end {
+        let line = &data[line_start..(line_start + width).min(end)];
+
+        let mut hex_part = String::new();
+        for (i, b) in line.iter().enumerate() {
+            if i > 0 && opts.group != 0 && i % opts.group == 0 {
+                hex_part.push(' ');
+            }
+            hex_part.push_str(&format!("{b:02x}"));
+        }
+        let ascii: String =
+            line.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect();
+        out.push_str(&format!("{line_start:08x}: {hex_part:<hex_width$}  {ascii}\n"));
+
+        if !fields.is_empty() {
+            let mut marks = vec![' '; hex_width];
+            let mut any = false;
+            for (i, _) in line.iter().enumerate() {
+                let at = line_start + i;
+                let Some(k) = fields.iter().position(|f| at >= f.offset && at < f.offset + f.len) else {
+                    continue;
+                };
+                any = true;
+                let col = column(i, opts.group);
+                let first = at == fields[k].offset || i == 0;
+                marks[col] = if first { label(k) } else { '-' };
+                marks[col + 1] = '-';
+                // Bridge the group gap when the previous byte is in the same field.
+                if !first && column(i - 1, opts.group) + 2 < col {
+                    marks[col - 1] = '-';
+                }
+            }
+            if any {
+                let marks: String = marks.into_iter().collect();
+                out.push_str(&format!("          {}\n", marks.trim_end()));
+            }
+        }
+        line_start += width;
+    }
+    out
+}
+
+/// The legend for `dump_annotated`: one line per field.
+pub fn field_table(fields: &[Located]) -> String {
+    let mut out = String::new();
+    for (k, f) in fields.iter().enumerate() {
+        out.push_str(&format!(
+            "  {} 0x{:04X} {:>3}B  {:<14} {:<9} {}{}\n",
+            label(k),
+            f.offset,
+            f.len,
+            f.name,
+            f.spec,
+            if f.ok { "= " } else { "!! " },
+            f.value
+        ));
+    }
+    out
+}
@@ -0,0 +1,137 @@
+// src/main.rs
+//
+// `hexdump`                                  => demo on an encoded SensorReading
+// `hexdump [options] <file>`                 => dump a file
+//
+// Options (same letters as xxd):
+//   -c N          bytes per line (default 16)
+//   -g N          bytes per group (default 2, 0 = no grouping)
+//   -s N          start offset (decimal or 0x..)
+//   -l N          number of bytes to show
+//   --layout SPEC overlay fields, e.g. "magic:u32le version:u8 name:str/u8",
+//                 or @path to read the spec from a file
+//   --record sensor   overlay the SensorReading layout from record_codec
+//   --at N        where the layout starts (default: the -s offset)
+
+use std::process::ExitCode;
+
+use hexdump::{dump, dump_annotated, field_table, locate, Layout, Options, MAX_WIDTH};
+use record_codec::SensorReading;
+
+fn parse_num(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(h) => usize::from_str_radix(h, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+struct Args {
+    opts: Options,
+    layout: Option<Layout>,
+    at: Option<usize>,
+    path: String,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut opts = Options::default();
+    let mut layout = None;
+    let mut at = None;
+    let mut path = None;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "-c" | "-g" | "-s" | "-l" | "--at" => {
+                let v = value()?;
+                let n = parse_num(v).ok_or(format!("{arg}: '{v}' is not a number"))?;
+                match arg.as_str() {
+                    "-c" if !(1..=MAX_WIDTH).contains(&n) => return Err(format!("-c: width must be 1..={MAX_WIDTH}")),
+                    "-c" => opts.width = n,
+                    "-g" => opts.group = n,
+                    "-s" => opts.start = n,
+                    "-l" => opts.len = Some(n),
+                    _ => at = Some(n),
+                }
+            }
+            "--layout" => {
+                let v = value()?;
+                let src = match v.strip_prefix('@') {
+                    Some(file) => std::fs::read_to_string(file).map_err(|e| format!("{file}: {e}"))?,
+                    None => v.clone(),
+                };
+                layout = Some(Layout::parse(&src).map_err(|e| format!("layout: {e}"))?);
+            }
+            "--record" => match value()?.as_str() {
+                "sensor" => {
+                    layout = Some(Layout::from_record(SensorReading::LAYOUT).map_err(|e| e.to_string())?)
+                }
+                other => return Err(format!("unknown record '{other}' (known: sensor)")),
+            },
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+    let path = path.ok_or("missing file")?;
+    Ok(Args { opts, layout, at, path })
+}
+
+fn show(data: &[u8], opts: &Options, layout: Option<&Layout>, at: usize) {
+    match layout {
+        None => print!("{}", dump(data, opts)),
+        Some(layout) => {
+            let fields = locate(layout, data, at);
+            print!("{}", dump_annotated(data, opts, &fields));
+            println!();
+            print!("{}", field_table(&fields));
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        demo();
+        return ExitCode::SUCCESS;
+    }
+    let args = match parse_args(&args) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("error: {e}");
+            eprintln!("usage: hexdump [-c N] [-g N] [-s N] [-l N] [--layout SPEC|@file] [--record sensor] [--at N] <file>");
+            return ExitCode::FAILURE;
+        }
+    };
+    let data = match std::fs::read(&args.path) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("error: {}: {e}", args.path);
+            return ExitCode::FAILURE;
+        }
+    };
+    show(&data, &args.opts, args.layout.as_ref(), args.at.unwrap_or(args.opts.start));
+    ExitCode::SUCCESS
+}
+
+fn demo() {
+    let reading = SensorReading { temp_tenths_c: -105, voltage: 3.98, name: "sensor-α".to_string() };
+    let bytes = reading.encode().expect("short name");
+    let opts = Options::default();
+
+    println!("=== SensorReading, plain dump ===");
+    print!("{}", dump(&bytes, &opts));
+
+    println!("\n=== Same bytes with the record's own layout ===");
+    let layout = Layout::from_record(SensorReading::LAYOUT).expect("record layouts are valid");
+    show(&bytes, &opts, Some(&layout), 0);
+
+    println!("\n=== Same bytes, read as if everything were big-endian ===");
+    let wrong = Layout::parse("magic:u32be version:u8 temp:i16be voltage:f32be name:str/u8").unwrap();
+    show(&bytes, &opts, Some(&wrong), 0);
+    println!("\nThe bytes did not change; only the reading did. 0x97 0xFF is -105 as i16le but");
+    println!("-26625 as i16be, and the voltage's four bytes turn into an unrelated huge float.");
+
+    println!("\n=== One byte per group, 8 per line ===");
+    print!("{}", dump(&bytes, &Options { width: 8, group: 1, ..opts }));
+
+    println!("\nTry: hexdump --record sensor <file>, or --layout \"a:u16le b:f64be ...\" on any file.");
+}
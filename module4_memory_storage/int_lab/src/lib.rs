@@ -0,0 +1,404 @@
+// src/lib.rs
+//
+// Integers are bit patterns; the TYPE decides what the pattern means.
+//
+// The same 8 bits 1111_1010 are:
+//   250    as unsigned
+//   -6     as two's complement   (what i8..i128 use: value = -bit7*128 + rest)
+//   -5     as one's complement   (negate = flip every bit; has a -0)
+//   -122   as sign-magnitude     (top bit is the sign, the rest is |value|; has a -0)
+//
+// Every width from 1 to 128 bits is handled the same way: a value is kept as a
+// u128 bit pattern plus an `IntType` (width and signedness). Arithmetic follows
+// Rust exactly — wrapping_*, checked_*, saturating_*, overflowing_* — and `cast`
+// reproduces what `as` does between integer types:
+//
+//   narrower target              keep the low bits (truncate)
+//   wider target, signed source  copy the sign bit into the new high bits (sign extension)
+//   wider target, unsigned       fill the new high bits with 0 (zero extension)
+//   same width                   same bits, reinterpreted
+
+use std::fmt;
+
+/// An integer type of any width: i8, u16, but also i12 or u24.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntType {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+impl IntType {
+    pub const fn new(bits: u32, signed: bool) -> IntType {
+        assert!(bits >= 1 && bits <= 128);
+        IntType { bits, signed }
+    }
+
+    /// "i8", "u128", "i12", ...
+    pub fn parse(s: &str) -> Option<IntType> {
+        let signed = match s.as_bytes().first()? {
+            b'i' => true,
+            b'u' => false,
+            _ => return None,
+        };
+        let bits: u32 = s[1..].parse().ok()?;
+        (1..=128).contains(&bits).then_some(IntType { bits, signed })
+    }
+
+    pub fn mask(self) -> u128 {
+        if self.bits == 128 { u128::MAX } else { (1 << self.bits) - 1 }
+    }
+
+    fn sign_bit(self) -> u128 {
+        1 << (self.bits - 1)
+    }
+
+    /// The pattern's value as i128 (for unsigned types, only if it fits).
+    fn to_i128(self, bits: u128) -> i128 {
+        if self.signed && bits & self.sign_bit() != 0 {
+            (bits | !self.mask()) as i128 // sign-extend to 128 bits
+        } else {
+            bits as i128
+        }
+    }
+
+    pub fn min(self) -> Value {
+        Value { ty: self, bits: if self.signed { self.sign_bit() } else { 0 } }
+    }
+
+    pub fn max(self) -> Value {
+        Value { ty: self, bits: if self.signed { self.sign_bit() - 1 } else { self.mask() } }
+    }
+
+    /// The value `neg`/`mag` in this type, or `None` if it does not fit.
+    pub fn from_parts(self, neg: bool, mag: u128) -> Option<Value> {
+        let fits = match (self.signed, neg) {
+            (false, false) => mag <= self.mask(),
+            (false, true) => mag == 0,
+            (true, false) => mag < self.sign_bit(),
+            (true, true) => mag <= self.sign_bit(),
+        };
+        let bits = if neg { mag.wrapping_neg() } else { mag } & self.mask();
+        fits.then_some(Value { ty: self, bits })
+    }
+
+    /// Parses "-6", "250", "0xFA", "0b1111_1010" for this type.
+    pub fn parse_value(self, s: &str) -> Option<Value> {
+        let (neg, mag) = parse_magnitude(s)?;
+        self.from_parts(neg, mag)
+    }
+}
+
+impl fmt::Display for IntType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("{}{}", if self.signed { 'i' } else { 'u' }, self.bits))
+    }
+}
+
+/// "-0x80" -> (true, 128). Underscores are allowed, like in Rust literals.
+pub fn parse_magnitude(s: &str) -> Option<(bool, u128)> {
+    let s = s.replace('_', "");
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest.to_string()),
+        None => (false, s),
+    };
+    let mag = if let Some(h) = s.strip_prefix("0x") {
+        u128::from_str_radix(h, 16).ok()?
+    } else if let Some(b) = s.strip_prefix("0b") {
+        u128::from_str_radix(b, 2).ok()?
+    } else {
+        s.parse().ok()?
+    };
+    Some((neg, mag))
+}
+
+/// A bit pattern together with its type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Value {
+    pub ty: IntType,
+    pub bits: u128,
+}
+
+impl Value {
+    pub fn is_negative(self) -> bool {
+        self.ty.signed && self.bits & self.ty.sign_bit() != 0
+    }
+
+    /// Binary, grouped in nibbles: 1111_1010.
+    pub fn binary(self) -> String {
+        let raw = format!("{:0w$b}", self.bits, w = self.ty.bits as usize);
+        let mut out = String::new();
+        for (i, c) in raw.chars().enumerate() {
+            if i > 0 && (raw.len() - i) % 4 == 0 {
+                out.push('_');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    pub fn hex(self) -> String {
+        format!("0x{:0w$X}", self.bits, w = self.ty.bits.div_ceil(4) as usize)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ty.signed {
+            f.pad(&self.ty.to_i128(self.bits).to_string())
+        } else {
+            f.pad(&self.bits.to_string())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// The four encodings
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Unsigned,
+    TwosComplement,
+    OnesComplement,
+    SignMagnitude,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] =
+        [Encoding::Unsigned, Encoding::TwosComplement, Encoding::OnesComplement, Encoding::SignMagnitude];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Unsigned => "unsigned",
+            Encoding::TwosComplement => "two's complement",
+            Encoding::OnesComplement => "one's complement",
+            Encoding::SignMagnitude => "sign-magnitude",
+        }
+    }
+
+    /// Bit pattern for `neg`/`mag` in `bits` bits, or `None` if out of range.
+    pub fn encode(self, bits: u32, neg: bool, mag: u128) -> Option<u128> {
+        let ty = IntType::new(bits, self != Encoding::Unsigned);
+        let top = ty.sign_bit();
+        match self {
+            Encoding::Unsigned | Encoding::TwosComplement => ty.from_parts(neg, mag).map(|v| v.bits),
+            // Both symmetric: -(2^(w-1) - 1) ..= 2^(w-1) - 1.
+            Encoding::OnesComplement if mag < top => Some(if neg { !mag & ty.mask() } else { mag }),
+            Encoding::SignMagnitude if mag < top => Some(if neg { top | mag } else { mag }),
+            _ => None,
+        }
+    }
+
+    /// (negative?, magnitude) of a pattern. One's complement and sign-magnitude can
+    /// return (true, 0): negative zero.
+    pub fn decode(self, bits: u32, pattern: u128) -> (bool, u128) {
+        let ty = IntType::new(bits, self != Encoding::Unsigned);
+        let top = ty.sign_bit();
+        let neg = self != Encoding::Unsigned && pattern & top != 0;
+        let mag = match self {
+            Encoding::Unsigned => pattern,
+            Encoding::TwosComplement if neg => pattern.wrapping_neg() & ty.mask(),
+            Encoding::TwosComplement => pattern,
+            Encoding::OnesComplement if neg => !pattern & ty.mask(),
+            Encoding::OnesComplement => pattern,
+            Encoding::SignMagnitude => pattern & !top,
+        };
+        (neg, mag)
+    }
+
+    /// Smallest and largest value as (negative?, magnitude).
+    pub fn range(self, bits: u32) -> ((bool, u128), (bool, u128)) {
+        let ty = IntType::new(bits, true);
+        let half = ty.sign_bit();
+        match self {
+            Encoding::Unsigned => ((false, 0), (false, ty.mask())),
+            Encoding::TwosComplement => ((true, half), (false, half - 1)),
+            _ => ((true, half - 1), (false, half - 1)),
+        }
+    }
+}
+
+pub fn show_parts((neg, mag): (bool, u128)) -> String {
+    format!("{}{mag}", if neg { "-" } else { "" })
+}
+
+// ---------------------------------------------------------------------------
+// Arithmetic, the Rust way
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Shl,
+    Shr,
+}
+
+impl Op {
+    pub fn parse(s: &str) -> Option<Op> {
+        Some(match s {
+            "+" | "add" => Op::Add,
+            "-" | "sub" => Op::Sub,
+            "*" | "x" | "mul" => Op::Mul,
+            "/" | "div" => Op::Div,
+            "%" | "rem" => Op::Rem,
+            "<<" | "shl" => Op::Shl,
+            ">>" | "shr" => Op::Shr,
+            _ => return None,
+        })
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Rem => "%",
+            Op::Shl => "<<",
+            Op::Shr => ">>",
+        }
+    }
+
+    /// The type of the right operand when the left one is a `ty`: the same
+    /// type, except that a shift amount is a u32 whatever is being shifted.
+    pub fn rhs_type(self, ty: IntType) -> IntType {
+        match self {
+            Op::Shl | Op::Shr => IntType::new(32, false),
+            _ => ty,
+        }
+    }
+}
+
+/// All four flavours of one operation. `None` where Rust would panic
+/// (division by zero) or where the method returns `None`.
+#[derive(Debug, Clone, Copy)]
+pub struct Outcome {
+    /// `wrapping_*`; `None` only for division by zero (which panics in every mode).
+    pub wrapping: Option<Value>,
+    pub checked: Option<Value>,
+    /// `saturating_*`; `None` where Rust has no such method (rem, shifts).
+    pub saturating: Option<Value>,
+    /// `overflowing_*`: the wrapped value plus "did it overflow?".
+    pub overflowed: bool,
+}
+
+/// `a op b` in `a.ty`; `b` is of type `op.rhs_type(a.ty)`, so for shifts it is
+/// the shift amount as a u32.
+pub fn arith(a: Value, op: Op, b: Value) -> Outcome {
+    let ty = a.ty;
+    debug_assert_eq!(b.ty, op.rhs_type(ty), "right operand of {}", op.symbol());
+    let wrap = |bits: u128| Value { ty, bits: bits & ty.mask() };
+
+    if matches!(op, Op::Shl | Op::Shr) {
+        // Rust: the amount must be < bits; wrapping_shl uses amount % bits.
+        let amount = b.bits;
+        let overflowed = amount >= ty.bits as u128;
+        let k = (amount % ty.bits as u128) as u32;
+        let bits = match op {
+            Op::Shl => a.bits << k,
+            _ if ty.signed => (ty.to_i128(a.bits) >> k) as u128, // arithmetic: copies the sign
+            _ => a.bits >> k,                                   // logical: shifts in zeros
+        };
+        let wrapped = wrap(bits);
+        return Outcome {
+            wrapping: Some(wrapped),
+            checked: (!overflowed).then_some(wrapped),
+            saturating: None,
+            overflowed,
+        };
+    }
+
+    if matches!(op, Op::Div | Op::Rem) && b.bits == 0 {
+        return Outcome { wrapping: None, checked: None, saturating: None, overflowed: false };
+    }
+
+    // Exact result where it fits in 128 bits; `None` means "even 128 bits overflowed",
+    // which for widths <= 128 always means overflow at `ty` too.
+    let (wrapped, exact_fits) = if ty.signed {
+        let (x, y) = (ty.to_i128(a.bits), ty.to_i128(b.bits));
+        let (r, o) = match op {
+            Op::Add => x.overflowing_add(y),
+            Op::Sub => x.overflowing_sub(y),
+            Op::Mul => x.overflowing_mul(y),
+            Op::Div => x.overflowing_div(y),
+            _ => x.overflowing_rem(y),
+        };
+        // MIN % -1 is 0, but Rust still reports it as overflow (MIN / -1 would overflow).
+        let min_rem_minus_one = op == Op::Rem && a.bits == ty.min().bits && y == -1;
+        let fits = !o && !min_rem_minus_one && r >= ty.to_i128(ty.min().bits) && r <= ty.max().bits as i128;
+        (wrap(r as u128), fits)
+    } else {
+        let (x, y) = (a.bits, b.bits);
+        let (r, o) = match op {
+            Op::Add => x.overflowing_add(y),
+            Op::Sub => x.overflowing_sub(y),
+            Op::Mul => x.overflowing_mul(y),
+            Op::Div => x.overflowing_div(y),
+            _ => x.overflowing_rem(y),
+        };
+        (wrap(r), !o && r <= ty.mask())
+    };
+
+    // Saturation clamps toward the side the exact result went to.
+    let saturating = match op {
+        Op::Rem => None,
+        _ if exact_fits => Some(wrapped),
+        _ if !ty.signed => Some(if op == Op::Sub { ty.min() } else { ty.max() }),
+        _ => {
+            let (an, bn) = (a.is_negative(), b.is_negative());
+            let goes_negative = match op {
+                Op::Add | Op::Sub => an,
+                Op::Mul => an != bn,
+                _ => false, // MIN / -1 is positive
+            };
+            Some(if goes_negative { ty.min() } else { ty.max() })
+        }
+    };
+
+    Outcome {
+        wrapping: Some(wrapped),
+        checked: exact_fits.then_some(wrapped),
+        saturating,
+        overflowed: !exact_fits,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// `as` between integer types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CastKind {
+    Reinterpret,
+    Truncate,
+    SignExtend,
+    ZeroExtend,
+}
+
+impl CastKind {
+    pub fn describe(self) -> &'static str {
+        match self {
+            CastKind::Reinterpret => "same width: identical bits, new meaning",
+            CastKind::Truncate => "narrower: the high bits are dropped",
+            CastKind::SignExtend => "wider from a signed type: the sign bit is copied into the new high bits",
+            CastKind::ZeroExtend => "wider from an unsigned type: the new high bits are 0",
+        }
+    }
+}
+
+/// What `value as to` does.
+pub fn cast(value: Value, to: IntType) -> (Value, CastKind) {
+    let from = value.ty;
+    let kind = match from.bits.cmp(&to.bits) {
+        std::cmp::Ordering::Equal => CastKind::Reinterpret,
+        std::cmp::Ordering::Greater => CastKind::Truncate,
+        std::cmp::Ordering::Less if from.signed => CastKind::SignExtend,
+        std::cmp::Ordering::Less => CastKind::ZeroExtend,
+    };
+    let bits = if kind == CastKind::SignExtend { from.to_i128(value.bits) as u128 } else { value.bits };
+    (Value { ty: to, bits: bits & to.mask() }, kind)
+}
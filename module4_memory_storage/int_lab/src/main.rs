@@ -0,0 +1,198 @@
+// src/main.rs
+//
+// `int_lab`                              => guided tour
+// `int_lab show <value> <bits>`          => the value in all four encodings
+// `int_lab ranges [bits...]`             => min/max per encoding (default 8 16 32 64 128)
+// `int_lab arith <a> <op> <b> <type>`    => wrapping/checked/saturating/overflowing, e.g. 100 + 100 i8
+// `int_lab cast <value> <from> <to>`     => what `as` does, e.g. -6 i8 u32
+//
+// Types are iN / uN for any N in 1..=128 (i8, u64, but also i12 for a 12-bit ADC).
+// Values: decimal, 0x.., 0b.., with optional '-' and '_' separators.
+
+use std::process::ExitCode;
+
+use int_lab::{arith, cast, parse_magnitude, show_parts, Encoding, IntType, Op, Value};
+
+fn show(input: &str, bits: u32) -> Result<(), String> {
+    let (neg, mag) = parse_magnitude(input).ok_or(format!("'{input}' is not an integer"))?;
+    println!("{input} in {bits} bits:");
+    for enc in Encoding::ALL {
+        match enc.encode(bits, neg, mag) {
+            Some(pattern) => {
+                let v = Value { ty: IntType::new(bits, false), bits: pattern };
+                println!("  {:<17} {}  {}", enc.name(), v.binary(), v.hex());
+            }
+            None => println!("  {:<17} (out of range)", enc.name()),
+        }
+    }
+    // The other direction: what the two's complement pattern means in each encoding.
+    if let Some(pattern) = Encoding::TwosComplement.encode(bits, neg, mag) {
+        let readings: Vec<String> = Encoding::ALL
+            .iter()
+            .map(|enc| format!("{} {}", enc.name(), show_parts(enc.decode(bits, pattern))))
+            .collect();
+        println!("  the two's complement pattern read as: {}", readings.join(", "));
+    }
+    Ok(())
+}
+
+fn ranges(widths: &[u32]) {
+    println!("{:>5}  {:<17} {:>42} {:>42}", "bits", "encoding", "min", "max");
+    for &bits in widths {
+        for enc in Encoding::ALL {
+            let (min, max) = enc.range(bits);
+            println!("{bits:>5}  {:<17} {:>42} {:>42}", enc.name(), show_parts(min), show_parts(max));
+        }
+    }
+    println!("two's complement has one more negative value than positive, and no -0;");
+    println!("one's complement and sign-magnitude are symmetric and waste a pattern on -0.");
+}
+
+fn show_opt(v: Option<Value>) -> String {
+    v.map_or("None".to_string(), |v| format!("Some({v})"))
+}
+
+fn arith_cmd(a: &str, op: &str, b: &str, ty: IntType) -> Result<(), String> {
+    let op = Op::parse(op).ok_or(format!("unknown operator '{op}' (+ - * / % << >>)"))?;
+    let va = ty.parse_value(a).ok_or(format!("{a} does not fit in {ty}"))?;
+    let rhs = op.rhs_type(ty);
+    let vb = rhs.parse_value(b).ok_or(format!("{b} does not fit in {rhs}"))?;
+    let r = arith(va, op, vb);
+    let name = match op {
+        Op::Add => "add",
+        Op::Sub => "sub",
+        Op::Mul => "mul",
+        Op::Div => "div",
+        Op::Rem => "rem",
+        Op::Shl => "shl",
+        Op::Shr => "shr",
+    };
+
+    let is_shift = matches!(op, Op::Shl | Op::Shr);
+    // Bit patterns only while they fit on a line; the shift amount is just a number.
+    let short = ty.bits <= 32;
+    if !short {
+        println!("{va}{ty} {} {vb}:", op.symbol());
+    } else if is_shift {
+        println!("{va}{ty} {} {vb}  ({} {} {vb}):", op.symbol(), va.binary(), op.symbol());
+    } else {
+        println!("{va}{ty} {} {vb}  ({} {} {}):", op.symbol(), va.binary(), op.symbol(), vb.binary());
+    }
+    let Some(wrapped) = r.wrapping else {
+        println!("  every flavour panics or returns None: division by zero");
+        println!("  checked_{name:<12} None");
+        return Ok(());
+    };
+    if short {
+        println!("  wrapping_{name:<11} {wrapped:<12} {}", wrapped.binary());
+    } else {
+        println!("  wrapping_{name:<11} {wrapped}");
+    }
+    println!("  checked_{name:<12} {}", show_opt(r.checked));
+    match r.saturating {
+        Some(s) => println!("  saturating_{name:<9} {s}"),
+        None => println!("  saturating_{name:<9} (no such method)"),
+    }
+    println!("  overflowing_{name:<8} ({wrapped}, {})", r.overflowed);
+    if r.overflowed && matches!(op, Op::Div | Op::Rem) {
+        println!("  plain `a {} b` panics, in release builds too", op.symbol());
+    } else if r.overflowed {
+        println!("  plain `a {} b` panics in debug builds and wraps in release builds", op.symbol());
+    }
+    Ok(())
+}
+
+fn cast_cmd(input: &str, from: IntType, to: IntType) -> Result<(), String> {
+    let v = from.parse_value(input).ok_or(format!("{input} does not fit in {from}"))?;
+    let (r, kind) = cast(v, to);
+    println!("({v}{from}) as {to} = {r}");
+    println!("  {:>40}  {from}", v.binary());
+    println!("  {:>40}  {to}", r.binary());
+    println!("  {}", kind.describe());
+    Ok(())
+}
+
+fn ty(s: &str) -> Result<IntType, String> {
+    IntType::parse(s).ok_or(format!("'{s}' is not a type like i8, u16, i12, u128"))
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        ["show", v, bits] => show(v, bits.parse().ok().filter(|b| (1..=128).contains(b)).ok_or("bits must be 1..=128")?),
+        ["ranges"] => {
+            ranges(&[8, 16, 32, 64, 128]);
+            Ok(())
+        }
+        ["ranges", widths @ ..] => {
+            let widths: Result<Vec<u32>, _> = widths.iter().map(|w| w.parse::<u32>()).collect();
+            let widths = widths.map_err(|e| e.to_string())?;
+            if widths.iter().any(|w| !(1..=128).contains(w)) {
+                return Err("bits must be 1..=128".into());
+            }
+            ranges(&widths);
+            Ok(())
+        }
+        ["arith", a, op, b, t] => arith_cmd(a, op, b, ty(t)?),
+        ["cast", v, from, to] => cast_cmd(v, ty(from)?, ty(to)?),
+        _ => Err("usage: int_lab [show <v> <bits> | ranges [bits...] | arith <a> <op> <b> <type> | cast <v> <from> <to>]".into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args.is_empty() {
+        tour();
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn tour() {
+    println!("=== 1) One value, four encodings ===\n");
+    show("-6", 8).unwrap();
+    println!();
+    show("-128", 8).unwrap();
+    println!();
+    show("-6", 12).unwrap();
+
+    println!("\n=== 2) Ranges ===\n");
+    ranges(&[8, 16, 32, 64, 128]);
+
+    println!("\n=== 3) Overflow, four ways ===\n");
+    for (a, op, b, t) in [
+        ("100", "+", "100", "i8"),
+        ("200", "+", "100", "u8"),
+        ("5", "-", "10", "u8"),
+        ("-128", "/", "-1", "i8"),
+        ("-128", "%", "-1", "i8"),
+        ("-100", "*", "2", "i8"),
+        ("1", "<<", "9", "u8"),
+        ("-16", ">>", "2", "i8"),
+        ("240", ">>", "2", "u8"),
+        ("7", "/", "0", "i32"),
+        ("170141183460469231731687303715884105727", "+", "1", "i128"),
+    ] {
+        run(&["arith", a, op, b, t]).unwrap();
+        println!();
+    }
+
+    println!("=== 4) `as` casts from this repo, explained ===\n");
+    println!("memory_storage_lab: `let a_bits: u8 = a as u8;` with a = -6i8");
+    cast_cmd("-6", IntType::new(8, true), IntType::new(8, false)).unwrap();
+    println!("\nrecord building: `name_bytes.len() as u8` — fine for 9 bytes, silently wrong for 300");
+    cast_cmd("300", IntType::new(64, false), IntType::new(8, false)).unwrap();
+    println!("\nwidening keeps the VALUE, but only if the source signedness is respected:");
+    cast_cmd("-6", IntType::new(8, true), IntType::new(32, true)).unwrap();
+    cast_cmd("-6", IntType::new(8, true), IntType::new(32, false)).unwrap();
+    cast_cmd("250", IntType::new(8, false), IntType::new(32, true)).unwrap();
+    println!("\nnarrowing a negative number keeps the low bits, which may even look positive:");
+    cast_cmd("-200", IntType::new(16, true), IntType::new(8, true)).unwrap();
+    println!("\nUse u8::try_from(x) when a value might not fit; `as` never fails, it just truncates.");
+}
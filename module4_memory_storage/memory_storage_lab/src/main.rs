@@ -1,81 +1,15 @@
 use std::mem;
 
-/// Pretty-print bytes as hex like: "78 56 34 12"
-fn hex(bytes: &[u8]) -> String {
-    bytes
-        .iter()
-        .map(|b| format!("{:02X}", b)) // :02x
-        .collect::<Vec<_>>()
-        .join(" ")
-}
-
-/// Build a demo binary record:
-/// [magic: u32 LE][temp_tenths_c: i16 LE][voltage: f32 bits LE][name_len: u8][name_bytes UTF-8]
-fn build_record(magic: u32, temp_tenths_c: i16, voltage: f32, name: &str) -> Vec<u8> {
-    let mut out = Vec::new();
-
-    // Endianness is explicit and portable:
-    out.extend_from_slice(&magic.to_le_bytes());
-
-    // Signed integer still becomes bytes; interpretation is done on decode.
-    out.extend_from_slice(&temp_tenths_c.to_le_bytes());
-
-    // IEEE 754 float: we serialize its bit pattern (u32) into LE bytes.
-    out.extend_from_slice(&voltage.to_bits().to_le_bytes());
-
-    // UTF-8: String is bytes. Length is bytes length, not "characters count".
-    let name_bytes = name.as_bytes();
-    assert!(name_bytes.len() <= 255);
-    out.push(name_bytes.len() as u8);
-    out.extend_from_slice(name_bytes);
-
-    out
-}
-
-#[derive(Debug)]
-#[allow(dead_code)]
-struct Record {
-    magic: u32,
-    temp_tenths_c: i16,
-    voltage: f32,
-    name: String,
-}
-
-/// Parse the record back out of bytes.
-fn parse_record(buf: &[u8]) -> Result<Record, String> {
-    if buf.len() < 4 + 2 + 4 + 1 {
-        return Err("buffer too small".into());
-    }
+use hexdump::hex;
+use record_codec::{DecodeError, SensorReading};
 
-    let magic = u32::from_le_bytes(buf[0..4].try_into().unwrap());
-    let temp_tenths_c = i16::from_le_bytes(buf[4..6].try_into().unwrap());
-
-    // voltage is stored as IEEE 754 bits (u32), then reconstructed to f32.
-    let voltage_bits = u32::from_le_bytes(buf[6..10].try_into().unwrap());
-    let voltage = f32::from_bits(voltage_bits);
-
-    let name_len = buf[10] as usize;
-    let start = 11;
-    let end = start + name_len;
-
-    if buf.len() < end {
-        return Err("buffer too small for name".into());
-    }
-
-    // UTF-8 validation happens here.
-    let name = std::str::from_utf8(&buf[start..end])
-        .map_err(|e| format!("invalid UTF-8: {e}"))?
-        .to_string();
-
-    Ok(Record {
-        magic,
-        temp_tenths_c,
-        voltage,
-        name,
-    })
-}
+// Heap allocations go through alloc_lab. Pick the allocator with
+// ALLOC_LAB=bump|free-list|slab|buddy (default: the system allocator).
+#[global_allocator]
+static GLOBAL: alloc_lab::LabAllocator = alloc_lab::LabAllocator;
 
 fn main() {
+    alloc_lab::select_from_env();
     println!("--- Machine basics ---");
     println!("size_of::<u8>()  = {}", mem::size_of::<u8>());
     println!("size_of::<u32>() = {}", mem::size_of::<u32>());
@@ -99,6 +33,7 @@ fn main() {
     println!("a = -6 as i8");
     println!("bit pattern (as u8 hex) = 0x{:02X}", a_bits);
     println!("That 0xFA is -6 in two's complement for 8 bits.");
+    println!("(`cargo run -p int_lab -- cast -6 i8 u8` explains what `as u8` did to the bits)");
     println!();
 
     println!("--- Float bits demo (IEEE 754) ---");
@@ -107,6 +42,7 @@ fn main() {
     println!("f = {f}");
     println!("f bits (u32 hex) = 0x{bits:08X}");
     println!("f bytes (LE) = {}", hex(&bits.to_le_bytes()));
+    println!("(prints 3.1415925: `cargo run -p float_lab -- show 3.1415926 f32` shows why)");
     println!();
 
     println!("--- UTF-8 string demo ---");
@@ -114,6 +50,7 @@ fn main() {
     println!("s = {s}");
     println!("s.len() bytes = {}", s.len());
     println!("s.as_bytes()  = {}", hex(s.as_bytes()));
+    println!("(`cargo run -p text_lab -- inspect \"DJ ✅\"` breaks that down per char)");
     println!();
 
     println!("--- Pointer demo (addresses) ---");
@@ -129,14 +66,50 @@ fn main() {
     println!();
 
     println!("--- Real-world binary record demo ---");
-    let record_bytes = build_record(0xA1B2C3D4, -105, 3.98, "sensor-α");
+    // The layout is declared once in record_codec; encoder and decoder come from it.
+    println!("layout of SensorReading (magic 0x{:08X}, version {}):", SensorReading::MAGIC, SensorReading::VERSION);
+    for field in SensorReading::LAYOUT {
+        let size = match (field.size, field.prefix) {
+            (Some(n), _) => format!("{n} bytes"),
+            (None, Some(prefix)) => format!("{prefix} + bytes"),
+            (None, None) => "variable".to_string(),
+        };
+        println!("  {:<14} {:<7} {:<13} {size}", field.name, field.rust_type, field.codec);
+    }
+
+    let reading = SensorReading {
+        temp_tenths_c: -105,
+        voltage: 3.98,
+        name: "sensor-α".to_string(),
+    };
+    let record_bytes = reading.encode().expect("name fits in a u8 length");
     println!("serialized record bytes:");
     println!("{}", hex(&record_bytes));
+    println!("annotated (letters mark where each field starts):");
+    let fields = hexdump::locate(&hexdump::Layout::from_record(SensorReading::LAYOUT).unwrap(), &record_bytes, 0);
+    print!("{}", hexdump::dump_annotated(&record_bytes, &hexdump::Options::default(), &fields));
+    print!("{}", hexdump::field_table(&fields));
 
-    let parsed = parse_record(&record_bytes).expect("parse ok");
+    let parsed = SensorReading::decode(&record_bytes).expect("parse ok");
     println!("parsed record: {parsed:?}");
 
     // Convert tenths °C to real °C
     let temp_c = parsed.temp_tenths_c as f32 / 10.0;
     println!("temperature = {temp_c}°C");
+
+    // Broken input gives a typed error, not a panic or garbage values.
+    let mut wrong_magic = record_bytes.clone();
+    wrong_magic[0] ^= 0xFF;
+    let truncated = &record_bytes[..record_bytes.len() - 3];
+    for (what, result) in [
+        ("wrong magic", SensorReading::decode(&wrong_magic)),
+        ("truncated", SensorReading::decode(truncated)),
+    ] {
+        match result {
+            Err(DecodeError::BadMagic { found, .. }) => println!("{what}: rejected, magic 0x{found:08X}"),
+            Err(e) => println!("{what}: rejected, {e}"),
+            Ok(r) => println!("{what}: unexpectedly parsed {r:?}"),
+        }
+    }
+    alloc_lab::print_report();
 }
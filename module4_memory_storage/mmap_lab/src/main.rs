@@ -0,0 +1,409 @@
+// src/main.rs
+//
+// mmap lab — the Linux virtual memory API, measured.
+//
+// mmap(addr, len, prot, flags, fd, offset) asks the kernel for a new range of
+// virtual addresses. Nothing is copied or allocated up front: the page table
+// entries are filled in lazily, on the first access to each page (a page fault).
+//
+//   MAP_SHARED  + file fd   => pages ARE the page cache of the file (writes reach the file)
+//   MAP_PRIVATE + file fd   => copy-on-write view of the file
+//   MAP_SHARED  | ANONYMOUS => zero-filled memory that survives fork() as SHARED
+//   MAP_PRIVATE | ANONYMOUS => plain zero-filled memory (what malloc uses for big blocks)
+//
+// Experiments:
+//   1) map a file, mutate it through memory, see the bytes on disk
+//   2) share an anonymous mapping between parent and forked child (vs a private one)
+//   3) copy-on-write faults after fork()
+//   4) madvise: DONTNEED throws pages away, WILLNEED prefetches them
+//   5) mprotect + a SIGSEGV handler that reports the faulting address
+//
+// Fault counts come from getrusage(): "minor" = no disk I/O needed (zero page,
+// page already in page cache, COW copy), "major" = had to wait for the disk.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// An owned mmap'd range. munmap on drop.
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Mapping {
+    fn new(len: usize, prot: i32, flags: i32, fd: i32) -> io::Result<Mapping> {
+        let p = unsafe { libc::mmap(ptr::null_mut(), len, prot, flags, fd, 0) };
+        if p == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping { ptr: p as *mut u8, len })
+    }
+
+    fn anon(len: usize, flags: i32) -> io::Result<Mapping> {
+        Mapping::new(len, libc::PROT_READ | libc::PROT_WRITE, flags | libc::MAP_ANONYMOUS, -1)
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    fn advise(&self, advice: i32) -> io::Result<()> {
+        check(unsafe { libc::madvise(self.ptr as *mut libc::c_void, self.len, advice) })
+    }
+
+    // How many of our pages currently have a physical frame behind them?
+    fn resident_pages(&self) -> usize {
+        let mut vec = vec![0u8; self.len.div_ceil(page_size())];
+        let rc = unsafe { libc::mincore(self.ptr as *mut libc::c_void, self.len, vec.as_mut_ptr()) };
+        if rc != 0 {
+            return 0;
+        }
+        vec.iter().filter(|b| **b & 1 == 1).count()
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+fn check(rc: i32) -> io::Result<()> {
+    if rc == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+// (minor, major) page faults of this process so far.
+fn faults() -> (i64, i64) {
+    let mut ru: libc::rusage = unsafe { std::mem::zeroed() };
+    unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut ru) };
+    (ru.ru_minflt, ru.ru_majflt)
+}
+
+// Runs `f` and returns (minor faults, major faults, time) it caused.
+fn measure(f: impl FnOnce()) -> (i64, i64, Duration) {
+    let (min0, maj0) = faults();
+    let t = Instant::now();
+    f();
+    let elapsed = t.elapsed();
+    let (min1, maj1) = faults();
+    (min1 - min0, maj1 - maj0, elapsed)
+}
+
+// Touch one byte per page (write), so every page gets faulted in.
+fn touch_write(buf: &mut [u8], value: u8) {
+    for i in (0..buf.len()).step_by(page_size()) {
+        unsafe { ptr::write_volatile(&mut buf[i], value) };
+    }
+}
+
+// Read one byte per page.
+fn touch_read(buf: &[u8]) -> u64 {
+    let mut sum = 0u64;
+    for i in (0..buf.len()).step_by(page_size()) {
+        sum += unsafe { ptr::read_volatile(&buf[i]) } as u64;
+    }
+    sum
+}
+
+fn main() -> io::Result<()> {
+    println!("mmap lab (page size {} bytes)\n", page_size());
+    file_mapping()?;
+    shared_vs_private_after_fork()?;
+    copy_on_write()?;
+    madvise_demo()?;
+    mprotect_demo()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 1) File mapping
+// ---------------------------------------------------------------------------
+fn file_mapping() -> io::Result<()> {
+    println!("=== 1) MAP_SHARED file mapping: memory writes ARE file writes ===");
+    // One file per run, removed again whether or not the demo got through.
+    let path = std::env::temp_dir().join(format!("mmap_lab_file.{}.dat", std::process::id()));
+    let result = map_file(&path);
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn map_file(path: &Path) -> io::Result<()> {
+    let pages = 64;
+    let len = pages * page_size();
+    let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+    file.set_len(len as u64)?; // mmap cannot extend a file; it must already be big enough
+
+    let mut m = Mapping::new(
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED,
+        file.as_raw_fd(),
+    )?;
+    println!("mapped {} ({} pages) at {:p}", path.display(), pages, m.ptr);
+    println!("resident right after mmap: {} / {pages} pages (nothing touched yet)", m.resident_pages());
+
+    let (minor, major, t) = measure(|| touch_write(m.as_mut_slice(), b'.'));
+    println!("first write to every page: {minor} minor, {major} major faults in {t:?}");
+    let (minor, _, t) = measure(|| touch_write(m.as_mut_slice(), b'.'));
+    println!("second write to every page: {minor} minor faults in {t:?} (PTEs already present)");
+
+    // Mutate through memory — no write() syscall anywhere.
+    let msg = b"hello from mmap";
+    m.as_mut_slice()[..msg.len()].copy_from_slice(msg);
+    let last = len - 1;
+    m.as_mut_slice()[last] = b'!';
+
+    // msync = "push dirty pages to the file now" (otherwise the kernel does it later).
+    check(unsafe { libc::msync(m.ptr as *mut libc::c_void, len, libc::MS_SYNC) })?;
+
+    let on_disk = fs::read(path)?;
+    println!(
+        "read back with fs::read: first bytes {:?}, last byte {:?}",
+        String::from_utf8_lossy(&on_disk[..msg.len()]),
+        on_disk[last] as char
+    );
+    println!();
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 2) Shared anonymous memory between parent and child
+// ---------------------------------------------------------------------------
+fn shared_vs_private_after_fork() -> io::Result<()> {
+    println!("=== 2) MAP_SHARED|MAP_ANONYMOUS vs MAP_PRIVATE across fork() ===");
+    let mut shared = Mapping::anon(page_size(), libc::MAP_SHARED)?;
+    let mut private = Mapping::anon(page_size(), libc::MAP_PRIVATE)?;
+    shared.as_mut_slice()[0] = 1;
+    private.as_mut_slice()[0] = 1;
+
+    let pid = unsafe { libc::fork() };
+    if pid < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if pid == 0 {
+        // Child: write to both mappings, then leave without running destructors.
+        shared.as_mut_slice()[0] = 42;
+        private.as_mut_slice()[0] = 42;
+        let msg = b"written by child";
+        shared.as_mut_slice()[8..8 + msg.len()].copy_from_slice(msg);
+        unsafe { libc::_exit(0) };
+    }
+    wait_child(pid)?;
+
+    println!("parent before fork wrote 1 to both; child wrote 42 to both");
+    println!("parent now sees: shared[0] = {}, private[0] = {}", shared.as_slice()[0], private.as_slice()[0]);
+    println!(
+        "shared[8..] = {:?}  (same physical page in both processes)",
+        String::from_utf8_lossy(&shared.as_slice()[8..24])
+    );
+    println!("private stays 1: the child's write triggered a copy-on-write of its own page\n");
+    Ok(())
+}
+
+fn wait_child(pid: libc::pid_t) -> io::Result<()> {
+    let mut status = 0;
+    if unsafe { libc::waitpid(pid, &mut status, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 3) Copy-on-write after fork
+// ---------------------------------------------------------------------------
+fn copy_on_write() -> io::Result<()> {
+    println!("=== 3) Copy-on-write faults after fork() ===");
+    let pages = 1024;
+    let mut mem = Mapping::anon(pages * page_size(), libc::MAP_PRIVATE)?;
+    // Without this, transparent huge pages could back the range with 2 MiB pages
+    // and we would see 2 faults instead of 1024.
+    let _ = mem.advise(libc::MADV_NOHUGEPAGE);
+
+    let (minor, _, t) = measure(|| touch_write(mem.as_mut_slice(), 1));
+    println!("parent, first touch of {pages} pages: {minor} minor faults in {t:?} (zero-fill)");
+
+    // The child reports its numbers through a small shared page.
+    let mut report = Mapping::anon(page_size(), libc::MAP_SHARED)?;
+
+    let pid = unsafe { libc::fork() };
+    if pid < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if pid == 0 {
+        // After fork both processes map the SAME frames, marked read-only.
+        let (read_faults, _, read_t) = measure(|| {
+            std::hint::black_box(touch_read(mem.as_slice()));
+        });
+        let (cow_faults, _, cow_t) = measure(|| touch_write(mem.as_mut_slice(), 2));
+        let (again_faults, _, again_t) = measure(|| touch_write(mem.as_mut_slice(), 3));
+
+        let out = report.as_mut_slice().as_mut_ptr() as *mut u64;
+        let values = [
+            read_faults as u64,
+            read_t.as_micros() as u64,
+            cow_faults as u64,
+            cow_t.as_micros() as u64,
+            again_faults as u64,
+            again_t.as_micros() as u64,
+        ];
+        for (i, v) in values.iter().enumerate() {
+            unsafe { out.add(i).write(*v) };
+        }
+        unsafe { libc::_exit(0) };
+    }
+    wait_child(pid)?;
+
+    let r = report.as_slice().as_ptr() as *const u64;
+    let v: Vec<u64> = (0..6).map(|i| unsafe { r.add(i).read() }).collect();
+    println!("child, read every page:        {:5} minor faults in {:6} us (frames still shared)", v[0], v[1]);
+    println!("child, first write every page: {:5} minor faults in {:6} us (one COW copy per page)", v[2], v[3]);
+    println!("child, second write:           {:5} minor faults in {:6} us (page is private now)", v[4], v[5]);
+    println!("parent still sees value {} (its copy was never touched)\n", mem.as_slice()[0]);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 4) madvise
+// ---------------------------------------------------------------------------
+fn madvise_demo() -> io::Result<()> {
+    println!("=== 4) madvise(MADV_DONTNEED / MADV_WILLNEED) ===");
+    let pages = 256;
+    let len = pages * page_size();
+
+    // DONTNEED on private anonymous memory: frames are freed, contents are LOST,
+    // the next access gets a fresh zero page.
+    let mut anon = Mapping::anon(len, libc::MAP_PRIVATE)?;
+    let _ = anon.advise(libc::MADV_NOHUGEPAGE);
+    touch_write(anon.as_mut_slice(), 0xAA);
+    println!("anon: wrote 0xAA to {pages} pages, resident = {}", anon.resident_pages());
+    anon.advise(libc::MADV_DONTNEED)?;
+    println!("anon: after MADV_DONTNEED resident = {}", anon.resident_pages());
+    let (minor, _, _) = measure(|| {
+        std::hint::black_box(touch_read(anon.as_slice()));
+    });
+    println!(
+        "anon: byte 0 now reads 0x{:02X} (zero page), re-reading cost {minor} minor faults",
+        anon.as_slice()[0]
+    );
+
+    // WILLNEED on a file mapping: start reading the file into the page cache now,
+    // so later accesses find it there.
+    // One file per run, removed again whether or not the demo got through.
+    let path = std::env::temp_dir().join(format!("mmap_lab_willneed.{}.dat", std::process::id()));
+    let result = (|| -> io::Result<()> {
+        fs::write(&path, vec![7u8; len])?;
+        let file = fs::File::open(&path)?;
+        file.sync_all()?;
+        // Ask the kernel to drop this file from the page cache (works for disk files;
+        // on tmpfs the page cache IS the file, so nothing is dropped).
+        unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+
+        let m = Mapping::new(len, libc::PROT_READ, libc::MAP_SHARED, file.as_raw_fd())?;
+        // For a file mapping, mincore reports page-cache residency.
+        println!("file: after POSIX_FADV_DONTNEED cached = {} / {pages} pages", m.resident_pages());
+        m.advise(libc::MADV_WILLNEED)?;
+        std::thread::sleep(Duration::from_millis(50)); // readahead is asynchronous
+        println!("file: after MADV_WILLNEED     cached = {} / {pages} pages", m.resident_pages());
+        let (minor, major, t) = measure(|| {
+            std::hint::black_box(touch_read(m.as_slice()));
+        });
+        println!("file: reading every page: {minor} minor, {major} major faults in {t:?}");
+        println!("      (minor = page was already cached; the kernel's \"fault-around\" maps up to 16");
+        println!("       cached neighbours per fault. Major faults would mean waiting for the disk.)");
+        Ok(())
+    })();
+    let _ = fs::remove_file(&path);
+    result?;
+    println!();
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 5) mprotect + SIGSEGV handler
+// ---------------------------------------------------------------------------
+
+// The handler can only safely touch atomics, so it talks to main() through these.
+static FAULT_ADDR: AtomicUsize = AtomicUsize::new(0);
+static FAULT_COUNT: AtomicUsize = AtomicUsize::new(0);
+static GUARD_START: AtomicUsize = AtomicUsize::new(0);
+static GUARD_LEN: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn on_segv(_sig: i32, info: *mut libc::siginfo_t, _ctx: *mut libc::c_void) {
+    let addr = unsafe { (*info).si_addr() } as usize;
+    FAULT_ADDR.store(addr, Ordering::SeqCst);
+    FAULT_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    let start = GUARD_START.load(Ordering::SeqCst);
+    let len = GUARD_LEN.load(Ordering::SeqCst);
+    if start <= addr && addr < start + len {
+        // Our page: make it accessible again. Returning from the handler re-runs
+        // the faulting instruction, which now succeeds. (This is how user-space
+        // garbage collectors and userfault-style tricks work.)
+        unsafe {
+            libc::mprotect(start as *mut libc::c_void, len, libc::PROT_READ | libc::PROT_WRITE);
+        }
+    } else {
+        // A real crash somewhere else: fall back to the default action (terminate).
+        unsafe { libc::signal(libc::SIGSEGV, libc::SIG_DFL) };
+    }
+}
+
+fn mprotect_demo() -> io::Result<()> {
+    println!("=== 5) mprotect + SIGSEGV handler ===");
+    let ps = page_size();
+    let mut m = Mapping::anon(2 * ps, libc::MAP_PRIVATE)?;
+    m.as_mut_slice()[ps] = 5;
+
+    let mut act: libc::sigaction = unsafe { std::mem::zeroed() };
+    act.sa_sigaction = on_segv as *const () as usize;
+    act.sa_flags = libc::SA_SIGINFO;
+    let mut old: libc::sigaction = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::sigaction(libc::SIGSEGV, &act, &mut old) })?;
+
+    let page1 = unsafe { m.ptr.add(ps) };
+    GUARD_START.store(page1 as usize, Ordering::SeqCst);
+    GUARD_LEN.store(ps, Ordering::SeqCst);
+
+    // Page 1 becomes read-only: reads are fine, the write below faults.
+    check(unsafe { libc::mprotect(page1 as *mut libc::c_void, ps, libc::PROT_READ) })?;
+    let v = unsafe { ptr::read_volatile(page1) };
+    println!("page 1 is PROT_READ: read {v} fine, faults so far = {}", FAULT_COUNT.load(Ordering::SeqCst));
+
+    let target = unsafe { page1.add(123) };
+    unsafe { ptr::write_volatile(target, 9) };
+    let addr = FAULT_ADDR.load(Ordering::SeqCst);
+    println!(
+        "write to {target:p} => SIGSEGV, handler saw si_addr = 0x{addr:x} (page 1 + {}), count = {}",
+        addr - page1 as usize,
+        FAULT_COUNT.load(Ordering::SeqCst)
+    );
+    println!("handler re-enabled PROT_WRITE, instruction retried, byte is now {}", m.as_slice()[ps + 123]);
+
+    // PROT_NONE: even reading faults.
+    check(unsafe { libc::mprotect(page1 as *mut libc::c_void, ps, libc::PROT_NONE) })?;
+    let v = unsafe { ptr::read_volatile(page1.add(8)) };
+    println!(
+        "page 1 is PROT_NONE: read of +8 faulted at 0x{:x}, then returned {v}; total faults = {}",
+        FAULT_ADDR.load(Ordering::SeqCst),
+        FAULT_COUNT.load(Ordering::SeqCst)
+    );
+
+    check(unsafe { libc::sigaction(libc::SIGSEGV, &old, ptr::null_mut()) })?;
+    println!();
+    Ok(())
+}
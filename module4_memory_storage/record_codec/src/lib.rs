@@ -0,0 +1,381 @@
+// src/lib.rs
+//
+// Schema-driven binary records.
+//
+// Instead of hand-slicing `buf[6..10]`, a record layout is declared ONCE and the
+// `record!` macro generates the struct, the encoder and the decoder from it:
+//
+//   record! {
+//       #[derive(Debug, Clone, PartialEq)]
+//       pub struct SensorReading {
+//           magic: u32 as Le = 0xA1B2_C3D4,
+//           version: u8 as Le = 1,
+//           fields {
+//               temp_tenths_c: i16 as Le,      // little-endian two's complement
+//               voltage: f32 as Le,            // IEEE 754 bits, little-endian
+//               name: String as Utf8<U8>,      // UTF-8 bytes after a 1-byte length
+//           }
+//       }
+//   }
+//
+// Wire format = magic, version, then every field in declaration order. Nothing
+// else: no padding, no type tags. The `as ...` part is the codec — how a Rust
+// value becomes bytes:
+//
+//   Le / Be               integers and floats, little- or big-endian
+//   Utf8<P> / Bytes<P>    String / Vec<u8> after a length prefix P:
+//                         U8, U16Le, U16Be, U32Le, U32Be
+//
+// Decoding checks magic first, then version, then reads each field, so a wrong
+// file type or an old record fails with a typed error instead of garbage values.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::Utf8Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The buffer ended in the middle of a field.
+    Truncated { field: &'static str, needed: usize, available: usize },
+    BadMagic { expected: u64, found: u64 },
+    UnsupportedVersion { expected: u64, found: u64 },
+    InvalidUtf8 { field: &'static str, error: Utf8Error },
+    /// `decode` expects the buffer to hold exactly one record.
+    TrailingBytes { count: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { field, needed, available } => {
+                write!(f, "truncated at field '{field}': needs {needed} bytes, {available} left")
+            }
+            DecodeError::BadMagic { expected, found } => {
+                write!(f, "bad magic: expected 0x{expected:X}, found 0x{found:X}")
+            }
+            DecodeError::UnsupportedVersion { expected, found } => {
+                write!(f, "unsupported version {found} (this decoder reads version {expected})")
+            }
+            DecodeError::InvalidUtf8 { field, error } => write!(f, "field '{field}' is not UTF-8: {error}"),
+            DecodeError::TrailingBytes { count } => write!(f, "{count} unexpected bytes after the record"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    /// A string/byte field is longer than its length prefix can express.
+    TooLong { field: &'static str, len: usize, max: usize },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::TooLong { field, len, max } => {
+                write!(f, "field '{field}' is {len} bytes, length prefix allows at most {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// How one Rust value of type `T` is written and read.
+pub trait Codec<T> {
+    /// Short description for layout tables, e.g. "i16 LE" or "UTF-8 string".
+    const DESCRIPTION: &'static str;
+    /// Encoded size in bytes, or `None` when it depends on the value (length-prefixed).
+    const SIZE: Option<usize>;
+    /// Description of the length prefix, for length-prefixed codecs.
+    const PREFIX: Option<&'static str> = None;
+
+    fn encode(value: &T, field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError>;
+
+    /// Reads at `*pos` and advances `pos` past the value.
+    fn decode(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<T, DecodeError>;
+}
+
+/// Takes `n` bytes at `*pos`, or reports which field ran out of input.
+pub fn take<'a>(buf: &'a [u8], pos: &mut usize, n: usize, field: &'static str) -> Result<&'a [u8], DecodeError> {
+    let available = buf.len().saturating_sub(*pos);
+    if available < n {
+        return Err(DecodeError::Truncated { field, needed: n, available });
+    }
+    let bytes = &buf[*pos..*pos + n];
+    *pos += n;
+    Ok(bytes)
+}
+
+/// Little-endian numbers (least significant byte first).
+pub struct Le;
+/// Big-endian numbers ("network order", most significant byte first).
+pub struct Be;
+
+macro_rules! numeric_codecs {
+    ($($t:ty),*) => {$(
+        impl Codec<$t> for Le {
+            const DESCRIPTION: &'static str = concat!(stringify!($t), " LE");
+            const SIZE: Option<usize> = Some(size_of::<$t>());
+            fn encode(value: &$t, _field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+                out.extend_from_slice(&value.to_le_bytes());
+                Ok(())
+            }
+            fn decode(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<$t, DecodeError> {
+                let bytes = take(buf, pos, size_of::<$t>(), field)?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+
+        impl Codec<$t> for Be {
+            const DESCRIPTION: &'static str = concat!(stringify!($t), " BE");
+            const SIZE: Option<usize> = Some(size_of::<$t>());
+            fn encode(value: &$t, _field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+                out.extend_from_slice(&value.to_be_bytes());
+                Ok(())
+            }
+            fn decode(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<$t, DecodeError> {
+                let bytes = take(buf, pos, size_of::<$t>(), field)?;
+                Ok(<$t>::from_be_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+// f32/f64 `to_le_bytes` writes the IEEE 754 bit pattern, same as `to_bits().to_le_bytes()`.
+numeric_codecs!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+/// Width of a length prefix in front of strings and byte blobs.
+pub trait Prefix {
+    const DESCRIPTION: &'static str;
+    const MAX: usize;
+    fn write(len: usize, out: &mut Vec<u8>);
+    fn read(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<usize, DecodeError>;
+}
+
+macro_rules! prefixes {
+    ($($name:ident => $t:ty, $to:ident, $from:ident, $desc:literal;)*) => {$(
+        pub struct $name;
+
+        impl Prefix for $name {
+            const DESCRIPTION: &'static str = $desc;
+            const MAX: usize = <$t>::MAX as usize;
+            fn write(len: usize, out: &mut Vec<u8>) {
+                out.extend_from_slice(&(len as $t).$to());
+            }
+            fn read(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<usize, DecodeError> {
+                let bytes = take(buf, pos, size_of::<$t>(), field)?;
+                Ok(<$t>::$from(bytes.try_into().unwrap()) as usize)
+            }
+        }
+    )*};
+}
+
+prefixes! {
+    U8 => u8, to_le_bytes, from_le_bytes, "u8 length";
+    U16Le => u16, to_le_bytes, from_le_bytes, "u16 LE length";
+    U16Be => u16, to_be_bytes, from_be_bytes, "u16 BE length";
+    U32Le => u32, to_le_bytes, from_le_bytes, "u32 LE length";
+    U32Be => u32, to_be_bytes, from_be_bytes, "u32 BE length";
+}
+
+/// UTF-8 string after a length prefix `P`. The length counts BYTES, not characters.
+pub struct Utf8<P>(PhantomData<P>);
+
+/// Raw bytes after a length prefix `P`.
+pub struct Bytes<P>(PhantomData<P>);
+
+fn encode_blob<P: Prefix>(bytes: &[u8], field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+    if bytes.len() > P::MAX {
+        return Err(EncodeError::TooLong { field, len: bytes.len(), max: P::MAX });
+    }
+    P::write(bytes.len(), out);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+impl<P: Prefix> Codec<String> for Utf8<P> {
+    const DESCRIPTION: &'static str = "UTF-8 string";
+    const SIZE: Option<usize> = None;
+    const PREFIX: Option<&'static str> = Some(P::DESCRIPTION);
+    fn encode(value: &String, field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        encode_blob::<P>(value.as_bytes(), field, out)
+    }
+    fn decode(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<String, DecodeError> {
+        let len = P::read(buf, pos, field)?;
+        let bytes = take(buf, pos, len, field)?;
+        // UTF-8 validation happens here.
+        std::str::from_utf8(bytes)
+            .map(str::to_string)
+            .map_err(|error| DecodeError::InvalidUtf8 { field, error })
+    }
+}
+
+impl<P: Prefix> Codec<Vec<u8>> for Bytes<P> {
+    const DESCRIPTION: &'static str = "bytes";
+    const SIZE: Option<usize> = None;
+    const PREFIX: Option<&'static str> = Some(P::DESCRIPTION);
+    fn encode(value: &Vec<u8>, field: &'static str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        encode_blob::<P>(value, field, out)
+    }
+    fn decode(buf: &[u8], pos: &mut usize, field: &'static str) -> Result<Vec<u8>, DecodeError> {
+        let len = P::read(buf, pos, field)?;
+        Ok(take(buf, pos, len, field)?.to_vec())
+    }
+}
+
+/// One row of a record's layout table (see `record!`'s generated `LAYOUT`).
+#[derive(Debug, Clone, Copy)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub rust_type: &'static str,
+    pub codec: &'static str,
+    /// Fixed size in bytes, or `None` for length-prefixed fields.
+    pub size: Option<usize>,
+    /// The length prefix in front of the field's bytes, if any.
+    pub prefix: Option<&'static str>,
+}
+
+/// What every `record!` type provides, for code that stores records of any schema
+/// (e.g. a log file that remembers which schema it holds).
+pub trait Record: Sized {
+    const NAME: &'static str;
+    /// `MAGIC` and `VERSION` widened to u64, whatever their wire types are.
+    const SCHEMA_MAGIC: u64;
+    const SCHEMA_VERSION: u64;
+    const LAYOUT: &'static [FieldInfo];
+
+    fn encode_into(&self, out: &mut Vec<u8>) -> Result<(), EncodeError>;
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Declares a record layout and generates the struct plus `encode`/`decode`.
+/// See the top of this file for the syntax.
+#[macro_export]
+macro_rules! record {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            magic: $mty:ty as $mcodec:ty = $magic:expr,
+            version: $vty:ty as $vcodec:ty = $version:expr,
+            fields {
+                $( $(#[$fmeta:meta])* $field:ident : $fty:ty as $fcodec:ty ),* $(,)?
+            }
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $( $(#[$fmeta])* pub $field: $fty, )*
+        }
+
+        impl $name {
+            pub const MAGIC: $mty = $magic;
+            pub const VERSION: $vty = $version;
+
+            /// Field table in wire order, magic and version included.
+            pub const LAYOUT: &'static [$crate::FieldInfo] = &[
+                $crate::FieldInfo {
+                    name: "magic",
+                    rust_type: stringify!($mty),
+                    codec: <$mcodec as $crate::Codec<$mty>>::DESCRIPTION,
+                    size: <$mcodec as $crate::Codec<$mty>>::SIZE,
+                    prefix: <$mcodec as $crate::Codec<$mty>>::PREFIX,
+                },
+                $crate::FieldInfo {
+                    name: "version",
+                    rust_type: stringify!($vty),
+                    codec: <$vcodec as $crate::Codec<$vty>>::DESCRIPTION,
+                    size: <$vcodec as $crate::Codec<$vty>>::SIZE,
+                    prefix: <$vcodec as $crate::Codec<$vty>>::PREFIX,
+                },
+                $(
+                    $crate::FieldInfo {
+                        name: stringify!($field),
+                        rust_type: stringify!($fty),
+                        codec: <$fcodec as $crate::Codec<$fty>>::DESCRIPTION,
+                        size: <$fcodec as $crate::Codec<$fty>>::SIZE,
+                        prefix: <$fcodec as $crate::Codec<$fty>>::PREFIX,
+                    },
+                )*
+            ];
+
+            /// Appends this record's bytes to `out`.
+            pub fn encode_into(&self, out: &mut Vec<u8>) -> Result<(), $crate::EncodeError> {
+                <$mcodec as $crate::Codec<$mty>>::encode(&Self::MAGIC, "magic", out)?;
+                <$vcodec as $crate::Codec<$vty>>::encode(&Self::VERSION, "version", out)?;
+                $( <$fcodec as $crate::Codec<$fty>>::encode(&self.$field, stringify!($field), out)?; )*
+                Ok(())
+            }
+
+            pub fn encode(&self) -> Result<Vec<u8>, $crate::EncodeError> {
+                let mut out = Vec::new();
+                self.encode_into(&mut out)?;
+                Ok(out)
+            }
+
+            /// Reads one record at `*pos` and advances `pos` (for streams of records).
+            pub fn decode_from(buf: &[u8], pos: &mut usize) -> Result<Self, $crate::DecodeError> {
+                let magic = <$mcodec as $crate::Codec<$mty>>::decode(buf, pos, "magic")?;
+                if magic != Self::MAGIC {
+                    return Err($crate::DecodeError::BadMagic {
+                        expected: Self::MAGIC as u64,
+                        found: magic as u64,
+                    });
+                }
+                let version = <$vcodec as $crate::Codec<$vty>>::decode(buf, pos, "version")?;
+                if version != Self::VERSION {
+                    return Err($crate::DecodeError::UnsupportedVersion {
+                        expected: Self::VERSION as u64,
+                        found: version as u64,
+                    });
+                }
+                Ok($name {
+                    $( $field: <$fcodec as $crate::Codec<$fty>>::decode(buf, pos, stringify!($field))?, )*
+                })
+            }
+
+            /// Decodes a buffer that holds exactly one record.
+            pub fn decode(buf: &[u8]) -> Result<Self, $crate::DecodeError> {
+                let mut pos = 0;
+                let record = Self::decode_from(buf, &mut pos)?;
+                if pos != buf.len() {
+                    return Err($crate::DecodeError::TrailingBytes { count: buf.len() - pos });
+                }
+                Ok(record)
+            }
+        }
+
+        impl $crate::Record for $name {
+            const NAME: &'static str = stringify!($name);
+            const SCHEMA_MAGIC: u64 = $name::MAGIC as u64;
+            const SCHEMA_VERSION: u64 = $name::VERSION as u64;
+            const LAYOUT: &'static [$crate::FieldInfo] = $name::LAYOUT;
+
+            fn encode_into(&self, out: &mut Vec<u8>) -> Result<(), $crate::EncodeError> {
+                $name::encode_into(self, out)
+            }
+
+            fn decode(buf: &[u8]) -> Result<Self, $crate::DecodeError> {
+                $name::decode(buf)
+            }
+        }
+    };
+}
+
+// The sensor record both memory labs use.
+record! {
+    /// One sensor reading. Wire format (little-endian throughout):
+    /// `[magic u32][version u8][temp_tenths_c i16][voltage f32][name_len u8][name UTF-8]`
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SensorReading {
+        magic: u32 as Le = 0xA1B2_C3D4,
+        version: u8 as Le = 1,
+        fields {
+            /// Tenths of a degree Celsius (-105 = -10.5 °C).
+            temp_tenths_c: i16 as Le,
+            voltage: f32 as Le,
+            name: String as Utf8<U8>,
+        }
+    }
+}
@@ -0,0 +1,397 @@
+// src/lib.rs
+//
+// Append-only record log: many `record_codec` records in one file.
+//
+// File layout:
+//
+//   [file header][frame][frame][frame] ...
+//
+//   file header (21 bytes, itself a record_codec record):
+//     [magic "RLOG"][format version u8][schema magic u64 LE][schema version u64 LE]
+//     The schema fields say WHICH record type the frames hold (e.g. SensorReading v1).
+//
+//   frame:
+//     [marker "RFRM"][payload len u32 LE][crc32 u32 LE][payload: one encoded record]
+//     The CRC covers the length bytes and the payload, so a flipped bit in either
+//     is caught.
+//
+// Appending only ever writes at the end, one whole frame per `write` call. If the
+// program dies mid-write the damage is limited to a torn last frame; everything
+// before it is still readable.
+//
+// The reader streams the file through a small buffer (the file can be bigger than
+// memory). When a frame is damaged it reports WHERE and WHY, then scans forward
+// byte by byte for the next marker whose frame passes the CRC check and carries on
+// from there. The marker makes resynchronisation possible; the CRC makes a marker
+// that happens to appear inside a payload harmless.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use record_codec::{record, DecodeError, EncodeError, Le, Record};
+
+pub const FRAME_MARKER: u32 = 0x4D52_4652; // "RFRM" when written little-endian
+pub const FRAME_HEADER: usize = 12; // marker + len + crc
+/// Frames claiming more than this are treated as corrupt rather than trusted.
+pub const MAX_PAYLOAD: u32 = 1 << 20;
+
+const READ_CHUNK: usize = 64 * 1024;
+
+record! {
+    /// First bytes of every log file.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FileHeader {
+        magic: u32 as Le = 0x474F_4C52, // "RLOG"
+        version: u8 as Le = 1,
+        fields {
+            schema_magic: u64 as Le,
+            schema_version: u64 as Le,
+        }
+    }
+}
+
+impl FileHeader {
+    pub const SIZE: usize = 21;
+
+    pub fn for_schema<T: Record>() -> Self {
+        FileHeader { schema_magic: T::SCHEMA_MAGIC, schema_version: T::SCHEMA_VERSION }
+    }
+
+    fn check<T: Record>(&self) -> Result<(), LogError> {
+        if *self != FileHeader::for_schema::<T>() {
+            return Err(LogError::SchemaMismatch {
+                expected: T::NAME,
+                magic: self.schema_magic,
+                version: self.schema_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CRC-32 (IEEE 802.3, the one zip/png/ethernet use), table driven
+// ---------------------------------------------------------------------------
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for part in parts {
+        for &b in *part {
+            crc = CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+    }
+    !crc
+}
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    /// The file does not start with a valid log header.
+    Header(DecodeError),
+    /// The file holds records of another schema (or another version of it).
+    SchemaMismatch { expected: &'static str, magic: u64, version: u64 },
+    Encode(EncodeError),
+    TooLarge { len: usize },
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Io(e) => write!(f, "I/O error: {e}"),
+            LogError::Header(e) => write!(f, "not a record log: {e}"),
+            LogError::SchemaMismatch { expected, magic, version } => write!(
+                f,
+                "file holds schema magic 0x{magic:X} version {version}, not {expected}"
+            ),
+            LogError::Encode(e) => write!(f, "cannot encode record: {e}"),
+            LogError::TooLarge { len } => write!(f, "record is {len} bytes, frame limit is {MAX_PAYLOAD}"),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e)
+    }
+}
+
+/// Why a frame was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corruption {
+    BadMarker { found: u32 },
+    BadLength { len: u32 },
+    BadCrc { stored: u32, computed: u32 },
+    /// The file ends in the middle of a frame (typically a torn last append).
+    TornTail { available: usize },
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Corruption::BadMarker { found } => write!(f, "no frame marker (found 0x{found:08X})"),
+            Corruption::BadLength { len } => write!(f, "implausible length {len}"),
+            Corruption::BadCrc { stored, computed } => {
+                write!(f, "CRC mismatch (stored 0x{stored:08X}, computed 0x{computed:08X})")
+            }
+            Corruption::TornTail { available } => write!(f, "file ends inside a frame ({available} bytes left)"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writer
+// ---------------------------------------------------------------------------
+
+/// Appends records of type `T`. The file is created (with its header) if needed.
+pub struct Writer<T: Record> {
+    file: File,
+    frame: Vec<u8>,
+    _schema: std::marker::PhantomData<T>,
+}
+
+impl<T: Record> Writer<T> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LogError> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            let header = FileHeader::for_schema::<T>().encode().map_err(LogError::Encode)?;
+            file.write_all(&header)?;
+        } else {
+            read_header(&mut file)?.check::<T>()?;
+        }
+        Ok(Writer { file, frame: Vec::new(), _schema: std::marker::PhantomData })
+    }
+
+    /// Writes one frame. Returns its file offset.
+    pub fn append(&mut self, record: &T) -> Result<u64, LogError> {
+        self.frame.clear();
+        self.frame.extend_from_slice(&[0; FRAME_HEADER]);
+        record.encode_into(&mut self.frame).map_err(LogError::Encode)?;
+
+        let len = self.frame.len() - FRAME_HEADER;
+        if len > MAX_PAYLOAD as usize {
+            return Err(LogError::TooLarge { len });
+        }
+        let len_bytes = (len as u32).to_le_bytes();
+        let crc = crc32(&[&len_bytes, &self.frame[FRAME_HEADER..]]);
+        self.frame[0..4].copy_from_slice(&FRAME_MARKER.to_le_bytes());
+        self.frame[4..8].copy_from_slice(&len_bytes);
+        self.frame[8..12].copy_from_slice(&crc.to_le_bytes());
+
+        let offset = self.file.metadata()?.len();
+        // One write for the whole frame: a crash can tear only this frame.
+        self.file.write_all(&self.frame)?;
+        Ok(offset)
+    }
+
+    /// Forces appended frames to the disk (fdatasync). Until then they may sit in
+    /// the page cache and vanish on power loss.
+    pub fn sync(&self) -> Result<(), LogError> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
+
+fn read_header(input: &mut impl Read) -> Result<FileHeader, LogError> {
+    let mut buf = [0u8; FileHeader::SIZE];
+    let mut got = 0;
+    while got < buf.len() {
+        match input.read(&mut buf[got..])? {
+            0 => break,
+            n => got += n,
+        }
+    }
+    FileHeader::decode(&buf[..got]).map_err(LogError::Header)
+}
+
+// ---------------------------------------------------------------------------
+// Streaming reader
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum Entry {
+    /// A frame that passed its checks. `offset` is where the frame starts in the file.
+    Frame { offset: u64, payload: Vec<u8> },
+    /// `skipped` bytes starting at `offset` did not form a valid frame.
+    Corrupt { offset: u64, skipped: u64, reason: Corruption },
+}
+
+/// Reads frames one at a time without loading the whole file.
+pub struct Reader<R: Read> {
+    input: R,
+    header: FileHeader,
+    buf: Vec<u8>,
+    pos: usize,    // next unread byte in `buf`
+    base: u64,     // file offset of buf[0]
+    eof: bool,
+}
+
+impl Reader<File> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LogError> {
+        Reader::new(File::open(path)?)
+    }
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(mut input: R) -> Result<Self, LogError> {
+        let header = read_header(&mut input)?;
+        Ok(Reader { input, header, buf: Vec::new(), pos: 0, base: FileHeader::SIZE as u64, eof: false })
+    }
+
+    pub fn header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Fails unless the file holds records of type `T`.
+    pub fn expect_schema<T: Record>(&self) -> Result<(), LogError> {
+        self.header.check::<T>()
+    }
+
+    fn offset(&self) -> u64 {
+        self.base + self.pos as u64
+    }
+
+    fn available(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    // Makes at least `n` unread bytes available, unless the input ends first.
+    fn fill(&mut self, n: usize) -> io::Result<bool> {
+        while self.available() < n && !self.eof {
+            // Drop what has been consumed so the buffer stays small.
+            if self.pos > 0 {
+                self.buf.drain(..self.pos);
+                self.base += self.pos as u64;
+                self.pos = 0;
+            }
+            let old = self.buf.len();
+            self.buf.resize(old + READ_CHUNK.max(n - old), 0);
+            let got = self.input.read(&mut self.buf[old..])?;
+            self.buf.truncate(old + got);
+            self.eof = got == 0;
+        }
+        Ok(self.available() >= n)
+    }
+
+    fn u32_at(&self, at: usize) -> u32 {
+        u32::from_le_bytes(self.buf[self.pos + at..self.pos + at + 4].try_into().unwrap())
+    }
+
+    // Checks the frame starting at `pos`. On success returns its payload length.
+    fn check_frame(&mut self) -> io::Result<Result<usize, Corruption>> {
+        if !self.fill(FRAME_HEADER)? {
+            return Ok(Err(Corruption::TornTail { available: self.available() }));
+        }
+        let marker = self.u32_at(0);
+        if marker != FRAME_MARKER {
+            return Ok(Err(Corruption::BadMarker { found: marker }));
+        }
+        let len = self.u32_at(4);
+        if len > MAX_PAYLOAD {
+            return Ok(Err(Corruption::BadLength { len }));
+        }
+        let len = len as usize;
+        if !self.fill(FRAME_HEADER + len)? {
+            return Ok(Err(Corruption::TornTail { available: self.available() }));
+        }
+        let stored = self.u32_at(8);
+        let start = self.pos + FRAME_HEADER;
+        let computed = crc32(&[&self.buf[self.pos + 4..self.pos + 8], &self.buf[start..start + len]]);
+        if stored != computed {
+            return Ok(Err(Corruption::BadCrc { stored, computed }));
+        }
+        Ok(Ok(len))
+    }
+
+    /// Next frame or corrupt stretch, `None` at the end of the file.
+    pub fn next_entry(&mut self) -> io::Result<Option<Entry>> {
+        if !self.fill(1)? {
+            return Ok(None);
+        }
+        let offset = self.offset();
+        let reason = match self.check_frame()? {
+            Ok(len) => {
+                let start = self.pos + FRAME_HEADER;
+                let payload = self.buf[start..start + len].to_vec();
+                self.pos = start + len;
+                return Ok(Some(Entry::Frame { offset, payload }));
+            }
+            Err(reason) => reason,
+        };
+
+        // Resynchronise: slide forward until a marker starts a frame that checks out.
+        loop {
+            self.pos += 1;
+            if !self.fill(4)? {
+                self.pos = self.buf.len();
+                break;
+            }
+            if self.u32_at(0) == FRAME_MARKER && self.check_frame()?.is_ok() {
+                break;
+            }
+        }
+        Ok(Some(Entry::Corrupt { offset, skipped: self.offset() - offset, reason }))
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+/// Totals from reading a whole log.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Summary {
+    pub frames: u64,
+    pub decoded: u64,
+    pub corrupt_stretches: u64,
+    pub corrupt_bytes: u64,
+}
+
+/// Reads every frame and decodes it as `T`, counting what is damaged.
+pub fn verify<T: Record>(path: impl AsRef<Path>) -> Result<Summary, LogError> {
+    let reader = Reader::open(path)?;
+    reader.expect_schema::<T>()?;
+    let mut summary = Summary::default();
+    for entry in reader {
+        match entry? {
+            Entry::Frame { payload, .. } => {
+                summary.frames += 1;
+                if T::decode(&payload).is_ok() {
+                    summary.decoded += 1;
+                }
+            }
+            Entry::Corrupt { skipped, .. } => {
+                summary.corrupt_stretches += 1;
+                summary.corrupt_bytes += skipped;
+            }
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,330 @@
+// src/lib.rs
+//
+// Text inspector: what a Rust &str actually holds.
+//
+// A String is a Vec<u8> that is guaranteed to be valid UTF-8. Lengths, indexing
+// and slicing all work in BYTES, while the thing a reader calls "a letter" is
+// something else again:
+//
+//   byte      one u8 of the encoding           "✅" is 3 bytes: E2 9C 85
+//   char      one Unicode code point (U+....)  "✅" is 1 char
+//   grapheme  one user-perceived character     "é" is 1 char (U+00E9) in NFC,
+//                                              but 2 chars (e + U+0301) in NFD
+//
+// s.len() counts bytes, s.chars().count() counts code points, and neither is
+// the number of symbols on screen. UTF-16 (JavaScript, Java, Windows) counts
+// yet another unit: 16-bit code units, two of them for anything above U+FFFF.
+//
+// &s[a..b] panics unless both a and b fall on char boundaries; check_slice()
+// reports which rule a range breaks, in the same order the standard library
+// checks them. decode_utf8() does the same job for raw bytes that may not be
+// valid UTF-8 at all.
+
+use std::ops::Range;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// One code point and where it sits in each encoding.
+#[derive(Debug, Clone, Copy)]
+pub struct CharInfo {
+    pub ch: char,
+    /// Offset in UTF-8 bytes: the unit `&s[a..b]` uses.
+    pub byte_offset: usize,
+    /// Offset in UTF-16 code units: what `"...".length` counts in JavaScript.
+    pub utf16_offset: usize,
+    /// Index of the grapheme cluster this char belongs to.
+    pub grapheme: usize,
+}
+
+impl CharInfo {
+    pub fn utf8(&self) -> Vec<u8> {
+        let mut buf = [0; 4];
+        self.ch.encode_utf8(&mut buf).as_bytes().to_vec()
+    }
+
+    pub fn utf16(&self) -> Vec<u16> {
+        let mut buf = [0; 2];
+        self.ch.encode_utf16(&mut buf).to_vec()
+    }
+
+    /// A short note on what kind of code point this is.
+    pub fn kind(&self) -> &'static str {
+        let c = self.ch;
+        match c as u32 {
+            _ if c.is_control() => "control",
+            0x00..=0x7F => "ASCII",
+            0x200D => "zero width joiner (glues emoji together)",
+            0xFE00..=0xFE0F => "variation selector (text vs emoji style)",
+            0x1F1E6..=0x1F1FF => "regional indicator (two make a flag)",
+            0x1F3FB..=0x1F3FF => "skin tone modifier",
+            _ if is_combining_mark(c) => "combining mark (attaches to the previous char)",
+            0x80..=0xFF => "Latin-1",
+            0x100..=0xFFFF => "Basic Multilingual Plane",
+            _ => "supplementary plane (surrogate pair in UTF-16)",
+        }
+    }
+}
+
+/// Every char of `s`, in order.
+pub fn chars(s: &str) -> Vec<CharInfo> {
+    let mut out = Vec::new();
+    let mut utf16_offset = 0;
+    for (grapheme, (start, g)) in s.grapheme_indices(true).enumerate() {
+        for (i, ch) in g.char_indices() {
+            out.push(CharInfo { ch, byte_offset: start + i, utf16_offset, grapheme });
+            utf16_offset += ch.len_utf16();
+        }
+    }
+    out
+}
+
+/// One extended grapheme cluster.
+#[derive(Debug, Clone)]
+pub struct Grapheme<'a> {
+    pub text: &'a str,
+    pub bytes: Range<usize>,
+    pub chars: usize,
+}
+
+pub fn graphemes(s: &str) -> Vec<Grapheme<'_>> {
+    s.grapheme_indices(true)
+        .map(|(start, text)| Grapheme { text, bytes: start..start + text.len(), chars: text.chars().count() })
+        .collect()
+}
+
+/// The four ways of counting "length".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lengths {
+    pub bytes: usize,
+    pub utf16_units: usize,
+    pub chars: usize,
+    pub graphemes: usize,
+}
+
+pub fn lengths(s: &str) -> Lengths {
+    Lengths {
+        bytes: s.len(),
+        utf16_units: s.encode_utf16().count(),
+        chars: s.chars().count(),
+        graphemes: s.graphemes(true).count(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Start,
+    End,
+}
+
+impl Side {
+    pub fn name(self) -> &'static str {
+        match self {
+            Side::Start => "start",
+            Side::End => "end",
+        }
+    }
+}
+
+/// Why `&s[start..end]` would panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceError {
+    OutOfBounds { side: Side, index: usize, len: usize },
+    Reversed { start: usize, end: usize },
+    /// `index` falls inside `ch`, which occupies `char_bytes`.
+    NotCharBoundary { side: Side, index: usize, ch: char, char_bytes: Range<usize> },
+}
+
+impl std::fmt::Display for SliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceError::OutOfBounds { side, index, len } => {
+                write!(f, "{} index {index} is past the end of the string ({len} bytes)", side.name())
+            }
+            SliceError::Reversed { start, end } => write!(f, "start {start} is after end {end}"),
+            SliceError::NotCharBoundary { side, index, ch, char_bytes } => write!(
+                f,
+                "{} index {index} is not a char boundary: it is byte {} of the {}-byte encoding of {ch:?} (bytes {char_bytes:?})",
+                side.name(),
+                index - char_bytes.start + 1,
+                char_bytes.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+/// The char of `s` whose encoding contains byte `index`.
+fn char_at_byte(s: &str, index: usize) -> (char, Range<usize>) {
+    let (start, ch) = s.char_indices().take_while(|&(i, _)| i <= index).last().expect("index inside s");
+    (ch, start..start + ch.len_utf8())
+}
+
+/// Checks `&s[start..end]` without panicking, in the same order as the
+/// standard library: bounds first, then start <= end, then char boundaries.
+pub fn check_slice(s: &str, start: usize, end: usize) -> Result<&str, SliceError> {
+    for (side, index) in [(Side::Start, start), (Side::End, end)] {
+        if index > s.len() {
+            return Err(SliceError::OutOfBounds { side, index, len: s.len() });
+        }
+    }
+    if start > end {
+        return Err(SliceError::Reversed { start, end });
+    }
+    for (side, index) in [(Side::Start, start), (Side::End, end)] {
+        if !s.is_char_boundary(index) {
+            let (ch, char_bytes) = char_at_byte(s, index);
+            return Err(SliceError::NotCharBoundary { side, index, ch, char_bytes });
+        }
+    }
+    Ok(&s[start..end])
+}
+
+/// Largest char boundary <= index (what the unstable `str::floor_char_boundary` does).
+pub fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len())).rev().find(|&i| s.is_char_boundary(i)).unwrap_or(0)
+}
+
+/// Smallest char boundary >= index.
+pub fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    (index.min(s.len())..=s.len()).find(|&i| s.is_char_boundary(i)).unwrap_or(s.len())
+}
+
+/// A valid slice can still cut a grapheme in half, e.g. keep the "e" of an
+/// NFD "é" and drop its accent. Returns the clusters that are cut.
+pub fn split_graphemes<'a>(s: &'a str, start: usize, end: usize) -> Vec<Grapheme<'a>> {
+    graphemes(s)
+        .into_iter()
+        .filter(|g| {
+            let cuts = |i: usize| g.bytes.start < i && i < g.bytes.end;
+            cuts(start) || cuts(end)
+        })
+        .collect()
+}
+
+pub fn nfc(s: &str) -> String {
+    s.nfc().collect()
+}
+
+pub fn nfd(s: &str) -> String {
+    s.nfd().collect()
+}
+
+/// Canonically equivalent: the same text once both sides are normalized.
+pub fn equivalent(a: &str, b: &str) -> bool {
+    a.nfc().eq(b.nfc())
+}
+
+/// A run of raw bytes decoded as UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Piece {
+    Char { offset: usize, ch: char, len: usize },
+    Invalid { offset: usize, bytes: Vec<u8>, reason: String },
+}
+
+fn sequence_len(lead: u8) -> usize {
+    match lead {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    }
+}
+
+/// Explains an invalid sequence the way a UTF-8 decoder would find it.
+/// `bad` starts at the offending lead byte; `truncated` means the input ended
+/// before the sequence was complete.
+fn invalid_reason(bad: &[u8], truncated: bool) -> String {
+    let lead = bad[0];
+    match (lead, bad.get(1)) {
+        (0x80..=0xBF, _) => format!("0x{lead:02X} is a continuation byte (10xxxxxx) with no lead byte before it"),
+        (0xC0 | 0xC1, _) => format!("0x{lead:02X} can only start an overlong encoding of an ASCII char"),
+        (0xF5..=0xFF, _) => format!("0x{lead:02X} never appears in UTF-8 (it would encode past U+10FFFF)"),
+        _ if truncated => {
+            format!("lead byte 0x{lead:02X} starts a {}-byte sequence, but the input ends first", sequence_len(lead))
+        }
+        (0xE0, Some(0x80..=0x9F)) | (0xF0, Some(0x80..=0x8F)) => {
+            format!("0x{lead:02X} 0x{:02X} is an overlong encoding (a shorter form exists)", bad[1])
+        }
+        (0xED, Some(0xA0..=0xBF)) => {
+            format!("0xED 0x{:02X} encodes a UTF-16 surrogate (U+D800..U+DFFF), which is not a char", bad[1])
+        }
+        (0xF4, Some(0x90..=0xBF)) => format!("0xF4 0x{:02X} encodes a value above U+10FFFF", bad[1]),
+        _ => {
+            let at = bad[1..].iter().position(|b| b & 0xC0 != 0x80).map_or(1, |p| p + 1);
+            format!(
+                "lead byte 0x{lead:02X} starts a {}-byte sequence, but byte {} (0x{:02X}) is not a continuation byte",
+                sequence_len(lead),
+                at + 1,
+                bad[at]
+            )
+        }
+    }
+}
+
+/// Decodes `bytes` as far as possible, marking every invalid sequence instead
+/// of giving up at the first one (which is what String::from_utf8 does).
+pub fn decode_utf8(bytes: &[u8]) -> Vec<Piece> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        let (valid, error) = match std::str::from_utf8(rest) {
+            Ok(s) => (s, None),
+            Err(e) => (std::str::from_utf8(&rest[..e.valid_up_to()]).expect("valid prefix"), Some(e)),
+        };
+        for (i, ch) in valid.char_indices() {
+            out.push(Piece::Char { offset: offset + i, ch, len: ch.len_utf8() });
+        }
+        offset += valid.len();
+        let Some(e) = error else { break };
+        let bad_len = e.error_len().unwrap_or(bytes.len() - offset);
+        let bad = &bytes[offset..];
+        out.push(Piece::Invalid {
+            offset,
+            bytes: bad[..bad_len].to_vec(),
+            reason: invalid_reason(bad, e.error_len().is_none()),
+        });
+        offset += bad_len;
+    }
+    out
+}
+
+/// Turns `\u{301}`, `\n`, `\t` and `\\` in command-line input into the real
+/// characters, so combining marks and joiners can be typed.
+pub fn unescape(input: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut it = input.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match it.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some('u') => {
+                let rest: String = it.by_ref().take_while(|&c| c != '}').collect();
+                let hex = rest.strip_prefix('{').ok_or("expected \\u{HEX}")?;
+                let code = u32::from_str_radix(hex, 16).map_err(|_| format!("bad hex in \\u{{{hex}}}"))?;
+                out.push(char::from_u32(code).ok_or(format!("U+{code:04X} is not a char"))?);
+            }
+            other => return Err(format!("unknown escape \\{}", other.map_or(String::new(), String::from))),
+        }
+    }
+    Ok(out)
+}
+
+/// Parses "E2 9C 85", "e29c85" or "0xE2,0x9C,0x85" into bytes.
+pub fn parse_hex_bytes(input: &str) -> Option<Vec<u8>> {
+    let digits: String = input
+        .split([' ', ',', ':'])
+        .map(|w| w.strip_prefix("0x").or_else(|| w.strip_prefix("0X")).unwrap_or(w))
+        .collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok()).collect()
+}
@@ -0,0 +1,206 @@
+// src/main.rs
+//
+// `text_lab`                          => guided tour
+// `text_lab inspect <text>`           => every char with byte/UTF-16 offsets and encodings
+// `text_lab slice <text> <a>..<b>`    => would &text[a..b] panic, and exactly why
+// `text_lab norm <text> [other]`      => NFC and NFD forms; compare with another string
+// `text_lab bytes <hex>`              => decode raw bytes as UTF-8, explaining invalid ones
+//
+// Text arguments understand \u{301}, \n, \t and \\ so invisible characters can be typed.
+
+use std::process::ExitCode;
+
+use hexdump::hex;
+use text_lab::{
+    ceil_char_boundary, chars, check_slice, decode_utf8, equivalent, floor_char_boundary, graphemes, lengths, nfc,
+    nfd, parse_hex_bytes, split_graphemes, unescape, Piece, SliceError,
+};
+
+fn utf16_hex(units: &[u16]) -> String {
+    units.iter().map(|u| format!("{u:04X}")).collect::<Vec<_>>().join(" ")
+}
+
+fn inspect(s: &str) {
+    let l = lengths(s);
+    println!("{s:?}");
+    println!(
+        "  {} bytes (s.len()), {} UTF-16 units, {} chars (s.chars().count()), {} graphemes",
+        l.bytes, l.utf16_units, l.chars, l.graphemes
+    );
+    println!("  {:>4} {:>6} {:>2}  {:<9} {:<10} {:<12} {:<10} kind", "byte", "utf16", "g", "code", "char", "UTF-8", "UTF-16");
+    for c in chars(s) {
+        println!(
+            "  {:>4} {:>6} {:>2}  U+{:<7X} {:<10} {:<12} {:<10} {}",
+            c.byte_offset,
+            c.utf16_offset,
+            c.grapheme,
+            c.ch as u32,
+            format!("{:?}", c.ch),
+            hex(&c.utf8()),
+            utf16_hex(&c.utf16()),
+            c.kind()
+        );
+    }
+    let gs = graphemes(s);
+    if gs.len() != l.chars {
+        let list: Vec<String> = gs
+            .iter()
+            .filter(|g| g.chars > 1)
+            .map(|g| format!("{:?} = bytes {:?}, {} chars", g.text, g.bytes, g.chars))
+            .collect();
+        println!("  multi-char graphemes: {}", list.join("; "));
+    }
+}
+
+// What the standard library itself says, by actually slicing and catching the panic.
+fn std_panic_message(s: &str, start: usize, end: usize) -> Option<String> {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(|| s[start..end].len());
+    std::panic::set_hook(previous);
+    let payload = result.err()?;
+    payload.downcast_ref::<String>().cloned().or_else(|| payload.downcast_ref::<&str>().map(|m| m.to_string()))
+}
+
+fn slice(s: &str, start: usize, end: usize) {
+    println!("&{s:?}[{start}..{end}]");
+    match check_slice(s, start, end) {
+        Ok(sub) => {
+            println!("  ok: {sub:?}");
+            for g in split_graphemes(s, start, end) {
+                println!(
+                    "  but it cuts the grapheme {:?} (bytes {:?}) apart: valid UTF-8, wrong text",
+                    g.text, g.bytes
+                );
+            }
+        }
+        Err(e) => {
+            println!("  PANICS: {e}");
+            if let Some(msg) = std_panic_message(s, start, end) {
+                println!("  std says: {msg}");
+            }
+            match e {
+                SliceError::NotCharBoundary { .. } => {
+                    let (a, b) = (floor_char_boundary(s, start), ceil_char_boundary(s, end));
+                    println!("  widen to char boundaries: &s[{a}..{b}] = {:?}", &s[a..b]);
+                }
+                SliceError::OutOfBounds { len, .. } => println!("  valid indices are 0..={len}"),
+                SliceError::Reversed { .. } => {}
+            }
+            println!("  s.get({start}..{end}) returns None instead of panicking");
+        }
+    }
+}
+
+fn char_list(s: &str) -> String {
+    s.chars().map(|c| format!("U+{:04X}", c as u32)).collect::<Vec<_>>().join(" ")
+}
+
+fn norm(s: &str, other: Option<&str>) {
+    let (c, d) = (nfc(s), nfd(s));
+    println!("{s:?}: {}", char_list(s));
+    println!("  NFC (composed)   {c:?}: {}  ({} bytes)", char_list(&c), c.len());
+    println!("  NFD (decomposed) {d:?}: {}  ({} bytes)", char_list(&d), d.len());
+    let form = match (s == c, s == d) {
+        (true, true) => "both NFC and NFD (nothing to compose or decompose)",
+        (true, false) => "already NFC",
+        (false, true) => "already NFD",
+        (false, false) => "neither NFC nor NFD",
+    };
+    println!("  input is {form}");
+    if let Some(o) = other {
+        println!("{o:?}: {}", char_list(o));
+        println!("  a == b                 {}", s == o);
+        println!("  nfc(a) == nfc(b)       {}", equivalent(s, o));
+    }
+}
+
+fn bytes(input: &[u8]) {
+    println!("{}", hex(input));
+    for piece in decode_utf8(input) {
+        match piece {
+            Piece::Char { offset, ch, len } => {
+                println!("  {offset:>4}  {:<12} U+{:04X} {ch:?}", hex(&input[offset..offset + len]), ch as u32)
+            }
+            Piece::Invalid { offset, bytes, reason } => println!("  {offset:>4}  {:<12} INVALID: {reason}", hex(&bytes)),
+        }
+    }
+    if let Err(e) = std::str::from_utf8(input) {
+        println!("  String::from_utf8 stops at the first problem: {e}");
+        println!("  String::from_utf8_lossy gives {:?}", String::from_utf8_lossy(input));
+    }
+}
+
+fn parse_range(r: &str, len: usize) -> Option<(usize, usize)> {
+    let (a, b) = r.split_once("..")?;
+    let a = if a.is_empty() { 0 } else { a.parse().ok()? };
+    let b = if b.is_empty() { len } else { b.parse().ok()? };
+    Some((a, b))
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        ["inspect", text] => inspect(&unescape(text)?),
+        ["slice", text, range] => {
+            let s = unescape(text)?;
+            let (a, b) = parse_range(range, s.len()).ok_or(format!("'{range}' is not a range like 2..4"))?;
+            slice(&s, a, b);
+        }
+        ["norm", text] => norm(&unescape(text)?, None),
+        ["norm", text, other] => norm(&unescape(text)?, Some(&unescape(other)?)),
+        ["bytes", hex_words @ ..] if !hex_words.is_empty() => {
+            bytes(&parse_hex_bytes(&hex_words.join(" ")).ok_or("expected hex bytes like 'E2 9C 85'")?)
+        }
+        _ => return Err("usage: text_lab [inspect <text> | slice <text> <a>..<b> | norm <text> [other] | bytes <hex>]".into()),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args.is_empty() {
+        tour();
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn tour() {
+    println!("=== 1) Bytes, chars, graphemes ===\n");
+    println!("memory_storage_lab prints s.len() = 6 for \"DJ ✅\". Here is where the 6 comes from:");
+    inspect("DJ ✅");
+    println!();
+    inspect("sensor-α");
+    println!();
+    inspect("👩‍🔧🇳🇴");
+    println!("  (the JavaScript .length of that string would be {})", lengths("👩‍🔧🇳🇴").utf16_units);
+
+    println!("\n=== 2) Slicing: byte indices must land on char boundaries ===\n");
+    println!("module2_collections/strings does &s[2..4] on \"Hello, world!\": all ASCII, so it is safe.");
+    slice("Hello, world!", 2, 4);
+    println!("\nThe same range on a sensor name from memory_storage_lab:");
+    slice("DJ ✅", 2, 4);
+    println!();
+    slice("sensor-α", 0, 8);
+    println!();
+    slice("sensor-α", 7, 20);
+    println!();
+    slice("cafe\u{301}", 0, 4);
+
+    println!("\n=== 3) Normalization: one letter, two spellings ===\n");
+    norm("café", Some("cafe\u{301}"));
+    println!("\nCompare names after NFC (or NFD) normalization, never as raw bytes.");
+
+    println!("\n=== 4) Raw bytes that are not UTF-8 ===\n");
+    bytes(&[0x44, 0x4A, 0x20, 0xE2, 0x9C, 0x85]);
+    println!();
+    bytes(&[0x73, 0xCE, 0x20, 0x85, 0xC0, 0xAF, 0xED, 0xA0, 0x80, 0xE2, 0x9C]);
+    println!("\nTry: text_lab slice \"sensor-α\" 7..8    text_lab norm \"\\u{{212B}}\"    text_lab bytes F0 9F 98");
+}
@@ -1,12 +1,62 @@
+mod maps;
+
 use std::env;
+use std::process::{Command, ExitCode};
+
+use maps::{Kind, Region};
 
 fn some_function() -> u64 {
     42
 }
 
-fn main() {
+// Usage:
+//   va_demo               => print the demo pointers, then this process's memory map
+//   va_demo pid <PID>     => memory map of another process (Rss/Swap need permission)
+//   va_demo aslr [RUNS]   => re-run this program RUNS times and compare the addresses
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            demo();
+            ExitCode::SUCCESS
+        }
+        ["pid", pid] => match maps::read_regions(pid) {
+            Ok(regions) => {
+                print_map(&regions);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("cannot read /proc/{pid}/maps: {e}");
+                ExitCode::FAILURE
+            }
+        },
+        ["aslr"] => aslr(5),
+        ["aslr", runs] => match runs.parse() {
+            Ok(runs) => aslr(runs),
+            Err(_) => {
+                eprintln!("RUNS must be a number, got '{runs}'");
+                ExitCode::FAILURE
+            }
+        },
+        // Internal: one child run of `aslr`, prints "name address" lines.
+        ["addrs"] => {
+            for (name, va) in demo_pointers().0 {
+                println!("{name} {va:x}");
+            }
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("usage: va_demo [pid <PID> | aslr [RUNS]]");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// The four pointers the demo has always printed, plus the values behind them so
+// the optimizer keeps everything alive.
+fn demo_pointers() -> ([(&'static str, usize); 4], u64) {
     // 1) CODE address (function pointer) => virtual address in the "text/code" region
-    let code_va = some_function as usize;
+    let code_va = some_function as *const () as usize;
 
     // 2) STACK address (local variable lives on stack)
     let stack_local: u64 = 123;
@@ -20,6 +70,17 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     let args_va = args.as_ptr() as usize;
 
+    let keep = std::hint::black_box(stack_local + *heap_box + some_function());
+    (
+        [("code", code_va), ("stack", stack_va), ("heap", heap_va), ("args", args_va)],
+        keep,
+    )
+}
+
+fn demo() {
+    let (ptrs, keep) = demo_pointers();
+    let [(_, code_va), (_, stack_va), (_, heap_va), (_, args_va)] = ptrs;
+
     println!("Virtual Address demo (these are VAs, not physical addresses)");
     println!("code_va  = 0x{code_va:016x}  (function pointer / code)");
     println!("stack_va = 0x{stack_va:016x}  (local variable / stack)");
@@ -27,5 +88,150 @@ fn main() {
     println!("args_va  = 0x{args_va:016x}  (Vec<String> buffer / heap-ish)");
 
     // Keep values used so optimizer doesn't erase them.
-    println!("values: stack_local={stack_local}, heap_box={}, func={}", *heap_box, some_function());
+    println!("values: sum={keep}, func={}", some_function());
+
+    // Now give those numbers context: which region of the address space is each one in?
+    let regions = match maps::read_regions("self") {
+        Ok(r) => r,
+        Err(e) => {
+            println!("\n(cannot read /proc/self/maps: {e} — Linux only)");
+            return;
+        }
+    };
+
+    println!();
+    print_map(&regions);
+
+    println!("\n--- Where the demo pointers fall ---");
+    for (name, va) in ptrs {
+        match maps::find(&regions, va) {
+            Some(r) => println!(
+                "{name:<5} 0x{va:012x} => {:<11} {} +0x{:x}  {}",
+                r.kind,
+                r.perms,
+                va - r.start,
+                r.path
+            ),
+            None => println!("{name:<5} 0x{va:012x} => not mapped?!"),
+        }
+    }
+    println!("NOTE: a small Box often lands in [heap] (brk), a large one in an anonymous mmap.");
+}
+
+fn print_map(regions: &[Region]) {
+    println!(
+        "{:<12} {:<12} {:>7} {:<4} {:>8} {:>8}  {:<11} PATH",
+        "START", "END", "SIZE", "PERM", "RSS", "SWAP", "KIND"
+    );
+    let kb = |v: Option<u64>| v.map_or("-".to_string(), |k| maps::human(k as usize * 1024));
+    for r in regions {
+        println!(
+            "{:012x} {:012x} {:>7} {:<4} {:>8} {:>8}  {:<11} {}",
+            r.start,
+            r.end,
+            maps::human(r.size()),
+            r.perms,
+            kb(r.rss_kb),
+            kb(r.swap_kb),
+            r.kind,
+            r.path
+        );
+    }
+
+    // Totals per kind: virtual size vs what is actually resident in RAM.
+    println!("\n--- Totals by kind (virtual vs resident) ---");
+    let kinds = [
+        Kind::Text,
+        Kind::RoData,
+        Kind::Data,
+        Kind::Bss,
+        Kind::Heap,
+        Kind::Stack,
+        Kind::Vdso,
+        Kind::SharedLib,
+        Kind::MappedFile,
+        Kind::Anonymous,
+        Kind::Guard,
+    ];
+    for kind in kinds {
+        let of_kind: Vec<&Region> = regions.iter().filter(|r| r.kind == kind).collect();
+        if of_kind.is_empty() {
+            continue;
+        }
+        let virt: usize = of_kind.iter().map(|r| r.size()).sum();
+        let rss: Option<u64> = of_kind.iter().map(|r| r.rss_kb).sum();
+        let swap: Option<u64> = of_kind.iter().map(|r| r.swap_kb).sum();
+        println!(
+            "{kind:<11} {:>3} regions  virt {:>7}  rss {:>7}  swap {:>7}",
+            of_kind.len(),
+            maps::human(virt),
+            kb(rss),
+            kb(swap)
+        );
+    }
+}
+
+// ASLR (Address Space Layout Randomization): every exec gets new random bases
+// for the program, heap, stack, libraries and mmap area.
+fn aslr(runs: usize) -> ExitCode {
+    let exe = match env::current_exe() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("cannot find own executable: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // 0 = off, 1 = stack/mmap/vdso randomized, 2 = also the brk heap
+    let setting = std::fs::read_to_string("/proc/sys/kernel/randomize_va_space")
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|_| "?".to_string());
+    println!("kernel.randomize_va_space = {setting} (0 = off, 1 = stack/mmap/vdso, 2 = + heap)\n");
+
+    let mut table: Vec<Vec<usize>> = Vec::new();
+    for _ in 0..runs {
+        let out = match Command::new(&exe).arg("addrs").output() {
+            Ok(out) => out,
+            Err(e) => {
+                eprintln!("cannot re-run {}: {e}", exe.display());
+                return ExitCode::FAILURE;
+            }
+        };
+        let row = String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|l| l.split_once(' '))
+            .filter_map(|(_, hex)| usize::from_str_radix(hex, 16).ok())
+            .collect();
+        table.push(row);
+    }
+
+    let names = ["code", "stack", "heap", "args"];
+    print!("{:<5}", "run");
+    for n in names {
+        print!("  {n:>16}");
+    }
+    println!();
+    for (i, row) in table.iter().enumerate() {
+        print!("{:<5}", i + 1);
+        for va in row {
+            print!("  0x{va:014x}");
+        }
+        println!();
+    }
+
+    // The low 12 bits are the offset inside a 4 KiB page: ASLR moves whole pages,
+    // so those bits never change between runs. (Linux additionally shifts the
+    // initial stack pointer by a random sub-page amount, so "stack" is the exception.)
+    println!();
+    for (col, name) in names.iter().enumerate() {
+        let vals: Vec<usize> = table.iter().filter_map(|row| row.get(col).copied()).collect();
+        let moved = vals.windows(2).any(|w| w[0] != w[1]);
+        let same_page_offset = vals.windows(2).all(|w| w[0] & 0xfff == w[1] & 0xfff);
+        println!(
+            "{name:<5} {}  (page offset {})",
+            if moved { "MOVES between runs" } else { "stays put" },
+            if same_page_offset { "constant" } else { "varies" }
+        );
+    }
+    ExitCode::SUCCESS
 }
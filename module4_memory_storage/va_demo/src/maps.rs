@@ -0,0 +1,194 @@
+// src/maps.rs
+//
+// Reads /proc/<pid>/maps (and smaps when allowed) into a list of regions.
+//
+// One line of /proc/<pid>/maps looks like:
+//
+//   55d0c1a00000-55d0c1a08000 r-xp 00004000 fd:01 1234567   /usr/bin/va_demo
+//   ^start       ^end         ^perm ^file off ^dev  ^inode  ^path (may be empty)
+//
+// smaps prints the same header line followed by "Key:   value kB" lines
+// (Rss, Swap, ...) for every region, so one parser handles both files.
+
+use std::fmt;
+use std::fs;
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Text,         // executable code of the program
+    RoData,       // read-only data of the program (string literals, consts)
+    Data,         // initialized read-write data of the program
+    Bss,          // zero-initialized data (anonymous, right after the program's data)
+    Heap,         // brk heap ("[heap]")
+    Stack,        // main thread stack ("[stack]")
+    Vdso,         // kernel-provided code for fast syscalls ("[vdso]", "[vvar]", "[vsyscall]")
+    SharedLib,    // mmap'd .so file (libc, ld.so, ...)
+    MappedFile,   // any other file mapping
+    Anonymous,    // anonymous mmap (big allocations, thread stacks, allocator arenas)
+    Guard,        // no permissions at all ("---p"): guard pages
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Kind::Text => "text",
+            Kind::RoData => "rodata",
+            Kind::Data => "data",
+            Kind::Bss => "bss",
+            Kind::Heap => "heap",
+            Kind::Stack => "stack",
+            Kind::Vdso => "vdso",
+            Kind::SharedLib => "shared lib",
+            Kind::MappedFile => "mapped file",
+            Kind::Anonymous => "anon mmap",
+            Kind::Guard => "guard",
+        };
+        // `f.pad` so width specifiers like {:<11} work in tables.
+        f.pad(s)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub start: usize,
+    pub end: usize,
+    pub perms: String,
+    pub path: String,
+    pub kind: Kind,
+    pub rss_kb: Option<u64>,  // None when smaps could not be read
+    pub swap_kb: Option<u64>,
+}
+
+impl Region {
+    pub fn size(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn contains(&self, addr: usize) -> bool {
+        self.start <= addr && addr < self.end
+    }
+}
+
+fn bad_line(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("unexpected maps line: {line}"))
+}
+
+// Parses one maps/smaps header line. Classification happens later, once we can
+// see the neighbours (bss is recognised by what comes before it).
+fn parse_header(line: &str) -> io::Result<Region> {
+    let mut fields = line.split_whitespace();
+    let range = fields.next().ok_or_else(|| bad_line(line))?;
+    let perms = fields.next().ok_or_else(|| bad_line(line))?;
+    // offset, dev, inode — not needed for classification
+    for _ in 0..3 {
+        fields.next().ok_or_else(|| bad_line(line))?;
+    }
+    // The path is the rest of the line and may itself contain spaces.
+    let path = fields.collect::<Vec<_>>().join(" ");
+
+    let (start, end) = range.split_once('-').ok_or_else(|| bad_line(line))?;
+    let start = usize::from_str_radix(start, 16).map_err(|_| bad_line(line))?;
+    let end = usize::from_str_radix(end, 16).map_err(|_| bad_line(line))?;
+
+    Ok(Region {
+        start,
+        end,
+        perms: perms.to_string(),
+        path,
+        kind: Kind::Anonymous,
+        rss_kb: None,
+        swap_kb: None,
+    })
+}
+
+// smaps header lines start with "start-end"; field lines start with "Name:".
+fn is_header(line: &str) -> bool {
+    line.split_whitespace()
+        .next()
+        .is_some_and(|first| !first.ends_with(':') && first.contains('-'))
+}
+
+fn parse(text: &str) -> io::Result<Vec<Region>> {
+    let mut regions: Vec<Region> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if is_header(line) {
+            regions.push(parse_header(line)?);
+            continue;
+        }
+        // "Rss:                 132 kB"
+        let mut parts = line.split_whitespace();
+        let key = parts.next().unwrap_or("");
+        let value = parts.next().and_then(|v| v.parse::<u64>().ok());
+        if let Some(region) = regions.last_mut() {
+            match key {
+                "Rss:" => region.rss_kb = value,
+                "Swap:" => region.swap_kb = value,
+                _ => {}
+            }
+        }
+    }
+    Ok(regions)
+}
+
+fn classify(regions: &mut [Region], exe: &str) {
+    let mut prev_was_exe_data = false;
+    for r in regions.iter_mut() {
+        let is_exe = !exe.is_empty() && r.path == exe;
+        r.kind = match r.path.as_str() {
+            _ if r.perms.starts_with("---") => Kind::Guard,
+            "[heap]" => Kind::Heap,
+            "[stack]" => Kind::Stack,
+            "[vdso]" | "[vvar]" | "[vvar_vclock]" | "[vsyscall]" => Kind::Vdso,
+            _ if is_exe && r.perms.contains('x') => Kind::Text,
+            _ if is_exe && r.perms.starts_with("rw") => Kind::Data,
+            _ if is_exe => Kind::RoData,
+            // The first anonymous rw region right after the program's data is its .bss
+            "" if prev_was_exe_data && r.perms.starts_with("rw") => Kind::Bss,
+            "" => Kind::Anonymous,
+            p if p.ends_with(".so") || p.contains(".so.") => Kind::SharedLib,
+            _ => Kind::MappedFile,
+        };
+        prev_was_exe_data = r.kind == Kind::Data;
+    }
+}
+
+// `pid` is "self" or a number. Prefers smaps (has Rss/Swap); falls back to plain
+// maps, e.g. for another user's process where smaps needs more privileges.
+pub fn read_regions(pid: &str) -> io::Result<Vec<Region>> {
+    let text = match fs::read_to_string(format!("/proc/{pid}/smaps")) {
+        Ok(t) if !t.is_empty() => t,
+        _ => fs::read_to_string(format!("/proc/{pid}/maps"))?,
+    };
+    let mut regions = parse(&text)?;
+
+    // Which file is the program itself? /proc/<pid>/exe is a symlink to it.
+    let exe = fs::read_link(format!("/proc/{pid}/exe"))
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    classify(&mut regions, &exe);
+    Ok(regions)
+}
+
+pub fn find(regions: &[Region], addr: usize) -> Option<&Region> {
+    regions.iter().find(|r| r.contains(addr))
+}
+
+// "4.0K", "132K", "2.1M", "1.0G" — like `ls -h`
+pub fn human(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+    let mut v = bytes as f64;
+    let mut unit = 0;
+    while v >= 1024.0 && unit < UNITS.len() - 1 {
+        v /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || v >= 100.0 {
+        format!("{v:.0}{}", UNITS[unit])
+    } else {
+        format!("{v:.1}{}", UNITS[unit])
+    }
+}
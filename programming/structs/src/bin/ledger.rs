@@ -0,0 +1,170 @@
+// src/bin/ledger.rs
+//
+// `ledger <file> <command>` operates on a ledger file:
+//   open <account> <owner> [overdraft]   new account, optional overdraft limit
+//   deposit <account> <amount>
+//   withdraw <account> <amount>
+//   transfer <from> <to> <amount>
+//   balances                             every account
+//   history                              every logged transaction
+//   verify                               replay from scratch and check invariants
+// Amounts are written like 12.34. With no arguments, runs a tour on a temp file.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+
+use record_log::FRAME_MARKER;
+use structs::ledger::{Cents, Ledger};
+use structs::store::{replay, LedgerFile};
+
+fn amount(s: &str) -> Result<Cents, String> {
+    Cents::parse(s).ok_or(format!("bad amount '{s}', expected e.g. 12.34"))
+}
+
+fn print_balances(ledger: &Ledger) {
+    println!("  {:<10} {:<12} {:>12} {:>10}", "account", "owner", "balance", "overdraft");
+    for a in ledger.accounts() {
+        println!("  {:<10} {:<12} {:>12} {:>10}", a.number, a.owner, a.balance.to_string(), a.overdraft_limit.to_string());
+    }
+    let total = ledger.total_balance().map_or_else(|e| e.to_string(), |t| t.to_string());
+    println!("  {:<23} {:>12}", "total", total);
+}
+
+fn verify(path: &Path) -> Result<(), String> {
+    let replayed = replay(path).map_err(|e| e.to_string())?;
+    println!("{} transactions replayed from {}", replayed.history.len(), path.display());
+    if let Some(offset) = replayed.torn_tail {
+        println!("torn last frame at byte {offset} (an interrupted write; cut off on next open)");
+    }
+    let problems = replayed.ledger.check_invariants();
+    for p in &problems {
+        println!("VIOLATION: {p}");
+    }
+    if problems.is_empty() {
+        println!("invariants hold: money is conserved, no account below its overdraft limit");
+        Ok(())
+    } else {
+        Err(format!("{} invariant violations", problems.len()))
+    }
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    let (path, command) = match args {
+        [path, command @ ..] if !command.is_empty() => (Path::new(path), command),
+        _ => return Err("expected <file> <command>".into()),
+    };
+    match command {
+        ["balances"] => print_balances(&replay(path).map_err(|e| e.to_string())?.ledger),
+        ["history"] => {
+            for l in replay(path).map_err(|e| e.to_string())?.history {
+                println!("  #{:<4} @{:<6} {}", l.seq, l.offset, l.tx);
+            }
+        }
+        ["verify"] => verify(path)?,
+        [name, ..] if !["open", "deposit", "withdraw", "transfer"].contains(name) => {
+            return Err(format!("unknown command '{name}'"));
+        }
+        _ => {
+            let mut file = LedgerFile::open(path).map_err(|e| e.to_string())?;
+            if let Some(bytes) = file.repaired_tail {
+                println!("note: cut off {bytes} bytes of an interrupted write at the end of the file");
+            }
+            let seq = match command {
+                ["open", account, owner] => file.open_account(account, owner, Cents(0)),
+                ["open", account, owner, overdraft] => file.open_account(account, owner, amount(overdraft)?),
+                ["deposit", account, a] => file.deposit(account, amount(a)?),
+                ["withdraw", account, a] => file.withdraw(account, amount(a)?),
+                ["transfer", from, to, a] => file.transfer(from, to, amount(a)?),
+                _ => return Err(format!("wrong arguments for '{}'", command.join(" "))),
+            }
+            .map_err(|e| e.to_string())?;
+            println!("ok, transaction #{seq}");
+        }
+    }
+    Ok(())
+}
+
+fn tour() -> Result<(), String> {
+    // One file per run, removed again whether or not the tour got through.
+    let path = std::env::temp_dir().join(format!("ledger-tour-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let result = tour_on(&path);
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn tour_on(path: &Path) -> Result<(), String> {
+    let err = |e: structs::store::StoreError| e.to_string();
+
+    println!("--- A ledger file: every change is one logged transaction ---");
+    let mut file = LedgerFile::open(path).map_err(err)?;
+    file.open_account("ACC-001", "Daniel", Cents(0)).map_err(err)?;
+    file.open_account("ACC-002", "Mira", Cents(50_00)).map_err(err)?;
+    file.deposit("ACC-001", amount("500")?).map_err(err)?;
+    file.withdraw("ACC-001", amount("200")?).map_err(err)?;
+    file.transfer("ACC-001", "ACC-002", amount("0.10")?).map_err(err)?;
+    file.transfer("ACC-001", "ACC-002", amount("0.20")?).map_err(err)?;
+    print_balances(file.ledger());
+    println!("(0.10 + 0.20 is exactly 0.30 here: 10 + 20 cents. As f64 it is 0.30000000000000004.)\n");
+
+    println!("--- Refused operations return errors and change nothing ---");
+    for result in [
+        file.withdraw("ACC-001", amount("300")?),
+        file.transfer("ACC-002", "ACC-001", amount("60.31")?),
+        file.transfer("ACC-001", "ACC-404", amount("1")?),
+        file.deposit("ACC-001", amount("-5")?),
+    ] {
+        println!("  {}", result.map(|seq| format!("ok #{seq}")).unwrap_or_else(|e| format!("error: {e}")));
+    }
+    println!("ACC-002 may go down to -50.00 (its overdraft limit), not further:");
+    file.transfer("ACC-002", "ACC-001", amount("50.30")?).map_err(err)?;
+    print_balances(file.ledger());
+    let before = file.ledger().clone();
+    drop(file);
+
+    println!("\n--- Replay: balances are rebuilt from the log alone ---");
+    run(&[path.to_str().ok_or("temp path is not UTF-8")?, "history"])?;
+    let replayed = replay(path).map_err(err)?;
+    let same = replayed.ledger.accounts().eq(before.accounts());
+    println!("replayed balances match the live ones: {same}");
+
+    println!("\n--- A crash in the middle of an append ---");
+    // Half a frame: the marker and part of the length, then nothing.
+    let mut f = OpenOptions::new().append(true).open(path).map_err(|e| e.to_string())?;
+    f.write_all(&FRAME_MARKER.to_le_bytes()).and_then(|_| f.write_all(&[9, 0])).map_err(|e| e.to_string())?;
+    drop(f);
+    verify(path)?;
+    let mut file = LedgerFile::open(path).map_err(err)?;
+    println!("reopened: cut off {} bytes, appending continues after the last good frame", file.repaired_tail.unwrap_or(0));
+    file.deposit("ACC-002", amount("0.30")?).map_err(err)?;
+    drop(file);
+    verify(path)?;
+
+    println!("\n--- Damage in the middle is refused, not skipped ---");
+    let mut bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let second = replay(path).map_err(err)?.history[1].offset as usize;
+    bytes[second + 20] ^= 0x01;
+    std::fs::write(path, &bytes).map_err(|e| e.to_string())?;
+    match replay(path) {
+        Ok(_) => println!("replay accepted a damaged file?!"),
+        Err(e) => println!("replay: {e}\n(skipping it would silently lose transaction #2 and every balance after it)"),
+    }
+
+    println!("\nTry it on a real file: cargo run -p structs --bin ledger -- bank.log open ACC-001 Daniel");
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = if args.is_empty() { tour() } else { run(&args) };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// src/ledger.rs
+//
+// --- Ledger ---
+// The grown-up version of BankAccount in main.rs:
+//   - money is an integer number of CENTS (i64), never f64: 0.1 + 0.2 != 0.3 in
+//     floating point, and a bank cannot lose fractions of a cent to rounding
+//   - many accounts, looked up by account number
+//   - every operation returns Result<_, LedgerError> instead of printing
+//   - every change is a Tx value; apply() is the ONLY way balances change, so
+//     replaying the same list of Tx always rebuilds the same balances
+//
+// Overdraft rule: each account has an overdraft limit (0 = none). A withdrawal
+// or outgoing transfer may take the balance down to -limit, never below.
+//
+// A transfer is checked completely (both accounts exist, enough funds, no
+// overflow) before either balance is touched, so it either happens in full or
+// not at all.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// An amount of money in cents. Displays and parses as "12.34".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Cents(pub i64);
+
+impl Cents {
+    /// Parses "12", "12.3", "12.34" or "-0.05". More than two decimals is an error,
+    /// not something to round away.
+    pub fn parse(s: &str) -> Option<Cents> {
+        let (neg, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() || frac.len() > 2 || !(whole.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())) {
+            return None;
+        }
+        let frac_cents: i64 = format!("{frac:0<2}").parse().ok()?;
+        let cents = whole.parse::<i64>().ok()?.checked_mul(100)?.checked_add(frac_cents)?;
+        Some(Cents(if neg { -cents } else { cents }))
+    }
+}
+
+impl fmt::Display for Cents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{sign}{}.{:02}", abs / 100, abs % 100)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub number: String,
+    pub owner: String,
+    pub balance: Cents,
+    pub overdraft_limit: Cents,
+}
+
+impl Account {
+    /// How much can leave the account right now. Overflow only if balance and
+    /// limit are both near i64::MAX.
+    pub fn available(&self) -> Result<Cents, LedgerError> {
+        add(self.balance, self.overdraft_limit)
+    }
+}
+
+/// One change to the ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tx {
+    Open { account: String, owner: String, overdraft_limit: Cents },
+    Deposit { account: String, amount: Cents },
+    Withdraw { account: String, amount: Cents },
+    Transfer { from: String, to: String, amount: Cents },
+}
+
+impl fmt::Display for Tx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tx::Open { account, owner, overdraft_limit } => {
+                write!(f, "open {account} for {owner} (overdraft limit {overdraft_limit})")
+            }
+            Tx::Deposit { account, amount } => write!(f, "deposit {amount} to {account}"),
+            Tx::Withdraw { account, amount } => write!(f, "withdraw {amount} from {account}"),
+            Tx::Transfer { from, to, amount } => write!(f, "transfer {amount} from {from} to {to}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerError {
+    UnknownAccount(String),
+    AccountExists(String),
+    /// Deposits, withdrawals and transfers must move a positive amount.
+    NonPositiveAmount(Cents),
+    NegativeOverdraftLimit(Cents),
+    InsufficientFunds { account: String, available: Cents, requested: Cents },
+    SameAccount(String),
+    /// A balance or total would leave the i64 range.
+    Overflow,
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::UnknownAccount(a) => write!(f, "no account {a}"),
+            LedgerError::AccountExists(a) => write!(f, "account {a} already exists"),
+            LedgerError::NonPositiveAmount(c) => write!(f, "amount must be positive, got {c}"),
+            LedgerError::NegativeOverdraftLimit(c) => write!(f, "overdraft limit cannot be negative, got {c}"),
+            LedgerError::InsufficientFunds { account, available, requested } => {
+                write!(f, "insufficient funds in {account}: {requested} requested, {available} available")
+            }
+            LedgerError::SameAccount(a) => write!(f, "cannot transfer from {a} to itself"),
+            LedgerError::Overflow => write!(f, "amount out of range"),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    accounts: BTreeMap<String, Account>,
+    /// Money that entered and left the ledger, for the conservation check.
+    deposited: Cents,
+    withdrawn: Cents,
+}
+
+fn positive(amount: Cents) -> Result<(), LedgerError> {
+    if amount.0 > 0 { Ok(()) } else { Err(LedgerError::NonPositiveAmount(amount)) }
+}
+
+fn add(a: Cents, b: Cents) -> Result<Cents, LedgerError> {
+    a.0.checked_add(b.0).map(Cents).ok_or(LedgerError::Overflow)
+}
+
+fn sub(a: Cents, b: Cents) -> Result<Cents, LedgerError> {
+    a.0.checked_sub(b.0).map(Cents).ok_or(LedgerError::Overflow)
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    pub fn account(&self, number: &str) -> Result<&Account, LedgerError> {
+        self.accounts.get(number).ok_or_else(|| LedgerError::UnknownAccount(number.to_string()))
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.values()
+    }
+
+    // New balance of `account` after `amount` leaves it, if the overdraft rule allows.
+    fn debit(&self, account: &str, amount: Cents) -> Result<Cents, LedgerError> {
+        let acc = self.account(account)?;
+        let available = acc.available()?;
+        if amount > available {
+            return Err(LedgerError::InsufficientFunds { account: account.to_string(), available, requested: amount });
+        }
+        sub(acc.balance, amount)
+    }
+
+    /// Checks `tx` against the current state without changing anything.
+    pub fn check(&self, tx: &Tx) -> Result<(), LedgerError> {
+        self.clone().apply(tx)
+    }
+
+    /// Applies `tx`, or returns why not and leaves the ledger unchanged.
+    pub fn apply(&mut self, tx: &Tx) -> Result<(), LedgerError> {
+        match tx {
+            Tx::Open { account, owner, overdraft_limit } => {
+                if self.accounts.contains_key(account) {
+                    return Err(LedgerError::AccountExists(account.clone()));
+                }
+                if overdraft_limit.0 < 0 {
+                    return Err(LedgerError::NegativeOverdraftLimit(*overdraft_limit));
+                }
+                self.accounts.insert(
+                    account.clone(),
+                    Account {
+                        number: account.clone(),
+                        owner: owner.clone(),
+                        balance: Cents(0),
+                        overdraft_limit: *overdraft_limit,
+                    },
+                );
+            }
+            Tx::Deposit { account, amount } => {
+                positive(*amount)?;
+                let balance = add(self.account(account)?.balance, *amount)?;
+                let deposited = add(self.deposited, *amount)?;
+                self.accounts.get_mut(account).expect("checked above").balance = balance;
+                self.deposited = deposited;
+            }
+            Tx::Withdraw { account, amount } => {
+                positive(*amount)?;
+                let balance = self.debit(account, *amount)?;
+                let withdrawn = add(self.withdrawn, *amount)?;
+                self.accounts.get_mut(account).expect("checked above").balance = balance;
+                self.withdrawn = withdrawn;
+            }
+            Tx::Transfer { from, to, amount } => {
+                positive(*amount)?;
+                if from == to {
+                    return Err(LedgerError::SameAccount(from.clone()));
+                }
+                // Compute both new balances first; only then write either one.
+                let from_balance = self.debit(from, *amount)?;
+                let to_balance = add(self.account(to)?.balance, *amount)?;
+                self.accounts.get_mut(from).expect("checked above").balance = from_balance;
+                self.accounts.get_mut(to).expect("checked above").balance = to_balance;
+            }
+        }
+        Ok(())
+    }
+
+    /// The sum of every balance. Each one fits in an i64, their sum need not.
+    pub fn total_balance(&self) -> Result<Cents, LedgerError> {
+        self.accounts.values().try_fold(Cents(0), |total, a| add(total, a.balance))
+    }
+
+    /// The properties every sequence of transactions must preserve. Returns the
+    /// list of violations (empty = consistent).
+    pub fn check_invariants(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        // Transfers move money around; only deposits and withdrawals change the total.
+        match (self.total_balance(), sub(self.deposited, self.withdrawn)) {
+            (Ok(total), Ok(expected)) if total == expected => {}
+            (Ok(total), Ok(_)) => problems.push(format!(
+                "total balance {total} != deposited {} - withdrawn {}",
+                self.deposited, self.withdrawn
+            )),
+            (Err(e), _) | (_, Err(e)) => problems.push(format!("cannot total the ledger: {e}")),
+        }
+        for a in self.accounts.values() {
+            if a.balance.0 < -a.overdraft_limit.0 {
+                problems.push(format!("{} is at {}, below its overdraft limit {}", a.number, a.balance, a.overdraft_limit));
+            }
+        }
+        problems
+    }
+}
@@ -0,0 +1,10 @@
+// src/lib.rs
+//
+// The bank ledger that main.rs's BankAccount grows into.
+//   ledger  accounts, integer-cent money, transactions and their rules (in memory)
+//   store   the append-only transaction log on disk, and replay from it
+//
+// `cargo run -p structs --bin ledger` walks through it.
+
+pub mod ledger;
+pub mod store;
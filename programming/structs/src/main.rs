@@ -66,5 +66,7 @@ fn main(){
 
     account.check_balance();
 
-
+    // f64 money and a printed "Insufficient funds" are fine for learning structs.
+    // src/ledger.rs does it properly: integer cents, Result errors, a transaction
+    // log (`cargo run -p structs --bin ledger`).
 }
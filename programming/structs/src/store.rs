@@ -0,0 +1,235 @@
+// src/store.rs
+//
+// --- Persistent ledger ---
+// The ledger file is a record_log: an append-only list of CRC-checked frames,
+// one TxRecord per frame. Balances are never stored; they are rebuilt by
+// replaying every transaction from the start.
+//
+// Order of operations for a change (write-ahead):
+//   1. check the Tx against the in-memory ledger (nothing changes on error)
+//   2. append it to the file as ONE frame, and sync; if either fails, truncate
+//      the file back to where the frame started
+//   3. apply it in memory
+// A crash before 2 finishes leaves at most a torn last frame, which replay
+// drops: the transaction did not happen, on disk or in memory.
+//
+// Replay is strict everywhere else. A damaged frame in the middle of the file,
+// or a gap in the sequence numbers, means transactions are missing, and a
+// ledger with missing transactions must not be trusted.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use record_codec::{record, DecodeError, Le, Utf8, U8};
+use record_log::{Corruption, Entry, LogError, Reader, Writer};
+
+use crate::ledger::{Cents, Ledger, LedgerError, Tx};
+
+record! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TxRecord {
+        magic: u32 as Le = 0x5844_474C, // "LGDX" when written little-endian
+        version: u8 as Le = 1,
+        fields {
+            seq: u64 as Le,
+            kind: u8 as Le,                   // 1 open, 2 deposit, 3 withdraw, 4 transfer
+            account: String as Utf8<U8>,      // the account, or the source of a transfer
+            other: String as Utf8<U8>,        // owner (open), destination (transfer), else ""
+            cents: i64 as Le,                 // amount, or the overdraft limit for open
+        }
+    }
+}
+
+impl TxRecord {
+    pub fn from_tx(seq: u64, tx: &Tx) -> TxRecord {
+        let (kind, account, other, cents) = match tx {
+            Tx::Open { account, owner, overdraft_limit } => (1, account, owner.as_str(), *overdraft_limit),
+            Tx::Deposit { account, amount } => (2, account, "", *amount),
+            Tx::Withdraw { account, amount } => (3, account, "", *amount),
+            Tx::Transfer { from, to, amount } => (4, from, to.as_str(), *amount),
+        };
+        TxRecord { seq, kind, account: account.clone(), other: other.to_string(), cents: cents.0 }
+    }
+
+    pub fn to_tx(&self) -> Option<Tx> {
+        let (account, other, amount) = (self.account.clone(), self.other.clone(), Cents(self.cents));
+        Some(match self.kind {
+            1 => Tx::Open { account, owner: other, overdraft_limit: amount },
+            2 => Tx::Deposit { account, amount },
+            3 => Tx::Withdraw { account, amount },
+            4 => Tx::Transfer { from: account, to: other, amount },
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Log(LogError),
+    /// The ledger refused the transaction; nothing was written.
+    Rejected(LedgerError),
+    /// A damaged stretch before the end of the file.
+    Corrupt { offset: u64, reason: Corruption },
+    Decode { offset: u64, error: DecodeError },
+    UnknownKind { seq: u64, kind: u8 },
+    SequenceGap { expected: u64, found: u64 },
+    /// A logged transaction does not apply during replay: the file was edited
+    /// or written by something that skipped the checks.
+    Replay { seq: u64, error: LedgerError },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Log(e) => write!(f, "{e}"),
+            StoreError::Rejected(e) => write!(f, "{e}"),
+            StoreError::Corrupt { offset, reason } => write!(f, "ledger file damaged at byte {offset}: {reason}"),
+            StoreError::Decode { offset, error } => write!(f, "bad transaction record at byte {offset}: {error}"),
+            StoreError::UnknownKind { seq, kind } => write!(f, "transaction #{seq} has unknown kind {kind}"),
+            StoreError::SequenceGap { expected, found } => {
+                write!(f, "transactions missing: expected #{expected}, found #{found}")
+            }
+            StoreError::Replay { seq, error } => write!(f, "transaction #{seq} does not replay: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<LogError> for StoreError {
+    fn from(e: LogError) -> Self {
+        StoreError::Log(e)
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        StoreError::Log(LogError::Io(e))
+    }
+}
+
+/// One replayed transaction and where it was in the file.
+#[derive(Debug, Clone)]
+pub struct Logged {
+    pub seq: u64,
+    pub offset: u64,
+    pub tx: Tx,
+}
+
+/// The result of reading a ledger file from the start.
+#[derive(Debug, Clone)]
+pub struct Replayed {
+    pub ledger: Ledger,
+    pub history: Vec<Logged>,
+    /// Offset of a torn last frame, if the file ends in one.
+    pub torn_tail: Option<u64>,
+}
+
+/// Rebuilds the ledger from `path`. A missing file is an empty ledger.
+pub fn replay(path: impl AsRef<Path>) -> Result<Replayed, StoreError> {
+    let mut replayed = Replayed { ledger: Ledger::new(), history: Vec::new(), torn_tail: None };
+    if !path.as_ref().exists() {
+        return Ok(replayed);
+    }
+    let reader = Reader::open(path)?;
+    reader.expect_schema::<TxRecord>()?;
+    let mut torn = None;
+    for entry in reader {
+        match entry? {
+            Entry::Frame { offset, payload } => {
+                if let Some((offset, reason)) = torn.take() {
+                    // A bad length can look like a torn frame; valid frames after
+                    // it mean the damage was in the middle after all.
+                    return Err(StoreError::Corrupt { offset, reason });
+                }
+                let rec = TxRecord::decode(&payload).map_err(|error| StoreError::Decode { offset, error })?;
+                let expected = replayed.history.len() as u64 + 1;
+                if rec.seq != expected {
+                    return Err(StoreError::SequenceGap { expected, found: rec.seq });
+                }
+                let tx = rec.to_tx().ok_or(StoreError::UnknownKind { seq: rec.seq, kind: rec.kind })?;
+                replayed.ledger.apply(&tx).map_err(|error| StoreError::Replay { seq: rec.seq, error })?;
+                replayed.history.push(Logged { seq: rec.seq, offset, tx });
+            }
+            Entry::Corrupt { offset, reason: reason @ Corruption::TornTail { .. }, .. } => torn = Some((offset, reason)),
+            Entry::Corrupt { offset, reason, .. } => return Err(StoreError::Corrupt { offset, reason }),
+        }
+    }
+    replayed.torn_tail = torn.map(|(offset, _)| offset);
+    Ok(replayed)
+}
+
+/// A ledger backed by a file. Every successful change is on disk before it
+/// is visible in memory.
+pub struct LedgerFile {
+    path: PathBuf,
+    writer: Writer<TxRecord>,
+    ledger: Ledger,
+    next_seq: u64,
+    /// Bytes of a torn last frame that were cut off when opening.
+    pub repaired_tail: Option<u64>,
+}
+
+impl LedgerFile {
+    /// Replays `path` (creating it if needed) and gets ready to append. A torn
+    /// last frame is truncated away first, so new frames follow valid ones.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let path = path.as_ref().to_path_buf();
+        let replayed = replay(&path)?;
+        let mut repaired_tail = None;
+        if let Some(offset) = replayed.torn_tail {
+            let file = OpenOptions::new().write(true).open(&path)?;
+            repaired_tail = Some(file.metadata()?.len() - offset);
+            file.set_len(offset)?;
+        }
+        Ok(LedgerFile {
+            writer: Writer::open(&path)?,
+            path,
+            next_seq: replayed.history.len() as u64 + 1,
+            ledger: replayed.ledger,
+            repaired_tail,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    /// Checks, logs and applies `tx`. Returns its sequence number.
+    pub fn execute(&mut self, tx: Tx) -> Result<u64, StoreError> {
+        self.ledger.check(&tx).map_err(StoreError::Rejected)?;
+        let seq = self.next_seq;
+        let end = fs::metadata(&self.path)?.len();
+        if let Err(e) = self.writer.append(&TxRecord::from_tx(seq, &tx)).and_then(|_| self.writer.sync()) {
+            // The frame may be in the file (whole, or torn) without being durable.
+            // Cut it off, so the next transaction can take `seq` at the same place
+            // instead of following a frame that memory never applied.
+            OpenOptions::new().write(true).open(&self.path)?.set_len(end)?;
+            return Err(e.into());
+        }
+        self.ledger.apply(&tx).expect("checked before logging");
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    pub fn open_account(&mut self, account: &str, owner: &str, overdraft_limit: Cents) -> Result<u64, StoreError> {
+        self.execute(Tx::Open { account: account.into(), owner: owner.into(), overdraft_limit })
+    }
+
+    pub fn deposit(&mut self, account: &str, amount: Cents) -> Result<u64, StoreError> {
+        self.execute(Tx::Deposit { account: account.into(), amount })
+    }
+
+    pub fn withdraw(&mut self, account: &str, amount: Cents) -> Result<u64, StoreError> {
+        self.execute(Tx::Withdraw { account: account.into(), amount })
+    }
+
+    pub fn transfer(&mut self, from: &str, to: &str, amount: Cents) -> Result<u64, StoreError> {
+        self.execute(Tx::Transfer { from: from.into(), to: to.into(), amount })
+    }
+}
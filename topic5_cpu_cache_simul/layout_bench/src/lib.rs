@@ -0,0 +1,389 @@
+// src/lib.rs
+//
+// The same particle records stored three ways:
+//
+//   AoS    array of structs     [x y z vx vy vz m id][x y z vx vy vz m id]...
+//   SoA    struct of arrays     [x x x x ...] [y y y y ...] ... [id id id ...]
+//   AoSoA  tiles of 16 records  [x*16 y*16 ... id*16][next tile]...
+//
+// A cache line is 64 bytes. A loop that only needs `x` pulls 2 useful records'
+// worth of x per line from AoS (the other 56 bytes are fields it never reads),
+// but 16 from SoA. A random lookup that needs a whole record is one line in
+// AoS (two if the record straddles a line boundary), and 8 different lines in
+// SoA. AoSoA sits in between: a tile holds 16 records, so each field of a tile
+// fills exactly one cache line, and a whole record is one 512-byte tile away.
+//
+// Every layout implements the same four workloads (`Workload`), and can also
+// report the address of any field, so the same access pattern can be replayed
+// through the cache simulator to count misses instead of guessing.
+
+use std::hint::black_box;
+
+/// 32 bytes, no padding.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub vz: f32,
+    pub mass: f32,
+    pub id: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    X,
+    Y,
+    Z,
+    Vx,
+    Vy,
+    Vz,
+    Mass,
+    Id,
+}
+
+pub const ALL_FIELDS: [Field; 8] = [Field::X, Field::Y, Field::Z, Field::Vx, Field::Vy, Field::Vz, Field::Mass, Field::Id];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Sum of x: one field out of eight.
+    ScanX,
+    /// Mean position: three fields.
+    Centroid,
+    /// x += vx * dt for all three axes, with gravity on vz: six fields, read and written.
+    Integrate,
+    /// Read whole records at pseudo-random indices.
+    RandomLookup,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 4] = [Workload::ScanX, Workload::Centroid, Workload::Integrate, Workload::RandomLookup];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::ScanX => "scan x",
+            Workload::Centroid => "centroid",
+            Workload::Integrate => "integrate",
+            Workload::RandomLookup => "random lookup",
+        }
+    }
+
+    /// The fields touched per record, in the order the kernels touch them.
+    pub fn fields(self) -> &'static [Field] {
+        match self {
+            Workload::ScanX => &[Field::X],
+            Workload::Centroid => &[Field::X, Field::Y, Field::Z],
+            Workload::Integrate => &[Field::Vz, Field::X, Field::Vx, Field::Y, Field::Vy, Field::Z],
+            Workload::RandomLookup => &ALL_FIELDS,
+        }
+    }
+}
+
+pub const DT: f32 = 0.01;
+const GRAVITY: f32 = -9.81;
+
+/// Pseudo-random record indices, the same LCG as cache_behavior.
+pub fn random_indices(n: usize, count: usize) -> Vec<usize> {
+    let mut idx = 0usize;
+    (0..count)
+        .map(|_| {
+            idx = (idx.wrapping_mul(1664525).wrapping_add(1013904223)) % n;
+            idx
+        })
+        .collect()
+}
+
+/// Deterministic sample data.
+pub fn particles(n: usize) -> Vec<Particle> {
+    (0..n)
+        .map(|i| {
+            let f = i as f32;
+            Particle {
+                x: f * 0.5,
+                y: 100.0 - f * 0.25,
+                z: (i % 97) as f32,
+                vx: 1.0,
+                vy: -0.5,
+                vz: (i % 7) as f32,
+                mass: 1.0 + (i % 3) as f32,
+                id: i as u32,
+            }
+        })
+        .collect()
+}
+
+pub trait Layout {
+    const NAME: &'static str;
+    fn from_particles(ps: &[Particle]) -> Self;
+    fn len(&self) -> usize;
+    fn get(&self, i: usize) -> Particle;
+    /// Address of field `f` of record `i`, for replaying through the simulator.
+    fn addr(&self, i: usize, f: Field) -> usize;
+
+    fn sum_x(&self) -> f32;
+    fn centroid(&self) -> [f32; 3];
+    /// One Euler step under gravity along z. Touches vz, x, y and z only: vx
+    /// and vy stay constant without a force on them, and mass and id are not
+    /// part of the motion.
+    fn integrate(&mut self, dt: f32);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sum of masses and ids of the records at `indices`, reading all fields.
+    fn lookup(&self, indices: &[usize]) -> f32 {
+        indices.iter().fold(0.0, |acc, &i| {
+            let p = black_box(self.get(i));
+            acc + p.x + p.y + p.z + p.vx + p.vy + p.vz + p.mass + p.id as f32
+        })
+    }
+
+    /// Runs `w` once and returns a value that depends on every read, so the
+    /// optimizer cannot drop the work.
+    fn run(&mut self, w: Workload, indices: &[usize]) -> f32 {
+        match w {
+            Workload::ScanX => self.sum_x(),
+            Workload::Centroid => self.centroid().iter().sum(),
+            Workload::Integrate => {
+                self.integrate(DT);
+                self.get(self.len() / 2).x
+            }
+            Workload::RandomLookup => self.lookup(indices),
+        }
+    }
+}
+
+/// Array of structs.
+pub struct Aos(pub Vec<Particle>);
+
+impl Layout for Aos {
+    const NAME: &'static str = "AoS";
+
+    fn from_particles(ps: &[Particle]) -> Self {
+        Aos(ps.to_vec())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get(&self, i: usize) -> Particle {
+        self.0[i]
+    }
+
+    fn addr(&self, i: usize, f: Field) -> usize {
+        let p = &self.0[i];
+        match f {
+            Field::X => &p.x as *const f32 as usize,
+            Field::Y => &p.y as *const f32 as usize,
+            Field::Z => &p.z as *const f32 as usize,
+            Field::Vx => &p.vx as *const f32 as usize,
+            Field::Vy => &p.vy as *const f32 as usize,
+            Field::Vz => &p.vz as *const f32 as usize,
+            Field::Mass => &p.mass as *const f32 as usize,
+            Field::Id => &p.id as *const u32 as usize,
+        }
+    }
+
+    fn sum_x(&self) -> f32 {
+        self.0.iter().map(|p| p.x).sum()
+    }
+
+    fn centroid(&self) -> [f32; 3] {
+        let n = self.0.len() as f32;
+        let s = self.0.iter().fold([0.0; 3], |s, p| [s[0] + p.x, s[1] + p.y, s[2] + p.z]);
+        [s[0] / n, s[1] / n, s[2] / n]
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        for p in &mut self.0 {
+            p.vz += GRAVITY * dt;
+            p.x += p.vx * dt;
+            p.y += p.vy * dt;
+            p.z += p.vz * dt;
+        }
+    }
+}
+
+/// Struct of arrays: one Vec per field.
+pub struct Soa {
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub z: Vec<f32>,
+    pub vx: Vec<f32>,
+    pub vy: Vec<f32>,
+    pub vz: Vec<f32>,
+    pub mass: Vec<f32>,
+    pub id: Vec<u32>,
+}
+
+impl Layout for Soa {
+    const NAME: &'static str = "SoA";
+
+    fn from_particles(ps: &[Particle]) -> Self {
+        Soa {
+            x: ps.iter().map(|p| p.x).collect(),
+            y: ps.iter().map(|p| p.y).collect(),
+            z: ps.iter().map(|p| p.z).collect(),
+            vx: ps.iter().map(|p| p.vx).collect(),
+            vy: ps.iter().map(|p| p.vy).collect(),
+            vz: ps.iter().map(|p| p.vz).collect(),
+            mass: ps.iter().map(|p| p.mass).collect(),
+            id: ps.iter().map(|p| p.id).collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn get(&self, i: usize) -> Particle {
+        Particle {
+            x: self.x[i],
+            y: self.y[i],
+            z: self.z[i],
+            vx: self.vx[i],
+            vy: self.vy[i],
+            vz: self.vz[i],
+            mass: self.mass[i],
+            id: self.id[i],
+        }
+    }
+
+    fn addr(&self, i: usize, f: Field) -> usize {
+        match f {
+            Field::X => &self.x[i] as *const f32 as usize,
+            Field::Y => &self.y[i] as *const f32 as usize,
+            Field::Z => &self.z[i] as *const f32 as usize,
+            Field::Vx => &self.vx[i] as *const f32 as usize,
+            Field::Vy => &self.vy[i] as *const f32 as usize,
+            Field::Vz => &self.vz[i] as *const f32 as usize,
+            Field::Mass => &self.mass[i] as *const f32 as usize,
+            Field::Id => &self.id[i] as *const u32 as usize,
+        }
+    }
+
+    fn sum_x(&self) -> f32 {
+        self.x.iter().sum()
+    }
+
+    fn centroid(&self) -> [f32; 3] {
+        let n = self.x.len() as f32;
+        let s = self.x.iter().zip(&self.y).zip(&self.z).fold([0.0; 3], |s, ((x, y), z)| [s[0] + x, s[1] + y, s[2] + z]);
+        [s[0] / n, s[1] / n, s[2] / n]
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        for i in 0..self.x.len() {
+            self.vz[i] += GRAVITY * dt;
+            self.x[i] += self.vx[i] * dt;
+            self.y[i] += self.vy[i] * dt;
+            self.z[i] += self.vz[i] * dt;
+        }
+    }
+}
+
+pub const LANES: usize = 16;
+
+/// 16 records, field by field: 512 bytes, one 64-byte line per field.
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct Tile {
+    pub x: [f32; LANES],
+    pub y: [f32; LANES],
+    pub z: [f32; LANES],
+    pub vx: [f32; LANES],
+    pub vy: [f32; LANES],
+    pub vz: [f32; LANES],
+    pub mass: [f32; LANES],
+    pub id: [u32; LANES],
+}
+
+/// Array of struct-of-arrays tiles. The last tile is padded with zeroed
+/// records, which contribute nothing to the sums.
+pub struct Aosoa {
+    pub tiles: Vec<Tile>,
+    pub len: usize,
+}
+
+impl Layout for Aosoa {
+    const NAME: &'static str = "AoSoA";
+
+    fn from_particles(ps: &[Particle]) -> Self {
+        let mut tiles = vec![Tile::default(); ps.len().div_ceil(LANES)];
+        for (i, p) in ps.iter().enumerate() {
+            let (t, l) = (&mut tiles[i / LANES], i % LANES);
+            t.x[l] = p.x;
+            t.y[l] = p.y;
+            t.z[l] = p.z;
+            t.vx[l] = p.vx;
+            t.vy[l] = p.vy;
+            t.vz[l] = p.vz;
+            t.mass[l] = p.mass;
+            t.id[l] = p.id;
+        }
+        Aosoa { tiles, len: ps.len() }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, i: usize) -> Particle {
+        let (t, l) = (&self.tiles[i / LANES], i % LANES);
+        Particle { x: t.x[l], y: t.y[l], z: t.z[l], vx: t.vx[l], vy: t.vy[l], vz: t.vz[l], mass: t.mass[l], id: t.id[l] }
+    }
+
+    fn addr(&self, i: usize, f: Field) -> usize {
+        let (t, l) = (&self.tiles[i / LANES], i % LANES);
+        match f {
+            Field::X => &t.x[l] as *const f32 as usize,
+            Field::Y => &t.y[l] as *const f32 as usize,
+            Field::Z => &t.z[l] as *const f32 as usize,
+            Field::Vx => &t.vx[l] as *const f32 as usize,
+            Field::Vy => &t.vy[l] as *const f32 as usize,
+            Field::Vz => &t.vz[l] as *const f32 as usize,
+            Field::Mass => &t.mass[l] as *const f32 as usize,
+            Field::Id => &t.id[l] as *const u32 as usize,
+        }
+    }
+
+    fn sum_x(&self) -> f32 {
+        self.tiles.iter().map(|t| t.x.iter().sum::<f32>()).sum()
+    }
+
+    fn centroid(&self) -> [f32; 3] {
+        let n = self.len as f32;
+        let s = self.tiles.iter().fold([0.0; 3], |s, t| {
+            [s[0] + t.x.iter().sum::<f32>(), s[1] + t.y.iter().sum::<f32>(), s[2] + t.z.iter().sum::<f32>()]
+        });
+        [s[0] / n, s[1] / n, s[2] / n]
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        let len = self.len;
+        for (i, t) in self.tiles.iter_mut().enumerate() {
+            // The last tile's padding must stay zero, or the sums above count it.
+            for l in 0..LANES.min(len - i * LANES) {
+                t.vz[l] += GRAVITY * dt;
+                t.x[l] += t.vx[l] * dt;
+                t.y[l] += t.vy[l] * dt;
+                t.z[l] += t.vz[l] * dt;
+            }
+        }
+    }
+}
+
+/// The addresses workload `w` touches, in order: per record for the scans,
+/// per index for the lookups. Reads and writes of the same field count once.
+pub fn trace<'a, L: Layout>(layout: &'a L, w: Workload, indices: &'a [usize]) -> Box<dyn Iterator<Item = usize> + 'a> {
+    let fields = w.fields();
+    match w {
+        Workload::RandomLookup => Box::new(indices.iter().flat_map(move |&i| fields.iter().map(move |&f| layout.addr(i, f)))),
+        _ => Box::new((0..layout.len()).flat_map(move |i| fields.iter().map(move |&f| layout.addr(i, f)))),
+    }
+}
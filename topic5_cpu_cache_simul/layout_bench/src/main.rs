@@ -0,0 +1,173 @@
+// src/main.rs
+//
+// `layout_bench [--sizes N,N,..] [--reps R] [--cache SIZE:LINE:WAYS] [--no-sim]`
+//
+// For every size, runs the four workloads on AoS, SoA and AoSoA and prints:
+//   M rec/s      records processed per second (best of R runs; use --release)
+//   misses/rec   simulated cache misses per record, warm cache (second pass)
+//   line use     bytes the workload needed / bytes the misses brought in
+//
+// Defaults: sizes 4096,65536,262144, 5 reps, a 32k:64:8 L1-like cache.
+
+use std::hint::black_box;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use layout_bench::{particles, random_indices, trace, Aos, Aosoa, Layout, Soa, LANES, Workload};
+use topic5_cpu_cache_simul::{Cache, CacheConfig, Stats};
+
+struct Options {
+    sizes: Vec<usize>,
+    reps: usize,
+    cache: CacheConfig,
+    simulate: bool,
+}
+
+struct Row {
+    layout: &'static str,
+    mrec_per_s: f64,
+    sim: Option<Stats>,
+}
+
+fn simulate<L: Layout>(layout: &L, w: Workload, indices: &[usize], config: CacheConfig) -> Stats {
+    let mut cache = Cache::new(config);
+    for pass in 0..2 {
+        if pass == 1 {
+            cache.reset_stats();
+        }
+        for addr in trace(layout, w, indices) {
+            cache.access(addr);
+        }
+    }
+    cache.stats()
+}
+
+fn measure<L: Layout>(n: usize, w: Workload, indices: &[usize], opts: &Options) -> Row {
+    let mut layout = L::from_particles(&particles(n));
+    let mut best = f64::INFINITY;
+    for _ in 0..opts.reps.max(1) {
+        let start = Instant::now();
+        black_box(layout.run(w, black_box(indices)));
+        best = best.min(start.elapsed().as_secs_f64());
+    }
+    let records = if w == Workload::RandomLookup { indices.len() } else { n };
+    let sim = opts.simulate.then(|| simulate(&layout, w, indices, opts.cache));
+    Row { layout: L::NAME, mrec_per_s: records as f64 / best / 1e6, sim }
+}
+
+fn print_rows(w: Workload, rows: &[Row], records: usize, line_bytes: usize) {
+    let best = rows.iter().map(|r| r.mrec_per_s).fold(0.0, f64::max);
+    for (i, r) in rows.iter().enumerate() {
+        let name = if i == 0 { w.name() } else { "" };
+        let mark = if r.mrec_per_s == best { " *" } else { "" };
+        let sim = match r.sim {
+            Some(s) if s.misses() == 0 => format!("{:>10.3} {:>9}", 0.0, "-"),
+            Some(s) => {
+                let needed = (records * w.fields().len() * 4) as f64;
+                let fetched = (s.misses() as usize * line_bytes) as f64;
+                format!("{:>10.3} {:>8.0}%", s.misses() as f64 / records as f64, 100.0 * needed.min(fetched) / fetched)
+            }
+            None => String::new(),
+        };
+        println!("  {name:<14} {:<6} {:>10.1}{mark:<2} {sim}", r.layout, r.mrec_per_s);
+    }
+}
+
+fn check_agreement() -> Result<(), String> {
+    let n = 1000;
+    let ps = particles(n);
+    let indices = random_indices(n, n);
+    let (mut aos, mut soa, mut aosoa) = (Aos::from_particles(&ps), Soa::from_particles(&ps), Aosoa::from_particles(&ps));
+    // Integrate first: the reads after it must see the moved records, and only
+    // those (n is not a multiple of the AoSoA tile, so its last tile is padded).
+    for w in [Workload::Integrate, Workload::ScanX, Workload::Centroid, Workload::RandomLookup] {
+        let r = [aos.run(w, &indices), soa.run(w, &indices), aosoa.run(w, &indices)];
+        // Summation order differs between layouts, so allow rounding noise.
+        if r.iter().any(|v| (v - r[0]).abs() > 1e-4 * r[0].abs().max(1.0)) {
+            return Err(format!("layouts disagree on {}: {r:?}", w.name()));
+        }
+    }
+    if (0..n).any(|i| aos.get(i) != soa.get(i) || aos.get(i) != aosoa.get(i)) {
+        return Err("layouts hold different records after integrate".into());
+    }
+    // Padding is not records: nothing may move it, or sums over whole tiles go wrong.
+    let pad = aosoa.tiles.last().map_or(0, |t| {
+        let used = n - (aosoa.tiles.len() - 1) * LANES;
+        (used..LANES).filter(|&l| [t.x[l], t.y[l], t.z[l], t.vx[l], t.vy[l], t.vz[l], t.mass[l]] != [0.0; 7] || t.id[l] != 0).count()
+    });
+    if pad > 0 {
+        return Err(format!("{pad} AoSoA padding lanes changed"));
+    }
+    Ok(())
+}
+
+fn run(opts: &Options) -> Result<(), String> {
+    check_agreement()?;
+    if cfg!(debug_assertions) {
+        println!("note: debug build, timings are not meaningful; use `cargo run --release -p layout_bench`\n");
+    }
+    for &n in &opts.sizes {
+        let indices = random_indices(n, n);
+        print!("n = {n} records, {} KiB as AoS", n * size_of::<layout_bench::Particle>() / 1024);
+        if opts.simulate {
+            print!("; simulated cache {}", opts.cache);
+        }
+        println!();
+        println!("  {:<14} {:<6} {:>10}   {:>10} {:>9}", "workload", "layout", "M rec/s", "misses/rec", "line use");
+        for w in Workload::ALL {
+            let rows = [
+                measure::<Aos>(n, w, &indices, opts),
+                measure::<Soa>(n, w, &indices, opts),
+                measure::<Aosoa>(n, w, &indices, opts),
+            ];
+            let records = if w == Workload::RandomLookup { indices.len() } else { n };
+            print_rows(w, &rows, records, opts.cache.line_bytes);
+        }
+        println!();
+    }
+    println!("* = fastest. Scans that need few fields favour SoA and AoSoA (every byte of a");
+    println!("fetched line is used); whole-record random lookups favour AoS (one line per record,");
+    println!("SoA needs one per field). AoSoA keeps most of both.");
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut opts = Options { sizes: vec![4096, 65536, 1 << 18], reps: 5, cache: CacheConfig::L1D, simulate: true };
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--sizes" => {
+                opts.sizes = value()?
+                    .split(',')
+                    .map(|s| s.replace('_', "").parse::<usize>().ok().filter(|&n| n > 0))
+                    .collect::<Option<_>>()
+                    .ok_or("--sizes takes positive numbers like 4096,65536")?;
+            }
+            "--reps" => opts.reps = value()?.parse().map_err(|_| "--reps takes a number")?,
+            "--cache" => {
+                let v = value()?;
+                opts.cache = CacheConfig::parse(v).ok_or(format!(
+                    "bad cache '{v}', expected e.g. 32k:64:8 (powers of two, at most {}m and {} lines)",
+                    CacheConfig::MAX_SIZE_BYTES >> 20,
+                    CacheConfig::MAX_LINES
+                ))?;
+            }
+            "--no-sim" => opts.simulate = false,
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(opts)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match parse_args(&args).and_then(|opts| run(&opts)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            eprintln!("usage: layout_bench [--sizes N,N,..] [--reps R] [--cache SIZE:LINE:WAYS] [--no-sim]");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,398 @@
+// src/lib.rs
+//
+// Byte-level layout maps for structs and enums.
+//
+// size_of/align_of say HOW BIG a type is; they do not say where the bytes go.
+// Wrap a type definition in `layout_struct!` or `layout_enum!` and it gets an
+// `Inspect` impl that measures the real layout the compiler chose:
+//
+//   layout_struct! {
+//       #[derive(Debug)]
+//       struct Simple { a: u8, b: u32 }
+//   }
+//   println!("{}", Simple::layout());      // offsets, padding, a one-char-per-byte map
+//   for l in Simple::reprs() { ... }       // the same fields as repr(Rust), repr(C), repr(C, packed)
+//
+// Struct offsets come from `offset_of!`. Enum variants cannot be named in
+// `offset_of!` on stable Rust, so each variant is built from Default values and
+// its fields located by address. Nothing here reads padding or tag bytes
+// (that would be reading uninitialized memory); the tag location is inferred
+// from the bytes that no variant stores data in.
+//
+// The map uses one letter per byte: A = first field, B = second, ... and '.'
+// for padding. In an enum map '.' is a byte only OTHER variants use, and '#' a
+// byte no variant stores data in: the tag, plus padding.
+
+use std::fmt;
+use std::ops::Range;
+
+#[doc(hidden)]
+pub use std::mem::{align_of, offset_of, size_of};
+
+/// One field: where it starts and how much room it takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    pub align: usize,
+}
+
+impl Field {
+    pub fn bytes(&self) -> Range<usize> {
+        self.offset..self.offset + self.size
+    }
+}
+
+fn letter(i: usize) -> char {
+    (b'A' + (i % 26) as u8) as char
+}
+
+/// Byte ranges in 0..size that none of `fields` covers, merged.
+fn uncovered<'a>(size: usize, fields: impl IntoIterator<Item = &'a Field>) -> Vec<Range<usize>> {
+    let mut used = vec![false; size];
+    for f in fields {
+        used[f.bytes()].iter_mut().for_each(|u| *u = true);
+    }
+    let mut out: Vec<Range<usize>> = Vec::new();
+    for (i, _) in used.iter().enumerate().filter(|(_, u)| !**u) {
+        match out.last_mut() {
+            Some(r) if r.end == i => r.end = i + 1,
+            _ => out.push(i..i + 1),
+        }
+    }
+    out
+}
+
+fn ranges(rs: &[Range<usize>]) -> String {
+    rs.iter().map(|r| format!("{}..{}", r.start, r.end)).collect::<Vec<_>>().join(", ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: &'static str,
+    /// "as declared", "repr(Rust)", "repr(C)" or "repr(C, packed)".
+    pub repr: &'static str,
+    pub size: usize,
+    pub align: usize,
+    /// In declaration order.
+    pub fields: Vec<Field>,
+}
+
+impl StructLayout {
+    pub fn padding(&self) -> Vec<Range<usize>> {
+        uncovered(self.size, &self.fields)
+    }
+
+    pub fn padding_bytes(&self) -> usize {
+        self.padding().iter().map(|r| r.len()).sum()
+    }
+
+    /// One char per byte: the field's letter, or '.' for padding.
+    pub fn map(&self) -> String {
+        let mut map = vec!['.'; self.size];
+        for (i, f) in self.fields.iter().enumerate() {
+            map[f.bytes()].iter_mut().for_each(|c| *c = letter(i));
+        }
+        map.into_iter().collect()
+    }
+
+    /// The same fields laid out with repr(C) rules in a given order.
+    pub fn c_layout(&self, order: &[Field]) -> StructLayout {
+        let mut offset = 0usize;
+        let mut align = 1;
+        let mut fields = Vec::new();
+        for f in order {
+            offset = offset.next_multiple_of(f.align);
+            align = align.max(f.align);
+            fields.push(Field { offset, ..*f });
+            offset += f.size;
+        }
+        StructLayout { name: self.name, repr: "repr(C)", size: offset.next_multiple_of(align), align, fields }
+    }
+
+    /// Field order that minimizes repr(C) padding: largest alignment first
+    /// (what the compiler already does for repr(Rust)). None if the declared
+    /// order is already as small.
+    pub fn suggest_order(&self) -> Option<StructLayout> {
+        let mut order = self.fields.clone();
+        order.sort_by(|a, b| b.align.cmp(&a.align).then(b.size.cmp(&a.size)));
+        let suggested = self.c_layout(&order);
+        (suggested.size < self.c_layout(&self.fields).size).then_some(suggested)
+    }
+}
+
+impl fmt::Display for StructLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}: size {}, align {}", self.name, self.repr, self.size, self.align)?;
+        let mut by_offset: Vec<(usize, &Field)> = self.fields.iter().enumerate().collect();
+        by_offset.sort_by_key(|(_, fd)| fd.offset);
+        let mut rows: Vec<(usize, String)> = by_offset
+            .iter()
+            .map(|(i, fd)| (fd.offset, format!("{:>6} {:>5}  {} {}: {}", fd.offset, fd.size, letter(*i), fd.name, fd.ty)))
+            .collect();
+        rows.extend(self.padding().into_iter().map(|r| (r.start, format!("{:>6} {:>5}  . (padding)", r.start, r.len()))));
+        rows.sort_by_key(|(offset, _)| *offset);
+        writeln!(f, "  offset  size  field")?;
+        for (_, row) in rows {
+            writeln!(f, "  {row}")?;
+        }
+        write!(f, "  map [{}]  {} of {} bytes are padding", self.map(), self.padding_bytes(), self.size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantLayout {
+    pub name: &'static str,
+    /// Tuple fields, named "0", "1", ...
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumLayout {
+    pub name: &'static str,
+    pub repr: &'static str,
+    pub size: usize,
+    pub align: usize,
+    pub variants: Vec<VariantLayout>,
+}
+
+impl EnumLayout {
+    /// Bytes that no variant keeps data in. The tag must be somewhere in here,
+    /// unless it is hidden in a niche.
+    pub fn dataless(&self) -> Vec<Range<usize>> {
+        uncovered(self.size, self.variants.iter().flat_map(|v| &v.fields))
+    }
+
+    /// No room for a separate tag: the discriminant is encoded in invalid
+    /// values of some field (a null pointer, a bool of 2, a char past U+10FFFF...).
+    pub fn uses_niche(&self) -> bool {
+        self.variants.len() > 1 && self.dataless().is_empty()
+    }
+
+    pub fn map(&self, variant: &VariantLayout) -> String {
+        let mut map = vec!['#'; self.size];
+        for v in &self.variants {
+            for f in &v.fields {
+                map[f.bytes()].iter_mut().for_each(|c| *c = '.');
+            }
+        }
+        for (i, f) in variant.fields.iter().enumerate() {
+            map[f.bytes()].iter_mut().for_each(|c| *c = letter(i));
+        }
+        map.into_iter().collect()
+    }
+}
+
+impl fmt::Display for EnumLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}: size {}, align {}", self.name, self.repr, self.size, self.align)?;
+        let width = self.variants.iter().map(|v| v.name.len()).max().unwrap_or(0);
+        for v in &self.variants {
+            let fields: Vec<String> =
+                v.fields.iter().enumerate().map(|(i, fd)| format!("{} {}: {} @{}", letter(i), fd.name, fd.ty, fd.offset)).collect();
+            let line = format!("  {:<width$}  [{}]  {}", v.name, self.map(v), fields.join(", "));
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        if self.uses_niche() {
+            write!(f, "  no separate tag: the variant is encoded in a niche (invalid values of a field)")
+        } else {
+            write!(f, "  tag: somewhere in bytes {} ('#', no variant stores data there)", ranges(&self.dataless()))
+        }
+    }
+}
+
+/// Implemented by `layout_struct!` and `layout_enum!`.
+pub trait Inspect {
+    type Layout: fmt::Display;
+    /// The layout of the type exactly as declared.
+    fn layout() -> Self::Layout;
+    /// The same fields under other representations, for comparison.
+    fn reprs() -> Vec<Self::Layout>;
+}
+
+/// `Option<T>` vs `T`: same size means None found a niche in T.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Niche {
+    pub ty: String,
+    pub size: usize,
+    pub option_size: usize,
+}
+
+pub fn niche<T>() -> Niche {
+    Niche { ty: short_type_name::<T>(), size: size_of::<T>(), option_size: size_of::<Option<T>>() }
+}
+
+/// `type_name` without module paths: "alloc::boxed::Box<u8>" -> "Box<u8>".
+fn short_type_name<T>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut out = String::new();
+    let mut segment = String::new();
+    let mut chars = full.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            out.push_str(&segment);
+            segment.clear();
+            out.push(c);
+        }
+    }
+    out + &segment
+}
+
+impl fmt::Display for Niche {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<16} {:>2} bytes, Option of it {:>2} ", self.ty, self.size, self.option_size)?;
+        if self.option_size == self.size {
+            write!(f, "-> niche: None is a bit pattern {} can never hold", self.ty)
+        } else {
+            write!(f, "-> every bit pattern is valid, so a separate tag costs {} bytes", self.option_size - self.size)
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn struct_field<T>(name: &'static str, ty: &'static str, offset: usize) -> Field {
+    Field { name, ty, offset, size: size_of::<T>(), align: align_of::<T>() }
+}
+
+#[doc(hidden)]
+pub fn located<E, T>(value: &E, field: &T, name: &'static str, ty: &'static str) -> Field {
+    let offset = field as *const T as usize - value as *const E as usize;
+    Field { name, ty, offset, size: size_of::<T>(), align: align_of::<T>() }
+}
+
+/// Declares a struct and implements [`Inspect`] for it. Fields must be
+/// `'static` types and the struct must not be generic.
+#[macro_export]
+macro_rules! layout_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $( $(#[$fmeta:meta])* $fvis:vis $field:ident : $fty:ty ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $( $(#[$fmeta])* $fvis $field: $fty, )*
+        }
+
+        impl $crate::Inspect for $name {
+            type Layout = $crate::StructLayout;
+
+            fn layout() -> $crate::StructLayout {
+                $crate::layout_struct!(@measure $name, stringify!($name), "as declared"; $($field: $fty),*)
+            }
+
+            fn reprs() -> Vec<$crate::StructLayout> {
+                #[allow(dead_code)]
+                struct Rust { $($field: $fty),* }
+                #[allow(dead_code)]
+                #[repr(C)]
+                struct C { $($field: $fty),* }
+                #[allow(dead_code)]
+                #[repr(C, packed)]
+                struct Packed { $($field: $fty),* }
+                vec![
+                    $crate::layout_struct!(@measure Rust, stringify!($name), "repr(Rust)"; $($field: $fty),*),
+                    $crate::layout_struct!(@measure C, stringify!($name), "repr(C)"; $($field: $fty),*),
+                    $crate::layout_struct!(@measure Packed, stringify!($name), "repr(C, packed)"; $($field: $fty),*),
+                ]
+            }
+        }
+    };
+
+    (@measure $ty:ident, $name:expr, $repr:expr; $($field:ident : $fty:ty),*) => {
+        $crate::StructLayout {
+            name: $name,
+            repr: $repr,
+            size: $crate::size_of::<$ty>(),
+            align: $crate::align_of::<$ty>(),
+            fields: vec![$(
+                $crate::struct_field::<$fty>(stringify!($field), stringify!($fty), $crate::offset_of!($ty, $field))
+            ),*],
+        }
+    };
+}
+
+/// Declares an enum with unit and tuple variants and implements [`Inspect`]
+/// for it. Every payload type must implement `Default` (used to build one
+/// value per variant), and a variant may have at most 8 fields.
+#[macro_export]
+macro_rules! layout_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $( $(#[$vmeta:meta])* $variant:ident $( ( $($vty:ty),* $(,)? ) )? ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $(#[$vmeta])* $variant $( ( $($vty),* ) )? ),*
+        }
+
+        impl $crate::Inspect for $name {
+            type Layout = $crate::EnumLayout;
+
+            fn layout() -> $crate::EnumLayout {
+                $crate::layout_enum!(@measure $name, stringify!($name), "as declared"; $($variant $( ( $($vty),* ) )?),*)
+            }
+
+            fn reprs() -> Vec<$crate::EnumLayout> {
+                #[allow(dead_code)]
+                enum Rust { $($variant $( ( $($vty),* ) )?),* }
+                #[allow(dead_code)]
+                #[repr(C)]
+                enum C { $($variant $( ( $($vty),* ) )?),* }
+                #[allow(dead_code)]
+                #[repr(u8)]
+                enum U8 { $($variant $( ( $($vty),* ) )?),* }
+                vec![
+                    $crate::layout_enum!(@measure Rust, stringify!($name), "repr(Rust)"; $($variant $( ( $($vty),* ) )?),*),
+                    $crate::layout_enum!(@measure C, stringify!($name), "repr(C)"; $($variant $( ( $($vty),* ) )?),*),
+                    $crate::layout_enum!(@measure U8, stringify!($name), "repr(u8)"; $($variant $( ( $($vty),* ) )?),*),
+                ]
+            }
+        }
+    };
+
+    (@measure $ty:ident, $name:expr, $repr:expr; $($variant:ident $( ( $($vty:ty),* ) )?),*) => {
+        $crate::EnumLayout {
+            name: $name,
+            repr: $repr,
+            size: $crate::size_of::<$ty>(),
+            align: $crate::align_of::<$ty>(),
+            variants: vec![$(
+                $crate::VariantLayout {
+                    name: stringify!($variant),
+                    fields: $crate::layout_enum!(@variant $ty $variant $( ( $($vty),* ) )?),
+                }
+            ),*],
+        }
+    };
+
+    (@variant $ty:ident $variant:ident) => {
+        Vec::new()
+    };
+    (@variant $ty:ident $variant:ident ( $($vty:ty),* )) => {
+        $crate::layout_enum!(@bind $ty $variant [] [(f0 "0") (f1 "1") (f2 "2") (f3 "3") (f4 "4") (f5 "5") (f6 "6") (f7 "7")] [$($vty),*])
+    };
+
+    // Give every payload field a binding name from the pool, then locate them.
+    (@bind $ty:ident $variant:ident [$($b:ident $n:literal $bty:ty;)*] [($p:ident $pn:literal) $($pool:tt)*] [$t:ty $(, $rest:ty)*]) => {
+        $crate::layout_enum!(@bind $ty $variant [$($b $n $bty;)* $p $pn $t;] [$($pool)*] [$($rest),*])
+    };
+    (@bind $ty:ident $variant:ident [$($b:ident $n:literal $bty:ty;)*] [$($pool:tt)*] []) => {{
+        let value = $ty::$variant($(<$bty as Default>::default()),*);
+        #[allow(unreachable_patterns)]
+        match &value {
+            $ty::$variant($($b),*) => vec![$($crate::located(&value, $b, $n, stringify!($bty))),*],
+            _ => unreachable!(),
+        }
+    }};
+}
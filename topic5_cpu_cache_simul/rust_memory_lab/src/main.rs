@@ -1,22 +1,42 @@
 use std::mem::{size_of, align_of};
+use std::num::NonZeroU32;
 
-#[derive(Debug)]
-struct Simple {
-    a: u8,
-    b: u32,
+use layout_lab::{layout_enum, layout_struct, niche, Inspect};
+
+layout_struct! {
+    #[derive(Debug)]
+    struct Simple {
+        a: u8,
+        b: u32,
+    }
 }
 
-#[derive(Debug)]
-struct Optimized {
-    b: u32,
-    a: u8,
+layout_struct! {
+    #[derive(Debug)]
+    struct Optimized {
+        b: u32,
+        a: u8,
+    }
 }
 
-#[derive(Debug)]
-enum Status {
-    Idle,
-    Running(u32),
-    Error(u8),
+// A record whose declared order wastes space once it has to be repr(C).
+layout_struct! {
+    #[derive(Debug)]
+    struct Reading {
+        valid: bool,
+        timestamp: u64,
+        channel: u8,
+        value: f32,
+    }
+}
+
+layout_enum! {
+    #[derive(Debug)]
+    enum Status {
+        Idle,
+        Running(u32),
+        Error(u8),
+    }
 }
 
 fn main() {
@@ -59,4 +79,39 @@ fn main() {
     println!("\n--- Option Optimization ---");
     println!("Option<&u8> size: {}", size_of::<Option<&u8>>());
     println!("&u8 size: {}", size_of::<&u8>());
+
+    println!("\n--- Byte Maps ---");
+    println!("{}\n", Simple::layout());
+    println!("{}\n", Optimized::layout());
+    println!("repr(Rust) reorders fields itself, so Simple and Optimized end up identical.");
+    println!("repr(C) keeps declaration order; repr(C, packed) drops padding (and alignment):\n");
+    for layout in Reading::reprs() {
+        println!("{layout}\n");
+    }
+    let declared_c = &Reading::reprs()[1];
+    if let Some(better) = declared_c.suggest_order() {
+        let order: Vec<&str> = better.fields.iter().map(|f| f.name).collect();
+        println!(
+            "suggested repr(C) order: {} -> {} bytes instead of {}",
+            order.join(", "),
+            better.size,
+            declared_c.size
+        );
+        println!("{better}\n");
+    }
+    println!("(what layout costs at run time: `cargo run --release -p layout_bench`)\n");
+
+    println!("{}\n", Status::layout());
+    for layout in &Status::reprs()[1..] {
+        println!("{layout}\n");
+    }
+
+    println!("--- Niches ---");
+    println!("{}", niche::<&u8>());
+    println!("{}", niche::<Box<u8>>());
+    println!("{}", niche::<NonZeroU32>());
+    println!("{}", niche::<u32>());
+    println!("{}", niche::<bool>());
+    println!("{}", niche::<char>());
+    println!("{}", niche::<Status>());
 }
@@ -16,8 +16,27 @@
 //
 // We also print a simple "external fragmentation" view of free holes,
 // which is a classic downside of variable-sized segments requiring contiguous placement.
+//
+// Hybrid mode (segmented paging):
+// - A segment may carry its own Page Table instead of a single base.
+// - The (already bounds-checked) offset is split into: page number | page offset.
+// - Each Page Table Entry (PTE) holds {frame, present, permissions}.
+// - physical_address = frame * PAGE_SIZE + page_offset
+//
+// So a fault can now come from two layers:
+//   segment layer => invalid segment, bounds fault, segment permission fault
+//   paging layer  => page not present (page fault), page protection fault
+//
+// NOTE: x86 protected mode layers them slightly differently: base + offset gives a
+// "linear address", which then goes through ONE page table shared by all segments.
+// Giving each segment its own table (Multics style) keeps the two steps easy to see.
+
+mod scenario;
 
 use std::fmt;
+use std::process::ExitCode;
+
+use scenario::Scenario;
 
 #[derive(Clone, Copy, Debug)]
 struct SegPerm {
@@ -27,21 +46,40 @@ struct SegPerm {
 }
 // NOTE: In a real system, permissions would be encoded in bits (e.g., R=4, W=2, X=1) for compactness.
 
-#[derive(Clone, Copy, Debug)] 
+// Tiny pages so the demo memory (128 bytes) holds 16 frames.
+const PAGE_SIZE: usize = 8;
+const PAGE_OFFSET_BITS: usize = 3; // 2^3 = 8 = PAGE_SIZE
+
+#[derive(Clone, Copy, Debug)]
+struct PageTableEntry {
+    frame: usize,   // physical frame number (frame * PAGE_SIZE = frame start)
+    present: bool,  // false => page not in memory (a real OS would load it on the fault)
+    perm: SegPerm,  // page-level protection, checked AFTER the segment permission
+}
+
+#[derive(Clone, Debug)]
+struct PageTable {
+    entries: Vec<PageTableEntry>,
+}
+
+#[derive(Clone, Debug)]
 // drive means: we want to print these permissions in error messages, so we derive Debug for easy formatting.
 struct Segment {
-    name: &'static str,
-    base: usize,  // physical start address
+    name: String,
+    base: usize,  // physical start address (ignored when the segment is paged)
     limit: usize, // size in bytes
     perm: SegPerm,
+    page_table: Option<PageTable>, // Some(..) => hybrid mode: offset goes through this table
 }
 
 #[derive(Debug)]
 enum SegError {
     InvalidSegment { seg: usize },
-    BoundsFault { seg_name: &'static str, offset: usize, limit: usize },
-    PermissionFault { seg_name: &'static str, op: &'static str },
-    PhysicalOutOfRange { phys: usize, mem_size: usize },
+    BoundsFault { seg_name: String, offset: usize, limit: usize },
+    PermissionFault { seg_name: String, op: &'static str },
+    PhysicalOutOfRange { phys: u128, mem_size: usize }, // u128: base + offset may not fit a usize
+    PageNotPresent { seg_name: String, page: usize },
+    PageProtectionFault { seg_name: String, page: usize, op: &'static str },
 }
 
 impl fmt::Display for SegError {
@@ -59,6 +97,27 @@ impl fmt::Display for SegError {
                 f,
                 "Physical address out of range: {phys} (mem size {mem_size})"
             ),
+            SegError::PageNotPresent { seg_name, page } => {
+                write!(f, "Page fault: page {page} of segment '{seg_name}' is not present")
+            }
+            SegError::PageProtectionFault { seg_name, page, op } => write!(
+                f,
+                "Page protection fault: '{op}' not allowed on page {page} of segment '{seg_name}'"
+            ),
+        }
+    }
+}
+
+impl SegError {
+    // Short fault name, used by scenario files: "expect fault Permission".
+    fn kind(&self) -> &'static str {
+        match self {
+            SegError::InvalidSegment { .. } => "InvalidSegment",
+            SegError::BoundsFault { .. } => "Bounds",
+            SegError::PermissionFault { .. } => "Permission",
+            SegError::PhysicalOutOfRange { .. } => "PhysicalOutOfRange",
+            SegError::PageNotPresent { .. } => "PageNotPresent",
+            SegError::PageProtectionFault { .. } => "PageProtection",
         }
     }
 }
@@ -70,27 +129,64 @@ enum Op {
     Exec,
 }
 
-fn check_perm(seg: &Segment, op: Op) -> Result<(), SegError> {
-    let allowed = match op {
-        Op::Read => seg.perm.read,
-        Op::Write => seg.perm.write,
-        Op::Exec => seg.perm.exec,
-    };
-    if allowed {
-        Ok(())
-    } else {
-        let op_str = match op {
+impl Op {
+    fn name(self) -> &'static str {
+        match self {
             Op::Read => "READ",
             Op::Write => "WRITE",
             Op::Exec => "EXEC",
-        };
+        }
+    }
+}
+
+impl SegPerm {
+    fn allows(&self, op: Op) -> bool {
+        match op {
+            Op::Read => self.read,
+            Op::Write => self.write,
+            Op::Exec => self.exec,
+        }
+    }
+}
+
+fn check_perm(seg: &Segment, op: Op) -> Result<(), SegError> {
+    if seg.perm.allows(op) {
+        Ok(())
+    } else {
         Err(SegError::PermissionFault {
-            seg_name: seg.name,
-            op: op_str,
+            seg_name: seg.name.clone(),
+            op: op.name(),
         })
     }
 }
 
+// Second translation step in hybrid mode: offset => (page, page_offset) => frame.
+// The segment checks have already passed when we get here.
+fn page_walk(seg: &Segment, table: &PageTable, offset: usize, op: Op, mem_size: usize) -> Result<usize, SegError> {
+    let page = offset >> PAGE_OFFSET_BITS;
+    let page_offset = offset & (PAGE_SIZE - 1);
+
+    // A page past the end of the table is simply unmapped => same as "not present".
+    let pte = match table.entries.get(page) {
+        Some(pte) if pte.present => pte,
+        _ => return Err(SegError::PageNotPresent { seg_name: seg.name.clone(), page }),
+    };
+
+    if !pte.perm.allows(op) {
+        return Err(SegError::PageProtectionFault {
+            seg_name: seg.name.clone(),
+            page,
+            op: op.name(),
+        });
+    }
+
+    // A frame number from a scenario file can be anything; past usize it is
+    // certainly past the end of memory.
+    pte.frame.checked_mul(PAGE_SIZE).and_then(|start| start.checked_add(page_offset)).ok_or(
+        SegError::PhysicalOutOfRange { phys: pte.frame as u128 * PAGE_SIZE as u128 + page_offset as u128, mem_size },
+    )
+}
+
 fn translate(
     mem_size: usize,
     segs: &[Segment],
@@ -98,25 +194,31 @@ fn translate(
     offset: usize,
     op: Op,
 ) -> Result<usize, SegError> {
-    let seg = *segs.get(seg_id).ok_or(SegError::InvalidSegment { seg: seg_id })?;
+    let seg = segs.get(seg_id).ok_or(SegError::InvalidSegment { seg: seg_id })?;
 
     // 1) Protection check (like hardware permission bits)
-    check_perm(&seg, op)?;
+    check_perm(seg, op)?;
 
     // 2) Bounds check (base/limit like classic segmentation)
     if offset >= seg.limit {
         return Err(SegError::BoundsFault {
-            seg_name: seg.name,
+            seg_name: seg.name.clone(),
             offset,
             limit: seg.limit,
         });
     }
 
-    // 3) Translate to physical address
-    let phys = seg.base + offset;
+    // 3) Translate to physical address (directly, or through the segment's page table)
+    let phys = match &seg.page_table {
+        None => seg.base.checked_add(offset).ok_or(SegError::PhysicalOutOfRange {
+            phys: seg.base as u128 + offset as u128,
+            mem_size,
+        })?,
+        Some(table) => page_walk(seg, table, offset, op, mem_size)?,
+    };
 
     if phys >= mem_size {
-        return Err(SegError::PhysicalOutOfRange { phys, mem_size });
+        return Err(SegError::PhysicalOutOfRange { phys: phys as u128, mem_size });
     }
     Ok(phys)
 }
@@ -132,7 +234,51 @@ fn write_u8(mem: &mut [u8], segs: &[Segment], seg_id: usize, offset: usize, v: u
     Ok(())
 }
 
-fn main() {
+// `segsim`                      => built-in walkthrough below
+// `segsim run <file.seg>...`    => execute scenario files and report pass/fail
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {
+            demo();
+            ExitCode::SUCCESS
+        }
+        Some("run") if args.len() > 1 => run_scenarios(&args[1..]),
+        _ => {
+            eprintln!("usage: segsim [run <scenario.seg>...]");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_scenarios(paths: &[String]) -> ExitCode {
+    let mut all_ok = true;
+    for path in paths {
+        println!("=== Scenario: {path} ===");
+        let src = match std::fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(e) => {
+                println!("  ERROR: cannot read {path}: {e}\n");
+                all_ok = false;
+                continue;
+            }
+        };
+        let scenario = match Scenario::parse(&src) {
+            Ok(sc) => sc,
+            Err(e) => {
+                println!("  ERROR: {path}: {e}\n");
+                all_ok = false;
+                continue;
+            }
+        };
+        let report = scenario.run();
+        println!("  {} passed, {} failed\n", report.passed, report.failed);
+        all_ok &= report.failed == 0;
+    }
+    if all_ok { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+fn demo() {
     // "Physical memory" — 128 bytes for a tiny demo.
     let mut mem = vec![0u8; 128];
 
@@ -142,22 +288,25 @@ fn main() {
     // That’s why external fragmentation can happen over time.
     let segs = [
         Segment {
-            name: "CODE",
+            name: "CODE".to_string(),
             base: 0,
             limit: 32, // bytes [0..31]
             perm: SegPerm { read: true, write: false, exec: true },
+            page_table: None,
         },
         Segment {
-            name: "DATA",
+            name: "DATA".to_string(),
             base: 40,
             limit: 24, // bytes [40..63]
             perm: SegPerm { read: true, write: true, exec: false },
+            page_table: None,
         },
         Segment {
-            name: "STACK",
+            name: "STACK".to_string(),
             base: 80,
             limit: 16, // bytes [80..95]
             perm: SegPerm { read: true, write: true, exec: false },
+            page_table: None,
         },
     ];
 
@@ -219,5 +368,127 @@ fn main() {
     println!("Request: NEW_SEG size=40 bytes");
     println!("Result: FAIL in classic segmentation (needs contiguous space); compaction required.\n");
 
+    segmented_paging_demo();
+
     println!("Done.");
 }
+
+fn print_page_table(seg: &Segment) {
+    let Some(table) = &seg.page_table else {
+        return;
+    };
+    println!("  {} (limit {} bytes = {} pages)", seg.name, seg.limit, seg.limit.div_ceil(PAGE_SIZE));
+    for (page, pte) in table.entries.iter().enumerate() {
+        if pte.present {
+            let p = pte.perm;
+            println!(
+                "    page {page} -> frame {:2} [{}..{}] {}{}{}",
+                pte.frame,
+                // In u128: a frame from a scenario file may be near usize::MAX.
+                pte.frame as u128 * PAGE_SIZE as u128,
+                pte.frame as u128 * PAGE_SIZE as u128 + PAGE_SIZE as u128 - 1,
+                if p.read { 'R' } else { '-' },
+                if p.write { 'W' } else { '-' },
+                if p.exec { 'X' } else { '-' },
+            );
+        } else {
+            println!("    page {page} -> (not present)");
+        }
+    }
+}
+
+fn segmented_paging_demo() {
+    println!("=== Segmented Paging (hybrid): (segment, offset) => (page, page offset) => frame ===");
+    println!("Page size: {PAGE_SIZE} bytes => offset = [page number | {PAGE_OFFSET_BITS}-bit page offset]\n");
+
+    let mut mem = vec![0u8; 128];
+
+    let rx = SegPerm { read: true, write: false, exec: true };
+    let rw = SegPerm { read: true, write: true, exec: false };
+    let ro = SegPerm { read: true, write: false, exec: false };
+    let pte = |frame, perm| PageTableEntry { frame, present: true, perm };
+    let missing = PageTableEntry { frame: 0, present: false, perm: rw };
+
+    // Same three segments, but now each one is split into pages scattered across
+    // physical memory. No segment needs a contiguous hole any more.
+    let segs = [
+        Segment {
+            name: "CODE".to_string(),
+            base: 0,
+            limit: 24,
+            perm: rx,
+            page_table: Some(PageTable { entries: vec![pte(5, rx), pte(2, rx), pte(9, rx)] }),
+        },
+        Segment {
+            name: "DATA".to_string(),
+            base: 0,
+            limit: 24,
+            perm: rw,
+            // page 1 is read-only at page level (think: shared copy-on-write page)
+            // page 2 has not been loaded yet (think: demand paging)
+            page_table: Some(PageTable { entries: vec![pte(12, rw), pte(0, ro), missing] }),
+        },
+        Segment {
+            name: "STACK".to_string(),
+            base: 0,
+            limit: 16,
+            perm: rw,
+            page_table: Some(PageTable { entries: vec![missing, pte(7, rw)] }),
+        },
+    ];
+
+    println!("Page tables:");
+    for seg in &segs {
+        print_page_table(seg);
+    }
+    println!();
+
+    // Seed CODE page 1 (frame 2) so the read below returns something visible.
+    for i in 0..PAGE_SIZE {
+        mem[2 * PAGE_SIZE + i] = 0xC0 + i as u8;
+    }
+
+    // ✅ Valid read: CODE offset 10 => page 1, page offset 2 => frame 2 => physical 18
+    match translate(mem.len(), &segs, 0, 10, Op::Read) {
+        Ok(phys) => println!("READ CODE[10] => page 1 + 2 => physical {phys} => 0x{:02X}", mem[phys]),
+        Err(e) => println!("Unexpected fault on CODE read: {e}"),
+    }
+
+    // ✅ Valid write: DATA offset 3 => page 0 => frame 12
+    write_u8(&mut mem, &segs, 1, 3, 0xAB).unwrap();
+    let b = read_u8(&mem, &segs, 1, 3).unwrap();
+    println!("WRITE+READ DATA[3] => 0x{b:02X} (page 0 -> frame 12)");
+
+    // ❌ Segment bounds fault: caught before the page table is even consulted
+    match read_u8(&mem, &segs, 2, 99) {
+        Ok(v) => println!("Unexpected: read STACK => {v}"),
+        Err(e) => println!("EXPECTED FAULT (segment bounds): {e}"),
+    }
+
+    // ❌ Segment permission fault: DATA is not executable as a whole
+    match translate(mem.len(), &segs, 1, 2, Op::Exec) {
+        Ok(phys) => println!("Unexpected: executed DATA at physical {phys}"),
+        Err(e) => println!("EXPECTED FAULT (segment permission): {e}"),
+    }
+
+    // ❌ Page protection fault: DATA allows WRITE, but page 1 is read-only
+    match write_u8(&mut mem, &segs, 1, 9, 0xFF) {
+        Ok(_) => println!("Unexpected: wrote to read-only page"),
+        Err(e) => println!("EXPECTED FAULT (page protection): {e}"),
+    }
+
+    // ❌ Page not present: offset 20 is inside DATA's limit, but page 2 is not loaded
+    match read_u8(&mem, &segs, 1, 20) {
+        Ok(v) => println!("Unexpected: read DATA[20] => {v}"),
+        Err(e) => println!("EXPECTED FAULT (missing page): {e}"),
+    }
+
+    // ❌ Page not present: STACK page 0 has never been touched
+    match write_u8(&mut mem, &segs, 2, 4, 1) {
+        Ok(_) => println!("Unexpected: wrote STACK[4]"),
+        Err(e) => println!("EXPECTED FAULT (missing page): {e}"),
+    }
+
+    println!("\nOrder of checks: segment id -> segment permission -> segment limit -> page present -> page permission.");
+    println!("Frames used are non-contiguous (5, 2, 9, 12, 0, 7) => no external fragmentation, only per-page internal waste.\n");
+}
@@ -0,0 +1,332 @@
+// src/scenario.rs
+//
+// Scenario files — write new segmentation / paging exercises without touching Rust.
+//
+// A scenario is a plain text file, one statement per line ('#' starts a comment):
+//
+//   memory 128                                   physical memory size in bytes
+//   segment CODE base=0 limit=32 perm=r-x        classic segment (base + offset)
+//   segment DATA limit=24 perm=rw- paged         hybrid segment (goes through a page table)
+//   page DATA 0 frame=12 perm=rw-                PTE for page 0 of DATA
+//   page DATA 2 missing                          PTE exists but page is not present
+//
+//   read  CODE 10            expect ok
+//   read  DATA 3             expect value 0xAB
+//   write DATA 3 0xAB        expect phys 43
+//   exec  DATA 2             expect fault Permission
+//
+// Segments get ids in declaration order (first = 0), so operations may name a
+// segment either by name or by id (useful for "expect fault InvalidSegment").
+// Pages that are never declared for a paged segment are treated as not present.
+// Physical memory is at most MAX_MEMORY bytes. A paged segment spans at most
+// MAX_PAGES pages, and a page must lie inside its segment's limit.
+//
+// Fault kinds: InvalidSegment, Bounds, Permission, PhysicalOutOfRange,
+//              PageNotPresent, PageProtection
+
+use std::fmt;
+
+use crate::{translate, Op, PageTable, PageTableEntry, SegPerm, Segment, PAGE_SIZE};
+
+// Page tables are built from the file, entry by entry up to the highest page
+// named; this keeps a typo like "page DATA 99999999" from allocating gigabytes.
+const MAX_PAGES: usize = 4096;
+// Physical memory is allocated in full when the scenario runs.
+const MAX_MEMORY: usize = 1 << 20;
+
+#[derive(Debug)]
+pub struct ParseError {
+    line: Option<usize>, // None: about the scenario as a whole
+    msg: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.msg),
+            None => write!(f, "{}", self.msg),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expect {
+    Ok,
+    Value(u8),
+    Phys(usize),
+    Fault(String),
+}
+
+#[derive(Debug)]
+struct Step {
+    line: usize,
+    text: String,
+    op: Op,
+    seg: String, // name or numeric id, resolved when the step runs
+    offset: usize,
+    value: Option<u8>, // only for write
+    expect: Expect,
+}
+
+#[derive(Debug)]
+pub struct Scenario {
+    mem_size: usize,
+    segs: Vec<Segment>,
+    steps: Vec<Step>,
+}
+
+// Result of running a whole scenario.
+pub struct Report {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+fn err(line: usize, msg: impl Into<String>) -> ParseError {
+    ParseError { line: Some(line), msg: msg.into() }
+}
+
+// Accepts decimal or 0x-prefixed hex.
+fn parse_num(line: usize, s: &str) -> Result<usize, ParseError> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| err(line, format!("expected a number, got '{s}'")))
+}
+
+fn parse_byte(line: usize, s: &str) -> Result<u8, ParseError> {
+    let n = parse_num(line, s)?;
+    u8::try_from(n).map_err(|_| err(line, format!("value {n} does not fit in a byte")))
+}
+
+// "rwx", "r-x", "rw-" ... (exactly like `ls -l` / /proc/<pid>/maps)
+fn parse_perm(line: usize, s: &str) -> Result<SegPerm, ParseError> {
+    let b = s.as_bytes();
+    let ok = b.len() == 3
+        && matches!(b[0], b'r' | b'-')
+        && matches!(b[1], b'w' | b'-')
+        && matches!(b[2], b'x' | b'-');
+    if !ok {
+        return Err(err(line, format!("bad permissions '{s}' (expected like r-x)")));
+    }
+    Ok(SegPerm { read: b[0] == b'r', write: b[1] == b'w', exec: b[2] == b'x' })
+}
+
+// Splits "key=value" words into a lookup, and keeps bare words as flags.
+fn key_values<'a>(words: &[&'a str]) -> (Vec<(&'a str, &'a str)>, Vec<&'a str>) {
+    let mut kv = Vec::new();
+    let mut flags = Vec::new();
+    for w in words {
+        match w.split_once('=') {
+            Some((k, v)) => kv.push((k, v)),
+            None => flags.push(*w),
+        }
+    }
+    (kv, flags)
+}
+
+fn lookup<'a>(line: usize, kv: &[(&str, &'a str)], key: &str) -> Result<&'a str, ParseError> {
+    kv.iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+        .ok_or_else(|| err(line, format!("missing '{key}='")))
+}
+
+const FAULT_KINDS: [&str; 6] = [
+    "InvalidSegment",
+    "Bounds",
+    "Permission",
+    "PhysicalOutOfRange",
+    "PageNotPresent",
+    "PageProtection",
+];
+
+fn parse_expect(line: usize, words: &[&str]) -> Result<Expect, ParseError> {
+    match words {
+        ["expect", "ok"] => Ok(Expect::Ok),
+        ["expect", "value", v] => Ok(Expect::Value(parse_byte(line, v)?)),
+        ["expect", "phys", p] => Ok(Expect::Phys(parse_num(line, p)?)),
+        ["expect", "fault", kind] => {
+            if FAULT_KINDS.contains(kind) {
+                Ok(Expect::Fault(kind.to_string()))
+            } else {
+                Err(err(line, format!("unknown fault kind '{kind}' (one of {})", FAULT_KINDS.join(", "))))
+            }
+        }
+        _ => Err(err(line, "expected 'expect ok | value N | phys N | fault KIND'")),
+    }
+}
+
+impl Scenario {
+    pub fn parse(src: &str) -> Result<Scenario, ParseError> {
+        let mut sc = Scenario { mem_size: 0, segs: Vec::new(), steps: Vec::new() };
+
+        for (i, raw) in src.lines().enumerate() {
+            let line = i + 1;
+            let text = raw.split('#').next().unwrap_or("").trim();
+            if text.is_empty() {
+                continue;
+            }
+            let words: Vec<&str> = text.split_whitespace().collect();
+
+            match words[0] {
+                "memory" => {
+                    let [_, size] = words[..] else {
+                        return Err(err(line, "usage: memory <bytes>"));
+                    };
+                    sc.mem_size = parse_num(line, size)?;
+                    if sc.mem_size > MAX_MEMORY {
+                        return Err(err(line, format!("memory is at most {MAX_MEMORY} bytes")));
+                    }
+                }
+                "segment" => {
+                    let Some(name) = words.get(1) else {
+                        return Err(err(line, "usage: segment <NAME> [base=N] limit=N perm=rwx [paged]"));
+                    };
+                    if sc.segs.iter().any(|s| s.name == *name) {
+                        return Err(err(line, format!("segment '{name}' declared twice")));
+                    }
+                    let (kv, flags) = key_values(&words[2..]);
+                    let paged = match flags[..] {
+                        [] => false,
+                        ["paged"] => true,
+                        _ => return Err(err(line, format!("unexpected words {flags:?}"))),
+                    };
+                    let base = match kv.iter().find(|(k, _)| *k == "base") {
+                        Some((_, v)) => parse_num(line, v)?,
+                        None if paged => 0,
+                        None => return Err(err(line, "missing 'base=' (required unless paged)")),
+                    };
+                    let limit = parse_num(line, lookup(line, &kv, "limit")?)?;
+                    if paged && limit > MAX_PAGES * PAGE_SIZE {
+                        let most = MAX_PAGES * PAGE_SIZE;
+                        return Err(err(line, format!("a paged segment holds at most {most} bytes ({MAX_PAGES} pages)")));
+                    }
+                    sc.segs.push(Segment {
+                        name: name.to_string(),
+                        base,
+                        limit,
+                        perm: parse_perm(line, lookup(line, &kv, "perm")?)?,
+                        page_table: paged.then(|| PageTable { entries: Vec::new() }),
+                    });
+                }
+                "page" => {
+                    let (Some(name), Some(page)) = (words.get(1), words.get(2)) else {
+                        return Err(err(line, "usage: page <SEG> <n> frame=N perm=rwx | page <SEG> <n> missing"));
+                    };
+                    let page = parse_num(line, page)?;
+                    let (kv, flags) = key_values(&words[3..]);
+                    let missing = PageTableEntry {
+                        frame: 0,
+                        present: false,
+                        perm: SegPerm { read: false, write: false, exec: false },
+                    };
+                    let pte = match flags[..] {
+                        ["missing"] => missing,
+                        [] => PageTableEntry {
+                            frame: parse_num(line, lookup(line, &kv, "frame")?)?,
+                            present: true,
+                            perm: parse_perm(line, lookup(line, &kv, "perm")?)?,
+                        },
+                        _ => return Err(err(line, format!("unexpected words {flags:?}"))),
+                    };
+                    let seg = sc
+                        .segs
+                        .iter_mut()
+                        .find(|s| s.name == *name)
+                        .ok_or_else(|| err(line, format!("unknown segment '{name}'")))?;
+                    let pages = seg.limit.div_ceil(PAGE_SIZE);
+                    let Some(table) = seg.page_table.as_mut() else {
+                        return Err(err(line, format!("segment '{name}' is not declared 'paged'")));
+                    };
+                    if page >= pages {
+                        return Err(err(line, format!("page {page} is past the end of '{name}' (pages 0..{pages})")));
+                    }
+                    // Grow the table with not-present entries up to this page.
+                    if table.entries.len() <= page {
+                        table.entries.resize(page + 1, missing);
+                    }
+                    table.entries[page] = pte;
+                }
+                "read" | "exec" | "write" => {
+                    let op = match words[0] {
+                        "read" => Op::Read,
+                        "write" => Op::Write,
+                        _ => Op::Exec,
+                    };
+                    let operands = if matches!(op, Op::Write) { 3 } else { 2 };
+                    if words.len() < 1 + operands {
+                        return Err(err(line, format!("'{}' needs {operands} operands", words[0])));
+                    }
+                    let value = match op {
+                        Op::Write => Some(parse_byte(line, words[3])?),
+                        _ => None,
+                    };
+                    let expect = parse_expect(line, &words[1 + operands..])?;
+                    if matches!(expect, Expect::Value(_)) && !matches!(op, Op::Read) {
+                        return Err(err(line, "'expect value' only makes sense for read"));
+                    }
+                    sc.steps.push(Step {
+                        line,
+                        text: text.to_string(),
+                        op,
+                        seg: words[1].to_string(),
+                        offset: parse_num(line, words[2])?,
+                        value,
+                        expect,
+                    });
+                }
+                other => return Err(err(line, format!("unknown statement '{other}'"))),
+            }
+        }
+
+        if sc.mem_size == 0 {
+            return Err(ParseError { line: None, msg: "scenario must declare 'memory <bytes>'".into() });
+        }
+        Ok(sc)
+    }
+
+    // Name first, then numeric id. An unknown name becomes an id past the end of
+    // the table so translation reports InvalidSegment like the hardware would.
+    fn seg_id(&self, seg: &str) -> usize {
+        self.segs
+            .iter()
+            .position(|s| s.name == seg)
+            .or_else(|| seg.parse().ok())
+            .unwrap_or(self.segs.len())
+    }
+
+    pub fn run(&self) -> Report {
+        let mut mem = vec![0u8; self.mem_size];
+        let mut report = Report { passed: 0, failed: 0 };
+
+        for step in &self.steps {
+            let seg_id = self.seg_id(&step.seg);
+            let result = translate(mem.len(), &self.segs, seg_id, step.offset, step.op).inspect(|&phys| {
+                if let Some(v) = step.value {
+                    mem[phys] = v;
+                }
+            });
+
+            let (pass, got) = match (&step.expect, result) {
+                (Expect::Fault(kind), Err(e)) => (e.kind() == kind, format!("fault {}: {e}", e.kind())),
+                (_, Err(e)) => (false, format!("fault {}: {e}", e.kind())),
+                (Expect::Fault(_), Ok(phys)) => (false, format!("ok (physical {phys})")),
+                (Expect::Ok, Ok(phys)) => (true, format!("ok (physical {phys})")),
+                (Expect::Phys(want), Ok(phys)) => (*want == phys, format!("physical {phys}")),
+                (Expect::Value(want), Ok(phys)) => {
+                    (*want == mem[phys], format!("value 0x{:02X} (physical {phys})", mem[phys]))
+                }
+            };
+
+            if pass {
+                report.passed += 1;
+                println!("  PASS  line {:3}: {:<40} => {got}", step.line, step.text);
+            } else {
+                report.failed += 1;
+                println!("  FAIL  line {:3}: {:<40} => got {got}", step.line, step.text);
+            }
+        }
+        report
+    }
+}
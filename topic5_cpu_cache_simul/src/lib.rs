@@ -0,0 +1,323 @@
+// src/lib.rs
+//
+// Set-associative cache simulator, the library form of the direct-mapped demo
+// in main.rs. Feed it byte addresses; it answers HIT or MISS and keeps count.
+//
+//   address = [ TAG | SET INDEX | OFFSET ]
+//   OFFSET picks the byte inside a line, SET INDEX picks the set, and the TAG
+//   is compared against every way of that set. ways = 1 is the direct-mapped
+//   cache from main.rs; ways = lines is fully associative.
+//
+// Replacement is LRU. Misses are classified with the usual three C's:
+//   cold      first touch of that line, no cache could have helped
+//   capacity  a fully associative LRU cache of the same size would ALSO miss:
+//             the working set is simply too big
+//   conflict  the fully associative cache would have hit; too many hot lines
+//             map to the same set
+// The capacity/conflict split comes from running a fully associative shadow
+// cache next to the real one.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    pub size_bytes: usize,
+    pub line_bytes: usize,
+    pub ways: usize,
+}
+
+impl CacheConfig {
+    /// A typical L1 data cache.
+    pub const L1D: CacheConfig = CacheConfig { size_bytes: 32 * 1024, line_bytes: 64, ways: 8 };
+    /// Largest cache simulated: 1 GiB, far past any real last-level cache.
+    pub const MAX_SIZE_BYTES: usize = 1 << 30;
+    /// Most lines simulated. Each line costs memory in the cache and its shadow,
+    /// and slot indices must stay below the u32 markers LineMap keeps next to them.
+    pub const MAX_LINES: usize = 1 << 22;
+
+    pub fn lines(&self) -> usize {
+        self.size_bytes / self.line_bytes
+    }
+
+    pub fn sets(&self) -> usize {
+        self.lines() / self.ways
+    }
+
+    /// Sizes must be powers of two, at most MAX_SIZE_BYTES and MAX_LINES, and
+    /// the ways must divide the line count.
+    pub fn is_valid(&self) -> bool {
+        self.line_bytes.is_power_of_two()
+            && self.size_bytes.is_power_of_two()
+            && self.size_bytes <= Self::MAX_SIZE_BYTES
+            && self.lines() <= Self::MAX_LINES
+            && self.ways > 0
+            && self.lines() >= self.ways
+            && self.lines().is_multiple_of(self.ways)
+            && self.sets().is_power_of_two()
+    }
+
+    /// Parses "32k:64:8" (size, line size, ways). Sizes accept k/m suffixes.
+    pub fn parse(s: &str) -> Option<CacheConfig> {
+        let size = |t: &str| -> Option<usize> {
+            let t = t.to_ascii_lowercase();
+            match t.strip_suffix('k').or(t.strip_suffix("kib")) {
+                Some(k) => k.parse::<usize>().ok()?.checked_mul(1024),
+                None => match t.strip_suffix('m').or(t.strip_suffix("mib")) {
+                    Some(m) => m.parse::<usize>().ok()?.checked_mul(1024 * 1024),
+                    None => t.parse().ok(),
+                },
+            }
+        };
+        let mut parts = s.split(':');
+        let config = CacheConfig {
+            size_bytes: size(parts.next()?)?,
+            line_bytes: size(parts.next()?)?,
+            ways: parts.next()?.parse().ok()?,
+        };
+        (parts.next().is_none() && config.is_valid()).then_some(config)
+    }
+}
+
+impl fmt::Display for CacheConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} KiB, {}-byte lines, {}-way ({} sets)",
+            self.size_bytes / 1024,
+            self.line_bytes,
+            self.ways,
+            self.sets()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissKind {
+    Cold,
+    Capacity,
+    Conflict,
+}
+
+impl fmt::Display for MissKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissKind::Cold => write!(f, "cold"),
+            MissKind::Capacity => write!(f, "capacity"),
+            MissKind::Conflict => write!(f, "conflict"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheEvent {
+    Hit,
+    Miss(MissKind),
+}
+
+impl fmt::Display for CacheEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheEvent::Hit => write!(f, "HIT"),
+            CacheEvent::Miss(kind) => write!(f, "MISS ({kind})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub accesses: u64,
+    pub hits: u64,
+    pub cold: u64,
+    pub capacity: u64,
+    pub conflict: u64,
+}
+
+impl Stats {
+    pub fn misses(&self) -> u64 {
+        self.cold + self.capacity + self.conflict
+    }
+
+    pub fn miss_rate(&self) -> f64 {
+        if self.accesses == 0 { 0.0 } else { self.misses() as f64 / self.accesses as f64 }
+    }
+}
+
+/// Per-line state for the shadow cache: which slot holds the line, or whether
+/// it was ever seen. Lines are stored in dense chunks of 1024 consecutive line
+/// numbers, and the last few chunks used are remembered in `recent`, so
+/// sequential traces (even several interleaved ones) rarely hash at all.
+struct LineMap {
+    chunk_of: HashMap<u64, usize>,
+    chunks: Vec<Box<[u32; LineMap::CHUNK]>>,
+    recent: [(u64, usize); 8],
+}
+
+// Slots are numbered 0..lines; the two highest u32 values are markers.
+const _: () = assert!(CacheConfig::MAX_LINES < LineMap::EVICTED as usize);
+
+impl LineMap {
+    const CHUNK: usize = 1024;
+    const UNSEEN: u32 = u32::MAX;
+    const EVICTED: u32 = u32::MAX - 1;
+
+    fn new() -> Self {
+        LineMap { chunk_of: HashMap::new(), chunks: Vec::new(), recent: [(u64::MAX, 0); 8] }
+    }
+
+    fn entry(&mut self, line: u64) -> &mut u32 {
+        let (key, offset) = (line / Self::CHUNK as u64, (line % Self::CHUNK as u64) as usize);
+        let recent = &mut self.recent[(key % 8) as usize];
+        let index = if recent.0 == key {
+            recent.1
+        } else {
+            let chunks = &mut self.chunks;
+            let i = *self.chunk_of.entry(key).or_insert_with(|| {
+                chunks.push(Box::new([Self::UNSEEN; Self::CHUNK]));
+                chunks.len() - 1
+            });
+            *recent = (key, i);
+            i
+        };
+        &mut self.chunks[index][offset]
+    }
+}
+
+/// Fully associative LRU over line numbers, O(1) per access: a map from line
+/// to slot plus an intrusive doubly linked list in recency order. Evicted
+/// lines stay in the map, so it also knows which lines were ever touched.
+struct LruShadow {
+    capacity: usize,
+    slots: Vec<(u64, usize, usize)>, // (line, prev, next)
+    index: LineMap,
+    head: usize, // most recent
+    tail: usize, // least recent
+}
+
+const NIL: usize = usize::MAX;
+
+impl LruShadow {
+    fn new(capacity: usize) -> Self {
+        LruShadow { capacity, slots: Vec::with_capacity(capacity), index: LineMap::new(), head: NIL, tail: NIL }
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let (_, prev, next) = self.slots[i];
+        if prev == NIL { self.head = next } else { self.slots[prev].2 = next }
+        if next == NIL { self.tail = prev } else { self.slots[next].1 = prev }
+    }
+
+    fn push_front(&mut self, i: usize) {
+        self.slots[i].1 = NIL;
+        self.slots[i].2 = self.head;
+        if self.head != NIL {
+            self.slots[self.head].1 = i;
+        }
+        self.head = i;
+        if self.tail == NIL {
+            self.tail = i;
+        }
+    }
+
+    /// Returns (hit, first touch ever). On a miss the line is inserted,
+    /// evicting the LRU line if full.
+    fn access(&mut self, line: u64) -> (bool, bool) {
+        let first_touch = match *self.index.entry(line) {
+            LineMap::UNSEEN => true,
+            LineMap::EVICTED => false,
+            slot => {
+                self.unlink(slot as usize);
+                self.push_front(slot as usize);
+                return (true, false);
+            }
+        };
+        let i = if self.slots.len() < self.capacity {
+            self.slots.push((line, NIL, NIL));
+            self.slots.len() - 1
+        } else {
+            let victim = self.tail;
+            self.unlink(victim);
+            *self.index.entry(self.slots[victim].0) = LineMap::EVICTED;
+            self.slots[victim].0 = line;
+            victim
+        };
+        *self.index.entry(line) = i as u32;
+        self.push_front(i);
+        (false, first_touch)
+    }
+}
+
+pub struct Cache {
+    config: CacheConfig,
+    // Geometry is all powers of two, so address splitting is shifts and masks.
+    line_shift: u32,
+    set_bits: u32,
+    /// Per set: (tag, last use), at most `ways` entries.
+    sets: Vec<Vec<(u64, u64)>>,
+    clock: u64,
+    shadow: LruShadow,
+    stats: Stats,
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Self {
+        assert!(config.is_valid(), "invalid cache geometry: {config:?}");
+        Cache {
+            config,
+            line_shift: config.line_bytes.trailing_zeros(),
+            set_bits: config.sets().trailing_zeros(),
+            sets: vec![Vec::with_capacity(config.ways); config.sets()],
+            clock: 0,
+            shadow: LruShadow::new(config.lines()),
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn config(&self) -> CacheConfig {
+        self.config
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Forgets the counters but keeps the cache contents (a warm cache).
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    pub fn access(&mut self, address: usize) -> CacheEvent {
+        let line = (address >> self.line_shift) as u64;
+        let (set, tag) = ((line & ((1 << self.set_bits) - 1)) as usize, line >> self.set_bits);
+        self.clock += 1;
+        self.stats.accesses += 1;
+
+        let (shadow_hit, first_touch) = self.shadow.access(line);
+        let ways = &mut self.sets[set];
+        if let Some(entry) = ways.iter_mut().find(|(t, _)| *t == tag) {
+            entry.1 = self.clock;
+            self.stats.hits += 1;
+            return CacheEvent::Hit;
+        }
+        if ways.len() < self.config.ways {
+            ways.push((tag, self.clock));
+        } else {
+            let lru = ways.iter_mut().min_by_key(|(_, used)| *used).expect("ways > 0");
+            *lru = (tag, self.clock);
+        }
+
+        let kind = if first_touch {
+            MissKind::Cold
+        } else if shadow_hit {
+            MissKind::Conflict
+        } else {
+            MissKind::Capacity
+        };
+        match kind {
+            MissKind::Cold => self.stats.cold += 1,
+            MissKind::Capacity => self.stats.capacity += 1,
+            MissKind::Conflict => self.stats.conflict += 1,
+        }
+        CacheEvent::Miss(kind)
+    }
+}
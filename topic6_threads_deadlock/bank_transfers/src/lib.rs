@@ -0,0 +1,326 @@
+// src/lib.rs
+//
+// Many threads moving money between many accounts, each account behind its own
+// Mutex. A transfer must hold BOTH accounts' locks so the debit and the credit
+// happen together. That is exactly the a/b pattern from ../src/main.rs:
+//
+//   thread 1: transfer(A -> B)  locks A, then wants B
+//   thread 2: transfer(B -> A)  locks B, then wants A      -> deadlock
+//
+// A deadlock needs all four Coffman conditions: mutual exclusion, hold and wait,
+// no preemption, and a circular wait. Each strategy here breaks one of them:
+//
+//   Naive        breaks nothing: locks `from` then `to`. Hangs sooner or later.
+//   Ordered      breaks CIRCULAR WAIT: always lock the lower account id first.
+//                Every thread climbs the same ladder, so no cycle can form.
+//   TryLock      breaks HOLD AND WAIT: lock `from`, only try_lock `to`; on
+//                failure let go of `from`, back off for a random while, retry.
+//   Coordinator  breaks MUTUAL EXCLUSION between workers: workers send requests
+//                over a channel and one thread owns every balance. No locks at all,
+//                but every transfer is serialised through that one thread.
+//
+// Whatever the strategy, money is only moved, never created: the total across
+// all accounts must be the same before and after.
+
+use std::fmt;
+use std::sync::mpsc;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Naive,
+    Ordered,
+    TryLock,
+    Coordinator,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [Strategy::Naive, Strategy::Ordered, Strategy::TryLock, Strategy::Coordinator];
+    /// The strategies that can never deadlock.
+    pub const SAFE: [Strategy; 3] = [Strategy::Ordered, Strategy::TryLock, Strategy::Coordinator];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Naive => "naive",
+            Strategy::Ordered => "ordered",
+            Strategy::TryLock => "trylock",
+            Strategy::Coordinator => "coordinator",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Strategy> {
+        Strategy::ALL.into_iter().find(|st| st.name() == s)
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub accounts: usize,
+    pub threads: usize,
+    pub transfers_per_thread: usize,
+    pub initial_balance: i64,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { accounts: 16, threads: 4, transfers_per_thread: 100_000, initial_balance: 1_000, seed: 1 }
+    }
+}
+
+/// Small xorshift generator, so runs are repeatable without extra crates.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// The transfers thread `thread` will attempt: random pairs of distinct accounts.
+pub fn workload(config: &Config, thread: usize) -> Vec<Transfer> {
+    assert!(config.accounts >= 2, "transfers need at least two accounts");
+    let mut rng = Rng::new(config.seed ^ ((thread as u64 + 1) << 32));
+    (0..config.transfers_per_thread)
+        .map(|_| {
+            let from = rng.below(config.accounts);
+            let to = (from + 1 + rng.below(config.accounts - 1)) % config.accounts;
+            let amount = 1 + rng.below((config.initial_balance / 2).max(1) as usize) as i64;
+            Transfer { from, to, amount }
+        })
+        .collect()
+}
+
+pub struct Bank {
+    accounts: Vec<Mutex<i64>>,
+}
+
+impl Bank {
+    pub fn new(accounts: usize, initial_balance: i64) -> Self {
+        Bank { accounts: (0..accounts).map(|_| Mutex::new(initial_balance)).collect() }
+    }
+
+    pub fn balances(&self) -> Vec<i64> {
+        self.accounts.iter().map(|a| *a.lock().unwrap()).collect()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.balances().iter().sum()
+    }
+}
+
+/// Moves the money if `from` can afford it. Returns whether it did.
+fn apply(from: &mut i64, to: &mut i64, amount: i64) -> bool {
+    if *from < amount {
+        return false;
+    }
+    *from -= amount;
+    *to += amount;
+    true
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Counts {
+    completed: u64,
+    rejected: u64,
+    retries: u64,
+}
+
+fn naive(bank: &Bank, t: Transfer, counts: &mut Counts) {
+    let mut from = bank.accounts[t.from].lock().unwrap();
+    // Widen the window between the two locks, as the sleep in ../src/main.rs does.
+    thread::yield_now();
+    let mut to = bank.accounts[t.to].lock().unwrap();
+    count(counts, apply(&mut from, &mut to, t.amount));
+}
+
+fn ordered(bank: &Bank, t: Transfer, counts: &mut Counts) {
+    let (first, second) = (t.from.min(t.to), t.from.max(t.to));
+    let mut a = bank.accounts[first].lock().unwrap();
+    let mut b = bank.accounts[second].lock().unwrap();
+    let (from, to) = if first == t.from { (&mut *a, &mut *b) } else { (&mut *b, &mut *a) };
+    count(counts, apply(from, to, t.amount));
+}
+
+fn try_lock(bank: &Bank, t: Transfer, counts: &mut Counts, rng: &mut Rng) {
+    let mut backoff = 1u32;
+    loop {
+        let mut from = bank.accounts[t.from].lock().unwrap();
+        let to: Option<MutexGuard<i64>> = match bank.accounts[t.to].try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(e)) => panic!("account {} poisoned: {e}", t.to),
+        };
+        if let Some(mut to) = to {
+            count(counts, apply(&mut from, &mut to, t.amount));
+            return;
+        }
+        drop(from);
+        counts.retries += 1;
+        // Random exponential back-off: two threads that collided must not retry in
+        // lockstep, or they can collide forever (livelock instead of deadlock).
+        for _ in 0..rng.below(1 << backoff.min(10)) {
+            std::hint::spin_loop();
+        }
+        if backoff >= 6 {
+            thread::yield_now();
+        }
+        backoff += 1;
+    }
+}
+
+fn count(counts: &mut Counts, moved: bool) {
+    if moved { counts.completed += 1 } else { counts.rejected += 1 }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub strategy: Strategy,
+    pub config: Config,
+    pub elapsed: Duration,
+    /// Transfers that moved money.
+    pub completed: u64,
+    /// Transfers refused because `from` could not afford them.
+    pub rejected: u64,
+    /// TryLock only: times a thread let go and started over.
+    pub retries: u64,
+    pub total_before: i64,
+    pub total_after: i64,
+    pub min_balance: i64,
+}
+
+impl Report {
+    pub fn conserved(&self) -> bool {
+        self.total_before == self.total_after
+    }
+
+    pub fn transfers_per_sec(&self) -> f64 {
+        (self.completed + self.rejected) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+fn run_locking(strategy: Strategy, config: &Config, bank: &Bank) -> Counts {
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..config.threads)
+            .map(|i| {
+                let transfers = workload(config, i);
+                s.spawn(move || {
+                    let mut counts = Counts::default();
+                    let mut rng = Rng::new(config.seed.wrapping_add(i as u64));
+                    for t in transfers {
+                        match strategy {
+                            Strategy::Naive => naive(bank, t, &mut counts),
+                            Strategy::Ordered => ordered(bank, t, &mut counts),
+                            Strategy::TryLock => try_lock(bank, t, &mut counts, &mut rng),
+                            Strategy::Coordinator => unreachable!("not a locking strategy"),
+                        }
+                    }
+                    counts
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).fold(Counts::default(), |a, c| Counts {
+            completed: a.completed + c.completed,
+            rejected: a.rejected + c.rejected,
+            retries: a.retries + c.retries,
+        })
+    })
+}
+
+// Workers only produce requests; the coordinator owns the balances outright.
+fn run_coordinator(config: &Config, bank: &Bank) -> Counts {
+    let (tx, rx) = mpsc::sync_channel::<Transfer>(1024);
+    thread::scope(|s| {
+        for i in 0..config.threads {
+            let tx = tx.clone();
+            let transfers = workload(config, i);
+            s.spawn(move || {
+                for t in transfers {
+                    tx.send(t).unwrap();
+                }
+            });
+        }
+        drop(tx);
+        let mut balances = bank.balances();
+        let mut counts = Counts::default();
+        // Ends once every worker has finished and dropped its sender.
+        for t in rx {
+            let (from, to) = if t.from < t.to {
+                let (lo, hi) = balances.split_at_mut(t.to);
+                (&mut lo[t.from], &mut hi[0])
+            } else {
+                let (lo, hi) = balances.split_at_mut(t.from);
+                (&mut hi[0], &mut lo[t.to])
+            };
+            count(&mut counts, apply(from, to, t.amount));
+        }
+        for (account, balance) in bank.accounts.iter().zip(balances) {
+            *account.lock().unwrap() = balance;
+        }
+        counts
+    })
+}
+
+/// Runs the whole workload with `strategy` and checks the books afterwards.
+/// With `Strategy::Naive` this can block forever; see `run_with_timeout`.
+pub fn run(strategy: Strategy, config: &Config) -> Report {
+    let bank = Bank::new(config.accounts, config.initial_balance);
+    let total_before = bank.total();
+    let start = Instant::now();
+    let counts = match strategy {
+        Strategy::Coordinator => run_coordinator(config, &bank),
+        _ => run_locking(strategy, config, &bank),
+    };
+    let elapsed = start.elapsed();
+    let balances = bank.balances();
+    Report {
+        strategy,
+        config: *config,
+        elapsed,
+        completed: counts.completed,
+        rejected: counts.rejected,
+        retries: counts.retries,
+        total_before,
+        total_after: balances.iter().sum(),
+        min_balance: balances.into_iter().min().unwrap_or(0),
+    }
+}
+
+/// Like `run`, but gives up after `limit`. `None` means the run hung. A hung run's
+/// threads stay blocked in the background; there is no way to unblock a thread
+/// waiting on a Mutex, which is why the only real fix is not to deadlock.
+pub fn run_with_timeout(strategy: Strategy, config: &Config, limit: Duration) -> Option<Report> {
+    let (tx, rx) = mpsc::channel();
+    let config = *config;
+    thread::spawn(move || {
+        let _ = tx.send(run(strategy, &config));
+    });
+    rx.recv_timeout(limit).ok()
+}
@@ -0,0 +1,198 @@
+// src/main.rs
+//
+// `bank_transfers run [--strategy S|all] [--accounts N] [--threads N] [--transfers N] [--seed N] [--timeout SECS]`
+//     one workload per strategy, with throughput and the conservation check
+// `bank_transfers stress [--rounds N] [--timeout SECS]`
+//     many randomised workloads per safe strategy; fails if money is created or
+//     lost, a balance goes negative, or any run hangs
+// No arguments: a short tour, starting with the naive strategy deadlocking.
+//
+// Strategies: naive, ordered, trylock, coordinator (see lib.rs).
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use bank_transfers::{run_with_timeout, Config, Report, Rng, Strategy};
+
+struct Options {
+    strategies: Vec<Strategy>,
+    config: Config,
+    rounds: usize,
+    timeout: Duration,
+}
+
+fn parse_options(args: &[&str]) -> Result<Options, String> {
+    let mut opts = Options {
+        strategies: Strategy::ALL.to_vec(),
+        config: Config::default(),
+        rounds: 200,
+        timeout: Duration::from_secs(10),
+    };
+    let mut it = args.iter();
+    while let Some(&arg) = it.next() {
+        let value = it.next().ok_or(format!("{arg} needs a value"))?;
+        let number = || value.replace('_', "").parse::<usize>().map_err(|_| format!("{arg} takes a number, got '{value}'"));
+        match arg {
+            "--strategy" if *value == "all" => opts.strategies = Strategy::ALL.to_vec(),
+            "--strategy" => {
+                opts.strategies = vec![Strategy::parse(value).ok_or(format!(
+                    "unknown strategy '{value}', expected naive, ordered, trylock, coordinator or all"
+                ))?]
+            }
+            "--accounts" => opts.config.accounts = number()?.max(2),
+            "--threads" => opts.config.threads = number()?.max(1),
+            "--transfers" => opts.config.transfers_per_thread = number()?,
+            "--seed" => opts.config.seed = number()? as u64,
+            "--rounds" => opts.rounds = number()?,
+            "--timeout" => opts.timeout = Duration::from_secs(number()? as u64),
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(opts)
+}
+
+fn print_header() {
+    println!("  {:<12} {:>9} {:>10} {:>9} {:>9} {:>12}  books", "strategy", "ms", "moved", "refused", "retries", "transfers/s");
+}
+
+fn print_row(strategy: Strategy, report: Option<&Report>, timeout: Duration) {
+    match report {
+        Some(r) => println!(
+            "  {:<12} {:>9.1} {:>10} {:>9} {:>9} {:>12.0}  {}",
+            strategy.name(),
+            r.elapsed.as_secs_f64() * 1e3,
+            r.completed,
+            r.rejected,
+            r.retries,
+            r.transfers_per_sec(),
+            if r.conserved() { format!("ok, total {}", r.total_after) } else { format!("WRONG: {} -> {}", r.total_before, r.total_after) }
+        ),
+        None => println!("  {:<12} DEADLOCKED: no progress for {}s, threads still blocked", strategy.name(), timeout.as_secs()),
+    }
+}
+
+fn run_table(opts: &Options) -> Result<(), String> {
+    let c = &opts.config;
+    println!(
+        "{} threads x {} transfers over {} accounts, {} each\n",
+        c.threads, c.transfers_per_thread, c.accounts, c.initial_balance
+    );
+    print_header();
+    let mut broken = 0;
+    for &s in &opts.strategies {
+        let report = run_with_timeout(s, c, opts.timeout);
+        print_row(s, report.as_ref(), opts.timeout);
+        if report.is_none_or(|r| !r.conserved()) {
+            broken += 1;
+        }
+    }
+    if cfg!(debug_assertions) {
+        println!("\nnote: debug build; use --release for meaningful transfers/s");
+    }
+    if broken > 0 { Err(format!("{broken} strategies hung or lost money")) } else { Ok(()) }
+}
+
+fn stress(opts: &Options) -> Result<(), String> {
+    let strategies: Vec<Strategy> = opts.strategies.iter().copied().filter(|s| *s != Strategy::Naive).collect();
+    println!("{} rounds per strategy, random workloads, {}s limit per run", opts.rounds, opts.timeout.as_secs());
+    let mut rng = Rng::new(opts.config.seed);
+    let mut failures = Vec::new();
+    for &strategy in &strategies {
+        let mut transfers = 0;
+        for round in 0..opts.rounds {
+            // Few accounts = heavy contention, where deadlocks and lost updates show up.
+            let config = Config {
+                accounts: [2, 3, 4, 8, 64][rng.below(5)],
+                threads: 2 + rng.below(7),
+                transfers_per_thread: 1_000 + rng.below(4_000),
+                initial_balance: 1 + rng.below(1_000) as i64,
+                seed: rng.next_u64(),
+            };
+            let problem = match run_with_timeout(strategy, &config, opts.timeout) {
+                None => Some("hung".to_string()),
+                Some(r) if !r.conserved() => Some(format!("total {} -> {}", r.total_before, r.total_after)),
+                Some(r) if r.min_balance < 0 => Some(format!("a balance went negative ({})", r.min_balance)),
+                Some(r) => {
+                    transfers += r.completed + r.rejected;
+                    None
+                }
+            };
+            if let Some(p) = problem {
+                failures.push(format!("{strategy} round {round} ({config:?}): {p}"));
+            }
+        }
+        println!("  {:<12} {} transfers checked", strategy.name(), transfers);
+    }
+    for f in &failures {
+        println!("FAIL {f}");
+    }
+    if failures.is_empty() {
+        println!("all runs finished, every total conserved, no negative balances");
+        Ok(())
+    } else {
+        Err(format!("{} failing runs", failures.len()))
+    }
+}
+
+fn tour() -> Result<(), String> {
+    println!("--- naive: lock `from`, then `to` (the a/b deadlock from ../src/main.rs) ---");
+    let timeout = Duration::from_secs(2);
+    let tiny = Config { accounts: 2, threads: 2, transfers_per_thread: 1_000_000, ..Config::default() };
+    print_header();
+    print_row(Strategy::Naive, run_with_timeout(Strategy::Naive, &tiny, timeout).as_ref(), timeout);
+    println!("(thread 1 holds account 0 and waits for 1; thread 2 holds 1 and waits for 0)\n");
+
+    println!("--- the three fixes, same workload: 2 accounts, 2 threads ---");
+    let small = Config { transfers_per_thread: 100_000, ..tiny };
+    print_header();
+    for s in Strategy::SAFE {
+        print_row(s, run_with_timeout(s, &small, Duration::from_secs(30)).as_ref(), timeout);
+    }
+
+    println!("\n--- and with more accounts and threads ---");
+    let opts = Options {
+        strategies: Strategy::SAFE.to_vec(),
+        config: Config::default(),
+        rounds: 0,
+        timeout: Duration::from_secs(30),
+    };
+    run_table(&opts)?;
+    println!("\nordered: no cycle possible. trylock: never waits while holding. coordinator: no");
+    println!("shared locks at all, but one thread does every transfer.");
+    println!("`bank_transfers stress` runs hundreds of randomised workloads against all three.");
+    Ok(())
+}
+
+// Bad arguments get the usage line; a run that fails says why and nothing more.
+enum Failure {
+    Usage(String),
+    Run(String),
+}
+
+fn run(args: &[&str]) -> Result<(), Failure> {
+    let options = |rest| parse_options(rest).map_err(Failure::Usage);
+    match args {
+        [] => tour().map_err(Failure::Run),
+        ["run", rest @ ..] => run_table(&options(rest)?).map_err(Failure::Run),
+        ["stress", rest @ ..] => stress(&options(rest)?).map_err(Failure::Run),
+        [other, ..] => Err(Failure::Usage(format!("unknown command '{other}'"))),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    // Returning from main ends the process, including threads stuck in a deadlock.
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Usage(e)) => {
+            eprintln!("error: {e}");
+            eprintln!("usage: bank_transfers [run|stress] [--strategy S|all] [--accounts N] [--threads N] [--transfers N] [--seed N] [--rounds N] [--timeout SECS]");
+            ExitCode::FAILURE
+        }
+        Err(Failure::Run(e)) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
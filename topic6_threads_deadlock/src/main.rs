@@ -53,4 +53,14 @@ thus, when you run this code, it will hang indefinitely as both threads are wait
 to release the locks they hold.
 So the output of this code will be that it will not produce any output and will 
 hang indefinitely due to the deadlock.
+
+bank_transfers/ (`cargo run --release -p bank_transfers`) runs the same pattern as
+bank transfers between many accounts, and three ways to avoid it: lock ordering,
+try_lock with back-off, and a single coordinator thread.
+tracked_mutex/ (`cargo run -p tracked_mutex -- deadlock`) runs this program with
+instrumented mutexes that name the threads, locks and lines in the cycle instead
+of hanging.
+sync_problems/ (`cargo run --release -p sync_problems`) has the classic problems built
+on these primitives: producer/consumer, readers-writers, and the dining philosophers,
+whose naive version is this deadlock with five locks in a circle.
 */
\ No newline at end of file
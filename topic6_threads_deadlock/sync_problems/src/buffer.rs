@@ -0,0 +1,296 @@
+// src/buffer.rs
+//
+// Bounded buffer: producers put items into a queue of fixed capacity, consumers
+// take them out. A producer must wait while the queue is full, a consumer while
+// it is empty, and nobody may touch the queue while someone else is changing it.
+//
+//   Condvar   a Mutex<VecDeque> plus two condition variables: `not_full` for
+//             producers, `not_empty` for consumers. Waiting on a Condvar
+//             releases the mutex and re-takes it on wake-up; the condition is
+//             re-checked in a `while` because a wake-up only means "maybe":
+//             another thread may have got there first, and spurious wake-ups
+//             are allowed.
+//   Channel   std::sync::mpsc::sync_channel(capacity) does all of that inside.
+//             mpsc means multi-producer, SINGLE consumer, so the consumers share
+//             the receiver through a Mutex and take turns waiting on it.
+//
+// When the producers are done the buffer is CLOSED (Condvar) or the last sender
+// dropped (Channel), and consumers drain what is left, then stop.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{pause, Rng, Waits};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Condvar,
+    Channel,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Condvar, Kind::Channel];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Condvar => "condvar",
+            Kind::Channel => "channel",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Kind> {
+        Kind::ALL.into_iter().find(|k| k.name() == s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub producers: usize,
+    pub consumers: usize,
+    pub items_per_producer: usize,
+    pub capacity: usize,
+    /// Work per item, random in 0..=this: slow producers keep the buffer empty,
+    /// slow consumers keep it full.
+    pub produce: Duration,
+    pub consume: Duration,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            producers: 4,
+            consumers: 4,
+            items_per_producer: 20_000,
+            capacity: 16,
+            produce: Duration::ZERO,
+            consume: Duration::ZERO,
+            seed: 1,
+        }
+    }
+}
+
+pub struct BoundedBuffer<T> {
+    state: Mutex<State<T>>,
+    not_full: Condvar,
+    not_empty: Condvar,
+    capacity: usize,
+}
+
+struct State<T> {
+    items: VecDeque<T>,
+    closed: bool,
+    max_len: usize,
+}
+
+impl<T> BoundedBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        BoundedBuffer {
+            state: Mutex::new(State { items: VecDeque::with_capacity(capacity), closed: false, max_len: 0 }),
+            not_full: Condvar::new(),
+            not_empty: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Waits for a free slot. Gives the item back if the buffer is closed.
+    pub fn put(&self, item: T) -> Result<(), T> {
+        let mut st = self.state.lock().unwrap();
+        while st.items.len() == self.capacity && !st.closed {
+            st = self.not_full.wait(st).unwrap();
+        }
+        if st.closed {
+            return Err(item);
+        }
+        st.items.push_back(item);
+        st.max_len = st.max_len.max(st.items.len());
+        // notify_one is enough: one new item can satisfy one consumer.
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Waits for an item. None once the buffer is closed and empty.
+    pub fn take(&self) -> Option<T> {
+        let mut st = self.state.lock().unwrap();
+        while st.items.is_empty() && !st.closed {
+            st = self.not_empty.wait(st).unwrap();
+        }
+        let item = st.items.pop_front()?;
+        self.not_full.notify_one();
+        Some(item)
+    }
+
+    /// No more puts; consumers drain what is queued and then get None.
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        // Everyone must re-check: waiting producers fail, empty consumers stop.
+        self.not_full.notify_all();
+        self.not_empty.notify_all();
+    }
+
+    /// The most items that were ever queued at once.
+    pub fn max_len(&self) -> usize {
+        self.state.lock().unwrap().max_len
+    }
+}
+
+/// (producer, sequence number within that producer)
+type Item = (usize, usize);
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub kind: Kind,
+    pub config: Config,
+    pub elapsed: Duration,
+    /// Items taken by each consumer.
+    pub consumed: Vec<u64>,
+    pub put_waits: Waits,
+    pub take_waits: Waits,
+    /// Condvar only; the channel does not expose its length.
+    pub max_occupancy: Option<usize>,
+    /// Broken safety properties; empty on a correct run.
+    pub violations: Vec<String>,
+}
+
+impl Report {
+    pub fn items(&self) -> u64 {
+        self.consumed.iter().sum()
+    }
+}
+
+fn produce(p: usize, config: &Config, put: impl Fn(Item)) -> Waits {
+    let mut rng = Rng::new(config.seed ^ ((p as u64 + 1) << 32));
+    let mut waits = Waits::default();
+    for seq in 0..config.items_per_producer {
+        pause(rng.duration(config.produce));
+        let start = Instant::now();
+        put((p, seq));
+        waits.record(start.elapsed());
+    }
+    waits
+}
+
+fn consume(c: usize, config: &Config, take: impl Fn() -> Option<Item>) -> (Vec<Item>, Waits) {
+    let mut rng = Rng::new(config.seed ^ ((c as u64 + 1000) << 32));
+    let mut log = Vec::new();
+    let mut waits = Waits::default();
+    loop {
+        let start = Instant::now();
+        let Some(item) = take() else { break };
+        waits.record(start.elapsed());
+        log.push(item);
+        pause(rng.duration(config.consume));
+    }
+    (log, waits)
+}
+
+pub fn run(kind: Kind, config: &Config) -> Report {
+    let start = Instant::now();
+    let (logs, put_waits, max_occupancy) = match kind {
+        Kind::Condvar => {
+            let buffer = BoundedBuffer::new(config.capacity);
+            let (logs, put_waits) = thread::scope(|s| {
+                let buffer = &buffer;
+                let consumers: Vec<_> =
+                    (0..config.consumers).map(|c| s.spawn(move || consume(c, config, || buffer.take()))).collect();
+                let producers: Vec<_> = (0..config.producers)
+                    .map(|p| s.spawn(move || produce(p, config, |item| buffer.put(item).expect("closed only after the producers"))))
+                    .collect();
+                let put_waits: Vec<Waits> = producers.into_iter().map(|h| h.join().unwrap()).collect();
+                buffer.close();
+                (consumers.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>(), put_waits)
+            });
+            (logs, put_waits, Some(buffer.max_len()))
+        }
+        Kind::Channel => {
+            let (tx, rx) = mpsc::sync_channel::<Item>(config.capacity.max(1));
+            let rx = Mutex::new(rx);
+            let (logs, put_waits) = thread::scope(|s| {
+                let rx = &rx;
+                let consumers: Vec<_> =
+                    (0..config.consumers).map(|c| s.spawn(move || consume(c, config, || rx.lock().unwrap().recv().ok()))).collect();
+                let producers: Vec<_> = (0..config.producers)
+                    .map(|p| {
+                        let tx = tx.clone();
+                        s.spawn(move || produce(p, config, |item| tx.send(item).expect("consumers outlive the producers")))
+                    })
+                    .collect();
+                // Once the producers' clones are gone too, recv() returns Err: closed.
+                drop(tx);
+                let put_waits: Vec<Waits> = producers.into_iter().map(|h| h.join().unwrap()).collect();
+                (consumers.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>(), put_waits)
+            });
+            (logs, put_waits, None)
+        }
+    };
+    let elapsed = start.elapsed();
+
+    let mut violations = check(config, &logs);
+    if let Some(max) = max_occupancy
+        && max > config.capacity
+    {
+        violations.push(format!("{max} items queued at once, capacity is {}", config.capacity));
+    }
+    let mut puts = Waits::default();
+    put_waits.iter().for_each(|w| puts.merge(w));
+    let mut takes = Waits::default();
+    logs.iter().for_each(|(_, w)| takes.merge(w));
+    Report {
+        kind,
+        config: *config,
+        elapsed,
+        consumed: logs.iter().map(|(log, _)| log.len() as u64).collect(),
+        put_waits: puts,
+        take_waits: takes,
+        max_occupancy,
+        violations,
+    }
+}
+
+// Exactly once, and FIFO: a consumer takes items in queue order, so it sees
+// each producer's items in increasing sequence.
+fn check(config: &Config, logs: &[(Vec<Item>, Waits)]) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut seen = vec![vec![0u32; config.items_per_producer]; config.producers];
+    for (c, (log, _)) in logs.iter().enumerate() {
+        let mut last = vec![None; config.producers];
+        for &(p, seq) in log {
+            seen[p][seq] += 1;
+            if last[p].is_some_and(|l| seq <= l) {
+                violations.push(format!("consumer {c} took producer {p}'s item {seq} after item {}", last[p].unwrap()));
+            }
+            last[p] = Some(seq);
+        }
+    }
+    for (p, counts) in seen.iter().enumerate() {
+        let lost = counts.iter().filter(|&&n| n == 0).count();
+        let duplicated = counts.iter().filter(|&&n| n > 1).count();
+        if lost > 0 {
+            violations.push(format!("{lost} of producer {p}'s items were never consumed"));
+        }
+        if duplicated > 0 {
+            violations.push(format!("{duplicated} of producer {p}'s items were consumed more than once"));
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_item_consumed_once_in_order() {
+        for capacity in [1, 3] {
+            let config = Config { producers: 3, consumers: 2, items_per_producer: 500, capacity, ..Config::default() };
+            for kind in Kind::ALL {
+                let r = run(kind, &config);
+                assert!(r.violations.is_empty(), "{} capacity {capacity}: {:?}", kind.name(), r.violations);
+                assert_eq!(r.items(), 1500);
+            }
+        }
+    }
+}
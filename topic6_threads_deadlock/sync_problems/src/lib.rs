@@ -0,0 +1,111 @@
+// src/lib.rs
+//
+// The three textbook synchronization problems, each with several solutions,
+// a workload you can tune, and checks that the solution is actually safe:
+//
+//   buffer         bounded-buffer producer/consumer: a Mutex + two Condvars, and
+//                  a std sync_channel. Safe = every item consumed exactly once,
+//                  in order per producer, never more than `capacity` queued.
+//   rw             readers-writers: one hand-written RwLock with three policies
+//                  (reader preference, writer preference, fair FIFO tickets).
+//                  Safe = a writer is always alone, and no reader sees a half
+//                  finished write.
+//   philosophers   dining philosophers: naive, ordered forks, a waiter, try-lock
+//                  with back-off, and a monitor. Safe = neighbours never eat at
+//                  the same time; live = nobody waits forever.
+//
+// SAFETY ("nothing bad happens") is checked on every run. LIVENESS ("something
+// good eventually happens") cannot be checked in finite time; the runs report
+// it as starvation metrics instead: how long the longest wait was and how
+// unevenly the work was spread.
+
+pub mod buffer;
+pub mod philosophers;
+pub mod rw;
+
+use std::fmt;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// xorshift64: deterministic per seed, good enough for workloads.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in 0..n (n > 0).
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform in 0..=max.
+    pub fn duration(&mut self, max: Duration) -> Duration {
+        if max.is_zero() { max } else { Duration::from_nanos(self.next_u64() % (max.as_nanos() as u64 + 1)) }
+    }
+}
+
+/// How long threads waited for something: a lock, a slot, a fork.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Waits {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl Waits {
+    pub fn record(&mut self, d: Duration) {
+        self.count += 1;
+        self.total += d;
+        self.max = self.max.max(d);
+    }
+
+    pub fn merge(&mut self, other: &Waits) {
+        self.count += other.count;
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 { Duration::ZERO } else { self.total / self.count as u32 }
+    }
+}
+
+impl fmt::Display for Waits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mean {:.3} ms, max {:.3} ms", self.mean().as_secs_f64() * 1e3, self.max.as_secs_f64() * 1e3)
+    }
+}
+
+/// Min and max over per-thread counts: far apart = some thread got much less.
+pub fn spread(counts: &[u64]) -> (u64, u64) {
+    (counts.iter().copied().min().unwrap_or(0), counts.iter().copied().max().unwrap_or(0))
+}
+
+/// Sleeps, except for zero (which would still yield the CPU).
+pub fn pause(d: Duration) {
+    if !d.is_zero() {
+        thread::sleep(d);
+    }
+}
+
+/// Runs `f` on its own thread and gives up after `limit`. `None` means it did
+/// not finish, e.g. it deadlocked; its threads then stay blocked in the
+/// background until the process exits.
+pub fn with_timeout<T: Send + 'static>(limit: Duration, f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(limit).ok()
+}
@@ -0,0 +1,373 @@
+// src/main.rs
+//
+// `sync_problems buffer [--kind condvar|channel|all] [--producers N] [--consumers N] [--items N]
+//                       [--capacity N] [--produce-us N] [--consume-us N] [--seed N]`
+// `sync_problems rw [--policy readers|writers|fair|all] [--readers N] [--writers N] [--ms N]
+//                   [--read-us N] [--write-us N] [--think-us N] [--seed N]`
+// `sync_problems philosophers [--strategy naive|ordered|waiter|trylock|monitor|all] [--n N] [--ms N]
+//                             [--think-us N] [--eat-us N] [--seed N] [--timeout SECS]`
+//     one workload per variant: throughput, waits, how evenly the work was spread,
+//     and whether the safety checks held
+// `sync_problems check [--rounds N] [--seed N]`
+//     many small randomised workloads against every variant that should be safe;
+//     fails on any safety violation or hang
+// No arguments: a tour of all three problems.
+//
+// Times ending in -us are microseconds; work and pauses are random in 0..=that.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use sync_problems::{buffer, philosophers, rw, spread, with_timeout, Rng};
+
+type Flags<'a> = Vec<(&'a str, &'a str)>;
+
+fn parse_flags<'a>(args: &[&'a str], allowed: &[&str]) -> Result<Flags<'a>, String> {
+    let mut flags = Vec::new();
+    let mut it = args.iter();
+    while let Some(&arg) = it.next() {
+        if !allowed.contains(&arg) {
+            return Err(format!("unknown argument '{arg}'"));
+        }
+        flags.push((arg, *it.next().ok_or(format!("{arg} needs a value"))?));
+    }
+    Ok(flags)
+}
+
+fn number(flag: &str, value: &str) -> Result<u64, String> {
+    value.replace('_', "").parse().map_err(|_| format!("{flag} takes a number, got '{value}'"))
+}
+
+fn micros(flag: &str, value: &str) -> Result<Duration, String> {
+    number(flag, value).map(Duration::from_micros)
+}
+
+fn status(violations: &[String]) -> String {
+    match violations.first() {
+        None => "ok".into(),
+        Some(first) => format!("VIOLATION: {first} (+{} more)", violations.len() - 1),
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1e3
+}
+
+fn buffer_header() {
+    println!(
+        "  {:<8} {:>8} {:>11} {:>15} {:>19} {:>19} {:>6}  safety",
+        "kind", "ms", "items/s", "per consumer", "put wait mean/max", "take wait mean/max", "max q"
+    );
+}
+
+fn buffer_row(r: &buffer::Report) {
+    let (lo, hi) = spread(&r.consumed);
+    println!(
+        "  {:<8} {:>8.1} {:>11.0} {:>15} {:>9.3}/{:<9.3} {:>9.3}/{:<9.3} {:>6}  {}",
+        r.kind.name(),
+        ms(r.elapsed),
+        r.items() as f64 / r.elapsed.as_secs_f64(),
+        format!("{lo}..{hi}"),
+        ms(r.put_waits.mean()),
+        ms(r.put_waits.max),
+        ms(r.take_waits.mean()),
+        ms(r.take_waits.max),
+        r.max_occupancy.map_or("-".into(), |m| m.to_string()),
+        status(&r.violations)
+    );
+}
+
+fn buffer_cmd(args: &[&str]) -> Result<(), String> {
+    let flags = parse_flags(
+        args,
+        &["--kind", "--producers", "--consumers", "--items", "--capacity", "--produce-us", "--consume-us", "--seed"],
+    )?;
+    let mut kinds = buffer::Kind::ALL.to_vec();
+    let mut config = buffer::Config::default();
+    for (flag, value) in flags {
+        match flag {
+            "--kind" if value == "all" => kinds = buffer::Kind::ALL.to_vec(),
+            "--kind" => kinds = vec![buffer::Kind::parse(value).ok_or(format!("unknown kind '{value}', expected condvar, channel or all"))?],
+            "--producers" => config.producers = number(flag, value)?.max(1) as usize,
+            "--consumers" => config.consumers = number(flag, value)?.max(1) as usize,
+            "--items" => config.items_per_producer = number(flag, value)? as usize,
+            "--capacity" => config.capacity = number(flag, value)?.max(1) as usize,
+            "--produce-us" => config.produce = micros(flag, value)?,
+            "--consume-us" => config.consume = micros(flag, value)?,
+            _ => config.seed = number(flag, value)?,
+        }
+    }
+    println!(
+        "{} producers x {} items, {} consumers, capacity {}",
+        config.producers, config.items_per_producer, config.consumers, config.capacity
+    );
+    buffer_header();
+    let mut broken = 0;
+    for kind in kinds {
+        let r = buffer::run(kind, &config);
+        buffer_row(&r);
+        broken += !r.violations.is_empty() as usize;
+    }
+    if broken > 0 { Err(format!("{broken} variant(s) broke a safety property")) } else { Ok(()) }
+}
+
+fn rw_header() {
+    println!(
+        "  {:<8} {:>7} {:>7} {:>16} {:>20} {:>20} {:>8}  safety",
+        "policy", "reads", "writes", "writes/writer", "read wait mean/max", "write wait mean/max", "readers"
+    );
+}
+
+fn rw_row(r: &rw::Report) {
+    let (lo, hi) = spread(&r.writes);
+    println!(
+        "  {:<8} {:>7} {:>7} {:>16} {:>9.2}/{:<10.2} {:>9.2}/{:<10.2} {:>8}  {}",
+        r.policy.name(),
+        r.reads.iter().sum::<u64>(),
+        r.writes.iter().sum::<u64>(),
+        format!("{lo}..{hi}"),
+        ms(r.read_waits.mean()),
+        ms(r.read_waits.max),
+        ms(r.write_waits.mean()),
+        ms(r.write_waits.max),
+        r.max_concurrent_readers,
+        status(&r.violations)
+    );
+}
+
+fn rw_cmd(args: &[&str]) -> Result<(), String> {
+    let flags =
+        parse_flags(args, &["--policy", "--readers", "--writers", "--ms", "--read-us", "--write-us", "--think-us", "--seed"])?;
+    let mut policies = rw::Policy::ALL.to_vec();
+    let mut config = rw::Config::default();
+    for (flag, value) in flags {
+        match flag {
+            "--policy" if value == "all" => policies = rw::Policy::ALL.to_vec(),
+            "--policy" => {
+                policies = vec![rw::Policy::parse(value).ok_or(format!("unknown policy '{value}', expected readers, writers, fair or all"))?]
+            }
+            "--readers" => config.readers = number(flag, value)? as usize,
+            "--writers" => config.writers = number(flag, value)? as usize,
+            "--ms" => config.duration = Duration::from_millis(number(flag, value)?),
+            "--read-us" => config.read_hold = micros(flag, value)?,
+            "--write-us" => config.write_hold = micros(flag, value)?,
+            "--think-us" => config.think = micros(flag, value)?,
+            _ => config.seed = number(flag, value)?,
+        }
+    }
+    println!(
+        "{} readers holding {:.1} ms, {} writers holding {:.1} ms, pauses up to {:.1} ms, for {} ms; waits in ms",
+        config.readers,
+        ms(config.read_hold),
+        config.writers,
+        ms(config.write_hold),
+        ms(config.think),
+        config.duration.as_millis()
+    );
+    rw_header();
+    let mut broken = 0;
+    for policy in policies {
+        let r = rw::run(policy, &config);
+        rw_row(&r);
+        broken += !r.violations.is_empty() as usize;
+    }
+    if broken > 0 { Err(format!("{broken} policies broke a safety property")) } else { Ok(()) }
+}
+
+fn philosophers_header() {
+    println!(
+        "  {:<8} {:>7} {:>15} {:>22} {:>8}  safety",
+        "strategy", "meals", "per philosopher", "hungry ms mean/max", "retries"
+    );
+}
+
+fn philosophers_row(strategy: philosophers::Strategy, report: Option<&philosophers::Report>, limit: Duration) {
+    match report {
+        Some(r) => {
+            let (lo, hi) = spread(&r.meals);
+            println!(
+                "  {:<8} {:>7} {:>15} {:>10.2}/{:<11.2} {:>8}  {}",
+                strategy.name(),
+                r.meals.iter().sum::<u64>(),
+                format!("{lo}..{hi}"),
+                ms(r.hungry.mean()),
+                ms(r.hungry.max),
+                r.retries,
+                status(&r.violations)
+            );
+        }
+        None => println!("  {:<8} DEADLOCKED (still not finished {}s after dinner ended)", strategy.name(), limit.as_secs()),
+    }
+}
+
+fn philosophers_cmd(args: &[&str]) -> Result<(), String> {
+    let flags = parse_flags(args, &["--strategy", "--n", "--ms", "--think-us", "--eat-us", "--seed", "--timeout"])?;
+    let mut strategies = philosophers::Strategy::ALL.to_vec();
+    let mut config = philosophers::Config::default();
+    let mut limit = Duration::from_secs(2);
+    for (flag, value) in flags {
+        match flag {
+            "--strategy" if value == "all" => strategies = philosophers::Strategy::ALL.to_vec(),
+            "--strategy" => {
+                strategies = vec![philosophers::Strategy::parse(value).ok_or(format!(
+                    "unknown strategy '{value}', expected naive, ordered, waiter, trylock, monitor or all"
+                ))?]
+            }
+            "--n" => config.philosophers = number(flag, value)?.max(2) as usize,
+            "--ms" => config.duration = Duration::from_millis(number(flag, value)?),
+            "--think-us" => config.think = micros(flag, value)?,
+            "--eat-us" => config.eat = micros(flag, value)?,
+            "--timeout" => limit = Duration::from_secs(number(flag, value)?),
+            _ => config.seed = number(flag, value)?,
+        }
+    }
+    run_philosophers(&strategies, &config, limit)
+}
+
+fn run_philosophers(strategies: &[philosophers::Strategy], config: &philosophers::Config, limit: Duration) -> Result<(), String> {
+    println!(
+        "{} philosophers, thinking up to {:.1} ms, eating {:.1} ms, for {} ms",
+        config.philosophers,
+        ms(config.think),
+        ms(config.eat),
+        config.duration.as_millis()
+    );
+    philosophers_header();
+    let mut broken = 0;
+    for &strategy in strategies {
+        let report = philosophers::run_with_timeout(strategy, config, limit);
+        philosophers_row(strategy, report.as_ref(), limit);
+        broken += report.is_none_or(|r| !r.violations.is_empty()) as usize;
+    }
+    if broken > 0 { Err(format!("{broken} strategies deadlocked or broke a safety property")) } else { Ok(()) }
+}
+
+fn check(args: &[&str]) -> Result<(), String> {
+    let mut rounds = 20;
+    let mut seed = 1;
+    for (flag, value) in parse_flags(args, &["--rounds", "--seed"])? {
+        match flag {
+            "--rounds" => rounds = number(flag, value)? as usize,
+            _ => seed = number(flag, value)?,
+        }
+    }
+    let limit = Duration::from_secs(10);
+    let mut rng = Rng::new(seed);
+    let mut failures = Vec::new();
+    let mut fail = |what: String, problem: Option<String>| {
+        if let Some(p) = problem {
+            failures.push(format!("{what}: {p}"));
+        }
+    };
+    let small = |rng: &mut Rng, max_us: usize| Duration::from_micros(rng.below(max_us + 1) as u64);
+    println!("{rounds} rounds of random workloads per variant, {}s limit per run", limit.as_secs());
+    for round in 0..rounds {
+        // Mostly tiny buffers and few threads: where lost wake-ups and overflows show up.
+        let config = buffer::Config {
+            producers: 1 + rng.below(4),
+            consumers: 1 + rng.below(4),
+            items_per_producer: 500 + rng.below(2_500),
+            capacity: 1 + rng.below(8),
+            produce: small(&mut rng, 20),
+            consume: small(&mut rng, 20),
+            seed: rng.next_u64(),
+        };
+        for kind in buffer::Kind::ALL {
+            let r = with_timeout(limit, move || buffer::run(kind, &config));
+            fail(format!("buffer {} round {round} ({config:?})", kind.name()), problem(r.map(|r| r.violations)));
+        }
+
+        let config = rw::Config {
+            readers: 1 + rng.below(6),
+            writers: 1 + rng.below(3),
+            duration: Duration::from_millis(30),
+            read_hold: small(&mut rng, 200),
+            write_hold: small(&mut rng, 200),
+            think: small(&mut rng, 200),
+            seed: rng.next_u64(),
+        };
+        for policy in rw::Policy::ALL {
+            let r = with_timeout(limit, move || rw::run(policy, &config));
+            fail(format!("rw {policy} round {round} ({config:?})"), problem(r.map(|r| r.violations)));
+        }
+
+        let config = philosophers::Config {
+            philosophers: 2 + rng.below(6),
+            duration: Duration::from_millis(30),
+            think: small(&mut rng, 200),
+            eat: small(&mut rng, 200),
+            seed: rng.next_u64(),
+        };
+        for strategy in philosophers::Strategy::SAFE {
+            let r = philosophers::run_with_timeout(strategy, &config, limit);
+            fail(format!("philosophers {strategy} round {round} ({config:?})"), problem(r.map(|r| r.violations)));
+        }
+    }
+    for f in &failures {
+        println!("FAIL {f}");
+    }
+    if failures.is_empty() {
+        println!("buffer: nothing lost, duplicated, reordered or over capacity");
+        println!("rw: no writer ever shared the lock, no reader saw a half-done write");
+        println!("philosophers: no neighbours ate together, no strategy deadlocked");
+        Ok(())
+    } else {
+        Err(format!("{} failing runs", failures.len()))
+    }
+}
+
+// None if the run finished with no violations.
+fn problem(violations: Option<Vec<String>>) -> Option<String> {
+    match violations {
+        None => Some("hung".into()),
+        Some(v) if v.is_empty() => None,
+        Some(v) => Some(v.join("; ")),
+    }
+}
+
+fn tour() -> Result<(), String> {
+    println!("=== producer/consumer ===");
+    buffer_cmd(&[])?;
+    println!("\nslow consumers: the buffer stays full, so producers do the waiting");
+    buffer_cmd(&["--items", "2000", "--consume-us", "100", "--capacity", "4"])?;
+
+    println!("\n=== readers-writers ===");
+    rw_cmd(&[])?;
+    println!("readers: overlapping readers keep the count above 0, so the writers wait for the whole run.");
+    println!("writers: the same for readers, behind the waiting writers. fair: arrival order, both get turns.");
+
+    println!("\n=== dining philosophers ===");
+    println!("naive, with no thinking: everyone grabs their left fork at once");
+    let hungry = philosophers::Config { think: Duration::ZERO, eat: Duration::ZERO, ..philosophers::Config::default() };
+    // Deadlocking is the expected result here, not a failure.
+    let _ = run_philosophers(&[philosophers::Strategy::Naive], &hungry, Duration::from_secs(2));
+    println!();
+    run_philosophers(&philosophers::Strategy::SAFE, &philosophers::Config::default(), Duration::from_secs(2))?;
+    println!("compare min..max meals and the max hungry time: that is how close each came to starving someone.");
+    println!("`sync_problems check` runs every safe variant against random workloads.");
+    Ok(())
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        [] => tour(),
+        ["buffer", rest @ ..] => buffer_cmd(rest),
+        ["rw", rest @ ..] => rw_cmd(rest),
+        ["philosophers", rest @ ..] => philosophers_cmd(rest),
+        ["check", rest @ ..] => check(rest),
+        [other, ..] => Err(format!("unknown command '{other}', expected buffer, rw, philosophers or check")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    // Returning from main ends the process, including philosophers stuck in a deadlock.
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,324 @@
+// src/philosophers.rs
+//
+// N philosophers sit around a table with one fork between each pair. To eat, a
+// philosopher needs both neighbouring forks. The ways to pick them up:
+//
+//   Naive    left fork, then right. If everyone picks up their left fork at the
+//            same moment, everyone waits for their right one forever: a circular
+//            wait, the same deadlock as ../../src/main.rs with N locks.
+//   Ordered  lower-numbered fork first (Dijkstra's resource hierarchy). The last
+//            philosopher goes right-then-left, so the circle cannot close.
+//   Waiter   a counting semaphore lets at most N-1 sit down at once. With one
+//            seat empty, at least one seated philosopher can get both forks.
+//            The others still hold one fork while waiting for the next, so the
+//            seated often form a chain in which only the last one eats.
+//   TryLock  left fork, then try the right one; if it is taken, put the left one
+//            back, wait a random moment and start over. No deadlock, but the
+//            retries are wasted work, and an unlucky philosopher can keep losing.
+//   Monitor  no fork locks: one Mutex guards everybody's state (thinking, hungry,
+//            eating) and a hungry philosopher eats only when neither neighbour
+//            is eating (Tanenbaum's solution). Deadlock-free, but two neighbours
+//            can take turns so that the one between them starves.
+//
+// Safety: two neighbours never eat at the same time. Liveness shows up in the
+// metrics: meals per philosopher and the longest time anyone stayed hungry.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{pause, with_timeout, Rng, Waits};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Naive,
+    Ordered,
+    Waiter,
+    TryLock,
+    Monitor,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [Strategy::Naive, Strategy::Ordered, Strategy::Waiter, Strategy::TryLock, Strategy::Monitor];
+    /// The strategies that can never deadlock.
+    pub const SAFE: [Strategy; 4] = [Strategy::Ordered, Strategy::Waiter, Strategy::TryLock, Strategy::Monitor];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Naive => "naive",
+            Strategy::Ordered => "ordered",
+            Strategy::Waiter => "waiter",
+            Strategy::TryLock => "trylock",
+            Strategy::Monitor => "monitor",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Strategy> {
+        Strategy::ALL.into_iter().find(|st| st.name() == s)
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub philosophers: usize,
+    /// How long dinner lasts.
+    pub duration: Duration,
+    /// Random in 0..=this, before each meal.
+    pub think: Duration,
+    pub eat: Duration,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            philosophers: 5,
+            duration: Duration::from_millis(500),
+            think: Duration::from_millis(1),
+            eat: Duration::from_millis(1),
+            seed: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub strategy: Strategy,
+    pub config: Config,
+    pub elapsed: Duration,
+    pub meals: Vec<u64>,
+    /// From getting hungry to starting to eat.
+    pub hungry: Waits,
+    /// TryLock only: forks put back because the other one was taken.
+    pub retries: u64,
+    pub violations: Vec<String>,
+}
+
+struct Semaphore {
+    permits: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl Semaphore {
+    fn acquire(&self) {
+        let mut permits = self.permits.lock().unwrap();
+        while *permits == 0 {
+            permits = self.freed.wait(permits).unwrap();
+        }
+        *permits -= 1;
+    }
+
+    fn release(&self) {
+        *self.permits.lock().unwrap() += 1;
+        self.freed.notify_one();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phil {
+    Thinking,
+    Hungry,
+    Eating,
+}
+
+struct Monitor {
+    states: Mutex<Vec<Phil>>,
+    // One per philosopher, so a release wakes exactly the neighbour it let in.
+    turn: Vec<Condvar>,
+}
+
+impl Monitor {
+    // Lets `i` eat if it is hungry and neither neighbour is eating.
+    fn test(&self, states: &mut [Phil], i: usize) {
+        let n = states.len();
+        if states[i] == Phil::Hungry && states[(i + n - 1) % n] != Phil::Eating && states[(i + 1) % n] != Phil::Eating {
+            states[i] = Phil::Eating;
+            self.turn[i].notify_one();
+        }
+    }
+
+    fn pick_up(&self, i: usize) {
+        let mut states = self.states.lock().unwrap();
+        states[i] = Phil::Hungry;
+        self.test(&mut states, i);
+        while states[i] != Phil::Eating {
+            states = self.turn[i].wait(states).unwrap();
+        }
+    }
+
+    fn put_down(&self, i: usize) {
+        let mut states = self.states.lock().unwrap();
+        let n = states.len();
+        states[i] = Phil::Thinking;
+        self.test(&mut states, (i + n - 1) % n);
+        self.test(&mut states, (i + 1) % n);
+    }
+}
+
+struct Table {
+    strategy: Strategy,
+    forks: Vec<Mutex<()>>,
+    waiter: Semaphore,
+    monitor: Monitor,
+    eating: Vec<AtomicBool>,
+    retries: AtomicU64,
+    violations: Mutex<Vec<String>>,
+}
+
+impl Table {
+    fn new(strategy: Strategy, n: usize) -> Self {
+        Table {
+            strategy,
+            forks: (0..n).map(|_| Mutex::new(())).collect(),
+            waiter: Semaphore { permits: Mutex::new(n.saturating_sub(1).max(1)), freed: Condvar::new() },
+            monitor: Monitor { states: Mutex::new(vec![Phil::Thinking; n]), turn: (0..n).map(|_| Condvar::new()).collect() },
+            eating: (0..n).map(|_| AtomicBool::new(false)).collect(),
+            retries: AtomicU64::new(0),
+            violations: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Returns how long `i` was hungry, from `since` to the first bite.
+    fn eat(&self, i: usize, since: Instant, config: &Config) -> Duration {
+        let hungry = since.elapsed();
+        let n = self.eating.len();
+        // Announce first, then look: of two neighbours starting together, at
+        // least one sees the other (SeqCst orders the four accesses).
+        self.eating[i].store(true, Ordering::SeqCst);
+        for neighbour in [(i + n - 1) % n, (i + 1) % n] {
+            if neighbour != i && self.eating[neighbour].load(Ordering::SeqCst) {
+                let mut v = self.violations.lock().unwrap();
+                if v.len() < 10 {
+                    v.push(format!("philosophers {i} and {neighbour} ate at the same time"));
+                }
+            }
+        }
+        pause(config.eat);
+        self.eating[i].store(false, Ordering::SeqCst);
+        hungry
+    }
+
+    fn dine(&self, i: usize, rng: &mut Rng, config: &Config) -> Duration {
+        let since = Instant::now();
+        let n = self.forks.len();
+        let (left, right) = (i, (i + 1) % n);
+        match self.strategy {
+            Strategy::Naive => {
+                let _l = self.forks[left].lock().unwrap();
+                // Reaching for the other fork takes a moment; the others use it
+                // to pick up their left forks.
+                thread::yield_now();
+                let _r = self.forks[right].lock().unwrap();
+                self.eat(i, since, config)
+            }
+            Strategy::Ordered => {
+                let _first = self.forks[left.min(right)].lock().unwrap();
+                let _second = self.forks[left.max(right)].lock().unwrap();
+                self.eat(i, since, config)
+            }
+            Strategy::Waiter => {
+                self.waiter.acquire();
+                let hungry = {
+                    let _l = self.forks[left].lock().unwrap();
+                    let _r = self.forks[right].lock().unwrap();
+                    self.eat(i, since, config)
+                };
+                self.waiter.release();
+                hungry
+            }
+            Strategy::TryLock => loop {
+                let _l = self.forks[left].lock().unwrap();
+                if let Ok(_r) = self.forks[right].try_lock() {
+                    return self.eat(i, since, config);
+                }
+                drop(_l);
+                self.retries.fetch_add(1, Ordering::Relaxed);
+                pause(rng.duration(Duration::from_micros(200)));
+            },
+            Strategy::Monitor => {
+                self.monitor.pick_up(i);
+                let hungry = self.eat(i, since, config);
+                self.monitor.put_down(i);
+                hungry
+            }
+        }
+    }
+}
+
+/// With `Strategy::Naive` this can block forever; see `run_with_timeout`.
+pub fn run(strategy: Strategy, config: &Config) -> Report {
+    // A single philosopher has one fork for both hands: left and right would
+    // be the same Mutex, and locking it twice deadlocks.
+    let n = config.philosophers.max(2);
+    let table = Table::new(strategy, n);
+    let stop = AtomicBool::new(false);
+    let start = Instant::now();
+    let results: Vec<(u64, Waits)> = thread::scope(|s| {
+        let (table, stop) = (&table, &stop);
+        let handles: Vec<_> = (0..n)
+            .map(|i| {
+                s.spawn(move || {
+                    let mut rng = Rng::new(config.seed ^ ((i as u64 + 1) << 32));
+                    let (mut meals, mut hungry) = (0, Waits::default());
+                    while !stop.load(Ordering::Relaxed) {
+                        pause(rng.duration(config.think));
+                        hungry.record(table.dine(i, &mut rng, config));
+                        meals += 1;
+                    }
+                    (meals, hungry)
+                })
+            })
+            .collect();
+        thread::sleep(config.duration);
+        stop.store(true, Ordering::Relaxed);
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let elapsed = start.elapsed();
+    let mut hungry = Waits::default();
+    results.iter().for_each(|(_, w)| hungry.merge(w));
+    Report {
+        strategy,
+        config: *config,
+        elapsed,
+        meals: results.iter().map(|(m, _)| *m).collect(),
+        hungry,
+        retries: table.retries.load(Ordering::Relaxed),
+        violations: table.violations.into_inner().unwrap(),
+    }
+}
+
+/// Like `run`, but gives up after `limit` past the end of dinner. `None` means
+/// the philosophers deadlocked.
+pub fn run_with_timeout(strategy: Strategy, config: &Config, limit: Duration) -> Option<Report> {
+    let config = *config;
+    with_timeout(config.duration + limit, move || run(strategy, &config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_strategies_finish_without_neighbours_eating_together() {
+        let config = Config {
+            philosophers: 5,
+            duration: Duration::from_millis(50),
+            think: Duration::from_micros(100),
+            eat: Duration::from_micros(100),
+            seed: 3,
+        };
+        for strategy in Strategy::SAFE {
+            let r = run_with_timeout(strategy, &config, Duration::from_secs(5))
+                .unwrap_or_else(|| panic!("{strategy} did not finish"));
+            assert!(r.violations.is_empty(), "{strategy}: {:?}", r.violations);
+        }
+    }
+}
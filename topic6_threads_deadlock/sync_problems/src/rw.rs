@@ -0,0 +1,355 @@
+// src/rw.rs
+//
+// Readers-writers: any number of readers may share the data, a writer needs it
+// alone. The interesting part is who goes next when both are waiting:
+//
+//   ReaderPreference  a reader gets in whenever no writer is INSIDE. While the
+//                     readers overlap, the reader count never drops to 0, and a
+//                     waiting writer can starve for as long as they keep coming.
+//   WriterPreference  a reader also stays out while a writer is WAITING. Writers
+//                     get in quickly; now a steady stream of writers starves the
+//                     readers.
+//   Fair              everybody takes a ticket on arrival and is served in ticket
+//                     order. Consecutive readers still share, but nobody is
+//                     overtaken, so nobody starves.
+//
+// All three are the same lock: a Mutex<State> and one Condvar that every
+// release notifies. One condvar with notify_all wakes more threads than needed;
+// it is the simplest version that is obviously correct.
+//
+// std::sync::RwLock leaves the policy to the OS (on Linux it currently prefers
+// writers); this one makes it explicit.
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{pause, Rng, Waits};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    ReaderPreference,
+    WriterPreference,
+    Fair,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 3] = [Policy::ReaderPreference, Policy::WriterPreference, Policy::Fair];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Policy::ReaderPreference => "readers",
+            Policy::WriterPreference => "writers",
+            Policy::Fair => "fair",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Policy> {
+        Policy::ALL.into_iter().find(|p| p.name() == s)
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    readers: usize,
+    writer: bool,
+    waiting_writers: usize,
+    // Fair only: the ticket of the next thread allowed in.
+    next_ticket: u64,
+    serving: u64,
+}
+
+pub struct RwLock<T> {
+    policy: Policy,
+    state: Mutex<State>,
+    changed: Condvar,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: same bounds as std::sync::RwLock. Readers share &T across threads
+// (T: Sync), a writer gets &mut T on whichever thread holds the lock (T: Send).
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+unsafe impl<T: Send> Send for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    pub fn new(policy: Policy, value: T) -> Self {
+        RwLock { policy, state: Mutex::new(State::default()), changed: Condvar::new(), data: UnsafeCell::new(value) }
+    }
+
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        let mut st = self.state.lock().unwrap();
+        let ticket = st.next_ticket;
+        st.next_ticket += 1;
+        loop {
+            let may_enter = match self.policy {
+                Policy::ReaderPreference => !st.writer,
+                Policy::WriterPreference => !st.writer && st.waiting_writers == 0,
+                Policy::Fair => !st.writer && st.serving == ticket,
+            };
+            if may_enter {
+                break;
+            }
+            st = self.changed.wait(st).unwrap();
+        }
+        st.readers += 1;
+        if self.policy == Policy::Fair {
+            // Let the next ticket in line check; if it is a reader, it shares.
+            st.serving += 1;
+            self.changed.notify_all();
+        }
+        ReadGuard { lock: self }
+    }
+
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        let mut st = self.state.lock().unwrap();
+        let ticket = st.next_ticket;
+        st.next_ticket += 1;
+        st.waiting_writers += 1;
+        loop {
+            let free = !st.writer && st.readers == 0;
+            if free && (self.policy != Policy::Fair || st.serving == ticket) {
+                break;
+            }
+            st = self.changed.wait(st).unwrap();
+        }
+        st.waiting_writers -= 1;
+        st.writer = true;
+        st.serving += 1;
+        WriteGuard { lock: self }
+    }
+}
+
+pub struct ReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: while a ReadGuard exists `readers` > 0, so no writer is inside.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut st = self.lock.state.lock().unwrap();
+        st.readers -= 1;
+        if st.readers == 0 {
+            self.lock.changed.notify_all();
+        }
+    }
+}
+
+pub struct WriteGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `writer` is set, so this guard is the only way in.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as above.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.lock().unwrap().writer = false;
+        self.lock.changed.notify_all();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub readers: usize,
+    pub writers: usize,
+    /// How long the threads keep coming back for more.
+    pub duration: Duration,
+    pub read_hold: Duration,
+    pub write_hold: Duration,
+    /// Pause between two visits, random in 0..=this.
+    pub think: Duration,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            // Enough readers, reading often enough, that there is always one
+            // inside: the case where the preference decides who starves.
+            readers: 8,
+            writers: 2,
+            duration: Duration::from_millis(500),
+            read_hold: Duration::from_millis(2),
+            write_hold: Duration::from_micros(500),
+            think: Duration::from_micros(200),
+            seed: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub policy: Policy,
+    pub config: Config,
+    pub elapsed: Duration,
+    /// Visits per reader thread, then per writer thread.
+    pub reads: Vec<u64>,
+    pub writes: Vec<u64>,
+    pub read_waits: Waits,
+    pub write_waits: Waits,
+    pub max_concurrent_readers: usize,
+    pub violations: Vec<String>,
+}
+
+// The protected data: a writer sets every slot to the next value, one at a
+// time, pausing in between. A reader that ever sees two different values saw a
+// write half done.
+const SLOTS: usize = 4;
+
+#[derive(Default)]
+struct Checker {
+    readers_inside: AtomicUsize,
+    writers_inside: AtomicUsize,
+    max_readers: AtomicUsize,
+    violations: Mutex<Vec<String>>,
+}
+
+impl Checker {
+    fn violation(&self, message: String) {
+        // Keep the first few; a broken lock would otherwise log millions.
+        let mut v = self.violations.lock().unwrap();
+        if v.len() < 10 {
+            v.push(message);
+        }
+    }
+}
+
+fn reader(id: usize, lock: &RwLock<[u64; SLOTS]>, check: &Checker, stop: &AtomicBool, config: &Config) -> (u64, Waits) {
+    let mut rng = Rng::new(config.seed ^ ((id as u64 + 1) << 32));
+    let (mut visits, mut waits) = (0, Waits::default());
+    while !stop.load(Ordering::Relaxed) {
+        let start = Instant::now();
+        let data = lock.read();
+        waits.record(start.elapsed());
+        let inside = check.readers_inside.fetch_add(1, Ordering::SeqCst) + 1;
+        check.max_readers.fetch_max(inside, Ordering::Relaxed);
+        if check.writers_inside.load(Ordering::SeqCst) > 0 {
+            check.violation(format!("reader {id} got in while a writer was inside"));
+        }
+        if data.iter().any(|&v| v != data[0]) {
+            check.violation(format!("reader {id} saw a half-done write: {:?}", *data));
+        }
+        pause(config.read_hold);
+        check.readers_inside.fetch_sub(1, Ordering::SeqCst);
+        drop(data);
+        visits += 1;
+        pause(rng.duration(config.think));
+    }
+    (visits, waits)
+}
+
+fn writer(id: usize, lock: &RwLock<[u64; SLOTS]>, check: &Checker, stop: &AtomicBool, config: &Config) -> (u64, Waits) {
+    let mut rng = Rng::new(config.seed ^ ((id as u64 + 1000) << 32));
+    let (mut visits, mut waits) = (0, Waits::default());
+    while !stop.load(Ordering::Relaxed) {
+        let start = Instant::now();
+        let mut data = lock.write();
+        waits.record(start.elapsed());
+        let writers = check.writers_inside.fetch_add(1, Ordering::SeqCst) + 1;
+        let readers = check.readers_inside.load(Ordering::SeqCst);
+        if writers > 1 || readers > 0 {
+            check.violation(format!("writer {id} got in with {} other writer(s) and {readers} reader(s)", writers - 1));
+        }
+        let next = data[0] + 1;
+        for slot in data.iter_mut() {
+            *slot = next;
+            // Give a reader that should not be here a chance to see the tear.
+            thread::yield_now();
+        }
+        pause(config.write_hold);
+        check.writers_inside.fetch_sub(1, Ordering::SeqCst);
+        drop(data);
+        visits += 1;
+        pause(rng.duration(config.think));
+    }
+    (visits, waits)
+}
+
+pub fn run(policy: Policy, config: &Config) -> Report {
+    let lock = RwLock::new(policy, [0u64; SLOTS]);
+    let check = Checker::default();
+    let stop = AtomicBool::new(false);
+    let start = Instant::now();
+    let (reads, writes) = thread::scope(|s| {
+        let (lock, check, stop) = (&lock, &check, &stop);
+        let readers: Vec<_> = (0..config.readers).map(|i| s.spawn(move || reader(i, lock, check, stop, config))).collect();
+        let writers: Vec<_> = (0..config.writers).map(|i| s.spawn(move || writer(i, lock, check, stop, config))).collect();
+        thread::sleep(config.duration);
+        stop.store(true, Ordering::Relaxed);
+        let join = |hs: Vec<thread::ScopedJoinHandle<'_, (u64, Waits)>>| -> Vec<(u64, Waits)> {
+            hs.into_iter().map(|h| h.join().unwrap()).collect()
+        };
+        (join(readers), join(writers))
+    });
+    let elapsed = start.elapsed();
+
+    let mut read_waits = Waits::default();
+    reads.iter().for_each(|(_, w)| read_waits.merge(w));
+    let mut write_waits = Waits::default();
+    writes.iter().for_each(|(_, w)| write_waits.merge(w));
+    Report {
+        policy,
+        config: *config,
+        elapsed,
+        reads: reads.iter().map(|(n, _)| *n).collect(),
+        writes: writes.iter().map(|(n, _)| *n).collect(),
+        read_waits,
+        write_waits,
+        max_concurrent_readers: check.max_readers.load(Ordering::Relaxed),
+        violations: check.violations.into_inner().unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writers_alone_and_reads_whole_under_every_policy() {
+        let config = Config {
+            readers: 4,
+            writers: 2,
+            duration: Duration::from_millis(50),
+            read_hold: Duration::from_micros(100),
+            write_hold: Duration::from_micros(50),
+            think: Duration::from_micros(50),
+            seed: 7,
+        };
+        for policy in Policy::ALL {
+            let r = run(policy, &config);
+            assert!(r.violations.is_empty(), "{policy}: {:?}", r.violations);
+            assert!(r.writes.iter().sum::<u64>() > 0, "{policy}: no writer got in");
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// src/lib.rs
+//
+// TrackedMutex: a Mutex that knows who holds it, who waits for it, and where in
+// the source they did so. All of that lives in one global registry, which gives
+// two checks:
+//
+// 1. Deadlock detection (always on), on the WAIT-FOR graph:
+//      thread --waits for--> lock --held by--> thread --waits for--> ...
+//    Before a thread goes to sleep on a lock, it follows that chain. If the chain
+//    comes back to itself, sleeping would be forever, so `lock()` returns
+//    Err(Deadlock) describing the whole cycle instead. The thread that closes
+//    the cycle is the one told; dropping its guards lets the others continue.
+//
+// 2. Lock-order checking (opt-in, like the Linux kernel's lockdep), on the
+//    LOCK-ORDER graph:
+//      a --> b   "some thread took b while holding a"
+//    A cycle here (a --> b and, somewhere else, b --> a) means two code paths
+//    take the same locks in opposite orders. That deadlocks only if the timing
+//    is unlucky, so this run may well finish fine; lockdep reports it anyway,
+//    with the call sites of both orders.
+//
+// The registry is a single Mutex + Condvar, so every lock and unlock takes a
+// global lock. Fine for labs and tests, far too slow for production.
+//
+// Poisoning is ignored: a panic while holding a TrackedMutex leaves the data as
+// it was, and the next lock() succeeds.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, LazyLock, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+
+type Site = &'static Location<'static>;
+
+/// One step of a deadlock cycle: `thread` waits (at `wait_site`) for `lock`,
+/// which `holder` locked at `held_site`.
+#[derive(Debug, Clone)]
+pub struct WaitEdge {
+    pub thread: String,
+    pub lock: String,
+    pub wait_site: Site,
+    pub holder: String,
+    pub held_site: Site,
+}
+
+/// Returned by `TrackedMutex::lock` instead of blocking forever.
+#[derive(Debug, Clone)]
+pub struct Deadlock {
+    pub cycle: Vec<WaitEdge>,
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "deadlock: {} thread(s) waiting in a cycle", self.cycle.len())?;
+        for e in &self.cycle {
+            writeln!(
+                f,
+                "  thread '{}' waits for '{}' at {}\n    '{}' is held by thread '{}', locked at {}",
+                e.thread, e.lock, e.wait_site, e.lock, e.holder, e.held_site
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Deadlock {}
+
+/// "`thread` took `second` while holding `first`, at `site`."
+#[derive(Debug, Clone)]
+pub struct OrderEdge {
+    pub first: String,
+    pub second: String,
+    pub thread: String,
+    pub site: Site,
+}
+
+/// A new lock-order edge that closes a cycle with edges seen earlier.
+#[derive(Debug, Clone)]
+pub struct OrderViolation {
+    pub new: OrderEdge,
+    /// The earlier edges, from `new.second` back round to `new.first`.
+    pub existing: Vec<OrderEdge>,
+}
+
+impl fmt::Display for OrderViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let e = &self.new;
+        writeln!(f, "inconsistent lock order: '{}' then '{}'", e.first, e.second)?;
+        writeln!(f, "  thread '{}' took '{}' while holding '{}' at {}", e.thread, e.second, e.first, e.site)?;
+        writeln!(f, "  but earlier:")?;
+        for x in &self.existing {
+            writeln!(f, "  thread '{}' took '{}' while holding '{}' at {}", x.thread, x.second, x.first, x.site)?;
+        }
+        Ok(())
+    }
+}
+
+struct Holder {
+    thread: ThreadId,
+    site: Site,
+}
+
+struct Waiter {
+    lock: usize,
+    site: Site,
+}
+
+#[derive(Default)]
+struct State {
+    names: HashMap<usize, String>,
+    thread_names: HashMap<ThreadId, String>,
+    owners: HashMap<usize, Holder>,
+    waiting: HashMap<ThreadId, Waiter>,
+    /// Per thread, the locks it holds in acquisition order.
+    held: HashMap<ThreadId, Vec<usize>>,
+    lockdep: bool,
+    order: HashMap<usize, HashMap<usize, OrderEdge>>,
+    reported: HashSet<(usize, usize)>,
+    violations: Vec<OrderViolation>,
+}
+
+impl State {
+    fn thread_name(&self, id: ThreadId) -> String {
+        self.thread_names.get(&id).cloned().unwrap_or_else(|| format!("{id:?}"))
+    }
+
+    // Follows waits-for and held-by edges from `start`. Some(cycle) if they lead back.
+    fn find_cycle(&self, start: ThreadId) -> Option<Vec<WaitEdge>> {
+        let mut cycle = Vec::new();
+        let mut current = start;
+        while let Some(wait) = self.waiting.get(&current) {
+            let holder = self.owners.get(&wait.lock)?;
+            cycle.push(WaitEdge {
+                thread: self.thread_name(current),
+                lock: self.names[&wait.lock].clone(),
+                wait_site: wait.site,
+                holder: self.thread_name(holder.thread),
+                held_site: holder.site,
+            });
+            if holder.thread == start {
+                return Some(cycle);
+            }
+            // Every other thread checked when it started waiting, so any cycle
+            // that exists goes through `start`; this only guards against loops.
+            if cycle.len() > self.waiting.len() {
+                return None;
+            }
+            current = holder.thread;
+        }
+        None
+    }
+
+    // Order edges on a path from `from` to `to`, if there is one (BFS).
+    fn order_path(&self, from: usize, to: usize) -> Option<Vec<OrderEdge>> {
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(n) = queue.pop_front() {
+            if n == to {
+                let mut path = Vec::new();
+                let mut cur = to;
+                while cur != from {
+                    let prev = came_from[&cur];
+                    path.push(self.order[&prev][&cur].clone());
+                    cur = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in self.order.get(&n).into_iter().flat_map(|m| m.keys()) {
+                if next != from && !came_from.contains_key(&next) {
+                    came_from.insert(next, n);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    fn record_order(&mut self, me: ThreadId, lock: usize, site: Site) {
+        let held = self.held.get(&me).cloned().unwrap_or_default();
+        for first in held {
+            if self.order.get(&first).is_some_and(|m| m.contains_key(&lock)) {
+                continue;
+            }
+            let edge = OrderEdge {
+                first: self.names[&first].clone(),
+                second: self.names[&lock].clone(),
+                thread: self.thread_name(me),
+                site,
+            };
+            match self.order_path(lock, first) {
+                Some(existing) => {
+                    let key = (first.min(lock), first.max(lock));
+                    if self.reported.insert(key) {
+                        let violation = OrderViolation { new: edge, existing };
+                        eprint!("lockdep: {violation}");
+                        self.violations.push(violation);
+                    }
+                }
+                None => {
+                    self.order.entry(first).or_default().insert(lock, edge);
+                }
+            }
+        }
+    }
+}
+
+struct Registry {
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+static REGISTRY: LazyLock<Registry> =
+    LazyLock::new(|| Registry { state: Mutex::new(State::default()), released: Condvar::new() });
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn state() -> MutexGuard<'static, State> {
+    REGISTRY.state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Turns lock-order checking on or off for the whole process. Edges are only
+/// recorded while it is on.
+pub fn set_lockdep(enabled: bool) {
+    state().lockdep = enabled;
+}
+
+/// Lock-order violations found so far (each pair of locks reported once).
+pub fn order_violations() -> Vec<OrderViolation> {
+    state().violations.clone()
+}
+
+/// Forgets recorded lock orders and violations, e.g. between experiments.
+pub fn reset_lockdep() {
+    let mut st = state();
+    st.order.clear();
+    st.reported.clear();
+    st.violations.clear();
+}
+
+pub struct TrackedMutex<T> {
+    id: usize,
+    data: Mutex<T>,
+}
+
+impl<T> TrackedMutex<T> {
+    /// `name` appears in every report about this lock.
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        state().names.insert(id, name.into());
+        TrackedMutex { id, data: Mutex::new(value) }
+    }
+
+    pub fn name(&self) -> String {
+        state().names[&self.id].clone()
+    }
+
+    /// Blocks until the lock is free, like `Mutex::lock`, unless waiting would
+    /// complete a cycle of waiting threads; then returns the cycle instead.
+    /// Locking a TrackedMutex the thread already holds is a cycle of one.
+    #[track_caller]
+    pub fn lock(&self) -> Result<TrackedGuard<'_, T>, Deadlock> {
+        let site = Location::caller();
+        let current = thread::current();
+        let me = current.id();
+        let mut st = state();
+        if let Some(name) = current.name() {
+            st.thread_names.entry(me).or_insert_with(|| name.to_string());
+        }
+        while st.owners.contains_key(&self.id) {
+            st.waiting.insert(me, Waiter { lock: self.id, site });
+            if let Some(cycle) = st.find_cycle(me) {
+                st.waiting.remove(&me);
+                return Err(Deadlock { cycle });
+            }
+            st = REGISTRY.released.wait(st).unwrap_or_else(|e| e.into_inner());
+        }
+        st.waiting.remove(&me);
+        if st.lockdep {
+            st.record_order(me, self.id, site);
+        }
+        st.owners.insert(self.id, Holder { thread: me, site });
+        st.held.entry(me).or_default().push(self.id);
+        drop(st);
+        // The registry says we own it, so this never waits for long: at most for
+        // the previous guard to finish dropping.
+        let guard = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(TrackedGuard { lock: self, guard: Some(guard) })
+    }
+}
+
+impl<T> Drop for TrackedMutex<T> {
+    fn drop(&mut self) {
+        state().names.remove(&self.id);
+    }
+}
+
+pub struct TrackedGuard<'a, T> {
+    lock: &'a TrackedMutex<T>,
+    guard: Option<MutexGuard<'a, T>>,
+}
+
+impl<T> Deref for TrackedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_ref().expect("present until drop")
+    }
+}
+
+impl<T> DerefMut for TrackedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_mut().expect("present until drop")
+    }
+}
+
+impl<T> Drop for TrackedGuard<'_, T> {
+    fn drop(&mut self) {
+        self.guard.take();
+        let mut st = state();
+        st.owners.remove(&self.lock.id);
+        let me = thread::current().id();
+        if let Some(held) = st.held.get_mut(&me) {
+            held.retain(|&id| id != self.lock.id);
+            if held.is_empty() {
+                st.held.remove(&me);
+            }
+        }
+        drop(st);
+        REGISTRY.released.notify_all();
+    }
+}
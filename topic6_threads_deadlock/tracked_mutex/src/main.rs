@@ -0,0 +1,154 @@
+// src/main.rs
+//
+// `tracked_mutex [deadlock|philosophers|relock|lockdep]`, or no argument for all.
+//
+//   deadlock      the a/b program from ../src/main.rs, with TrackedMutex: instead
+//                 of hanging, the thread that closes the cycle gets a report
+//   philosophers  five threads, five forks, everyone takes the left fork first:
+//                 a cycle of five
+//   relock        one thread locking the same mutex twice (a cycle of one)
+//   lockdep       a then b in one thread, later b then a in another. Never
+//                 deadlocks in this run, but the lock-order check still flags it
+
+use std::process::ExitCode;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use tracked_mutex::{order_violations, reset_lockdep, set_lockdep, TrackedMutex};
+
+fn deadlock() {
+    println!("--- two threads, two locks, opposite order ---");
+    let a = Arc::new(TrackedMutex::new("a", 0));
+    let b = Arc::new(TrackedMutex::new("b", 0));
+    // The barrier replaces the sleep in ../src/main.rs: both threads are sure to
+    // hold their first lock before either asks for its second.
+    let barrier = Arc::new(Barrier::new(2));
+    let spawn = |name: &str, first: Arc<TrackedMutex<i32>>, second: Arc<TrackedMutex<i32>>, barrier: Arc<Barrier>| {
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let mut x = first.lock().unwrap();
+                barrier.wait();
+                match second.lock() {
+                    Ok(mut y) => {
+                        *x += 1;
+                        *y += 1;
+                        format!("{} finished", thread::current().name().unwrap())
+                    }
+                    Err(deadlock) => format!("{deadlock}{} gave up and released its lock", thread::current().name().unwrap()),
+                }
+            })
+            .unwrap()
+    };
+    let t1 = spawn("t1", Arc::clone(&a), Arc::clone(&b), Arc::clone(&barrier));
+    let t2 = spawn("t2", Arc::clone(&b), Arc::clone(&a), barrier);
+    let mut results = [t1.join().unwrap(), t2.join().unwrap()];
+    results.sort_by_key(|r| !r.starts_with("deadlock"));
+    for r in results {
+        println!("{r}");
+    }
+}
+
+fn philosophers() {
+    println!("--- dining philosophers, everyone left fork first ---");
+    let n = 5;
+    let forks: Vec<_> = (0..n).map(|i| Arc::new(TrackedMutex::new(format!("fork {i}"), ()))).collect();
+    let barrier = Arc::new(Barrier::new(n));
+    let handles: Vec<_> = (0..n)
+        .map(|i| {
+            let (left, right) = (Arc::clone(&forks[i]), Arc::clone(&forks[(i + 1) % n]));
+            let barrier = Arc::clone(&barrier);
+            thread::Builder::new()
+                .name(format!("philosopher {i}"))
+                .spawn(move || {
+                    let _left = left.lock().unwrap();
+                    barrier.wait();
+                    right.lock().map(|_| ()).map_err(|d| d.to_string())
+                })
+                .unwrap()
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    for r in &results {
+        if let Err(report) = r {
+            print!("{report}");
+        }
+    }
+    let ate = results.iter().filter(|r| r.is_ok()).count();
+    println!("{ate} of {n} ate, after the philosopher who closed the cycle put their fork down");
+}
+
+fn relock() {
+    println!("--- the same thread locking twice ---");
+    let m = TrackedMutex::new("config", 0);
+    let _first = m.lock().unwrap();
+    match m.lock() {
+        Ok(_) => println!("locked twice?!"),
+        Err(d) => print!("{d}"),
+    }
+    println!("(std's Mutex would deadlock or panic here; its docs leave it unspecified)");
+}
+
+fn lockdep() {
+    println!("--- lock-order checking: no deadlock happens, the bug is still there ---");
+    set_lockdep(true);
+    reset_lockdep();
+    let accounts = Arc::new(TrackedMutex::new("accounts", 0));
+    let audit = Arc::new(TrackedMutex::new("audit log", 0));
+    let (acc, aud) = (Arc::clone(&accounts), Arc::clone(&audit));
+    thread::Builder::new()
+        .name("transfer".into())
+        .spawn(move || {
+            let _a = acc.lock().unwrap();
+            let _l = aud.lock().unwrap();
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    // Runs strictly after the first thread, so it cannot deadlock in this run.
+    let (acc, aud) = (Arc::clone(&accounts), Arc::clone(&audit));
+    thread::Builder::new()
+        .name("report".into())
+        .spawn(move || {
+            let _l = aud.lock().unwrap();
+            let _a = acc.lock().unwrap();
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    let found = order_violations().len();
+    println!("both threads finished; {found} lock-order violation(s) reported above (on stderr)");
+    set_lockdep(false);
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        [] => {
+            deadlock();
+            println!();
+            philosophers();
+            println!();
+            relock();
+            println!();
+            lockdep();
+        }
+        ["deadlock"] => deadlock(),
+        ["philosophers"] => philosophers(),
+        ["relock"] => relock(),
+        ["lockdep"] => lockdep(),
+        [other, ..] => return Err(format!("unknown demo '{other}', expected deadlock, philosophers, relock or lockdep")),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
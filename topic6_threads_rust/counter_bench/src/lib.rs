@@ -0,0 +1,230 @@
+// src/lib.rs
+//
+// Six ways for many threads to count into one total, from most to least shared:
+//
+//   atomic       one AtomicU64, fetch_add from every thread
+//   mutex        one Mutex<u64>
+//   rwlock       one RwLock<u64>, write-locked for every increment
+//   sharded      one AtomicU64 per thread, packed next to each other
+//   padded       one AtomicU64 per thread, each on its own cache line(s)
+//   per-thread   a plain local u64 per thread, added to the total after the loop
+//
+// Cores do not share memory, they share CACHE LINES (64 bytes), through the
+// coherence protocol: before writing a line a core must own it exclusively,
+// which means taking it away from whichever core wrote it last. When several
+// threads write the same line, that line BOUNCES from core to core, and every
+// write waits for a transfer of about a hundred cycles.
+//
+//   atomic, mutex, rwlock   all threads write one line: it bounces on every op,
+//                           so more threads get LESS total throughput
+//   sharded                 different variables, but 8 of them per line: the line
+//                           bounces just the same (FALSE sharing)
+//   padded                  one line per thread: no bouncing, scales with cores
+//   per-thread              no shared writes until the end: scales best
+//
+// Reading the total is the price: sharded and padded sum every shard; the
+// per-thread total only exists once the threads are done.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 128 bytes: a 64-byte line plus its neighbour, because Intel's spatial
+/// prefetcher fetches lines in pairs and can make adjacent lines interfere.
+#[repr(align(128))]
+#[derive(Default)]
+pub struct CachePadded<T>(pub T);
+
+pub trait Counter: Sync {
+    const NAME: &'static str;
+
+    fn new(threads: usize) -> Self;
+
+    fn add(&self, thread: usize);
+
+    fn total(&self) -> u64;
+
+    /// One thread's share of the work. Overridden by counters that batch.
+    fn run_thread(&self, thread: usize, ops: u64) {
+        for _ in 0..ops {
+            self.add(thread);
+        }
+    }
+}
+
+pub struct Atomic(AtomicU64);
+
+impl Counter for Atomic {
+    const NAME: &'static str = "atomic";
+
+    fn new(_: usize) -> Self {
+        Atomic(AtomicU64::new(0))
+    }
+
+    fn add(&self, _: usize) {
+        // Relaxed: only the final total matters, and join() orders that.
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+pub struct Locked(Mutex<u64>);
+
+impl Counter for Locked {
+    const NAME: &'static str = "mutex";
+
+    fn new(_: usize) -> Self {
+        Locked(Mutex::new(0))
+    }
+
+    fn add(&self, _: usize) {
+        *self.0.lock().unwrap() += 1;
+    }
+
+    fn total(&self) -> u64 {
+        *self.0.lock().unwrap()
+    }
+}
+
+pub struct ReadWrite(RwLock<u64>);
+
+impl Counter for ReadWrite {
+    const NAME: &'static str = "rwlock";
+
+    fn new(_: usize) -> Self {
+        ReadWrite(RwLock::new(0))
+    }
+
+    fn add(&self, _: usize) {
+        *self.0.write().unwrap() += 1;
+    }
+
+    fn total(&self) -> u64 {
+        *self.0.read().unwrap()
+    }
+}
+
+pub struct Sharded(Vec<AtomicU64>);
+
+impl Counter for Sharded {
+    const NAME: &'static str = "sharded";
+
+    fn new(threads: usize) -> Self {
+        Sharded((0..threads).map(|_| AtomicU64::new(0)).collect())
+    }
+
+    fn add(&self, thread: usize) {
+        self.0[thread].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        self.0.iter().map(|s| s.load(Ordering::Relaxed)).sum()
+    }
+}
+
+pub struct Padded(Vec<CachePadded<AtomicU64>>);
+
+impl Counter for Padded {
+    const NAME: &'static str = "padded";
+
+    fn new(threads: usize) -> Self {
+        Padded((0..threads).map(|_| CachePadded::default()).collect())
+    }
+
+    fn add(&self, thread: usize) {
+        self.0[thread].0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        self.0.iter().map(|s| s.0.load(Ordering::Relaxed)).sum()
+    }
+}
+
+pub struct PerThread(AtomicU64);
+
+impl Counter for PerThread {
+    const NAME: &'static str = "per-thread";
+
+    fn new(_: usize) -> Self {
+        PerThread(AtomicU64::new(0))
+    }
+
+    fn add(&self, _: usize) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn run_thread(&self, _: usize, ops: u64) {
+        let mut local = 0u64;
+        for _ in 0..ops {
+            // black_box keeps the compiler from turning the loop into `local += ops`.
+            local = std::hint::black_box(local + 1);
+        }
+        self.0.fetch_add(local, Ordering::Relaxed);
+    }
+}
+
+pub const NAMES: [&str; 6] = [Atomic::NAME, Locked::NAME, ReadWrite::NAME, Sharded::NAME, Padded::NAME, PerThread::NAME];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub threads: usize,
+    pub ops_per_thread: u64,
+    pub elapsed: Duration,
+    pub total: u64,
+}
+
+impl Measurement {
+    pub fn correct(&self) -> bool {
+        self.total == self.threads as u64 * self.ops_per_thread
+    }
+
+    /// Million increments per second, all threads together.
+    pub fn mops(&self) -> f64 {
+        (self.threads as u64 * self.ops_per_thread) as f64 / self.elapsed.as_secs_f64() / 1e6
+    }
+}
+
+/// `threads` threads, `ops` increments each, all released at the same moment.
+pub fn measure<C: Counter>(threads: usize, ops: u64) -> Measurement {
+    let counter = C::new(threads);
+    let start_line = std::sync::Barrier::new(threads + 1);
+    let elapsed = thread::scope(|s| {
+        for t in 0..threads {
+            let (counter, start_line) = (&counter, &start_line);
+            s.spawn(move || {
+                start_line.wait();
+                counter.run_thread(t, ops);
+            });
+        }
+        // The barrier opens once the last worker arrives. Reading the clock before
+        // it may count a little spawn time; reading it after could miss work the
+        // workers did before main got the CPU back.
+        let start = Instant::now();
+        start_line.wait();
+        // Leaving the scope joins every thread.
+        start
+    })
+    .elapsed();
+    Measurement { threads, ops_per_thread: ops, elapsed, total: counter.total() }
+}
+
+/// `measure` for the counter called `name` (one of `NAMES`).
+pub fn measure_named(name: &str, threads: usize, ops: u64) -> Option<Measurement> {
+    Some(match name {
+        Atomic::NAME => measure::<Atomic>(threads, ops),
+        Locked::NAME => measure::<Locked>(threads, ops),
+        ReadWrite::NAME => measure::<ReadWrite>(threads, ops),
+        Sharded::NAME => measure::<Sharded>(threads, ops),
+        Padded::NAME => measure::<Padded>(threads, ops),
+        PerThread::NAME => measure::<PerThread>(threads, ops),
+        _ => return None,
+    })
+}
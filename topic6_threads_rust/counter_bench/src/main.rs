@@ -0,0 +1,109 @@
+// src/main.rs
+//
+// `counter_bench [--threads N] [--ops N] [--counters a,b,..] [--reps R]`
+//
+// For 1..=N threads, how many increments per second each counter reaches in
+// total (best of R runs), and the speed-up over one thread. A counter that scales
+// doubles its total when the threads double; a bouncing cache line makes the
+// total flat or falling.
+//
+// Defaults: N = available cores (at least 4), 2M increments per thread, all
+// six counters, 3 reps.
+
+use std::process::ExitCode;
+use std::thread;
+
+use counter_bench::{measure_named, NAMES};
+
+struct Options {
+    max_threads: usize,
+    ops: u64,
+    counters: Vec<String>,
+    reps: usize,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut opts = Options {
+        max_threads: cores.max(4),
+        ops: 2_000_000,
+        counters: NAMES.iter().map(|s| s.to_string()).collect(),
+        reps: 3,
+    };
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let value = it.next().ok_or(format!("{arg} needs a value"))?;
+        let number = || value.replace('_', "").parse::<u64>().map_err(|_| format!("{arg} takes a number, got '{value}'"));
+        match arg.as_str() {
+            "--threads" => opts.max_threads = number()?.max(1) as usize,
+            "--ops" => opts.ops = number()?.max(1),
+            "--reps" => opts.reps = number()?.max(1) as usize,
+            "--counters" => {
+                opts.counters = value.split(',').map(str::to_string).collect();
+                if let Some(bad) = opts.counters.iter().find(|c| !NAMES.contains(&c.as_str())) {
+                    return Err(format!("unknown counter '{bad}', expected some of {}", NAMES.join(",")));
+                }
+            }
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    Ok(opts)
+}
+
+fn run(opts: &Options) -> Result<(), String> {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    println!("{} increments per thread, best of {}; {cores} core(s) available", opts.ops, opts.reps);
+    if cores < 2 {
+        println!("note: with one core the threads take turns, so nothing bounces and nothing scales.");
+    }
+    if cfg!(debug_assertions) {
+        println!("note: debug build; use `cargo run --release -p counter_bench`");
+    }
+    println!("\nM increments/s, all threads together (x speed-up over 1 thread):");
+    print!("  {:>7}", "threads");
+    for c in &opts.counters {
+        print!(" {c:>18}");
+    }
+    println!();
+
+    let mut single: Vec<f64> = vec![0.0; opts.counters.len()];
+    let mut wrong = Vec::new();
+    for threads in 1..=opts.max_threads {
+        print!("  {threads:>7}");
+        for (i, name) in opts.counters.iter().enumerate() {
+            let mut best = 0.0f64;
+            for _ in 0..opts.reps {
+                let m = measure_named(name, threads, opts.ops).expect("validated names");
+                if !m.correct() {
+                    wrong.push(format!("{name} with {threads} threads counted {}", m.total));
+                }
+                best = best.max(m.mops());
+            }
+            if threads == 1 {
+                single[i] = best;
+            }
+            print!(" {:>10.1} (x{:>4.1})", best, best / single[i]);
+        }
+        println!();
+        if threads == cores && threads < opts.max_threads {
+            println!("  {:>7} (more threads than cores from here on)", "");
+        }
+    }
+    println!();
+    println!("atomic/mutex/rwlock: one cache line bounces between every core that writes it.");
+    println!("sharded: separate counters, but 8 share a 64-byte line (false sharing).");
+    println!("padded: one 128-byte slot per thread, nothing shared. per-thread: nothing shared until the end.");
+    if wrong.is_empty() { Ok(()) } else { Err(format!("lost increments: {}", wrong.join("; "))) }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match parse_args(&args).and_then(|opts| run(&opts)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            eprintln!("usage: counter_bench [--threads N] [--ops N] [--counters {}] [--reps R]", NAMES.join(","));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,554 @@
+// src/lib.rs
+//
+// A deterministic scheduler for tiny concurrent programs, in the spirit of loom.
+//
+// Real threads decide for themselves when to run, so a race shows up once in a
+// million runs, or never on a given machine. Here every thread of a `Program`
+// is still a real OS thread, but it may only touch shared state (`Var`, `Lock`)
+// when the scheduler says so. Each shared operation is a SWITCH POINT: the
+// thread stops there, and the scheduler picks which stopped thread goes next.
+// A run is therefore fully described by its SCHEDULE, the list of thread ids
+// picked at each switch point, and replaying a schedule replays the run
+// exactly.
+//
+// `explore` runs the program under many schedules:
+//   Exhaustive  depth-first over every choice at every switch point (stateless
+//               model checking). Complete for small programs.
+//   Random      a random thread at every switch point, for programs too big
+//               to enumerate.
+// Every run ends with the program's check (e.g. "counter == 2"), a panic, a
+// deadlock (threads left, all waiting on held locks), or the step limit.
+//
+// Of all failing runs, the report keeps the one with the fewest PREEMPTIONS
+// (switching away from a thread that could have kept running), which is the
+// easiest failing schedule to read. Lost updates need just one.
+//
+// Code between switch points runs unscheduled, so it must only touch the
+// thread's own locals. Anything shared must go through Var or Lock.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+type Body = Box<dyn FnOnce() + Send>;
+type Check = Box<dyn FnOnce() -> Result<(), String>>;
+
+/// The threads of one run, plus the property to check once they have all finished.
+/// Built fresh for every run, so each run starts from the same state.
+pub struct Program {
+    threads: Vec<(String, Body)>,
+    check: Option<Check>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Program { threads: Vec::new(), check: None }
+    }
+
+    pub fn thread(mut self, name: impl Into<String>, body: impl FnOnce() + Send + 'static) -> Self {
+        self.threads.push((name.into(), Box::new(body)));
+        self
+    }
+
+    pub fn check(mut self, check: impl FnOnce() -> Result<(), String> + 'static) -> Self {
+        self.check = Some(Box::new(check));
+        self
+    }
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Program::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// One run
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pending {
+    Op,
+    Acquire(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    /// Between switch points (or not yet at the first one).
+    Running,
+    Waiting(Pending),
+    Finished,
+}
+
+/// One shared operation, as it appears in a trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub thread: usize,
+    pub action: String,
+}
+
+struct RunState {
+    status: Vec<Status>,
+    turn: Option<usize>,
+    aborted: bool,
+    trace: Vec<Step>,
+    lock_holder: HashMap<u64, usize>,
+    lock_names: HashMap<u64, String>,
+    panic: Option<String>,
+}
+
+struct Run {
+    state: Mutex<RunState>,
+    changed: Condvar,
+}
+
+impl Run {
+    fn state(&self) -> MutexGuard<'_, RunState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Unwinds a thread out of an abandoned run. Never reported as a panic.
+struct Aborted;
+
+#[derive(Clone)]
+struct Worker {
+    run: Arc<Run>,
+    id: usize,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Worker>> = const { RefCell::new(None) };
+}
+
+fn current() -> Option<Worker> {
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+impl Worker {
+    // Stops at a switch point until the scheduler picks this thread.
+    fn switch(&self, pending: Pending) {
+        let mut st = self.run.state();
+        st.status[self.id] = Status::Waiting(pending);
+        self.run.changed.notify_all();
+        loop {
+            if st.aborted {
+                drop(st);
+                panic::resume_unwind(Box::new(Aborted));
+            }
+            if st.turn == Some(self.id) {
+                st.turn = None;
+                st.status[self.id] = Status::Running;
+                return;
+            }
+            st = self.run.changed.wait(st).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn record(&self, action: String) {
+        self.run.state().trace.push(Step { thread: self.id, action });
+    }
+}
+
+/// A shared variable. Every load, store or read-modify-write is one switch point,
+/// exactly as if it were one machine instruction.
+pub struct Var<T> {
+    name: String,
+    value: Arc<Mutex<T>>,
+}
+
+impl<T> Clone for Var<T> {
+    fn clone(&self) -> Self {
+        Var { name: self.name.clone(), value: Arc::clone(&self.value) }
+    }
+}
+
+impl<T: Copy + fmt::Debug> Var<T> {
+    pub fn new(name: impl Into<String>, value: T) -> Self {
+        Var { name: name.into(), value: Arc::new(Mutex::new(value)) }
+    }
+
+    // Switch point, then `f` on the value, then the trace entry.
+    fn op<R>(&self, f: impl FnOnce(&mut T) -> R, describe: impl FnOnce(&R) -> String) -> R {
+        let worker = current();
+        if let Some(w) = &worker {
+            w.switch(Pending::Op);
+        }
+        let result = f(&mut self.value.lock().unwrap_or_else(|e| e.into_inner()));
+        if let Some(w) = &worker {
+            w.record(describe(&result));
+        }
+        result
+    }
+
+    pub fn load(&self) -> T {
+        self.op(|v| *v, |r| format!("load {} -> {r:?}", self.name))
+    }
+
+    pub fn store(&self, value: T) {
+        self.op(|v| *v = value, |_| format!("store {} <- {value:?}", self.name))
+    }
+
+    pub fn swap(&self, value: T) -> T {
+        self.op(|v| std::mem::replace(v, value), |r| format!("swap {} <- {value:?} (was {r:?})", self.name))
+    }
+
+    /// Reads the value without a switch point; for checks after all threads are done.
+    pub fn get(&self) -> T {
+        *self.value.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Copy + fmt::Debug + PartialEq> Var<T> {
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        self.op(
+            |v| if *v == current { Ok(std::mem::replace(v, new)) } else { Err(*v) },
+            |r| match r {
+                Ok(_) => format!("cas {} {current:?} -> {new:?} ok", self.name),
+                Err(seen) => format!("cas {} {current:?} -> {new:?} failed, saw {seen:?}", self.name),
+            },
+        )
+    }
+}
+
+impl Var<i64> {
+    /// One indivisible step, like `AtomicI64::fetch_add`.
+    pub fn fetch_add(&self, delta: i64) -> i64 {
+        self.op(|v| std::mem::replace(v, *v + delta), |r| format!("fetch_add {} {delta:+} (was {r})", self.name))
+    }
+}
+
+static NEXT_LOCK: AtomicU64 = AtomicU64::new(0);
+
+/// A mutual-exclusion lock the scheduler understands: a thread waiting for a held
+/// Lock is not runnable, and if no thread is runnable the run is a deadlock.
+#[derive(Clone)]
+pub struct Lock {
+    id: u64,
+    name: String,
+}
+
+impl Lock {
+    pub fn new(name: impl Into<String>) -> Self {
+        Lock { id: NEXT_LOCK.fetch_add(1, Ordering::Relaxed), name: name.into() }
+    }
+
+    pub fn lock(&self) -> LockGuard<'_> {
+        if let Some(w) = current() {
+            w.run.state().lock_names.insert(self.id, self.name.clone());
+            w.switch(Pending::Acquire(self.id));
+            w.run.state().lock_holder.insert(self.id, w.id);
+            w.record(format!("lock {}", self.name));
+        }
+        LockGuard { lock: self }
+    }
+}
+
+pub struct LockGuard<'a> {
+    lock: &'a Lock,
+}
+
+impl Drop for LockGuard<'_> {
+    // Not a switch point: releasing changes nothing another thread could observe
+    // before its own next switch point.
+    fn drop(&mut self) {
+        if let Some(w) = current() {
+            w.run.state().lock_holder.remove(&self.lock.id);
+            if !thread::panicking() {
+                w.record(format!("unlock {}", self.lock.name));
+            }
+        }
+    }
+}
+
+/// Everything about one run under one schedule.
+#[derive(Debug, Clone)]
+pub struct Execution {
+    pub threads: Vec<String>,
+    /// Thread picked at each switch point.
+    pub schedule: Vec<usize>,
+    pub preemptions: usize,
+    pub trace: Vec<Step>,
+    pub result: Result<(), String>,
+    // Per switch point: index picked among the runnable threads, and how many there were.
+    choices: Vec<(usize, usize)>,
+}
+
+impl Execution {
+    /// The schedule in the form `replay` and the CLI accept, e.g. "0,1,1,0".
+    pub fn schedule_string(&self) -> String {
+        self.schedule.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(",")
+    }
+}
+
+impl fmt::Display for Execution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.threads.iter().map(|n| n.len()).max().unwrap_or(0);
+        let mut last = None;
+        for step in &self.trace {
+            let marker = if last.is_some_and(|l| l != step.thread) { "~" } else { " " };
+            writeln!(f, "  {marker} {:<width$}  {}", self.threads[step.thread], step.action)?;
+            last = Some(step.thread);
+        }
+        match &self.result {
+            Ok(()) => write!(f, "  ok"),
+            Err(e) => write!(f, "  FAILED: {e}"),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic".into())
+}
+
+/// Runs `program` once. `choose(runnable, k)` picks an index into `runnable`
+/// at the k-th switch point; `runnable` lists the previous thread first when it
+/// can continue, so index 0 never preempts.
+fn run_once(program: Program, max_steps: usize, mut choose: impl FnMut(&[usize], usize) -> Result<usize, String>) -> Execution {
+    let names: Vec<String> = program.threads.iter().map(|(n, _)| n.clone()).collect();
+    let run = Arc::new(Run {
+        state: Mutex::new(RunState {
+            status: vec![Status::Running; names.len()],
+            turn: None,
+            aborted: false,
+            trace: Vec::new(),
+            lock_holder: HashMap::new(),
+            lock_names: HashMap::new(),
+            panic: None,
+        }),
+        changed: Condvar::new(),
+    });
+    let mut exec = Execution {
+        threads: names.clone(),
+        schedule: Vec::new(),
+        preemptions: 0,
+        trace: Vec::new(),
+        result: Ok(()),
+        choices: Vec::new(),
+    };
+
+    thread::scope(|s| {
+        for (id, (name, body)) in program.threads.into_iter().enumerate() {
+            let run = Arc::clone(&run);
+            s.spawn(move || {
+                CURRENT.with(|c| *c.borrow_mut() = Some(Worker { run: Arc::clone(&run), id }));
+                let outcome = panic::catch_unwind(AssertUnwindSafe(body));
+                CURRENT.with(|c| *c.borrow_mut() = None);
+                let mut st = run.state();
+                st.status[id] = Status::Finished;
+                if let Err(payload) = outcome
+                    && !payload.is::<Aborted>()
+                {
+                    st.panic.get_or_insert(format!("thread {name} panicked: {}", panic_message(&*payload)));
+                }
+                run.changed.notify_all();
+            });
+        }
+
+        let mut prev: Option<usize> = None;
+        let mut st = run.state();
+        loop {
+            st = run
+                .changed
+                .wait_while(st, |st| st.turn.is_some() || st.status.contains(&Status::Running))
+                .unwrap_or_else(|e| e.into_inner());
+            if let Some(p) = st.panic.take() {
+                exec.result = Err(p);
+                break;
+            }
+            if st.status.iter().all(|s| *s == Status::Finished) {
+                break;
+            }
+            let mut runnable: Vec<usize> = (0..names.len())
+                .filter(|&t| match st.status[t] {
+                    Status::Waiting(Pending::Op) => true,
+                    Status::Waiting(Pending::Acquire(l)) => !st.lock_holder.contains_key(&l),
+                    _ => false,
+                })
+                .collect();
+            if runnable.is_empty() {
+                let waits: Vec<String> = (0..names.len())
+                    .filter_map(|t| match st.status[t] {
+                        Status::Waiting(Pending::Acquire(l)) => Some(format!(
+                            "{} waits for {} (held by {})",
+                            names[t],
+                            st.lock_names[&l],
+                            names[st.lock_holder[&l]]
+                        )),
+                        _ => None,
+                    })
+                    .collect();
+                exec.result = Err(format!("deadlock: {}", waits.join(", ")));
+                break;
+            }
+            if exec.schedule.len() >= max_steps {
+                exec.result = Err(format!("no end after {max_steps} steps (livelock or spin loop?)"));
+                break;
+            }
+            if let Some(p) = prev
+                && let Some(i) = runnable.iter().position(|&t| t == p)
+            {
+                runnable[..=i].rotate_right(1);
+            }
+            let index = match choose(&runnable, exec.schedule.len()) {
+                Ok(i) => i,
+                Err(e) => {
+                    exec.result = Err(e);
+                    break;
+                }
+            };
+            let picked = runnable[index];
+            if prev.is_some_and(|p| p != picked && runnable.contains(&p)) {
+                exec.preemptions += 1;
+            }
+            exec.choices.push((index, runnable.len()));
+            exec.schedule.push(picked);
+            prev = Some(picked);
+            st.turn = Some(picked);
+            run.changed.notify_all();
+        }
+        // Release every thread still parked at a switch point.
+        st.aborted = true;
+        exec.trace = std::mem::take(&mut st.trace);
+        drop(st);
+        run.changed.notify_all();
+    });
+
+    if exec.result.is_ok()
+        && let Some(check) = program.check
+    {
+        exec.result = check();
+    }
+    exec
+}
+
+// ---------------------------------------------------------------------------
+// Exploring many runs
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Exhaustive,
+    Random { seed: u64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub mode: Mode,
+    /// Stop after this many runs (an exhaustive search is then incomplete).
+    pub max_runs: usize,
+    /// Switch points allowed per run before it counts as not terminating.
+    pub max_steps: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { mode: Mode::Exhaustive, max_runs: 100_000, max_steps: 1_000 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub runs: usize,
+    pub failing_runs: usize,
+    /// Exhaustive mode only: every schedule was tried.
+    pub complete: bool,
+    /// The failing run with the fewest preemptions (then the fewest steps).
+    pub minimal: Option<Execution>,
+    /// Distinct final results seen, with how many runs produced each.
+    pub outcomes: Vec<(String, usize)>,
+}
+
+/// Runs `build()`'s program under many schedules (see the top of the file).
+pub fn explore(config: &Config, build: impl Fn() -> Program) -> Report {
+    let mut report = Report { runs: 0, failing_runs: 0, complete: false, minimal: None, outcomes: Vec::new() };
+    let mut rng = match config.mode {
+        Mode::Random { seed } => seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        Mode::Exhaustive => 0,
+    };
+    let mut prefix: Vec<usize> = Vec::new();
+    // Panics inside explored threads are results, not noise on stderr.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    while report.runs < config.max_runs {
+        let exec = run_once(build(), config.max_steps, |runnable, k| {
+            Ok(match config.mode {
+                Mode::Exhaustive => prefix.get(k).copied().unwrap_or(0),
+                Mode::Random { .. } => {
+                    rng ^= rng << 13;
+                    rng ^= rng >> 7;
+                    rng ^= rng << 17;
+                    (rng % runnable.len() as u64) as usize
+                }
+            })
+        });
+        report.runs += 1;
+        let outcome = match &exec.result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => e.clone(),
+        };
+        match report.outcomes.iter_mut().find(|(o, _)| *o == outcome) {
+            Some((_, n)) => *n += 1,
+            None => report.outcomes.push((outcome, 1)),
+        }
+        if exec.result.is_err() {
+            report.failing_runs += 1;
+            let better = report
+                .minimal
+                .as_ref()
+                .is_none_or(|m| (exec.preemptions, exec.schedule.len()) < (m.preemptions, m.schedule.len()));
+            if better {
+                report.minimal = Some(exec.clone());
+            }
+        }
+        if config.mode == Mode::Exhaustive {
+            // Next schedule in depth-first order: bump the last choice that has
+            // an untried alternative, drop everything after it.
+            let mut choices = exec.choices;
+            while let Some((index, count)) = choices.pop() {
+                if index + 1 < count {
+                    choices.push((index + 1, count));
+                    break;
+                }
+            }
+            if choices.is_empty() {
+                report.complete = true;
+                break;
+            }
+            prefix = choices.into_iter().map(|(i, _)| i).collect();
+        }
+    }
+    panic::set_hook(hook);
+    report
+}
+
+/// Runs `build()`'s program once under `schedule` (thread ids, as printed by
+/// `Execution::schedule_string`). After the schedule runs out, threads keep
+/// running without preemption.
+pub fn replay(schedule: &[usize], max_steps: usize, build: impl FnOnce() -> Program) -> Execution {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let exec = run_once(build(), max_steps, |runnable, k| match schedule.get(k) {
+        None => Ok(0),
+        Some(t) => runnable
+            .iter()
+            .position(|r| r == t)
+            .ok_or(format!("schedule step {k}: thread {t} cannot run there (runnable: {runnable:?})")),
+    });
+    panic::set_hook(hook);
+    exec
+}
+
+/// Parses "0,1,1,0".
+pub fn parse_schedule(s: &str) -> Option<Vec<usize>> {
+    s.split(',').filter(|t| !t.trim().is_empty()).map(|t| t.trim().parse().ok()).collect()
+}
@@ -0,0 +1,189 @@
+// src/main.rs
+//
+// `interleave_lab explore <example> [--threads N] [--increments K] [--random RUNS] [--seed S]`
+// `interleave_lab replay <example> <schedule> [--threads N] [--increments K]`
+// No arguments: a tour through every example.
+//
+// Examples (each thread adds 1 to a shared counter K times, unless noted):
+//   racy        v = load(counter); store(counter, v + 1)    -- two steps, can lose updates
+//   fetch-add   fetch_add(counter, 1)                       -- one indivisible step
+//   locked      lock(m); v = load; store(v + 1); unlock(m)  -- two steps, but exclusive
+//   deadlock    two threads, two locks, opposite order (no counter)
+//
+// `racy` is what `counter += 1` on a plain shared integer compiles to. The
+// counters in ../src/main.rs and concurrency/ use fetch_add, so they can never
+// show this, however often they run.
+
+use std::process::ExitCode;
+
+use interleave_lab::{explore, parse_schedule, replay, Config, Lock, Mode, Program, Report, Var};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Example {
+    Racy,
+    FetchAdd,
+    Locked,
+    Deadlock,
+}
+
+impl Example {
+    fn parse(s: &str) -> Option<Example> {
+        match s {
+            "racy" => Some(Example::Racy),
+            "fetch-add" => Some(Example::FetchAdd),
+            "locked" => Some(Example::Locked),
+            "deadlock" => Some(Example::Deadlock),
+            _ => None,
+        }
+    }
+
+    fn build(self, threads: usize, increments: usize) -> Program {
+        if self == Example::Deadlock {
+            let (a, b) = (Lock::new("a"), Lock::new("b"));
+            let (a2, b2) = (a.clone(), b.clone());
+            return Program::new()
+                .thread("t0", move || {
+                    let _a = a.lock();
+                    let _b = b.lock();
+                })
+                .thread("t1", move || {
+                    let _b = b2.lock();
+                    let _a = a2.lock();
+                });
+        }
+        let counter = Var::new("counter", 0i64);
+        let lock = Lock::new("m");
+        let mut program = Program::new();
+        for t in 0..threads {
+            let (counter, lock) = (counter.clone(), lock.clone());
+            program = program.thread(format!("t{t}"), move || {
+                for _ in 0..increments {
+                    match self {
+                        Example::Racy => {
+                            let v = counter.load();
+                            counter.store(v + 1);
+                        }
+                        Example::FetchAdd => {
+                            counter.fetch_add(1);
+                        }
+                        Example::Locked => {
+                            let _guard = lock.lock();
+                            let v = counter.load();
+                            counter.store(v + 1);
+                        }
+                        Example::Deadlock => unreachable!(),
+                    }
+                }
+            });
+        }
+        let expected = (threads * increments) as i64;
+        program.check(move || match counter.get() {
+            v if v == expected => Ok(()),
+            v => Err(format!("counter = {v}, expected {expected}: {} update(s) lost", expected - v)),
+        })
+    }
+}
+
+struct Options {
+    threads: usize,
+    increments: usize,
+    config: Config,
+}
+
+fn parse_options(args: &[&str]) -> Result<Options, String> {
+    let mut opts = Options { threads: 2, increments: 1, config: Config::default() };
+    let mut seed = 1;
+    let mut random = None;
+    let mut it = args.iter();
+    while let Some(&arg) = it.next() {
+        let value = it.next().ok_or(format!("{arg} needs a value"))?;
+        let n: usize = value.replace('_', "").parse().map_err(|_| format!("{arg} takes a number, got '{value}'"))?;
+        match arg {
+            "--threads" => opts.threads = n.max(1),
+            "--increments" => opts.increments = n,
+            "--random" => random = Some(n),
+            "--seed" => seed = n as u64,
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    if let Some(runs) = random {
+        opts.config = Config { mode: Mode::Random { seed }, max_runs: runs, ..opts.config };
+    }
+    Ok(opts)
+}
+
+fn print_report(name: &str, flags: &str, report: &Report) {
+    let how = match report.complete {
+        true => "every schedule",
+        false => "sampled schedules",
+    };
+    println!("{} runs ({how}), {} failing", report.runs, report.failing_runs);
+    let mut outcomes = report.outcomes.clone();
+    outcomes.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    for (outcome, n) in &outcomes {
+        println!("  {n:>7} x {outcome}");
+    }
+    if let Some(m) = &report.minimal {
+        println!(
+            "simplest failing schedule: {} preemption(s), {} steps ('~' marks a thread switch)",
+            m.preemptions,
+            m.schedule.len()
+        );
+        println!("{m}");
+        println!("replay it: cargo run -p interleave_lab -- replay {name} {}{flags}", m.schedule_string());
+    }
+}
+
+fn run_explore(example: Example, name: &str, opts: &Options) {
+    let report = explore(&opts.config, || example.build(opts.threads, opts.increments));
+    let flags = match (opts.threads, opts.increments) {
+        (2, 1) => String::new(),
+        (t, k) => format!(" --threads {t} --increments {k}"),
+    };
+    print_report(name, &flags, &report);
+}
+
+fn tour() {
+    let opts = Options { threads: 2, increments: 1, config: Config::default() };
+    println!("--- racy: two threads, each does `v = load; store(v + 1)` once ---");
+    run_explore(Example::Racy, "racy", &opts);
+    println!("\n--- fetch-add: the same increment as one atomic step ---");
+    run_explore(Example::FetchAdd, "fetch-add", &opts);
+    println!("\n--- locked: load and store again, inside a lock ---");
+    run_explore(Example::Locked, "locked", &opts);
+    println!("\n--- deadlock: a then b, b then a ---");
+    run_explore(Example::Deadlock, "deadlock", &opts);
+    println!("\n--- racy with 3 threads x 3 increments: too many schedules to try, so sample ---");
+    let sampled = Options { threads: 3, increments: 3, config: Config { mode: Mode::Random { seed: 7 }, max_runs: 2_000, ..Config::default() } };
+    run_explore(Example::Racy, "racy", &sampled);
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    let example = |s: &str| Example::parse(s).ok_or(format!("unknown example '{s}', expected racy, fetch-add, locked or deadlock"));
+    match args {
+        [] => tour(),
+        ["explore", name, rest @ ..] => run_explore(example(name)?, name, &parse_options(rest)?),
+        ["replay", name, schedule, rest @ ..] => {
+            let ex = example(name)?;
+            let opts = parse_options(rest)?;
+            let schedule = parse_schedule(schedule).ok_or(format!("bad schedule '{schedule}', expected e.g. 0,1,1,0"))?;
+            let exec = replay(&schedule, opts.config.max_steps, || ex.build(opts.threads, opts.increments));
+            println!("schedule {} ({} preemptions)", exec.schedule_string(), exec.preemptions);
+            println!("{exec}");
+        }
+        _ => return Err("expected `explore <example> ..` or `replay <example> <schedule> ..`".into()),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
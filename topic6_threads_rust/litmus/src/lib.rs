@@ -0,0 +1,384 @@
+// src/lib.rs
+//
+// Litmus tests: tiny multi-threaded programs whose possible results pin down
+// what a memory model allows. Each test has one WEAK outcome, the one that no
+// interleaving of the threads' instructions can produce, and the question is
+// whether the hardware/compiler/memory model can produce it anyway.
+//
+//   SB    store buffering    T0: x = 1; r0 = y        T1: y = 1; r1 = x
+//         weak: r0 = 0, r1 = 0   (each store still sitting in its core's store buffer)
+//   MP    message passing    T0: data = 1; flag = 1   T1: r0 = flag; r1 = data
+//         weak: r0 = 1, r1 = 0   (saw the flag but not the data it guards)
+//   LB    load buffering     T0: r0 = x; y = 1        T1: r1 = y; x = 1
+//         weak: r0 = 1, r1 = 1   (each load saw a store that came after the other load)
+//   IRIW  independent reads of independent writes
+//         T0: x = 1   T1: y = 1   T2: r0 = x; r1 = y   T3: r2 = y; r3 = x
+//         weak: r0 = 1, r1 = 0, r2 = 1, r3 = 0   (the readers disagree on which write came first)
+//   2+2W  two plus two writes T0: x = 1; y = 2        T1: y = 1; x = 2
+//         weak: x = 1, y = 1 at the end   (each thread's first write landed last)
+//
+// What the Rust (C++20) model forbids, with loads and stores at the given orderings:
+//   SB    SeqCst loads and stores, or a SeqCst fence between the two ops
+//   MP    Release store of the flag + Acquire load of it (or stronger), or fences
+//   LB    Acquire loads + Release stores, or fences
+//   IRIW  SeqCst loads and stores, or SeqCst fences between the reads
+//   2+2W  SeqCst stores, or fences
+// A forbidden outcome that shows up anyway is a bug in the compiler or the CPU.
+//
+// What x86 can do (TSO, total store order): the only reordering is a store
+// passing a LATER load, through the store buffer. So x86 shows SB's weak outcome
+// with anything but SeqCst (SeqCst stores compile to xchg, which drains the
+// buffer), and never shows the weak outcome of MP, LB, IRIW or 2+2W, whatever
+// ordering the Rust code asks for. Those need a weaker CPU, like ARM or POWER,
+// or a compiler that reorders relaxed accesses.
+//
+// The runner keeps the threads alive and starts every iteration with a spin
+// barrier, with a little random delay so the threads line up differently each
+// time. The weak outcomes need truly simultaneous threads: with fewer cores
+// than threads they practically never appear.
+
+use std::fmt;
+use std::sync::atomic::{fence, AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Test {
+    Sb,
+    Mp,
+    Lb,
+    Iriw,
+    TwoPlusTwoW,
+}
+
+impl Test {
+    pub const ALL: [Test; 5] = [Test::Sb, Test::Mp, Test::Lb, Test::Iriw, Test::TwoPlusTwoW];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Test::Sb => "SB",
+            Test::Mp => "MP",
+            Test::Lb => "LB",
+            Test::Iriw => "IRIW",
+            Test::TwoPlusTwoW => "2+2W",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Test> {
+        Test::ALL.into_iter().find(|t| t.name().eq_ignore_ascii_case(s))
+    }
+
+    pub fn threads(self) -> usize {
+        if self == Test::Iriw { 4 } else { 2 }
+    }
+
+    /// Names of the values that make up an outcome.
+    pub fn labels(self) -> &'static [&'static str] {
+        match self {
+            Test::Sb | Test::Mp | Test::Lb => &["r0", "r1"],
+            Test::Iriw => &["r0", "r1", "r2", "r3"],
+            Test::TwoPlusTwoW => &["x", "y"],
+        }
+    }
+
+    /// The outcome no interleaving can produce.
+    pub fn weak(self) -> Outcome {
+        Outcome(match self {
+            Test::Sb => vec![0, 0],
+            Test::Mp => vec![1, 0],
+            Test::Lb => vec![1, 1],
+            Test::Iriw => vec![1, 0, 1, 0],
+            Test::TwoPlusTwoW => vec![1, 1],
+        })
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Test::Sb => "T0: x=1; r0=y   T1: y=1; r1=x",
+            Test::Mp => "T0: data=1; flag=1   T1: r0=flag; r1=data",
+            Test::Lb => "T0: r0=x; y=1   T1: r1=y; x=1",
+            Test::Iriw => "T0: x=1   T1: y=1   T2: r0=x; r1=y   T3: r2=y; r3=x",
+            Test::TwoPlusTwoW => "T0: x=1; y=2   T1: y=1; x=2   (outcome: final x, y)",
+        }
+    }
+
+    /// Whether the Rust memory model forbids the weak outcome under `o`.
+    pub fn forbidden(self, o: Orderings) -> bool {
+        if o.fence {
+            return true;
+        }
+        let (load_acq, store_rel) = (o.load != Ordering::Relaxed, o.store != Ordering::Relaxed);
+        let sc = o.load == Ordering::SeqCst && o.store == Ordering::SeqCst;
+        match self {
+            Test::Sb | Test::Iriw => sc,
+            Test::Mp | Test::Lb => load_acq && store_rel,
+            Test::TwoPlusTwoW => o.store == Ordering::SeqCst,
+        }
+    }
+
+    /// Whether an x86 (TSO) machine can produce the weak outcome under `o`.
+    pub fn tso_allows(self, o: Orderings) -> bool {
+        self == Test::Sb && !o.fence && o.store != Ordering::SeqCst
+    }
+}
+
+impl fmt::Display for Test {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Orderings for the test's loads and stores, plus optional SeqCst fences
+/// between each thread's two operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orderings {
+    pub load: Ordering,
+    pub store: Ordering,
+    pub fence: bool,
+}
+
+impl Orderings {
+    /// One ordering for everything, the way code usually reads: Acquire and
+    /// Release only mean something on one side, so the other side gets Relaxed
+    /// (AcqRel becomes Acquire loads and Release stores).
+    pub fn uniform(o: Ordering) -> Orderings {
+        let load = match o {
+            Ordering::Release => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Acquire,
+            other => other,
+        };
+        let store = match o {
+            Ordering::Acquire => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Release,
+            other => other,
+        };
+        Orderings { load, store, fence: false }
+    }
+}
+
+impl fmt::Display for Orderings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "loads {:?}, stores {:?}", self.load, self.store)?;
+        if self.fence {
+            write!(f, ", SeqCst fence between")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_ordering(s: &str) -> Option<Ordering> {
+    match s.to_ascii_lowercase().as_str() {
+        "relaxed" => Some(Ordering::Relaxed),
+        "acquire" => Some(Ordering::Acquire),
+        "release" => Some(Ordering::Release),
+        "acqrel" => Some(Ordering::AcqRel),
+        "seqcst" => Some(Ordering::SeqCst),
+        _ => None,
+    }
+}
+
+/// The values of `Test::labels`, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Outcome(pub Vec<u32>);
+
+// Each location on its own cache line, so the test measures ordering rather
+// than false sharing.
+#[repr(align(64))]
+#[derive(Default)]
+struct Padded<T>(T);
+
+#[derive(Default)]
+struct Memory {
+    x: Padded<AtomicU32>,
+    y: Padded<AtomicU32>,
+}
+
+// Thread `t`'s part of `test`. Returns its registers (unused ones stay 0).
+fn body(test: Test, t: usize, m: &Memory, o: Orderings) -> [u32; 2] {
+    let (x, y) = (&m.x.0, &m.y.0);
+    let between = || {
+        if o.fence {
+            fence(Ordering::SeqCst);
+        }
+    };
+    match (test, t) {
+        (Test::Sb, 0) => {
+            x.store(1, o.store);
+            between();
+            [y.load(o.load), 0]
+        }
+        (Test::Sb, _) => {
+            y.store(1, o.store);
+            between();
+            [x.load(o.load), 0]
+        }
+        // x is `data`, y is `flag`.
+        (Test::Mp, 0) => {
+            x.store(1, o.store);
+            between();
+            y.store(1, o.store);
+            [0, 0]
+        }
+        (Test::Mp, _) => {
+            let flag = y.load(o.load);
+            between();
+            [flag, x.load(o.load)]
+        }
+        (Test::Lb, 0) => {
+            let r = x.load(o.load);
+            between();
+            y.store(1, o.store);
+            [r, 0]
+        }
+        (Test::Lb, _) => {
+            let r = y.load(o.load);
+            between();
+            x.store(1, o.store);
+            [r, 0]
+        }
+        (Test::Iriw, 0) => {
+            x.store(1, o.store);
+            [0, 0]
+        }
+        (Test::Iriw, 1) => {
+            y.store(1, o.store);
+            [0, 0]
+        }
+        (Test::Iriw, 2) => {
+            let a = x.load(o.load);
+            between();
+            [a, y.load(o.load)]
+        }
+        (Test::Iriw, _) => {
+            let a = y.load(o.load);
+            between();
+            [a, x.load(o.load)]
+        }
+        (Test::TwoPlusTwoW, 0) => {
+            x.store(1, o.store);
+            between();
+            y.store(2, o.store);
+            [0, 0]
+        }
+        (Test::TwoPlusTwoW, _) => {
+            y.store(1, o.store);
+            between();
+            x.store(2, o.store);
+            [0, 0]
+        }
+    }
+}
+
+fn outcome(test: Test, regs: &[[u32; 2]], m: &Memory) -> Outcome {
+    Outcome(match test {
+        Test::Sb | Test::Lb => vec![regs[0][0], regs[1][0]],
+        Test::Mp => vec![regs[1][0], regs[1][1]],
+        Test::Iriw => vec![regs[2][0], regs[2][1], regs[3][0], regs[3][1]],
+        Test::TwoPlusTwoW => vec![m.x.0.load(Ordering::Relaxed), m.y.0.load(Ordering::Relaxed)],
+    })
+}
+
+/// Sense-reversing spin barrier. Spins briefly, then yields, so it still works
+/// (slowly) when there are more threads than cores.
+struct SpinBarrier {
+    n: usize,
+    count: AtomicUsize,
+    sense: AtomicBool,
+}
+
+impl SpinBarrier {
+    fn new(n: usize) -> Self {
+        SpinBarrier { n, count: AtomicUsize::new(0), sense: AtomicBool::new(false) }
+    }
+
+    fn wait(&self, local_sense: &mut bool) {
+        *local_sense = !*local_sense;
+        if self.count.fetch_add(1, Ordering::AcqRel) + 1 == self.n {
+            self.count.store(0, Ordering::Relaxed);
+            self.sense.store(*local_sense, Ordering::Release);
+        } else {
+            let mut spins = 0u32;
+            while self.sense.load(Ordering::Acquire) != *local_sense {
+                spins += 1;
+                if spins < 1_000 { std::hint::spin_loop() } else { thread::yield_now() }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub test: Test,
+    pub orderings: Orderings,
+    pub iterations: u64,
+    pub elapsed: Duration,
+    /// Outcomes and how often each was seen, most frequent first.
+    pub counts: Vec<(Outcome, u64)>,
+}
+
+impl Histogram {
+    pub fn weak_count(&self) -> u64 {
+        let weak = self.test.weak();
+        self.counts.iter().find(|(o, _)| *o == weak).map_or(0, |(_, n)| *n)
+    }
+
+    /// The weak outcome appeared although the memory model forbids it.
+    pub fn violation(&self) -> bool {
+        self.weak_count() > 0 && self.test.forbidden(self.orderings)
+    }
+}
+
+/// Runs `test` `iterations` times on real threads and counts the outcomes.
+pub fn run(test: Test, orderings: Orderings, iterations: u64) -> Histogram {
+    let n = test.threads();
+    let memory = Memory::default();
+    let barrier = SpinBarrier::new(n);
+    let regs: Vec<[AtomicU32; 2]> = (0..n).map(|_| [AtomicU32::new(0), AtomicU32::new(0)]).collect();
+    let jitter_seed = AtomicU64::new(0x2545_F491_4F6C_DD1D);
+    let start = Instant::now();
+
+    let mut counts: Vec<(Outcome, u64)> = thread::scope(|s| {
+        let worker = |t: usize| {
+            let (memory, barrier, regs, jitter_seed) = (&memory, &barrier, &regs, &jitter_seed);
+            move || {
+                let mut sense = false;
+                let mut rng = jitter_seed.fetch_add(0x9E37_79B9 * (t as u64 + 1), Ordering::Relaxed) | 1;
+                let mut counts: Vec<(Outcome, u64)> = Vec::new();
+                for _ in 0..iterations {
+                    barrier.wait(&mut sense);
+                    rng ^= rng << 13;
+                    rng ^= rng >> 7;
+                    rng ^= rng << 17;
+                    for _ in 0..rng % 8 {
+                        std::hint::spin_loop();
+                    }
+                    let r = body(test, t, memory, orderings);
+                    regs[t][0].store(r[0], Ordering::Relaxed);
+                    regs[t][1].store(r[1], Ordering::Relaxed);
+                    barrier.wait(&mut sense);
+                    // Thread 0 tallies and resets; the next barrier publishes the reset.
+                    if t == 0 {
+                        let all: Vec<[u32; 2]> =
+                            regs.iter().map(|r| [r[0].load(Ordering::Relaxed), r[1].load(Ordering::Relaxed)]).collect();
+                        let o = outcome(test, &all, memory);
+                        match counts.iter_mut().find(|(k, _)| *k == o) {
+                            Some((_, c)) => *c += 1,
+                            None => counts.push((o, 1)),
+                        }
+                        memory.x.0.store(0, Ordering::Relaxed);
+                        memory.y.0.store(0, Ordering::Relaxed);
+                    }
+                }
+                counts
+            }
+        };
+        let others: Vec<_> = (1..n).map(|t| s.spawn(worker(t))).collect();
+        let counts = worker(0)();
+        for h in others {
+            h.join().unwrap();
+        }
+        counts
+    });
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Histogram { test, orderings, iterations, elapsed: start.elapsed(), counts }
+}
@@ -0,0 +1,124 @@
+// src/main.rs
+//
+// `litmus [TEST|all] [--ordering O | --load O --store O] [--fence] [--iterations N]`
+//     TEST is SB, MP, LB, IRIW or 2+2W; O is relaxed, acquire, release, acqrel or seqcst
+// No arguments: every test with Relaxed, AcqRel and SeqCst, fewer iterations.
+//
+// For every outcome: how often it appeared, and for the weak one whether the
+// Rust memory model forbids it and whether x86 (TSO) hardware can produce it.
+// A forbidden outcome that was observed is reported as a VIOLATION, and the
+// exit status is non-zero.
+
+use std::process::ExitCode;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use litmus::{parse_ordering, run, Histogram, Orderings, Test};
+
+fn print_histogram(h: &Histogram) {
+    let test = h.test;
+    println!("{} ({}), {}: {} iterations in {:.1}s", test, test.describe(), h.orderings, h.iterations, h.elapsed.as_secs_f64());
+    let weak = test.weak();
+    for (outcome, n) in &h.counts {
+        let values: Vec<String> = test.labels().iter().zip(&outcome.0).map(|(l, v)| format!("{l}={v}")).collect();
+        let note = if *outcome == weak { "  <- weak" } else { "" };
+        println!("  {:<24} {n:>10}{note}", values.join(" "));
+    }
+    let forbidden = test.forbidden(h.orderings);
+    let weak_values: Vec<String> = test.labels().iter().zip(&weak.0).map(|(l, v)| format!("{l}={v}")).collect();
+    println!(
+        "  weak outcome {}: {} by the Rust model, {} on x86-TSO, seen {} times{}",
+        weak_values.join(" "),
+        if forbidden { "forbidden" } else { "allowed" },
+        if test.tso_allows(h.orderings) { "possible" } else { "impossible" },
+        h.weak_count(),
+        if h.violation() { "  VIOLATION" } else { "" }
+    );
+}
+
+fn cores_note(tests: &[Test]) {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let needed = tests.iter().map(|t| t.threads()).max().unwrap_or(2);
+    if cores < needed {
+        println!(
+            "note: {cores} core(s) available, these tests need {needed} running at once. Threads will take turns, so weak\n\
+             outcomes will almost never appear; counts of 0 here say nothing about the hardware.\n"
+        );
+    }
+    if cfg!(target_arch = "x86_64") {
+        println!("x86_64: expect SB's weak outcome without SeqCst, and no weak outcome for MP, LB, IRIW, 2+2W.\n");
+    } else {
+        println!("not x86: this CPU may be weaker than TSO; MP, LB and IRIW may show weak outcomes with Relaxed.\n");
+    }
+}
+
+fn run_tests(tests: &[Test], orderings: &[Orderings], iterations: u64) -> Result<(), String> {
+    cores_note(tests);
+    let mut violations = 0;
+    for &test in tests {
+        for &o in orderings {
+            let h = run(test, o, iterations);
+            print_histogram(&h);
+            println!();
+            violations += h.violation() as usize;
+        }
+    }
+    if violations > 0 { Err(format!("{violations} forbidden outcome(s) observed")) } else { Ok(()) }
+}
+
+fn run_cli(args: &[&str]) -> Result<(), String> {
+    if args.is_empty() {
+        let orderings = [Ordering::Relaxed, Ordering::AcqRel, Ordering::SeqCst].map(Orderings::uniform);
+        run_tests(&Test::ALL, &orderings, 50_000)?;
+        println!("Try `litmus SB --ordering relaxed --fence`: one fence per thread forbids SB's weak outcome too.");
+        return Ok(());
+    }
+    let (tests, rest) = match args[0] {
+        "all" => (Test::ALL.to_vec(), &args[1..]),
+        name if !name.starts_with("--") => {
+            (vec![Test::parse(name).ok_or(format!("unknown test '{name}', expected SB, MP, LB, IRIW, 2+2W or all"))?], &args[1..])
+        }
+        _ => (Test::ALL.to_vec(), args),
+    };
+    let mut o = Orderings::uniform(Ordering::SeqCst);
+    let mut iterations = 1_000_000;
+    let mut it = rest.iter();
+    while let Some(&arg) = it.next() {
+        if arg == "--fence" {
+            o.fence = true;
+            continue;
+        }
+        let value = *it.next().ok_or(format!("{arg} needs a value"))?;
+        let ordering = || parse_ordering(value).ok_or(format!("unknown ordering '{value}'"));
+        match arg {
+            "--ordering" => o = Orderings { fence: o.fence, ..Orderings::uniform(ordering()?) },
+            "--load" => {
+                o.load = match ordering()? {
+                    Ordering::Release | Ordering::AcqRel => return Err("loads cannot be Release or AcqRel".into()),
+                    l => l,
+                }
+            }
+            "--store" => {
+                o.store = match ordering()? {
+                    Ordering::Acquire | Ordering::AcqRel => return Err("stores cannot be Acquire or AcqRel".into()),
+                    s => s,
+                }
+            }
+            "--iterations" => iterations = value.replace('_', "").parse().map_err(|_| format!("bad iteration count '{value}'"))?,
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    run_tests(&tests, &[o], iterations)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run_cli(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// src/aba.rs
+//
+// The ABA problem, with nodes from a fixed pool instead of the allocator: a
+// popped node goes back on a free list and the next push, onto any stack that
+// shares the pool, reuses it. That is what allocators do too, just less
+// predictably. Indices instead of pointers and atomics for every field keep
+// this free of undefined behaviour, so the naive stack can be run concurrently
+// and simply gets the wrong answer.
+//
+// Naive pop:  h = head; n = next[h]; CAS(head, h -> n)
+//
+//   stack: A -> B -> C
+//   T1: h = A, n = B               ... paused
+//   T2: pop A, pop B               (free list: B -> A)
+//       push x onto another stack  (gets node B)
+//       push y onto this stack     (gets node A: head = A again, A.next = C)
+//   T1: CAS(head, A -> B) succeeds, because head is A, and T1 returns y.
+//       head = B, the other stack's node: C is lost, and x is on both stacks.
+//
+// Tagged: head also holds a counter bumped by every successful CAS. T1's CAS
+// expects (A, tag 0) but finds (A, tag 3) and fails. The tag can wrap around,
+// but only after 2^32 changes during one pop.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+const NIL: u32 = u32::MAX;
+
+fn pack(index: u32, tag: u32) -> u64 {
+    (tag as u64) << 32 | index as u64
+}
+
+fn index(word: u64) -> u32 {
+    word as u32
+}
+
+fn tag(word: u64) -> u32 {
+    (word >> 32) as u32
+}
+
+/// Nodes shared by any number of `PoolStack`s, like a heap shared by every
+/// data structure in a program.
+pub struct Pool {
+    values: Vec<AtomicU64>,
+    next: Vec<AtomicU32>,
+    free: AtomicU64,
+    tagged: bool,
+}
+
+impl Pool {
+    /// `tagged` false: stacks (and the free list) that suffer from ABA.
+    pub fn new(capacity: usize, tagged: bool) -> Self {
+        let n = capacity as u32;
+        Pool {
+            values: (0..n).map(|_| AtomicU64::new(0)).collect(),
+            // Every node starts on the free list: 0 -> 1 -> ... -> NIL.
+            next: (0..n).map(|i| AtomicU32::new(if i + 1 < n { i + 1 } else { NIL })).collect(),
+            free: AtomicU64::new(pack(if n > 0 { 0 } else { NIL }, 0)),
+            tagged,
+        }
+    }
+
+    pub fn stack(&self) -> PoolStack<'_> {
+        PoolStack { pool: self, head: AtomicU64::new(pack(NIL, 0)) }
+    }
+
+    fn bump(&self, word: u64) -> u32 {
+        if self.tagged { tag(word).wrapping_add(1) } else { 0 }
+    }
+
+    // Pop from a list (a stack or the free list), calling `between` after
+    // reading head and next and before the CAS: the window where ABA strikes.
+    fn pop_list(&self, list: &AtomicU64, between: &mut dyn FnMut()) -> Option<u32> {
+        loop {
+            let h = list.load(Ordering::Acquire);
+            let i = index(h);
+            if i == NIL {
+                return None;
+            }
+            let n = self.next[i as usize].load(Ordering::Relaxed);
+            between();
+            if list.compare_exchange(h, pack(n, self.bump(h)), Ordering::AcqRel, Ordering::Acquire).is_ok() {
+                return Some(i);
+            }
+        }
+    }
+
+    fn push_list(&self, list: &AtomicU64, i: u32) {
+        let mut h = list.load(Ordering::Relaxed);
+        loop {
+            self.next[i as usize].store(index(h), Ordering::Relaxed);
+            match list.compare_exchange_weak(h, pack(i, self.bump(h)), Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(now) => h = now,
+            }
+        }
+    }
+}
+
+/// A lock-free stack of u64s whose nodes come from a `Pool`.
+pub struct PoolStack<'a> {
+    pool: &'a Pool,
+    head: AtomicU64,
+}
+
+impl PoolStack<'_> {
+    /// Gives the value back if the pool is out of nodes.
+    pub fn push(&self, value: u64) -> Result<(), u64> {
+        let pool = self.pool;
+        let Some(i) = pool.pop_list(&pool.free, &mut || {}) else { return Err(value) };
+        pool.values[i as usize].store(value, Ordering::Relaxed);
+        pool.push_list(&self.head, i);
+        Ok(())
+    }
+
+    pub fn pop(&self) -> Option<u64> {
+        self.pop_with(|| {})
+    }
+
+    /// `pop`, running `between` inside the ABA window (on every attempt).
+    pub fn pop_with(&self, mut between: impl FnMut()) -> Option<u64> {
+        let pool = self.pool;
+        let i = pool.pop_list(&self.head, &mut between)?;
+        let value = pool.values[i as usize].load(Ordering::Relaxed);
+        pool.push_list(&pool.free, i);
+        Some(value)
+    }
+
+    /// (node, value) from top to bottom. Stops after more nodes than the pool
+    /// has, since a corrupted list can contain a cycle.
+    pub fn contents(&self) -> Vec<(u32, u64)> {
+        let pool = self.pool;
+        let mut out = Vec::new();
+        let mut i = index(self.head.load(Ordering::Acquire));
+        while i != NIL && out.len() <= pool.values.len() {
+            out.push((i, pool.values[i as usize].load(Ordering::Relaxed)));
+            i = pool.next[i as usize].load(Ordering::Relaxed);
+        }
+        out
+    }
+}
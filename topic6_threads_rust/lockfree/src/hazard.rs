@@ -0,0 +1,208 @@
+// src/hazard.rs
+//
+// Hazard pointers: when may a node that was unlinked from a lock-free structure
+// be freed? Another thread may have loaded a pointer to it just before the
+// unlink and be about to read it. With a lock, the unlinker would know nobody is
+// inside; without one it has to ask.
+//
+//   reader   before dereferencing a shared pointer, publish it in a HAZARD slot
+//            ("I am using this node"), then re-read the source: if it still
+//            holds the same pointer, the node was not unlinked before the slot
+//            became visible, so nobody will free it while the slot holds it.
+//   unlinker does not free the node, it RETIRES it to a thread-local list. Once
+//            the list is long enough it SCANS every hazard slot of every thread
+//            and frees the retired nodes that no slot points to; the rest wait
+//            for the next scan.
+//
+// A side effect is the cure for ABA: a node cannot be freed, so its address
+// cannot be reused by a new node, while anyone holds a hazard on it.
+//
+// The slots live in a global list that only grows (slots are reused, never
+// freed), so walking it needs no protection itself. A thread that exits with
+// retired nodes still protected hands them to an orphan list that the next scan
+// on any thread picks up.
+
+use std::cell::RefCell;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+struct Slot {
+    hazard: AtomicPtr<u8>,
+    in_use: AtomicBool,
+    // Set once before the slot is published, never changed.
+    next: *const Slot,
+}
+
+// SAFETY: `next` is written before publication and only read afterwards; the
+// slots are leaked, so the pointer stays valid forever.
+unsafe impl Sync for Slot {}
+
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+static ORPHANS: Mutex<Vec<Retired>> = Mutex::new(Vec::new());
+static RETIRED: AtomicUsize = AtomicUsize::new(0);
+static FREED: AtomicUsize = AtomicUsize::new(0);
+
+// Scan once this many nodes wait on a thread.
+const SCAN_AT: usize = 64;
+
+fn slots() -> impl Iterator<Item = &'static Slot> {
+    let mut p = SLOTS.load(Ordering::Acquire) as *const Slot;
+    std::iter::from_fn(move || {
+        // SAFETY: slots are leaked, and `next` never changes after publication.
+        let slot = unsafe { p.as_ref()? };
+        p = slot.next;
+        Some(slot)
+    })
+}
+
+fn acquire_slot() -> &'static Slot {
+    for slot in slots() {
+        if !slot.in_use.load(Ordering::Relaxed)
+            && slot.in_use.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+        {
+            return slot;
+        }
+    }
+    let slot: &'static mut Slot =
+        Box::leak(Box::new(Slot { hazard: AtomicPtr::new(ptr::null_mut()), in_use: AtomicBool::new(true), next: ptr::null() }));
+    let mut head = SLOTS.load(Ordering::Relaxed);
+    loop {
+        slot.next = head;
+        match SLOTS.compare_exchange_weak(head, slot, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return slot,
+            Err(h) => head = h,
+        }
+    }
+}
+
+/// One hazard slot, owned by one thread at a time.
+pub struct Hazard {
+    slot: &'static Slot,
+}
+
+impl Hazard {
+    pub fn new() -> Self {
+        Hazard { slot: acquire_slot() }
+    }
+
+    /// Loads `src` and protects the result: until `clear` (or the next
+    /// `protect`), whatever it points to is not freed by `retire`.
+    pub fn protect<T>(&self, src: &AtomicPtr<T>) -> *mut T {
+        let mut p = src.load(Ordering::Relaxed);
+        loop {
+            // SeqCst store then SeqCst load: a scanner that unlinked `p` before
+            // our re-read either sees this hazard or made us see the new value.
+            self.slot.hazard.store(p.cast(), Ordering::SeqCst);
+            let again = src.load(Ordering::SeqCst);
+            if again == p {
+                return p;
+            }
+            p = again;
+        }
+    }
+
+    pub fn clear(&self) {
+        self.slot.hazard.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Hazard::new()
+    }
+}
+
+impl Drop for Hazard {
+    fn drop(&mut self) {
+        self.clear();
+        self.slot.in_use.store(false, Ordering::Release);
+    }
+}
+
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+}
+
+// SAFETY: a retired node is unreachable from the structure; whoever holds the
+// Retired is the only one who will ever free it.
+unsafe impl Send for Retired {}
+
+unsafe fn free_box<T>(p: *mut u8) {
+    // SAFETY: `retire` only takes pointers from Box::into_raw, freed once.
+    drop(unsafe { Box::from_raw(p.cast::<T>()) });
+}
+
+struct RetireList(Vec<Retired>);
+
+impl Drop for RetireList {
+    fn drop(&mut self) {
+        // Thread exit: free what we can, leave the rest to other threads.
+        scan(&mut self.0);
+        ORPHANS.lock().unwrap().append(&mut self.0);
+    }
+}
+
+thread_local! {
+    static RETIRED_HERE: RefCell<RetireList> = const { RefCell::new(RetireList(Vec::new())) };
+    static HAZARDS: (Hazard, Hazard) = (Hazard::new(), Hazard::new());
+}
+
+fn scan(list: &mut Vec<Retired>) {
+    if let Ok(mut orphans) = ORPHANS.try_lock() {
+        list.append(&mut orphans);
+    }
+    // Pairs with the SeqCst store/load in `protect`: every node in `list` was
+    // unlinked before this point.
+    fence(Ordering::SeqCst);
+    let mut hazards: Vec<*mut u8> = slots().map(|s| s.hazard.load(Ordering::SeqCst)).filter(|p| !p.is_null()).collect();
+    hazards.sort_unstable();
+    list.retain(|r| {
+        if hazards.binary_search(&r.ptr).is_ok() {
+            return true;
+        }
+        // SAFETY: unlinked, and no hazard points to it, so no thread can reach it.
+        unsafe { (r.free)(r.ptr) };
+        FREED.fetch_add(1, Ordering::Relaxed);
+        false
+    });
+}
+
+/// Hands a node that was unlinked from a structure over for freeing, once no
+/// hazard points to it.
+///
+/// # Safety
+/// `ptr` comes from `Box::<T>::into_raw`, is no longer reachable from any
+/// shared structure, and is retired only once.
+pub unsafe fn retire<T>(ptr: *mut T) {
+    RETIRED.fetch_add(1, Ordering::Relaxed);
+    let r = Retired { ptr: ptr.cast(), free: free_box::<T> };
+    let kept = RETIRED_HERE.try_with(|list| {
+        let list = &mut list.borrow_mut().0;
+        list.push(r);
+        if list.len() >= SCAN_AT {
+            scan(list);
+        }
+    });
+    if kept.is_err() {
+        // Called during thread exit, after our list is gone.
+        ORPHANS.lock().unwrap().push(Retired { ptr: ptr.cast(), free: free_box::<T> });
+    }
+}
+
+/// Runs `f` with this thread's two hazard slots (a queue needs two at once).
+pub fn with_hazards<R>(f: impl FnOnce(&Hazard, &Hazard) -> R) -> R {
+    HAZARDS.with(|(a, b)| f(a, b))
+}
+
+/// Scans now, freeing everything this thread (or an exited thread) retired
+/// that is no longer protected.
+pub fn reclaim() {
+    RETIRED_HERE.with(|list| scan(&mut list.borrow_mut().0));
+}
+
+/// (nodes retired, nodes freed) since the program started.
+pub fn counts() -> (usize, usize) {
+    (RETIRED.load(Ordering::Relaxed), FREED.load(Ordering::Relaxed))
+}
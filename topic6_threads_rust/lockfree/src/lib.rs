@@ -0,0 +1,29 @@
+// src/lib.rs
+//
+// Lock-free data structures: shared structures changed with compare-and-swap
+// (CAS) loops instead of locks. Read the current state, compute the new state,
+// CAS it in; if another thread changed it in between, the CAS fails and we try
+// again. Some thread's CAS always succeeds, so the structure as a whole always
+// makes progress even if a thread is descheduled at the worst moment (a thread
+// holding a lock that gets descheduled stops everybody).
+//
+//   stack.rs   Treiber stack on AtomicPtr
+//   queue.rs   Michael-Scott queue on AtomicPtr
+//   hazard.rs  hazard pointers: when an unlinked node may be freed
+//   aba.rs     the ABA problem on a pool-based stack, naive and tagged
+//   model.rs   the same pool stack for interleave_lab's schedule explorer
+//
+// The hard part is not the CAS loops but MEMORY: a node unlinked by one thread
+// may still be read by another that loaded a pointer to it a moment earlier.
+// Freeing it immediately is a use-after-free, and reusing its address lets a
+// stale CAS succeed (ABA). Garbage-collected languages get this for free; here
+// hazard.rs delays every free until no thread can still be looking.
+
+pub mod aba;
+pub mod hazard;
+pub mod model;
+pub mod queue;
+pub mod stack;
+
+pub use queue::MsQueue;
+pub use stack::TreiberStack;
@@ -0,0 +1,330 @@
+// src/main.rs
+//
+// `lockfree aba`
+//     the ABA problem step by step, on the naive and the tagged pool stack
+// `lockfree model [naive|tagged]`
+//     every interleaving of the ABA scenario (see model.rs), with the shortest
+//     schedule that breaks the naive stack
+// `lockfree stress [--threads N] [--ops N] [--rounds N]`
+//     Treiber stack, Michael-Scott queue and both pool stacks under real
+//     threads; fails if an item is lost, duplicated or reordered (queue), a
+//     value is not dropped exactly once, or a retired node is never freed.
+//     Corruption of the naive stack is reported but expected.
+// `lockfree bench [--threads N] [--ops N]`
+//     push/pop pairs per second against Mutex<VecDeque>
+// No arguments: all of the above, smaller.
+
+use std::collections::VecDeque;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Barrier, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use interleave_lab::{explore, Config, Mode};
+use lockfree::aba::Pool;
+use lockfree::model::aba_program;
+use lockfree::{hazard, MsQueue, TreiberStack};
+
+fn show(contents: &[(u32, u64)]) -> String {
+    let items: Vec<String> = contents.iter().map(|(node, value)| format!("n{node}={value}")).collect();
+    format!("[{}]", items.join(" -> "))
+}
+
+// The scenario from aba.rs, with T2's steps run inside T1's pop window.
+fn aba_demo(tagged: bool) -> bool {
+    let pool = Pool::new(4, tagged);
+    let (stack, other) = (pool.stack(), pool.stack());
+    for v in [3, 2, 1] {
+        stack.push(v).expect("pool has room");
+    }
+    println!("  stack {}, other {}", show(&stack.contents()), show(&other.contents()));
+    let mut paused = true;
+    let got = stack.pop_with(|| {
+        if !std::mem::take(&mut paused) {
+            println!("  T1 retries with the fresh head");
+            return;
+        }
+        println!("  T1 has read head and head.next, and is paused before its CAS");
+        let (a, b) = (stack.pop(), stack.pop());
+        other.push(10).expect("pool has room");
+        stack.push(20).expect("pool has room");
+        println!("  T2 pops {a:?} and {b:?}, pushes 10 onto other and 20 onto stack");
+        println!("  stack {}, other {}  (n2 is back on top)", show(&stack.contents()), show(&other.contents()));
+    });
+    let (s, o) = (stack.contents(), other.contents());
+    println!("  T1 pops {got:?}: stack {}, other {}", show(&s), show(&o));
+    let values = |c: &[(u32, u64)]| c.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+    let correct = got == Some(20) && values(&s) == [3] && values(&o) == [10];
+    println!("  {}", if correct { "correct: stack [3], other [10]" } else { "WRONG: expected stack [3], other [10]" });
+    correct
+}
+
+fn aba() -> Result<(), String> {
+    println!("naive pool stack:");
+    let naive = aba_demo(false);
+    println!("\ntagged pool stack (head carries a change counter):");
+    let tagged = aba_demo(true);
+    println!("\nTreiberStack and MsQueue avoid it differently: a node is not freed, so its address");
+    println!("cannot come back, while any thread holds a hazard pointer to it.");
+    match (naive, tagged) {
+        (false, true) => Ok(()),
+        (true, _) => Err("the naive stack survived the ABA scenario".into()),
+        (_, false) => Err("the tagged stack was corrupted".into()),
+    }
+}
+
+fn model(which: &[&str], config: &Config) -> Result<(), String> {
+    let mut ok = true;
+    for tagged in [false, true] {
+        let name = if tagged { "tagged" } else { "naive" };
+        if !which.is_empty() && !which.contains(&name) {
+            continue;
+        }
+        let report = explore(config, || aba_program(tagged));
+        let how = if report.complete { "every schedule" } else { "sampled schedules" };
+        println!("{name}: {} runs ({how}), {} failing", report.runs, report.failing_runs);
+        if let Some(m) = &report.minimal {
+            println!("simplest failing schedule, {} preemption(s) ('~' marks a thread switch):", m.preemptions);
+            println!("{m}");
+        }
+        ok &= (report.failing_runs > 0) != tagged;
+    }
+    if ok { Ok(()) } else { Err("the model explorer disagreed with the expected result".into()) }
+}
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// An item that counts its drops: the structures must drop each one once.
+struct Tracked(u64);
+
+impl Tracked {
+    fn new(id: u64) -> Self {
+        CREATED.fetch_add(1, Ordering::Relaxed);
+        Tracked(id)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn id(thread: usize, i: usize) -> u64 {
+    (thread as u64) << 32 | i as u64
+}
+
+// Every id pushed exactly once must come out exactly once.
+fn exactly_once(threads: usize, ops: usize, logs: &[Vec<u64>]) -> Option<String> {
+    let mut seen = vec![0u8; threads * ops];
+    for &v in logs.iter().flatten() {
+        let (t, i) = ((v >> 32) as usize, (v & 0xFFFF_FFFF) as usize);
+        match seen.get_mut(t * ops + i) {
+            Some(n) => *n = n.saturating_add(1),
+            None => return Some(format!("popped {v:#x}, which nobody pushed")),
+        }
+    }
+    let lost = seen.iter().filter(|&&n| n == 0).count();
+    let duplicated = seen.iter().filter(|&&n| n > 1).count();
+    (lost + duplicated > 0).then(|| format!("{lost} lost, {duplicated} duplicated of {}", threads * ops))
+}
+
+// Each thread pushes its ids and pops after every second push; `pop` returns
+// what it got. The rest is drained afterwards as one more log.
+fn hammer<P: Fn(u64) + Sync, Q: Fn() -> Option<u64> + Sync>(threads: usize, ops: usize, push: P, pop: Q) -> Vec<Vec<u64>> {
+    let mut logs: Vec<Vec<u64>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let (push, pop) = (&push, &pop);
+                s.spawn(move || {
+                    let mut log = Vec::new();
+                    for i in 0..ops {
+                        push(id(t, i));
+                        if i % 2 == 1 {
+                            log.extend(pop());
+                            log.extend(pop());
+                        }
+                    }
+                    log
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    // Bounded: a corrupted naive stack may contain a cycle.
+    let rest: Vec<u64> = std::iter::from_fn(&pop).take(threads * ops + 1).collect();
+    logs.push(rest);
+    logs
+}
+
+// A consumer sees each producer's items in the order they were enqueued.
+fn fifo(logs: &[Vec<u64>]) -> Option<String> {
+    for (c, log) in logs.iter().enumerate() {
+        let mut last: Vec<(u64, u64)> = Vec::new();
+        for &v in log {
+            let (t, i) = (v >> 32, v & 0xFFFF_FFFF);
+            match last.iter_mut().find(|(p, _)| *p == t) {
+                Some((_, prev)) if i <= *prev => {
+                    return Some(format!("consumer {c} got thread {t}'s item {i} after item {prev}"));
+                }
+                Some((_, prev)) => *prev = i,
+                None => last.push((t, i)),
+            }
+        }
+    }
+    None
+}
+
+fn stress(args: &[&str]) -> Result<(), String> {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let (mut threads, mut ops, mut rounds) = (cores.max(4), 20_000, 10);
+    parse(args, &mut [("--threads", &mut threads), ("--ops", &mut ops), ("--rounds", &mut rounds)])?;
+    println!("{rounds} rounds, {threads} threads x {ops} pushes each, {cores} core(s)");
+    let mut failures = Vec::new();
+    let mut naive_broken = 0;
+    for round in 0..rounds {
+        let stack = TreiberStack::new();
+        let logs = hammer(threads, ops, |v| stack.push(Tracked::new(v)), || stack.pop().map(|t| t.0));
+        failures.extend(exactly_once(threads, ops, &logs).map(|e| format!("treiber round {round}: {e}")));
+
+        let queue = MsQueue::new();
+        let logs = hammer(threads, ops, |v| queue.enqueue(Tracked::new(v)), || queue.dequeue().map(|t| t.0));
+        failures.extend(exactly_once(threads, ops, &logs).map(|e| format!("ms-queue round {round}: {e}")));
+        failures.extend(fifo(&logs).map(|e| format!("ms-queue round {round}: {e}")));
+        // Left in the queue for Drop to clean up.
+        for v in 0..100 {
+            queue.enqueue(Tracked::new(v));
+        }
+        drop(queue);
+
+        for tagged in [false, true] {
+            let pool = Pool::new(threads * ops, tagged);
+            let stack = pool.stack();
+            // A failed push (free list corrupted) shows up as a lost item.
+            let logs = hammer(threads, ops, |v| { let _ = stack.push(v); }, || stack.pop());
+            match (exactly_once(threads, ops, &logs), tagged) {
+                (Some(e), true) => failures.push(format!("tagged pool stack round {round}: {e}")),
+                (Some(_), false) => naive_broken += 1,
+                (None, _) => {}
+            }
+        }
+    }
+    hazard::reclaim();
+    let (retired, freed) = hazard::counts();
+    let (created, dropped) = (CREATED.load(Ordering::Relaxed), DROPPED.load(Ordering::Relaxed));
+    if created != dropped {
+        failures.push(format!("{created} items created, {dropped} dropped"));
+    }
+    if retired != freed {
+        failures.push(format!("{retired} nodes retired, {freed} freed"));
+    }
+    println!("treiber, ms-queue, tagged pool stack: checked exactly-once (and FIFO for the queue)");
+    println!("{created} items created and dropped once each; {retired} nodes retired, {freed} freed");
+    println!("naive pool stack: corrupted in {naive_broken} of {rounds} rounds (ABA; expected with several cores)");
+    for f in &failures {
+        println!("FAIL {f}");
+    }
+    if failures.is_empty() { Ok(()) } else { Err(format!("{} failures", failures.len())) }
+}
+
+// Million push+pop pairs per second, all threads together.
+fn measure(threads: usize, ops: usize, push: impl Fn(u64) + Sync, pop: impl Fn() -> Option<u64> + Sync) -> f64 {
+    let start_line = Barrier::new(threads + 1);
+    let start = thread::scope(|s| {
+        for _ in 0..threads {
+            let (push, pop, start_line) = (&push, &pop, &start_line);
+            s.spawn(move || {
+                start_line.wait();
+                for i in 0..ops as u64 {
+                    push(i);
+                    std::hint::black_box(pop());
+                }
+            });
+        }
+        let start = Instant::now();
+        start_line.wait();
+        start
+    });
+    (threads * ops) as f64 / start.elapsed().as_secs_f64() / 1e6
+}
+
+fn bench(args: &[&str]) -> Result<(), String> {
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let (mut threads, mut ops) = (cores.max(4), 200_000);
+    parse(args, &mut [("--threads", &mut threads), ("--ops", &mut ops)])?;
+    println!("M push+pop pairs/s, {ops} pairs per thread, {cores} core(s)");
+    if cores < 2 {
+        println!("note: with one core nothing runs in parallel; this measures the cost of each operation.");
+    }
+    if cfg!(debug_assertions) {
+        println!("note: debug build; use `cargo run --release -p lockfree -- bench`");
+    }
+    println!("  {:>7} {:>10} {:>12} {:>10} {:>12}", "threads", "treiber", "mutex stack", "ms-queue", "mutex queue");
+    for t in 1..=threads {
+        let treiber = TreiberStack::new();
+        let locked_stack = Mutex::new(VecDeque::new());
+        let queue = MsQueue::new();
+        let locked_queue = Mutex::new(VecDeque::new());
+        println!(
+            "  {t:>7} {:>10.2} {:>12.2} {:>10.2} {:>12.2}",
+            measure(t, ops, |v| treiber.push(v), || treiber.pop()),
+            measure(t, ops, |v| locked_stack.lock().unwrap().push_back(v), || locked_stack.lock().unwrap().pop_back()),
+            measure(t, ops, |v| queue.enqueue(v), || queue.dequeue()),
+            measure(t, ops, |v| locked_queue.lock().unwrap().push_back(v), || locked_queue.lock().unwrap().pop_front()),
+        );
+    }
+    println!("Lock-free is about progress, not speed: an uncontended mutex is one CAS too, and every");
+    println!("lock-free op here also allocates a node and publishes a hazard pointer.");
+    Ok(())
+}
+
+fn parse(args: &[&str], options: &mut [(&str, &mut usize)]) -> Result<(), String> {
+    let mut it = args.iter();
+    while let Some(&arg) = it.next() {
+        let value = it.next().ok_or(format!("{arg} needs a value"))?;
+        let slot = options.iter_mut().find(|(name, _)| *name == arg).ok_or(format!("unknown argument '{arg}'"))?;
+        *slot.1 = value.replace('_', "").parse().map_err(|_| format!("{arg} takes a number, got '{value}'"))?;
+    }
+    Ok(())
+}
+
+fn tour() -> Result<(), String> {
+    println!("=== ABA, step by step ===");
+    aba()?;
+    println!("\n=== ABA, 3000 random interleavings (`lockfree model` tries all, ~15 s each) ===");
+    model(&[], &Config { mode: Mode::Random { seed: 1 }, max_runs: 3_000, ..Config::default() })?;
+    println!("\n=== stress ===");
+    stress(&["--rounds", "3", "--ops", "5000"])?;
+    println!("\n=== bench ===");
+    bench(&["--ops", "50000"])
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        [] => tour(),
+        ["aba"] => aba(),
+        ["model", rest @ ..] => {
+            if let Some(bad) = rest.iter().find(|w| !["naive", "tagged"].contains(w)) {
+                return Err(format!("unknown model '{bad}', expected naive or tagged"));
+            }
+            model(rest, &Config::default())
+        }
+        ["stress", rest @ ..] => stress(rest),
+        ["bench", rest @ ..] => bench(rest),
+        [other, ..] => Err(format!("unknown command '{other}', expected aba, model, stress or bench")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// src/model.rs
+//
+// The pool stack from aba.rs, rewritten on interleave_lab's Vars so every load,
+// store and CAS is a switch point the explorer controls. Real threads hit the
+// ABA window rarely (and on one core almost never); the explorer tries every
+// schedule and hands back the shortest one that breaks the stack.
+//
+// Scenario, nodes A, B, C, stack A -> B -> C:
+//   t1: pop
+//   t2: pop, pop, push the first node it popped
+// Whatever the order, the stack must end up holding exactly one node, and not
+// one that a thread still holds as popped.
+
+use interleave_lab::{Program, Var};
+
+const NIL: i64 = 3;
+const NAMES: [&str; 4] = ["A", "B", "C", "nil"];
+
+// head = tag * 4 + node
+fn node(head: i64) -> i64 {
+    head % 4
+}
+
+#[derive(Clone)]
+struct Model {
+    head: Var<i64>,
+    next: Vec<Var<i64>>,
+    tagged: bool,
+}
+
+impl Model {
+    fn bump(&self, head: i64) -> i64 {
+        if self.tagged { head - node(head) + 4 } else { 0 }
+    }
+
+    fn pop(&self) -> i64 {
+        loop {
+            let h = self.head.load();
+            if node(h) == NIL {
+                return NIL;
+            }
+            let n = self.next[node(h) as usize].load();
+            if self.head.compare_exchange(h, self.bump(h) + n).is_ok() {
+                return node(h);
+            }
+        }
+    }
+
+    fn push(&self, i: i64) {
+        loop {
+            let h = self.head.load();
+            self.next[i as usize].store(node(h));
+            if self.head.compare_exchange(h, self.bump(h) + i).is_ok() {
+                return;
+            }
+        }
+    }
+}
+
+/// The scenario above on the naive (`tagged` false) or tagged stack.
+pub fn aba_program(tagged: bool) -> Program {
+    let model = Model {
+        head: Var::new("head", 0),
+        next: vec![Var::new("A.next", 1), Var::new("B.next", 2), Var::new("C.next", NIL)],
+        tagged,
+    };
+    let held_by_t1 = Var::new("t1.popped", NIL);
+    let held_by_t2 = Var::new("t2.popped", NIL);
+
+    let (m1, h1) = (model.clone(), held_by_t1.clone());
+    let (m2, h2) = (model.clone(), held_by_t2.clone());
+    Program::new()
+        .thread("t1", move || h1.store(m1.pop()))
+        .thread("t2", move || {
+            let first = m2.pop();
+            let second = m2.pop();
+            h2.store(second);
+            if first != NIL {
+                m2.push(first);
+            }
+        })
+        .check(move || {
+            let mut listed = Vec::new();
+            let mut i = node(model.head.get());
+            while i != NIL && listed.len() <= 3 {
+                listed.push(i);
+                i = model.next[i as usize].get();
+            }
+            let names: Vec<&str> = listed.iter().map(|&i| NAMES[i as usize]).collect();
+            let held = [held_by_t1.get(), held_by_t2.get()];
+            if let Some(&both) = listed.iter().find(|i| held.contains(i)) {
+                return Err(format!("{} is popped but still on the stack [{}]", NAMES[both as usize], names.join(" -> ")));
+            }
+            if listed.len() != 1 {
+                return Err(format!("stack holds [{}], expected one node", names.join(" -> ")));
+            }
+            Ok(())
+        })
+}
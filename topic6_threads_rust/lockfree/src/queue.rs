@@ -0,0 +1,148 @@
+// src/queue.rs
+//
+// Michael-Scott queue: a linked list with a `head` and a `tail` pointer, both
+// atomic, and a DUMMY node at the front so the list is never empty. The first
+// real item is head.next; dequeuing makes that node the new dummy.
+//
+//   enqueue  CAS the last node's `next` from null to the new node (this is the
+//            moment the item is in the queue), then try to swing `tail` to it.
+//   dequeue  read head.next; if it is null the queue is empty, else CAS `head`
+//            to it and take its value. The old dummy is retired.
+//
+// `tail` may lag one node behind: an enqueuer that linked its node may not have
+// moved `tail` yet. Everybody who sees tail.next != null helps by swinging it
+// forward first, so no thread ever waits for another: lock-free.
+//
+// Two hazard slots: one on head (or tail) and one on head.next, which a
+// dequeuer reads the value from after another dequeuer may have moved past it.
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::hazard;
+
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn alloc(value: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node { value, next: AtomicPtr::new(ptr::null_mut()) }))
+    }
+}
+
+pub struct MsQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    _owns: PhantomData<T>,
+}
+
+// SAFETY: as for TreiberStack.
+unsafe impl<T: Send> Send for MsQueue<T> {}
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T> MsQueue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::alloc(MaybeUninit::uninit());
+        MsQueue { head: AtomicPtr::new(dummy), tail: AtomicPtr::new(dummy), _owns: PhantomData }
+    }
+
+    pub fn enqueue(&self, value: T) {
+        let node = Node::alloc(MaybeUninit::new(value));
+        hazard::with_hazards(|h, _| {
+            loop {
+                let tail = h.protect(&self.tail);
+                // SAFETY: protected, so not freed.
+                let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+                if tail != self.tail.load(Ordering::Acquire) {
+                    continue;
+                }
+                if !next.is_null() {
+                    // Tail lags behind; help the other enqueuer, then retry.
+                    let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                    continue;
+                }
+                // SAFETY: as above.
+                let linked = unsafe { &(*tail).next }.compare_exchange(next, node, Ordering::Release, Ordering::Relaxed);
+                if linked.is_ok() {
+                    // If this fails, someone already helped.
+                    let _ = self.tail.compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+                    h.clear();
+                    return;
+                }
+            }
+        })
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        hazard::with_hazards(|h_head, h_next| {
+            let result = loop {
+                let head = h_head.protect(&self.head);
+                // SAFETY: protected, so not freed.
+                let next = h_next.protect(unsafe { &(*head).next });
+                // Still the head: then `next` was reachable after h_next was set,
+                // so it is protected too.
+                if head != self.head.load(Ordering::Acquire) {
+                    continue;
+                }
+                if next.is_null() {
+                    break None;
+                }
+                let tail = self.tail.load(Ordering::Acquire);
+                if head == tail {
+                    // The item is linked but tail not moved yet; never let head
+                    // pass tail, or tail would point at a retired node.
+                    let _ = self.tail.compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                    continue;
+                }
+                if self.head.compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                    // SAFETY: our CAS made `next` the dummy, so its value is ours
+                    // alone; h_next keeps it alive. The old dummy is unlinked.
+                    let value = unsafe { (*next).value.assume_init_read() };
+                    unsafe { hazard::retire(head) };
+                    break Some(value);
+                }
+            };
+            h_head.clear();
+            h_next.clear();
+            result
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        hazard::with_hazards(|h, _| {
+            let head = h.protect(&self.head);
+            // SAFETY: protected.
+            let empty = unsafe { (*head).next.load(Ordering::Acquire) }.is_null();
+            h.clear();
+            empty
+        })
+    }
+}
+
+impl<T> Default for MsQueue<T> {
+    fn default() -> Self {
+        MsQueue::new()
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        // The dummy holds no value; every node after it does.
+        let mut p = *self.head.get_mut();
+        let mut dummy = true;
+        while !p.is_null() {
+            // SAFETY: linked nodes came from Box::into_raw; &mut self means no
+            // other thread can be using them.
+            let mut node = unsafe { Box::from_raw(p) };
+            if !dummy {
+                unsafe { node.value.assume_init_drop() };
+            }
+            dummy = false;
+            p = *node.next.get_mut();
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// src/stack.rs
+//
+// Treiber stack: a singly linked list whose head is one AtomicPtr.
+//
+//   push   make a node pointing at the current head, then CAS head from that
+//          head to the node. If another thread moved head in between, the CAS
+//          fails and we retry with the new head.
+//   pop    read head, read head.next, CAS head from head to next.
+//
+// Two things go wrong in pop without help:
+//   - use after free: between reading head and reading head.next, another
+//     thread may pop that node and free it.
+//   - ABA: between reading head (A) and the CAS, others may pop A, pop B, and
+//     push a new node that the allocator put at A's old address. The CAS sees
+//     A again and succeeds, installing the stale `next` (B, long gone).
+// A hazard pointer on head fixes both: A is not freed while we hold it, so no
+// new node can get its address (see hazard.rs, and aba.rs for the failure).
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::hazard;
+
+struct Node<T> {
+    // Moved out by the pop that unlinks the node; a retired node holds none.
+    value: MaybeUninit<T>,
+    // Written before the node is published, then never again.
+    next: *mut Node<T>,
+}
+
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    _owns: PhantomData<T>,
+}
+
+// SAFETY: values move between threads through push/pop (T: Send); nodes are
+// only shared through atomics and hazard-protected reads.
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub fn new() -> Self {
+        TreiberStack { head: AtomicPtr::new(ptr::null_mut()), _owns: PhantomData }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node { value: MaybeUninit::new(value), next: ptr::null_mut() }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: `node` is not published yet; only we can see it.
+            unsafe { (*node).next = head };
+            // Release: whoever pops the node sees its value and next.
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        hazard::with_hazards(|h, _| {
+            loop {
+                let head = h.protect(&self.head);
+                if head.is_null() {
+                    h.clear();
+                    return None;
+                }
+                // SAFETY: protected, so not freed even if popped by now; `next`
+                // was set before publication.
+                let next = unsafe { (*head).next };
+                if self.head.compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                    h.clear();
+                    // SAFETY: our CAS unlinked it, so we alone take the value,
+                    // and retire it once.
+                    let value = unsafe { (*head).value.assume_init_read() };
+                    unsafe { hazard::retire(head) };
+                    return Some(value);
+                }
+            }
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        TreiberStack::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        // &mut self: no other thread can hold a hazard on a node still linked.
+        let mut p = *self.head.get_mut();
+        while !p.is_null() {
+            // SAFETY: linked nodes came from Box::into_raw and hold a value.
+            let mut node = unsafe { Box::from_raw(p) };
+            unsafe { node.value.assume_init_drop() };
+            p = node.next;
+        }
+    }
+}
@@ -6,10 +6,15 @@ fn main() {
     let counter = Arc::new(AtomicU64::new(0));
     let mut handles = Vec::new();
 
+    // One OS thread per task is fine for 8; for thousands of small tasks a pool
+    // of reused workers is cheaper (`cargo run --release -p thread_pool`).
     for _ in 0..8 {
         let counter = Arc::clone(&counter);
         handles.push(thread::spawn(move || {
             for _ in 0..100_000 {
+                // SeqCst is the strongest ordering; a plain count only needs Relaxed,
+                // since join() already makes every increment visible to the final
+                // load. `cargo run -p litmus` shows what the weaker orderings allow.
                 counter.fetch_add(1, Ordering::SeqCst);
             }
         }));
@@ -23,10 +28,15 @@ fn main() {
 }
 
 /* 
-This code creates a shared counter protected by a mutex and spawns 8 threads that 
-increment the counter 100,000 times each. After all threads have finished, 
-it prints the final value of the counter, which should be 800,000.
-Note: The use of Arc allows multiple threads to share ownership of the counter,
-while the Mutex ensures that only one thread can modify the counter at a time,
-preventing race conditions.
+This code creates a shared atomic counter (AtomicU64) and spawns 8 threads that
+increment it 100,000 times each with fetch_add. After all threads have finished,
+it prints the final value of the counter, which is always 800,000.
+Note: The use of Arc allows multiple threads to share ownership of the counter.
+There is no Mutex: fetch_add is a single indivisible read-modify-write, so two
+threads can never both read the same old value and lose an increment.
+It is not free, though: all 8 threads write the same cache line, which has to
+move between cores on every increment. `cargo run --release -p counter_bench`
+compares this with a Mutex, an RwLock, sharded and per-thread counters.
+Going further than one counter, whole data structures can be shared without
+any lock: `cargo run --release -p lockfree` (Treiber stack, Michael-Scott queue).
 */
\ No newline at end of file
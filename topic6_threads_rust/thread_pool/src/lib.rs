@@ -0,0 +1,502 @@
+// src/lib.rs
+//
+// A fixed set of worker threads that run jobs (closures) from queues, instead
+// of one `thread::spawn` per task. Spawning a thread costs tens of
+// microseconds and a stack; a pool pays that once.
+//
+// Two modes:
+//
+//   Shared        one FIFO queue for everybody. Simple and fair, but every push
+//                 and pop takes the same lock.
+//   WorkStealing  the shared queue (the INJECTOR) only takes jobs submitted from
+//                 outside. Each worker also has its own deque: jobs a job spawns
+//                 go to the back of its worker's deque, and the worker pops from
+//                 the back (newest first, still warm in cache). An idle worker
+//                 first tries the injector, then STEALS from the front of another
+//                 worker's deque (the oldest job, usually the biggest piece of a
+//                 divide-and-conquer tree).
+//
+// Guarantees:
+//   - bounded:  the injector holds at most `capacity` jobs; `execute` blocks
+//               and `try_execute` gives the job back when it is full. Jobs that
+//               jobs spawn skip the bound, since blocking there could deadlock.
+//   - panics:   a panicking job is caught and counted; its worker carries on.
+//               `spawn` hands the panic message to whoever joins the handle.
+//   - shutdown: `shutdown` (or dropping the pool) stops new submissions, lets
+//               the workers finish every queued job, and joins them.
+//   - scope:    jobs spawned in `scope` may borrow local variables, because
+//               `scope` does not return until all of them have finished. A worker
+//               waiting in a scope runs queued jobs meanwhile, so scopes nested
+//               inside jobs cannot leave the pool with nobody working.
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Shared,
+    WorkStealing,
+}
+
+/// Counters since the pool started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub executed: usize,
+    pub panicked: usize,
+    /// Jobs taken from the shared queue (the injector).
+    pub from_injector: usize,
+    /// Jobs a worker popped from its own deque.
+    pub from_own_deque: usize,
+    /// Jobs taken from another worker's deque.
+    pub steals: usize,
+    /// Jobs run by a worker while it waited in a nested `scope`.
+    pub helped: usize,
+    /// Most jobs queued at once, over all queues.
+    pub max_queued: usize,
+    pub queued_now: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} run ({} panicked): {} from the injector, {} own deque, {} stolen, {} while waiting in a scope; queue depth max {}",
+            self.executed, self.panicked, self.from_injector, self.from_own_deque, self.steals, self.helped, self.max_queued
+        )
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    executed: AtomicUsize,
+    panicked: AtomicUsize,
+    from_injector: AtomicUsize,
+    from_own_deque: AtomicUsize,
+    steals: AtomicUsize,
+    helped: AtomicUsize,
+    max_queued: AtomicUsize,
+}
+
+struct State {
+    injector: VecDeque<Job>,
+    /// Jobs in the injector plus every deque; workers sleep only when it is 0.
+    queued: usize,
+    shutdown: bool,
+}
+
+struct Inner {
+    id: usize,
+    mode: Mode,
+    capacity: usize,
+    state: Mutex<State>,
+    deques: Vec<Mutex<VecDeque<Job>>>,
+    work: Condvar,
+    space: Condvar,
+    counters: Counters,
+}
+
+static NEXT_POOL: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // (pool id, worker index) when this thread is a pool worker.
+    static WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Jobs run outside every lock, so poisoning can only come from a bug here.
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic".into())
+}
+
+impl Inner {
+    fn note_queued(&self, st: &mut State) {
+        st.queued += 1;
+        self.counters.max_queued.fetch_max(st.queued, Ordering::Relaxed);
+    }
+
+    fn dequeued(&self) {
+        lock(&self.state).queued -= 1;
+    }
+
+    // Queues `job`: on the worker's own deque if called from one of our workers
+    // in stealing mode, else on the injector. `block` decides what a full
+    // injector does: wait for space, or hand the job back.
+    fn push(&self, job: Job, block: bool) -> Result<(), Job> {
+        let worker = WORKER.with(|w| w.get()).filter(|(pool, _)| *pool == self.id).map(|(_, i)| i);
+        if let (Mode::WorkStealing, Some(i)) = (self.mode, worker) {
+            // Count the job before it becomes stealable: a thief that takes it
+            // the moment the deque lock drops must find it counted already.
+            self.note_queued(&mut lock(&self.state));
+            lock(&self.deques[i]).push_back(job);
+        } else {
+            let mut st = lock(&self.state);
+            // A job submitting into its own full pool would wait for itself.
+            let must_fit = worker.is_none();
+            while must_fit && st.injector.len() >= self.capacity && !st.shutdown {
+                if !block {
+                    return Err(job);
+                }
+                st = self.space.wait(st).unwrap_or_else(|e| e.into_inner());
+            }
+            if st.shutdown {
+                return Err(job);
+            }
+            st.injector.push_back(job);
+            self.note_queued(&mut st);
+        }
+        self.work.notify_one();
+        Ok(())
+    }
+
+    // Next job for worker `me`: own deque (newest), injector, then steal (oldest).
+    fn find_job(&self, me: usize) -> Option<Job> {
+        if let Some(job) = lock(&self.deques[me]).pop_back() {
+            self.dequeued();
+            self.counters.from_own_deque.fetch_add(1, Ordering::Relaxed);
+            return Some(job);
+        }
+        {
+            let mut st = lock(&self.state);
+            if let Some(job) = st.injector.pop_front() {
+                st.queued -= 1;
+                drop(st);
+                self.space.notify_one();
+                self.counters.from_injector.fetch_add(1, Ordering::Relaxed);
+                return Some(job);
+            }
+        }
+        let n = self.deques.len();
+        for k in 1..n {
+            let victim = (me + k) % n;
+            if let Some(job) = lock(&self.deques[victim]).pop_front() {
+                self.dequeued();
+                self.counters.steals.fetch_add(1, Ordering::Relaxed);
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    fn run(&self, job: Job) {
+        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+            self.counters.panicked.fetch_add(1, Ordering::Relaxed);
+        }
+        self.counters.executed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn worker_loop(&self, me: usize) {
+        WORKER.with(|w| w.set(Some((self.id, me))));
+        loop {
+            if let Some(job) = self.find_job(me) {
+                self.run(job);
+                continue;
+            }
+            let st = lock(&self.state);
+            if st.queued > 0 {
+                // Queued somewhere, but not yet visible to us (or just taken); look again.
+                drop(st);
+                thread::yield_now();
+                continue;
+            }
+            if st.shutdown {
+                break;
+            }
+            drop(self.work.wait(st).unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+pub struct Builder {
+    workers: usize,
+    capacity: usize,
+    mode: Mode,
+    name: String,
+}
+
+impl Builder {
+    pub fn workers(mut self, n: usize) -> Self {
+        self.workers = n.max(1);
+        self
+    }
+
+    /// Most jobs the shared queue holds before `execute` blocks.
+    pub fn capacity(mut self, n: usize) -> Self {
+        self.capacity = n.max(1);
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Worker threads are called "<name>-0", "<name>-1", ...
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn build(self) -> ThreadPool {
+        let inner = Arc::new(Inner {
+            id: NEXT_POOL.fetch_add(1, Ordering::Relaxed),
+            mode: self.mode,
+            capacity: self.capacity,
+            state: Mutex::new(State { injector: VecDeque::new(), queued: 0, shutdown: false }),
+            deques: (0..self.workers).map(|_| Mutex::new(VecDeque::new())).collect(),
+            work: Condvar::new(),
+            space: Condvar::new(),
+            counters: Counters::default(),
+        });
+        let workers = (0..self.workers)
+            .map(|i| {
+                let inner = Arc::clone(&inner);
+                thread::Builder::new()
+                    .name(format!("{}-{i}", self.name))
+                    .spawn(move || inner.worker_loop(i))
+                    .expect("failed to spawn pool worker")
+            })
+            .collect();
+        ThreadPool { inner, workers }
+    }
+}
+
+/// Returned by `JobHandle::join` when the job panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobPanicked(pub String);
+
+impl fmt::Display for JobPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "job panicked: {}", self.0)
+    }
+}
+
+impl std::error::Error for JobPanicked {}
+
+/// The result of a `spawn`ed job.
+pub struct JobHandle<T> {
+    result: mpsc::Receiver<Result<T, JobPanicked>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Waits for the job. Err if it panicked (or the pool shut down without
+    /// running it).
+    pub fn join(self) -> Result<T, JobPanicked> {
+        self.result.recv().unwrap_or_else(|_| Err(JobPanicked("job was dropped before it ran".into())))
+    }
+}
+
+/// Submitted after `shutdown` began; the job is handed back.
+pub struct ShutDown(pub Box<dyn FnOnce() + Send + 'static>);
+
+impl fmt::Debug for ShutDown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ShutDown(..)")
+    }
+}
+
+pub struct ThreadPool {
+    inner: Arc<Inner>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// `workers` threads, shared queue, capacity 1024.
+    pub fn new(workers: usize) -> Self {
+        ThreadPool::builder().workers(workers).build()
+    }
+
+    pub fn builder() -> Builder {
+        let workers = thread::available_parallelism().map_or(4, |n| n.get());
+        Builder { workers, capacity: 1024, mode: Mode::Shared, name: "pool".into() }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.inner.mode
+    }
+
+    /// Queues `job`, waiting while the queue is full.
+    pub fn execute(&self, job: impl FnOnce() + Send + 'static) -> Result<(), ShutDown> {
+        self.inner.push(Box::new(job), true).map_err(ShutDown)
+    }
+
+    /// Queues `job` unless the queue is full (or the pool is shutting down), in
+    /// which case the job comes back.
+    pub fn try_execute(&self, job: impl FnOnce() + Send + 'static) -> Result<(), ShutDown> {
+        self.inner.push(Box::new(job), false).map_err(ShutDown)
+    }
+
+    /// Queues `f` and returns a handle for its result.
+    pub fn spawn<T: Send + 'static>(&self, f: impl FnOnce() -> T + Send + 'static) -> JobHandle<T> {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let job = move || match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(v) => {
+                let _ = tx.send(Ok(v));
+            }
+            Err(payload) => {
+                let _ = tx.send(Err(JobPanicked(panic_message(&*payload))));
+                // Let the worker count it too (resume_unwind skips the panic hook).
+                panic::resume_unwind(payload);
+            }
+        };
+        // A refused job drops `tx`, and join() reports it.
+        let _ = self.execute(job);
+        JobHandle { result: rx }
+    }
+
+    /// Runs `f` with a `Scope` whose jobs may borrow from the caller's stack.
+    /// Returns after every job spawned in the scope has finished. If any of them
+    /// panicked, panics after that (like `std::thread::scope`).
+    pub fn scope<'env, R>(&self, f: impl for<'s> FnOnce(&'s Scope<'s, 'env>) -> R) -> R {
+        let scope = Scope { pool: self, pending: Arc::new(ScopeState::default()), _env: PhantomData };
+        // The guard waits even if `f` panics, so no borrowed data can go away
+        // while a job still uses it.
+        let guard = WaitGuard(&scope);
+        let result = f(&scope);
+        drop(guard);
+        let panicked = scope.pending.panicked.load(Ordering::Relaxed);
+        if panicked > 0 {
+            panic!("{panicked} job(s) in a thread pool scope panicked");
+        }
+        result
+    }
+
+    /// `f` applied to every item, in parallel, results in input order.
+    pub fn map<T: Send, R: Send>(&self, items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+        let results: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+        self.scope(|s| {
+            for (item, slot) in items.into_iter().zip(&results) {
+                let f = &f;
+                s.spawn(move || *lock(slot) = Some(f(item)));
+            }
+        });
+        results.into_iter().map(|r| r.into_inner().unwrap_or_else(|e| e.into_inner()).expect("scope ran every job")).collect()
+    }
+
+    pub fn stats(&self) -> Stats {
+        let c = &self.inner.counters;
+        Stats {
+            executed: c.executed.load(Ordering::Relaxed),
+            panicked: c.panicked.load(Ordering::Relaxed),
+            from_injector: c.from_injector.load(Ordering::Relaxed),
+            from_own_deque: c.from_own_deque.load(Ordering::Relaxed),
+            steals: c.steals.load(Ordering::Relaxed),
+            helped: c.helped.load(Ordering::Relaxed),
+            max_queued: c.max_queued.load(Ordering::Relaxed),
+            queued_now: lock(&self.inner.state).queued,
+        }
+    }
+
+    /// Stops taking jobs, runs everything already queued, joins the workers.
+    pub fn shutdown(mut self) -> Stats {
+        self.stop();
+        self.stats()
+    }
+
+    fn stop(&mut self) {
+        lock(&self.inner.state).shutdown = true;
+        self.inner.work.notify_all();
+        self.inner.space.notify_all();
+        for w in self.workers.drain(..) {
+            // Jobs' panics are caught inside the worker, so this only fails on a
+            // bug in the pool itself.
+            w.join().expect("pool worker died");
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[derive(Default)]
+struct ScopeState {
+    running: Mutex<usize>,
+    finished: Condvar,
+    panicked: AtomicUsize,
+}
+
+/// Spawns jobs that may borrow anything that outlives the `scope` call.
+pub struct Scope<'pool, 'env> {
+    pool: &'pool ThreadPool,
+    pending: Arc<ScopeState>,
+    // Invariant in 'env, as in std::thread::Scope.
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'env> Scope<'_, 'env> {
+    pub fn spawn(&self, f: impl FnOnce() + Send + 'env) {
+        *lock(&self.pending.running) += 1;
+        let state = Arc::clone(&self.pending);
+        let job = move || {
+            if panic::catch_unwind(AssertUnwindSafe(f)).is_err() {
+                state.panicked.fetch_add(1, Ordering::Relaxed);
+            }
+            *lock(&state.running) -= 1;
+            state.finished.notify_all();
+        };
+        let job: Box<dyn FnOnce() + Send + 'env> = Box::new(job);
+        // SAFETY: the job only borrows data that lives for 'env, and `scope`
+        // does not return (or unwind) before `running` is back to 0, i.e. before
+        // this job has finished. So the borrows never outlive their data, even
+        // though the queue's type claims 'static.
+        let job: Job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'env>, Job>(job) };
+        if let Err(job) = self.pool.inner.push(job, true) {
+            // Pool is shutting down: run it here rather than break the promise.
+            job();
+        }
+    }
+}
+
+struct WaitGuard<'a, 'pool, 'env>(&'a Scope<'pool, 'env>);
+
+impl Drop for WaitGuard<'_, '_, '_> {
+    fn drop(&mut self) {
+        let scope = self.0;
+        let inner = &scope.pool.inner;
+        let me = WORKER.with(|w| w.get()).filter(|(pool, _)| *pool == inner.id).map(|(_, i)| i);
+        loop {
+            if *lock(&scope.pending.running) == 0 {
+                return;
+            }
+            // A worker helps instead of just blocking: if every worker were
+            // waiting in a scope, nobody would be left to run the jobs they wait
+            // for. Other threads only wait; the workers are there to do the work.
+            if let Some(me) = me
+                && let Some(job) = inner.find_job(me)
+            {
+                inner.counters.helped.fetch_add(1, Ordering::Relaxed);
+                inner.run(job);
+                continue;
+            }
+            let running = lock(&scope.pending.running);
+            if *running > 0 {
+                // Timed: a worker must also wake for new jobs, which do not
+                // notify this condvar.
+                let _ = scope.pending.finished.wait_timeout(running, Duration::from_millis(1));
+            }
+        }
+    }
+}
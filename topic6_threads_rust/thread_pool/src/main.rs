@@ -0,0 +1,297 @@
+// src/main.rs
+//
+// `thread_pool [DEMO] [--workers N] [--mode shared|stealing]`
+//     DEMO is spawn-cost, bounded, panics, scope, fork-join, stress or all (the default)
+//
+//   spawn-cost   10k tiny tasks: one thread::spawn each vs one pool
+//   bounded      a capacity-4 queue: execute waits, try_execute gives the job back
+//   panics       panicking jobs are counted, the workers survive, join() reports it
+//   scope        jobs that borrow a local Vec, no Arc needed
+//   fork-join    a recursive sum where jobs spawn jobs, both modes, with stats
+//   stress       many rounds of scopes nested four deep, both modes; fails on a
+//                lost job, a queue count left over, or a hang
+//
+// Exits non-zero if any demo computes a wrong answer.
+
+use std::panic;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thread_pool::{Mode, ThreadPool};
+
+const DEMOS: [&str; 6] = ["spawn-cost", "bounded", "panics", "scope", "fork-join", "stress"];
+
+fn pool(workers: usize, mode: Mode) -> ThreadPool {
+    ThreadPool::builder().workers(workers).mode(mode).build()
+}
+
+fn spawn_cost(workers: usize, mode: Mode) -> Result<(), String> {
+    const TASKS: usize = 10_000;
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let done = Arc::clone(&done);
+            thread::spawn(move || done.fetch_add(1, Ordering::Relaxed))
+        })
+        .collect();
+    for h in handles {
+        h.join().map_err(|_| "a spawned thread panicked")?;
+    }
+    let spawned = start.elapsed();
+
+    let start = Instant::now();
+    let p = pool(workers, mode);
+    for _ in 0..TASKS {
+        let done = Arc::clone(&done);
+        p.execute(move || {
+            done.fetch_add(1, Ordering::Relaxed);
+        })
+        .map_err(|_| "pool refused a job")?;
+    }
+    p.shutdown();
+    let pooled = start.elapsed();
+
+    println!("{TASKS} tasks that each increment a counter:");
+    println!("  thread::spawn per task   {:>8.1} ms", spawned.as_secs_f64() * 1e3);
+    println!("  {workers}-worker pool            {:>8.1} ms (including starting and joining the pool)", pooled.as_secs_f64() * 1e3);
+    let total = done.load(Ordering::Relaxed);
+    if total == 2 * TASKS { Ok(()) } else { Err(format!("spawn-cost: expected {} increments, got {total}", 2 * TASKS)) }
+}
+
+fn bounded(workers: usize, mode: Mode) -> Result<(), String> {
+    let p = ThreadPool::builder().workers(workers).mode(mode).capacity(4).build();
+    let done = Arc::new(AtomicUsize::new(0));
+    let job = |done: &Arc<AtomicUsize>| {
+        let done = Arc::clone(done);
+        move || {
+            thread::sleep(Duration::from_millis(5));
+            done.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+
+    let mut refused = 0;
+    for _ in 0..20 {
+        if p.try_execute(job(&done)).is_err() {
+            refused += 1;
+        }
+    }
+    let accepted = 20 - refused;
+    println!("capacity 4, {workers} worker(s), 20 x try_execute of a 5 ms job: {accepted} accepted, {refused} handed back");
+
+    let start = Instant::now();
+    for _ in 0..20 {
+        p.execute(job(&done)).map_err(|_| "pool refused a job")?;
+    }
+    println!("20 x execute: returned after {:.0} ms, because it waits while the queue is full", start.elapsed().as_secs_f64() * 1e3);
+    let stats = p.shutdown();
+    println!("after shutdown: {stats}");
+    let total = done.load(Ordering::Relaxed);
+    if total == accepted + 20 { Ok(()) } else { Err(format!("bounded: {} jobs accepted but {total} ran", accepted + 20)) }
+}
+
+fn panics(workers: usize, mode: Mode) -> Result<(), String> {
+    let p = pool(workers, mode);
+    // The default hook would print every (expected) panic with its backtrace.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let handles: Vec<_> = (0..8u64)
+        .map(|i| {
+            p.spawn(move || {
+                if i % 3 == 2 {
+                    panic!("job {i} gave up");
+                }
+                i * i
+            })
+        })
+        .collect();
+    let mut ok = 0;
+    for (i, h) in handles.into_iter().enumerate() {
+        match h.join() {
+            Ok(v) => {
+                ok += 1;
+                println!("  job {i}: {v}");
+            }
+            Err(e) => println!("  job {i}: {e}"),
+        }
+    }
+    // Same workers, still alive.
+    let after = p.spawn(|| "still running").join().map_err(|e| e.to_string())?;
+    let stats = p.shutdown();
+    panic::set_hook(hook);
+    println!("after the panics: {after:?}; {} of {} jobs panicked", stats.panicked, stats.executed);
+    if ok == 6 && stats.panicked == 2 { Ok(()) } else { Err(format!("panics: {ok} jobs succeeded, {} panicked", stats.panicked)) }
+}
+
+fn scope(workers: usize, mode: Mode) -> Result<(), String> {
+    let p = pool(workers, mode);
+    let data: Vec<u64> = (1..=1_000_000).collect();
+    let mut partial = [0u64; 8];
+    let chunk = data.len().div_ceil(partial.len());
+    p.scope(|s| {
+        // Each job borrows a slice of `data` and one slot of `partial`.
+        for (slot, part) in partial.iter_mut().zip(data.chunks(chunk)) {
+            s.spawn(move || *slot = part.iter().sum());
+        }
+    });
+    let total: u64 = partial.iter().sum();
+    println!("8 scoped jobs summing slices of a local Vec: {total}");
+
+    let squares = p.map((1..=10u64).collect(), |x| x * x);
+    println!("map(1..=10, x*x) = {squares:?}");
+    let expected = 1_000_000 * 1_000_001 / 2;
+    if total == expected && squares[9] == 100 { Ok(()) } else { Err(format!("scope: sum {total}, expected {expected}")) }
+}
+
+// Splits in halves down to 4096 elements. Each level waits in its own scope
+// (helping while it waits) - the shape of rayon::join.
+fn par_sum(pool: &ThreadPool, data: &[u64], out: &AtomicUsize) {
+    if data.len() <= 4096 {
+        out.fetch_add(data.iter().sum::<u64>() as usize, Ordering::Relaxed);
+        return;
+    }
+    let (left, right) = data.split_at(data.len() / 2);
+    pool.scope(|s| {
+        s.spawn(|| par_sum(pool, left, out));
+        s.spawn(|| par_sum(pool, right, out));
+    });
+}
+
+fn fork_join(workers: usize, _: Mode) -> Result<(), String> {
+    let data: Vec<u64> = (1..=1 << 22).collect();
+    let expected = data.iter().sum::<u64>() as usize;
+    println!("sum of 4M numbers, split in halves down to 4096 (1024 leaf jobs), both modes:");
+    let mut wrong = Vec::new();
+    for mode in [Mode::Shared, Mode::WorkStealing] {
+        let p = pool(workers, mode);
+        let out = AtomicUsize::new(0);
+        let start = Instant::now();
+        // Started on a worker, so the forks land on workers' deques.
+        p.scope(|s| s.spawn(|| par_sum(&p, &data, &out)));
+        let elapsed = start.elapsed();
+        println!("  {mode:?}: {:.1} ms", elapsed.as_secs_f64() * 1e3);
+        println!("    {}", p.shutdown());
+        if out.load(Ordering::Relaxed) != expected {
+            wrong.push(format!("{mode:?} summed {}", out.load(Ordering::Relaxed)));
+        }
+    }
+    println!("Shared: every fork goes through the one queue. WorkStealing: forks stay on the forking");
+    println!("worker's deque (\"own deque\") unless an idle worker steals them.");
+    if wrong.is_empty() { Ok(()) } else { Err(format!("fork-join: expected {expected}, {}", wrong.join(", "))) }
+}
+
+// A tree of scopes: every job below the top opens a scope of its own and
+// waits in it, so workers keep pushing onto their deques while others steal.
+fn nested(pool: &ThreadPool, depth: u32, leaves: &AtomicUsize) {
+    if depth == 0 {
+        leaves.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    pool.scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| nested(pool, depth - 1, leaves));
+        }
+    });
+}
+
+fn stress(workers: usize, _: Mode) -> Result<(), String> {
+    const ROUNDS: usize = 200;
+    const DEPTH: u32 = 4;
+    let per_round = 4usize.pow(DEPTH);
+    println!("{ROUNDS} rounds of scopes nested {DEPTH} deep ({per_round} leaves each), both modes:");
+    let mut wrong = Vec::new();
+    for mode in [Mode::Shared, Mode::WorkStealing] {
+        // A lost wake-up or a worker killed by a bookkeeping bug shows up as a
+        // scope that never returns; wait for the rounds on another thread.
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let p = pool(workers, mode);
+            let leaves = AtomicUsize::new(0);
+            let start = Instant::now();
+            for _ in 0..ROUNDS {
+                p.scope(|s| s.spawn(|| nested(&p, DEPTH, &leaves)));
+            }
+            let elapsed = start.elapsed();
+            let left = p.stats().queued_now;
+            let _ = tx.send((leaves.into_inner(), left, elapsed, p.shutdown()));
+        });
+        match rx.recv_timeout(Duration::from_secs(60)) {
+            Ok((leaves, left, elapsed, stats)) => {
+                println!("  {mode:?}: {:.1} ms", elapsed.as_secs_f64() * 1e3);
+                println!("    {stats}");
+                if leaves != ROUNDS * per_round || left != 0 {
+                    wrong.push(format!("{mode:?} ran {leaves} leaves with {left} job(s) still counted as queued"));
+                }
+            }
+            Err(_) => wrong.push(format!("{mode:?} hung: not finished after 60 s")),
+        }
+    }
+    let expected = ROUNDS * per_round;
+    if wrong.is_empty() { Ok(()) } else { Err(format!("stress: expected {expected} leaves, {}", wrong.join(", "))) }
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    let (demo, rest) = match args.first() {
+        Some(d) if !d.starts_with("--") => (*d, &args[1..]),
+        _ => ("all", args),
+    };
+    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut workers = cores.max(4);
+    let mut mode = Mode::WorkStealing;
+    let mut it = rest.iter();
+    while let Some(&arg) = it.next() {
+        let value = *it.next().ok_or(format!("{arg} needs a value"))?;
+        match arg {
+            "--workers" => workers = value.parse::<usize>().map_err(|_| format!("--workers takes a number, got '{value}'"))?.max(1),
+            "--mode" => {
+                mode = match value {
+                    "shared" => Mode::Shared,
+                    "stealing" => Mode::WorkStealing,
+                    _ => return Err(format!("unknown mode '{value}', expected shared or stealing")),
+                }
+            }
+            _ => return Err(format!("unknown argument '{arg}'")),
+        }
+    }
+    let demos: Vec<&str> = match demo {
+        "all" => DEMOS.to_vec(),
+        d if DEMOS.contains(&d) => vec![d],
+        d => return Err(format!("unknown demo '{d}', expected {} or all", DEMOS.join(", "))),
+    };
+    println!("{workers} workers, {mode:?} mode, {cores} core(s) available");
+    if cores < 2 {
+        println!("note: with one core the workers take turns; timings show overhead, not speed-up.");
+    }
+    let mut failures = Vec::new();
+    for d in demos {
+        println!("\n== {d} ==");
+        let result = match d {
+            "spawn-cost" => spawn_cost(workers, mode),
+            "bounded" => bounded(workers, mode),
+            "panics" => panics(workers, mode),
+            "scope" => scope(workers, mode),
+            "fork-join" => fork_join(workers, mode),
+            _ => stress(workers, mode),
+        };
+        if let Err(e) = result {
+            failures.push(e);
+        }
+    }
+    if failures.is_empty() { Ok(()) } else { Err(failures.join("; ")) }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
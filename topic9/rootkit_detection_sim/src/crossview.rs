@@ -0,0 +1,93 @@
+// src/crossview.rs
+//
+// Cross-view detection: ask the same question through different paths and
+// compare. A rootkit has to hook every path consistently to stay hidden;
+// hooking only getdents() (the common case) leaves the probe and kill views
+// telling the truth.
+//
+// Processes start and exit during a scan that walks every pid, so a pid
+// missing from the first listing is only reported if it is still alive and
+// still missing from a listing taken after the probes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::procfs::{ProcInfo, Source};
+
+/// A pid that exists but is missing from the readdir view.
+pub struct Finding {
+    pub pid: u32,
+    pub info: Option<ProcInfo>, // None: no /proc entry even by name, only kill sees it
+    pub kill: Option<bool>,     // None: the source has no kill view
+}
+
+pub struct Report {
+    pub source: String,
+    pub pid_max: u32,
+    pub listed: usize,
+    pub probed: usize,
+    pub killed: Option<usize>,
+    pub threads: usize,   // thread ids the probe found; not processes, ignored
+    pub transient: usize, // started or exited during the scan, ignored
+    pub limitation: Option<String>,
+    pub hidden: Vec<Finding>,
+}
+
+/// Probes pids 1..=pid_max (usually `source.pid_max()`).
+pub fn scan(source: &dyn Source, pid_max: u32) -> Result<Report, String> {
+    let list = || source.list().map_err(|e| format!("{}: {e}", source.describe()));
+    let first = list()?;
+
+    let mut probed: BTreeMap<u32, ProcInfo> = BTreeMap::new();
+    let mut threads = 0;
+    for pid in 1..=pid_max {
+        match source.probe(pid) {
+            Some(info) if info.is_thread() => threads += 1,
+            Some(info) => {
+                probed.insert(pid, info);
+            }
+            None => {}
+        }
+    }
+    let has_kill = source.signal0(1).is_some();
+    let killed: BTreeSet<u32> =
+        if has_kill { (1..=pid_max).filter(|&pid| source.signal0(pid) == Some(true)).collect() } else { BTreeSet::new() };
+
+    let last = list()?;
+    let listed: BTreeSet<u32> = first.union(&last).copied().collect();
+    let seen: BTreeSet<u32> = probed.keys().chain(&killed).copied().collect();
+    // Listed but found by neither probe nor kill: exited meanwhile. Pids above
+    // the probed range were never looked for, so they say nothing.
+    let mut transient = listed.iter().filter(|pid| (1..=pid_max).contains(*pid) && !seen.contains(*pid)).count();
+
+    let mut hidden = Vec::new();
+    for &pid in seen.difference(&listed) {
+        // Look again: a pid only kill saw may be a thread (already counted by
+        // the probe), and anything may have exited by now.
+        let info = source.probe(pid);
+        if info.as_ref().is_some_and(ProcInfo::is_thread) {
+            continue;
+        }
+        let kill = has_kill.then(|| source.signal0(pid) == Some(true));
+        if info.is_none() && kill != Some(true) {
+            transient += 1;
+            continue;
+        }
+        hidden.push(Finding { pid, info, kill });
+    }
+    // One more listing: anything that shows up now simply started late.
+    let latest = list()?;
+    transient += hidden.iter().filter(|f| latest.contains(&f.pid)).count();
+    hidden.retain(|f| !latest.contains(&f.pid));
+
+    Ok(Report {
+        source: source.describe(),
+        pid_max,
+        listed: listed.len(),
+        probed: probed.len(),
+        killed: has_kill.then_some(killed.len()),
+        threads,
+        transient,
+        limitation: source.limitation(),
+        hidden,
+    })
+}
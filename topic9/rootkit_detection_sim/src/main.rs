@@ -1,49 +1,170 @@
-// We import HashSet to compare process lists efficiently
-use std::collections::HashSet;
+// src/main.rs
+//
+// `rootkit_detection_sim sim`
+//     the simulation: a process table as the kernel view, a hooked ps as the user view
+// `rootkit_detection_sim scan [--proc DIR] [--max-pid N]`
+//     cross-view scan of the live /proc (default) or of a fixture directory laid
+//     out like /proc (see procfs.rs); fails if anything is hidden
+// `rootkit_detection_sim ps [--proc DIR]`
+//     what readdir shows, with the metadata ps would print
+// No arguments: the simulation, then a scan of fixtures/proc.
+//
+// A live scan probes every pid up to pid_max and sees only what this user may
+// see; run it as root for a complete picture. With /proc mounted hidepid=2 a
+// non-root scan checks only this user's own processes, and says so.
 
-// Define a struct representing a process
+mod crossview;
+mod procfs;
+mod sim;
 
-#[allow(dead_code)]
-struct Process {
-    pid: u32,
-    name: String,
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crossview::Report;
+use procfs::{Fixture, Live, ProcInfo, Source};
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/proc")
+}
+
+// Like ps: kernel threads have no command line and show their name in brackets.
+fn command(info: &ProcInfo) -> String {
+    if info.cmdline.is_empty() { format!("[{}]", info.name) } else { info.cmdline.clone() }
 }
 
-// Function to simulate kernel process list (ground truth)
-fn kernel_process_list() -> Vec<Process> {
-    vec![
-        Process { pid: 1, name: "init".to_string() },
-        Process { pid: 100, name: "nginx".to_string() },
-        Process { pid: 666, name: "malware_agent".to_string() }, // hidden process
-    ]
+fn uid(info: &ProcInfo) -> String {
+    info.uid.map_or("?".into(), |u| u.to_string())
 }
 
-// Function to simulate user-space `ps` output
-// Rootkit hides pid 666
-fn user_process_list() -> Vec<Process> {
-    vec![
-        Process { pid: 1, name: "init".to_string() },
-        Process { pid: 100, name: "nginx".to_string() },
-        // malware_agent missing
-    ]
+fn describe(info: &ProcInfo) -> String {
+    format!("{} state {} ppid {} uid {}: {}", info.name, info.state, info.ppid, uid(info), command(info))
 }
 
-// Detection function using cross-view comparison
-fn detect_hidden_processes() {
-    let kernel = kernel_process_list();
-    let user = user_process_list();
+// Simulate the kernel process list (ground truth) and the user-space `ps`
+// output a rootkit tampers with, and compare them.
+fn simulation() -> Result<(), String> {
+    println!("Running rootkit cross-view detection simulation...\n");
+    let table = sim::boot().map_err(|e| format!("booting the simulated system: {e}"))?;
+    let kernel = sim::kernel_view(&table);
+    let user = sim::user_view(&table);
+    println!("  kernel process table: {} processes, hooked ps: {}", kernel.len(), user.len());
+    for e in sim::detect(&kernel, &user) {
+        let parent = table.get(e.ppid).map_or("?", |p| p.name.as_str());
+        println!(
+            "⚠ Suspicious: PID {} exists in kernel but not visible to user-space\n    {} ({}), parent {} ({parent}), {} KB",
+            e.pid(),
+            e.name,
+            e.state,
+            e.ppid,
+            e.process.memory_kb
+        );
+    }
+    Ok(())
+}
 
-    let kernel_pids: HashSet<u32> = kernel.iter().map(|p| p.pid).collect();
-    let user_pids: HashSet<u32> = user.iter().map(|p| p.pid).collect();
+fn open(dir: Option<&str>) -> Result<Box<dyn Source>, String> {
+    Ok(match dir {
+        None | Some("/proc") => Box::new(Live::new()),
+        Some(dir) => Box::new(Fixture::open(Path::new(dir))?),
+    })
+}
 
-    for pid in kernel_pids.difference(&user_pids) {
-        println!("⚠ Suspicious: PID {} exists in kernel but not visible to user-space", pid);
+fn print_report(report: &Report) {
+    println!("cross-view scan of {}, pids 1..={}", report.source, report.pid_max);
+    println!("  readdir lists {} processes", report.listed);
+    println!("  probing /proc/<pid> finds {} processes (plus {} thread ids, ignored)", report.probed, report.threads);
+    match report.killed {
+        Some(n) => println!("  kill(pid, 0) finds {n} pids (threads included)"),
+        None => println!("  kill(pid, 0) not available for this source"),
+    }
+    if let Some(limitation) = &report.limitation {
+        println!("  note: {limitation}");
+    }
+    if report.transient > 0 {
+        println!("  {} pids started or exited during the scan, ignored", report.transient);
+    }
+    for f in &report.hidden {
+        let seen = match (&f.info, f.kill) {
+            (Some(_), Some(true)) => "probe and kill",
+            (Some(_), _) => "probe",
+            (None, _) => "kill only; no /proc entry even by name",
+        };
+        println!("⚠ Suspicious: PID {} is hidden from readdir (seen by {seen})", f.pid);
+        if let Some(info) = &f.info {
+            println!("    {}", describe(info));
+        }
     }
 }
 
-fn main() {
-    println!("Running rootkit cross-view detection simulation...\n");
-    detect_hidden_processes();
+fn scan_cmd(args: &[&str]) -> Result<(), String> {
+    let (mut dir, mut max_pid) = (None, None);
+    let mut it = args.iter();
+    while let Some(&flag) = it.next() {
+        let value = *it.next().ok_or(format!("{flag} needs a value"))?;
+        match flag {
+            "--proc" => dir = Some(value),
+            "--max-pid" => max_pid = Some(value.parse().map_err(|_| format!("--max-pid takes a number, got '{value}'"))?),
+            _ => return Err(format!("unknown argument '{flag}'")),
+        }
+    }
+    let source = open(dir)?;
+    let report = crossview::scan(source.as_ref(), max_pid.unwrap_or(source.pid_max()))?;
+    print_report(&report);
+    match report.hidden.len() {
+        0 => {
+            println!("all views agree: nothing hidden");
+            Ok(())
+        }
+        n => Err(format!("{n} hidden process(es)")),
+    }
+}
+
+fn ps_cmd(args: &[&str]) -> Result<(), String> {
+    let dir = match args {
+        [] => None,
+        ["--proc", dir] => Some(*dir),
+        _ => return Err("usage: rootkit_detection_sim ps [--proc DIR]".into()),
+    };
+    let source = open(dir)?;
+    let pids = source.list().map_err(|e| format!("{}: {e}", source.describe()))?;
+    println!("  {:>7} {:>7} {:>5} S  {:<15} CMD", "PID", "PPID", "UID", "NAME");
+    for pid in pids {
+        // Gone between the listing and the read: it exited.
+        if let Some(info) = source.probe(pid) {
+            println!("  {pid:>7} {:>7} {:>5} {}  {:<15} {}", info.ppid, uid(&info), info.state, info.name, command(&info));
+        }
+    }
+    Ok(())
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    match args {
+        [] => {
+            simulation()?;
+            let dir = fixture_dir();
+            println!("\n--- the same idea on a fake /proc whose readdir is hooked ---\n");
+            let fixture = Fixture::open(&dir)?;
+            print_report(&crossview::scan(&fixture, fixture.pid_max())?);
+            println!("\nTry: rootkit_detection_sim scan    (the live /proc; as root for every process)");
+            Ok(())
+        }
+        ["sim"] => simulation(),
+        ["scan", rest @ ..] => scan_cmd(rest),
+        ["ps", rest @ ..] => ps_cmd(rest),
+        _ => Err("usage: rootkit_detection_sim [sim | scan [--proc DIR] [--max-pid N] | ps [--proc DIR]]".into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
 
 //1️⃣ Why are kernel rootkits harder to detect than user-space rootkits?
@@ -51,4 +172,4 @@ fn main() {
 //2️⃣ What does cross-view detection compare?
 // Cross-view detection compares process lists from different system views (e.g., kernel vs. user-space) to identify discrepancies that may indicate hidden processes.
 //3️⃣ Why is file integrity monitoring useful against rootkits?
-// File integrity monitoring helps detect rootkits by tracking changes to critical system files and identifying unauthorized modifications that may be signs of rootkit presence.
\ No newline at end of file
+// File integrity monitoring helps detect rootkits by tracking changes to critical system files and identifying unauthorized modifications that may be signs of rootkit presence.
@@ -0,0 +1,231 @@
+// src/procfs.rs
+//
+// Where process lists come from. On Linux every process has a directory
+// /proc/<pid>, and there are three ways to ask "does pid N exist?":
+//
+//   readdir  list /proc and keep the numeric names: what ps and top do.
+//            A rootkit that hooks getdents() can drop entries from this list.
+//   probe    look up /proc/<N> by name and read /proc/<N>/stat, for every N up
+//            to pid_max. The hooked listing is not involved.
+//   kill     kill(N, 0) sends no signal, it only checks that N exists (EPERM
+//            still means it exists, just not ours). Does not touch /proc at all.
+//
+// A pid seen by probe or kill but missing from readdir is being hidden.
+//
+// Unless /proc is mounted with hidepid=2 (or invisible, or ptraceable): then
+// readdir and probe both skip other users' processes for anyone but root,
+// while kill still answers EPERM for them. For a non-root scan of such a /proc
+// an EPERM pid is one we are not allowed to see, not evidence of a rootkit.
+//
+// Thread ids are a catch: /proc/<tid> can be looked up by name and kill()
+// accepts them, but readdir lists only thread group leaders. Every probed pid
+// whose Tgid is not itself is a thread, not a hidden process.
+//
+// A `Fixture` is a directory laid out like /proc for testing without a
+// rootkit: numeric directories with stat/status/cmdline, sys/kernel/pid_max,
+// and a file `rootkit_hides` listing the pids its "hooked" readdir leaves out.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What /proc/<pid>/{stat,status,cmdline} say about a process.
+#[derive(Debug, Clone)]
+pub struct ProcInfo {
+    pub pid: u32,
+    pub name: String, // comm: at most 15 characters, set by the program
+    pub state: char,
+    pub ppid: u32,
+    pub tgid: u32,
+    pub uid: Option<u32>,
+    pub cmdline: String, // empty for kernel threads
+}
+
+impl ProcInfo {
+    pub fn is_thread(&self) -> bool {
+        self.tgid != self.pid
+    }
+}
+
+// "pid (comm) S ppid ...". comm may itself contain spaces and ')', so split at
+// the LAST ')'.
+fn parse_stat(text: &str) -> Option<(String, char, u32)> {
+    let open = text.find('(')?;
+    let close = text.rfind(')')?;
+    let name = text.get(open + 1..close)?.to_string();
+    let mut rest = text.get(close + 1..)?.split_whitespace();
+    let state = rest.next()?.chars().next()?;
+    let ppid = rest.next()?.parse().ok()?;
+    Some((name, state, ppid))
+}
+
+// "Key:\tvalue ..." lines; the first number after `key`.
+fn status_field(status: &str, key: &str) -> Option<u32> {
+    status.lines().find_map(|line| line.strip_prefix(key)?.split_whitespace().next()?.parse().ok())
+}
+
+/// Reads one process from a /proc-like directory. None if it does not exist
+/// (or vanished halfway through: processes exit while we look).
+pub fn read_info(root: &Path, pid: u32) -> Option<ProcInfo> {
+    let dir = root.join(pid.to_string());
+    let (name, state, ppid) = parse_stat(&fs::read_to_string(dir.join("stat")).ok()?)?;
+    let status = fs::read_to_string(dir.join("status")).unwrap_or_default();
+    let cmdline = fs::read(dir.join("cmdline")).unwrap_or_default();
+    let cmdline = String::from_utf8_lossy(&cmdline).split('\0').filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ");
+    Some(ProcInfo {
+        pid,
+        name,
+        state,
+        ppid,
+        tgid: status_field(&status, "Tgid:").unwrap_or(pid),
+        uid: status_field(&status, "Uid:"),
+        cmdline,
+    })
+}
+
+pub trait Source {
+    fn describe(&self) -> String;
+    /// The highest pid the kernel hands out.
+    fn pid_max(&self) -> u32;
+    /// The readdir view.
+    fn list(&self) -> io::Result<BTreeSet<u32>>;
+    /// The probe view for one pid.
+    fn probe(&self, pid: u32) -> Option<ProcInfo>;
+    /// The kill view for one pid; None if this source has no such view.
+    fn signal0(&self, pid: u32) -> Option<bool>;
+    /// What this source cannot see, if anything.
+    fn limitation(&self) -> Option<String> {
+        None
+    }
+}
+
+fn numeric_entries(root: &Path) -> io::Result<BTreeSet<u32>> {
+    let mut pids = BTreeSet::new();
+    for entry in fs::read_dir(root)? {
+        if let Some(pid) = entry?.file_name().to_str().and_then(|name| name.parse().ok()) {
+            pids.insert(pid);
+        }
+    }
+    Ok(pids)
+}
+
+fn read_pid_max(root: &Path) -> Option<u32> {
+    fs::read_to_string(root.join("sys/kernel/pid_max")).ok()?.trim().parse().ok()
+}
+
+// The hidepid option of the procfs mounted at `root`, if it hides other
+// users' processes from the listing (values 2 and 4, or their names).
+fn hidepid(root: &Path) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    // Fields: device, mount point, type, options. The last mount on top wins.
+    let options = mounts.lines().map(|line| line.split_whitespace().collect::<Vec<_>>()).rfind(|f| {
+        f.len() >= 4 && Path::new(f[1]) == root && f[2] == "proc"
+    })?[3]
+        .to_string();
+    let value = options.split(',').find_map(|o| o.strip_prefix("hidepid="))?;
+    matches!(value, "2" | "invisible" | "4" | "ptraceable").then(|| format!("hidepid={value}"))
+}
+
+/// The running kernel's /proc.
+pub struct Live {
+    root: PathBuf,
+    /// The hidepid option, when it hides processes from us (never for root).
+    hidepid: Option<String>,
+}
+
+impl Live {
+    pub fn new() -> Self {
+        let root = PathBuf::from("/proc");
+        // SAFETY: geteuid cannot fail and touches no memory of ours.
+        let root_user = unsafe { libc::geteuid() } == 0;
+        let hidepid = if root_user { None } else { hidepid(&root) };
+        Live { root, hidepid }
+    }
+}
+
+impl Source for Live {
+    fn describe(&self) -> String {
+        "live /proc".into()
+    }
+
+    fn pid_max(&self) -> u32 {
+        read_pid_max(&self.root).unwrap_or(32768)
+    }
+
+    fn list(&self) -> io::Result<BTreeSet<u32>> {
+        numeric_entries(&self.root)
+    }
+
+    fn probe(&self, pid: u32) -> Option<ProcInfo> {
+        read_info(&self.root, pid)
+    }
+
+    fn signal0(&self, pid: u32) -> Option<bool> {
+        let pid = libc::pid_t::try_from(pid).ok()?;
+        // SAFETY: signal 0 performs the existence and permission checks only;
+        // nothing is delivered.
+        if unsafe { libc::kill(pid, 0) } == 0 {
+            return Some(true);
+        }
+        // EPERM: it exists but is not ours. Under hidepid that is every other
+        // user's process, which readdir was never going to show us.
+        Some(io::Error::last_os_error().raw_os_error() == Some(libc::EPERM) && self.hidepid.is_none())
+    }
+
+    fn limitation(&self) -> Option<String> {
+        self.hidepid.as_ref().map(|opt| {
+            format!("/proc is mounted {opt}: only processes this user may see are checked; run as root for all of them")
+        })
+    }
+}
+
+/// A fake procfs directory whose readdir view hides what `rootkit_hides` says.
+pub struct Fixture {
+    root: PathBuf,
+    hidden: BTreeSet<u32>,
+}
+
+impl Fixture {
+    pub fn open(root: &Path) -> Result<Self, String> {
+        if !root.is_dir() {
+            return Err(format!("{}: not a directory", root.display()));
+        }
+        let hidden = match fs::read_to_string(root.join("rootkit_hides")) {
+            Ok(text) => text
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.parse().map_err(|_| format!("rootkit_hides: '{line}' is not a pid")))
+                .collect::<Result<_, _>>()?,
+            Err(_) => BTreeSet::new(),
+        };
+        Ok(Fixture { root: root.to_path_buf(), hidden })
+    }
+}
+
+impl Source for Fixture {
+    fn describe(&self) -> String {
+        format!("fixture {} (hooked readdir hides {:?})", self.root.display(), self.hidden)
+    }
+
+    fn pid_max(&self) -> u32 {
+        read_pid_max(&self.root).unwrap_or(32768)
+    }
+
+    // Like the kernel, list thread group leaders only; like the rootkit, drop
+    // the hidden ones.
+    fn list(&self) -> io::Result<BTreeSet<u32>> {
+        let mut pids = numeric_entries(&self.root)?;
+        pids.retain(|&pid| !self.hidden.contains(&pid) && self.probe(pid).is_none_or(|info| !info.is_thread()));
+        Ok(pids)
+    }
+
+    fn probe(&self, pid: u32) -> Option<ProcInfo> {
+        read_info(&self.root, pid)
+    }
+
+    fn signal0(&self, _pid: u32) -> Option<bool> {
+        None
+    }
+}
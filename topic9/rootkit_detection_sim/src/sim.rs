@@ -0,0 +1,49 @@
+// src/sim.rs
+//
+// The simulated version, no /proc needed. The kernel view is a real process
+// table (module2_structs/process_table) that a small system is booted into;
+// the user view is what a `ps` hooked by a rootkit reports from it: every
+// entry except the ones whose name the rootkit was told to hide.
+
+use std::collections::BTreeSet;
+
+use process_table::{Entry, Error, ProcessTable, INIT};
+
+const HIDDEN_NAME: &str = "malware_agent";
+
+// fork + exec, the way every process after init comes to exist. Both are
+// system calls, so first the parent and then the child gets the CPU.
+fn spawn(table: &mut ProcessTable, parent: u32, name: &str, memory_kb: u64) -> Result<u32, Error> {
+    table.switch_to(parent)?;
+    let pid = table.fork(parent)?;
+    table.admit(pid)?;
+    table.switch_to(pid)?;
+    table.exec(pid, name, memory_kb)?;
+    Ok(pid)
+}
+
+/// init starts nginx and sshd, a user logs in, and an exploited nginx worker
+/// drops the agent.
+pub fn boot() -> Result<ProcessTable, Error> {
+    let mut table = ProcessTable::new(1 << 20, 32768);
+    let nginx = spawn(&mut table, INIT, "nginx", 8192)?;
+    let sshd = spawn(&mut table, INIT, "sshd", 4096)?;
+    let worker = spawn(&mut table, nginx, "nginx", 6144)?;
+    spawn(&mut table, sshd, "bash", 2048)?;
+    spawn(&mut table, worker, HIDDEN_NAME, 1536)?;
+    Ok(table)
+}
+
+pub fn kernel_view(table: &ProcessTable) -> Vec<&Entry> {
+    table.iter().collect()
+}
+
+pub fn user_view(table: &ProcessTable) -> Vec<&Entry> {
+    table.iter().filter(|e| e.name != HIDDEN_NAME).collect()
+}
+
+/// Entries in the kernel view that the user view does not show.
+pub fn detect<'a>(kernel: &[&'a Entry], user: &[&Entry]) -> Vec<&'a Entry> {
+    let user_pids: BTreeSet<u32> = user.iter().map(|e| e.pid()).collect();
+    kernel.iter().copied().filter(|e| !user_pids.contains(&e.pid())).collect()
+}